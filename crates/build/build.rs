@@ -0,0 +1,33 @@
+use std::{collections::BTreeSet, path::Path};
+
+use anyhow::{Context, Result};
+use wit_parser::{Resolve, WorldItem};
+
+// Collects the names of all interfaces imported by the Spin worlds, which are
+// the interfaces a Spin host provides to guests, so that built components can
+// be checked against them without depending on the host implementation.
+fn main() -> Result<()> {
+    let wit_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../world/wit");
+    println!("cargo:rerun-if-changed={}", wit_dir.display());
+
+    let mut resolve = Resolve::default();
+    resolve
+        .push_dir(&wit_dir)
+        .with_context(|| format!("cannot parse Spin WIT definitions in {}", wit_dir.display()))?;
+    let interfaces: BTreeSet<_> = resolve
+        .worlds
+        .iter()
+        .flat_map(|(_, world)| world.imports.values())
+        .filter_map(|item| match item {
+            WorldItem::Interface(id) => resolve.id_of(*id),
+            _ => None,
+        })
+        .collect();
+
+    let names: Vec<_> = interfaces.iter().map(|name| format!("{name:?}")).collect();
+    let out_dir = std::env::var("OUT_DIR").context("OUT_DIR is not set")?;
+    let out_file = Path::new(&out_dir).join("host_interfaces.rs");
+    std::fs::write(&out_file, format!("&[{}]", names.join(", ")))
+        .with_context(|| format!("cannot write {}", out_file.display()))?;
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use spin_common::sha256;
+
+use crate::manifest::ComponentBuildInfo;
+
+const STATE_DIR: &str = ".spin";
+const STATE_FILE: &str = "build-state.json";
+
+/// Records the inputs and output of each component's last successful build,
+/// so that a component can be skipped if neither has changed since.
+///
+/// Only components which declare their inputs with `build.watch` and whose
+/// source is a local file are tracked.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct BuildState {
+    components: BTreeMap<String, Fingerprint>,
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(PartialEq, Serialize, Deserialize)]
+struct Fingerprint {
+    /// Digest of the build command, working directory and watched files.
+    inputs: String,
+    /// Digest of the component Wasm.
+    output: String,
+}
+
+impl BuildState {
+    /// Loads the build state for the app in `app_dir`. A missing or unreadable
+    /// state file is treated as empty, so that everything is rebuilt.
+    pub fn load(app_dir: &Path) -> Self {
+        let path = app_dir.join(STATE_DIR).join(STATE_FILE);
+        let state = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self { path, ..state }
+    }
+
+    /// Whether the component's inputs and output are unchanged since it was
+    /// last built successfully.
+    pub fn is_up_to_date(&self, info: &ComponentBuildInfo, app_dir: &Path) -> bool {
+        match fingerprint(info, app_dir) {
+            Some(current) => self.components.get(&info.id) == Some(&current),
+            None => false,
+        }
+    }
+
+    /// Records a successful build of the component.
+    pub fn record(&mut self, info: &ComponentBuildInfo, app_dir: &Path) {
+        match fingerprint(info, app_dir) {
+            Some(fingerprint) => {
+                self.components.insert(info.id.clone(), fingerprint);
+                self.dirty = true;
+            }
+            None => self.forget(&info.id),
+        }
+    }
+
+    /// Forgets the component's last build, so that it is rebuilt next time.
+    pub fn forget(&mut self, id: &str) {
+        if self.components.remove(id).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    /// Writes the build state back to the app's `.spin` directory if it changed.
+    pub fn save(&self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Cannot create directory {}", dir.display()))?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Cannot write build state to {}", self.path.display()))
+    }
+}
+
+fn fingerprint(info: &ComponentBuildInfo, app_dir: &Path) -> Option<Fingerprint> {
+    let build = info.build.as_ref()?;
+    if build.watch.is_empty() {
+        return None;
+    }
+    let output = sha256::hex_digest_from_file(app_dir.join(info.source.as_ref()?)).ok()?;
+
+    let workdir = app_dir.join(build.workdir.as_deref().unwrap_or_default());
+    let mut files = BTreeSet::new();
+    for pattern in &build.watch {
+        let pattern = workdir.join(pattern);
+        for path in glob::glob(pattern.to_str()?).ok()?.flatten() {
+            if path.is_file() {
+                files.insert(path);
+            }
+        }
+    }
+
+    let mut inputs = format!("{}\n{}\n", build.command, workdir.display());
+    for file in files {
+        let digest = sha256::hex_digest_from_file(&file).ok()?;
+        inputs.push_str(&format!("{}\0{digest}\n", file.display()));
+    }
+
+    Some(Fingerprint {
+        inputs: sha256::hex_digest_from_bytes(inputs),
+        output,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use spin_manifest::schema::v2::ComponentBuildConfig;
+
+    use super::*;
+
+    fn component(watch: &[&str]) -> ComponentBuildInfo {
+        ComponentBuildInfo {
+            id: "test".into(),
+            source: Some("out.wasm".into()),
+            capabilities: None,
+            dependencies: vec![],
+            build: Some(ComponentBuildConfig {
+                command: "build it".into(),
+                workdir: None,
+                watch: watch.iter().map(|s| s.to_string()).collect(),
+                depends_on: vec![],
+            }),
+        }
+    }
+
+    #[test]
+    fn up_to_date_until_inputs_or_output_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dir = dir.path();
+        std::fs::create_dir(app_dir.join("src")).unwrap();
+        std::fs::write(app_dir.join("src/lib.rs"), "one").unwrap();
+        std::fs::write(app_dir.join("out.wasm"), "wasm").unwrap();
+        let info = component(&["src/**/*.rs"]);
+
+        let mut state = BuildState::load(app_dir);
+        assert!(!state.is_up_to_date(&info, app_dir));
+        state.record(&info, app_dir);
+        state.save().unwrap();
+
+        let state = BuildState::load(app_dir);
+        assert!(state.is_up_to_date(&info, app_dir));
+
+        std::fs::write(app_dir.join("src/lib.rs"), "two").unwrap();
+        assert!(!state.is_up_to_date(&info, app_dir));
+
+        std::fs::write(app_dir.join("src/lib.rs"), "one").unwrap();
+        std::fs::write(app_dir.join("out.wasm"), "tampered").unwrap();
+        assert!(!state.is_up_to_date(&info, app_dir));
+    }
+
+    #[test]
+    fn does_not_track_components_without_watch() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dir = dir.path();
+        std::fs::write(app_dir.join("out.wasm"), "wasm").unwrap();
+        let info = component(&[]);
+
+        let mut state = BuildState::load(app_dir);
+        state.record(&info, app_dir);
+        state.save().unwrap();
+
+        assert!(!state.is_up_to_date(&info, app_dir));
+        assert!(!app_dir.join(STATE_DIR).exists());
+    }
+}
@@ -2,21 +2,62 @@
 
 //! A library for building Spin components.
 
+mod incremental;
 mod manifest;
+mod verify;
 
 use anyhow::{anyhow, bail, Context, Result};
+use incremental::BuildState;
 use manifest::ComponentBuildInfo;
 use spin_common::{paths::parent_dir, ui::quoted_path};
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
+    io::BufRead,
     path::{Path, PathBuf},
 };
 use subprocess::{Exec, Redirection};
+use tokio::task::JoinSet;
 
 use crate::manifest::component_build_configs;
 
+/// Options controlling how components are built.
+#[derive(Clone, Debug)]
+pub struct BuildOptions {
+    /// The maximum number of build commands to run at the same time. The
+    /// default is the number of CPUs. When builds may run at the same time,
+    /// each line of their output is prefixed with the component ID.
+    pub jobs: usize,
+    /// Build components even if their inputs have not changed since they
+    /// were last built.
+    pub force: bool,
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            jobs: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            force: false,
+        }
+    }
+}
+
 /// If present, run the build command of each component.
 pub async fn build(manifest_file: &Path, component_ids: &[String]) -> Result<()> {
+    build_with_options(manifest_file, component_ids, &BuildOptions::default()).await
+}
+
+/// If present, run the build command of each component, using the given options.
+///
+/// Components are built once the components they depend on have been built,
+/// with independent components built in parallel. A component whose watched
+/// files and output are unchanged since its last successful build is skipped.
+/// Once all builds have succeeded, the Wasm they produced is checked for
+/// problems which would prevent the application from running.
+pub async fn build_with_options(
+    manifest_file: &Path,
+    component_ids: &[String],
+    options: &BuildOptions,
+) -> Result<()> {
     let (components, manifest_err) =
         component_build_configs(manifest_file)
             .await
@@ -28,7 +69,7 @@ pub async fn build(manifest_file: &Path, component_ids: &[String]) -> Result<()>
             })?;
     let app_dir = parent_dir(manifest_file)?;
 
-    let build_result = build_components(component_ids, components, app_dir);
+    let build_result = build_components(component_ids, components, app_dir, options).await;
 
     if let Some(e) = manifest_err {
         terminal::warn!("The manifest has errors not related to the Wasm component build. Error details:\n{e:#}");
@@ -37,18 +78,21 @@ pub async fn build(manifest_file: &Path, component_ids: &[String]) -> Result<()>
     build_result
 }
 
-fn build_components(
+async fn build_components(
     component_ids: &[String],
     components: Vec<ComponentBuildInfo>,
     app_dir: PathBuf,
+    options: &BuildOptions,
 ) -> Result<(), anyhow::Error> {
+    check_dependencies(&components)?;
+    let all_ids: HashSet<_> = components.iter().map(|c| c.id.clone()).collect();
+
     let components_to_build = if component_ids.is_empty() {
         components
     } else {
-        let all_ids: HashSet<_> = components.iter().map(|c| &c.id).collect();
         let unknown_component_ids: Vec<_> = component_ids
             .iter()
-            .filter(|id| !all_ids.contains(id))
+            .filter(|id| !all_ids.contains(*id))
             .map(|s| s.as_str())
             .collect();
 
@@ -68,18 +112,144 @@ fn build_components(
         return Ok(());
     }
 
-    components_to_build
+    let mut pending: Vec<_> = components_to_build
+        .into_iter()
+        .filter(|c| c.build.is_some())
+        .collect();
+    // Components which are not being built count as already built.
+    let mut finished: HashSet<_> = all_ids
         .into_iter()
-        .map(|c| build_component(c, &app_dir))
-        .collect::<Result<Vec<_>, _>>()?;
+        .filter(|id| !pending.iter().any(|c| &c.id == id))
+        .collect();
+    let mut rebuilt = HashSet::new();
+    let mut built = vec![];
+    let mut state = BuildState::load(&app_dir);
+    let jobs = options.jobs.max(1);
+    let prefix_output = jobs > 1 && pending.len() > 1;
+    let mut running = JoinSet::new();
+    let mut failure = None;
+
+    loop {
+        while failure.is_none() && running.len() < jobs {
+            let Some(index) = pending
+                .iter()
+                .position(|c| dependencies(c).all(|dep| finished.contains(dep)))
+            else {
+                break;
+            };
+            let component = pending.remove(index);
+
+            let dependency_rebuilt = dependencies(&component).any(|dep| rebuilt.contains(dep));
+            if !options.force && !dependency_rebuilt && state.is_up_to_date(&component, &app_dir) {
+                terminal::step!("Skipping", "component {} (up to date)", component.id);
+                finished.insert(component.id.clone());
+                built.push(component);
+                continue;
+            }
+
+            let app_dir = app_dir.clone();
+            running.spawn_blocking(move || {
+                let result = build_component(&component, &app_dir, prefix_output);
+                (component, result)
+            });
+        }
+
+        let Some(joined) = running.join_next().await else {
+            break;
+        };
+        let (component, result) = joined.context("Build task failed")?;
+        match result {
+            Ok(()) => {
+                state.record(&component, &app_dir);
+                rebuilt.insert(component.id.clone());
+                finished.insert(component.id.clone());
+                built.push(component);
+            }
+            Err(e) => {
+                state.forget(&component.id);
+                // Let builds which are already running finish, but don't start any more.
+                failure.get_or_insert(e);
+            }
+        }
+    }
+
+    if let Err(e) = state.save() {
+        terminal::warn!("Could not save build state: {e:#}");
+    }
+    if let Some(e) = failure {
+        return Err(e);
+    }
+
+    verify::verify_components(&built, &rebuilt, &app_dir)?;
 
     terminal::step!("Finished", "building all Spin components");
     Ok(())
 }
 
-/// Run the build command of the component.
-fn build_component(build_info: ComponentBuildInfo, app_dir: &Path) -> Result<()> {
-    match build_info.build {
+fn dependencies(component: &ComponentBuildInfo) -> impl Iterator<Item = &String> {
+    component.build.iter().flat_map(|b| &b.depends_on)
+}
+
+/// Checks that every component a build depends on exists, and that there
+/// are no dependency cycles.
+fn check_dependencies(components: &[ComponentBuildInfo]) -> Result<()> {
+    let all_ids: HashSet<_> = components.iter().map(|c| &c.id).collect();
+    for component in components {
+        let unknown: Vec<_> = dependencies(component)
+            .filter(|dep| !all_ids.contains(dep))
+            .map(|s| s.as_str())
+            .collect();
+        if !unknown.is_empty() {
+            bail!(
+                "Build of component {} depends on unknown component(s) {}",
+                component.id,
+                unknown.join(", ")
+            );
+        }
+    }
+
+    // Repeatedly remove components with no remaining dependencies; any left
+    // over are part of, or depend on, a cycle.
+    let mut remaining: HashMap<_, HashSet<_>> = components
+        .iter()
+        .map(|c| (&c.id, dependencies(c).collect()))
+        .collect();
+    loop {
+        let ready: Vec<_> = remaining
+            .iter()
+            .filter(|(_, deps)| deps.is_empty())
+            .map(|(id, _)| *id)
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+        for id in ready {
+            remaining.remove(id);
+            for deps in remaining.values_mut() {
+                deps.remove(id);
+            }
+        }
+    }
+    if !remaining.is_empty() {
+        let mut ids: Vec<_> = remaining.keys().map(|id| id.as_str()).collect();
+        ids.sort();
+        bail!(
+            "Component builds have circular dependencies: {}",
+            ids.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Run the build command of the component. If `prefix_output` is set, each
+/// line of the command's output is prefixed with the component ID, so that
+/// the output of concurrent builds can be told apart.
+fn build_component(
+    build_info: &ComponentBuildInfo,
+    app_dir: &Path,
+    prefix_output: bool,
+) -> Result<()> {
+    match &build_info.build {
         Some(b) => {
             terminal::step!(
                 "Building",
@@ -92,21 +262,38 @@ fn build_component(build_info: ComponentBuildInfo, app_dir: &Path) -> Result<()>
                 println!("Working directory: {}", quoted_path(&workdir));
             }
 
-            let exit_status = Exec::shell(&b.command)
+            let spawn_err = |err: subprocess::PopenError| {
+                anyhow!(
+                    "Cannot spawn build process '{:?}' for component {}: {}",
+                    &b.command,
+                    build_info.id,
+                    err
+                )
+            };
+            let exec = Exec::shell(&b.command)
                 .cwd(workdir)
-                .stdout(Redirection::None)
-                .stderr(Redirection::None)
-                .stdin(Redirection::None)
-                .popen()
-                .map_err(|err| {
-                    anyhow!(
-                        "Cannot spawn build process '{:?}' for component {}: {}",
-                        &b.command,
-                        build_info.id,
-                        err
-                    )
-                })?
-                .wait()?;
+                .stdin(Redirection::None);
+            let exit_status = if prefix_output {
+                let mut process = exec
+                    .stdout(Redirection::Pipe)
+                    .stderr(Redirection::Merge)
+                    .popen()
+                    .map_err(spawn_err)?;
+                if let Some(output) = process.stdout.take() {
+                    for line in std::io::BufReader::new(output).split(b'\n') {
+                        let line = line?;
+                        let line = String::from_utf8_lossy(&line);
+                        println!("[{}] {}", build_info.id, line.trim_end_matches('\r'));
+                    }
+                }
+                process.wait()?
+            } else {
+                exec.stdout(Redirection::None)
+                    .stderr(Redirection::None)
+                    .popen()
+                    .map_err(spawn_err)?
+                    .wait()?
+            };
 
             if !exit_status.success() {
                 bail!(
@@ -150,7 +337,145 @@ mod tests {
 
     #[tokio::test]
     async fn can_load_even_if_trigger_invalid() {
-        let bad_trigger_file = test_data_root().join("bad_trigger.toml");
+        // Built components are verified, so build a copy of the manifest
+        // alongside an (empty) component source.
+        let dir = tempfile::tempdir().unwrap();
+        let bad_trigger_file = dir.path().join("bad_trigger.toml");
+        std::fs::copy(test_data_root().join("bad_trigger.toml"), &bad_trigger_file).unwrap();
+        std::fs::copy(
+            test_data_root().join("component.wasm"),
+            dir.path().join("component.wasm"),
+        )
+        .unwrap();
         build(&bad_trigger_file, &[]).await.unwrap();
     }
+
+    fn component(id: &str, depends_on: &[&str]) -> ComponentBuildInfo {
+        ComponentBuildInfo {
+            id: id.into(),
+            source: None,
+            capabilities: None,
+            dependencies: vec![],
+            build: Some(spin_manifest::schema::v2::ComponentBuildConfig {
+                command: "true".into(),
+                workdir: None,
+                watch: vec![],
+                depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            }),
+        }
+    }
+
+    fn with_command(mut component: ComponentBuildInfo, command: &str) -> ComponentBuildInfo {
+        component.build.as_mut().unwrap().command = command.into();
+        component
+    }
+
+    fn log_command(id: &str) -> String {
+        format!("echo {id} >> build.log")
+    }
+
+    fn build_log(app_dir: &Path) -> Vec<String> {
+        std::fs::read_to_string(app_dir.join("build.log"))
+            .unwrap_or_default()
+            .lines()
+            .map(|line| line.trim().to_owned())
+            .collect()
+    }
+
+    fn options(jobs: usize) -> BuildOptions {
+        BuildOptions { jobs, force: false }
+    }
+
+    #[tokio::test]
+    async fn builds_dependencies_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let components = vec![
+            with_command(component("c", &["b"]), &log_command("c")),
+            with_command(component("b", &["a"]), &log_command("b")),
+            with_command(component("a", &[]), &log_command("a")),
+        ];
+
+        build_components(&[], components, dir.path().to_owned(), &options(4))
+            .await
+            .unwrap();
+
+        assert_eq!(vec!["a", "b", "c"], build_log(dir.path()));
+    }
+
+    #[tokio::test]
+    async fn skips_up_to_date_components() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dir = dir.path();
+        std::fs::write(app_dir.join("input.txt"), "one").unwrap();
+        // An empty component, so that verification passes.
+        std::fs::write(app_dir.join("a.wasm"), b"\0asm\x0d\0\x01\0").unwrap();
+        let component = || {
+            let mut component = with_command(component("a", &[]), &log_command("a"));
+            component.source = Some("a.wasm".into());
+            component.build.as_mut().unwrap().watch = vec!["input.txt".into()];
+            component
+        };
+
+        for _ in 0..2 {
+            build_components(&[], vec![component()], app_dir.to_owned(), &options(1))
+                .await
+                .unwrap();
+        }
+        assert_eq!(vec!["a"], build_log(app_dir));
+
+        std::fs::write(app_dir.join("input.txt"), "two").unwrap();
+        build_components(&[], vec![component()], app_dir.to_owned(), &options(1))
+            .await
+            .unwrap();
+        assert_eq!(vec!["a", "a"], build_log(app_dir));
+    }
+
+    #[tokio::test]
+    async fn failed_build_stops_further_builds() {
+        let dir = tempfile::tempdir().unwrap();
+        let components = vec![
+            with_command(component("a", &[]), "exit 1"),
+            with_command(component("b", &[]), &log_command("b")),
+            with_command(component("c", &["a"]), &log_command("c")),
+        ];
+
+        let err = build_components(&[], components, dir.path().to_owned(), &options(1))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("component a failed"), "{err:#}");
+        assert!(build_log(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn accepts_acyclic_dependencies() {
+        let components = [
+            component("a", &[]),
+            component("b", &["a"]),
+            component("c", &["a", "b"]),
+        ];
+        check_dependencies(&components).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_dependencies() {
+        let components = [component("a", &["nope"])];
+        let err = check_dependencies(&components).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown component(s) nope"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_circular_dependencies() {
+        let components = [
+            component("a", &[]),
+            component("b", &["c"]),
+            component("c", &["b"]),
+            component("d", &["c"]),
+        ];
+        let err = check_dependencies(&components).unwrap_err();
+        assert!(err.to_string().ends_with("b, c, d"), "{err}");
+    }
 }
@@ -30,6 +30,20 @@ fn build_configs_from_manifest(
         .into_iter()
         .map(|(id, c)| ComponentBuildInfo {
             id: id.to_string(),
+            source: match c.source {
+                v2::ComponentSource::Local(path) => Some(path),
+                _ => None,
+            },
+            capabilities: Some(ComponentCapabilities {
+                outbound_hosts: !c.allowed_outbound_hosts.is_empty()
+                    || !c.allowed_http_hosts.is_empty(),
+                key_value_stores: !c.key_value_stores.is_empty(),
+                sqlite_databases: !c.sqlite_databases.is_empty(),
+                postgres_databases: !c.postgres_databases.is_empty(),
+                mysql_databases: !c.mysql_databases.is_empty(),
+                ai_models: !c.ai_models.is_empty(),
+            }),
+            dependencies: c.dependencies.into_keys().collect(),
             build: c.build,
         })
         .collect()
@@ -61,7 +75,46 @@ async fn fallback_load_build_configs(
 pub struct ComponentBuildInfo {
     #[serde(default)]
     pub id: String,
+    /// The path of the component Wasm, if it is a local file.
+    #[serde(default, deserialize_with = "local_source")]
+    pub source: Option<String>,
     pub build: Option<v2::ComponentBuildConfig>,
+    /// The names of the imports satisfied by composing the component with
+    /// its `dependencies` when the application is loaded.
+    #[serde(default, deserialize_with = "table_keys")]
+    pub dependencies: Vec<String>,
+    /// The host capabilities granted by the manifest, if it could be loaded.
+    #[serde(skip)]
+    pub capabilities: Option<ComponentCapabilities>,
+}
+
+/// Which kinds of host capability the manifest grants a component.
+#[derive(Clone, Debug, Default)]
+pub struct ComponentCapabilities {
+    pub outbound_hosts: bool,
+    pub key_value_stores: bool,
+    pub sqlite_databases: bool,
+    pub postgres_databases: bool,
+    pub mysql_databases: bool,
+    pub ai_models: bool,
+}
+
+// Accepts any form of component source, keeping only local paths.
+fn local_source<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = toml::Value::deserialize(deserializer)?;
+    Ok(value.as_str().map(str::to_owned))
+}
+
+// Accepts a table, keeping only its keys.
+fn table_keys<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let table = BTreeMap::<String, toml::Value>::deserialize(deserializer)?;
+    Ok(table.into_keys().collect())
 }
 
 #[derive(Deserialize)]
@@ -0,0 +1,261 @@
+//! Checks that built components can be run by Spin, so that problems are
+//! reported by `spin build` rather than when the application is started.
+
+use std::{collections::HashSet, path::Path};
+
+use anyhow::{Context, Result};
+use spin_common::ui::quoted_path;
+use wit_component::DecodedWasm;
+use wit_parser::{WorldItem, WorldKey};
+
+use crate::manifest::{ComponentBuildInfo, ComponentCapabilities};
+
+/// The names of the interfaces a Spin host provides for guests to import,
+/// such as `fermyon:spin/key-value@2.0.0`.
+const HOST_INTERFACES: &[&str] = include!(concat!(env!("OUT_DIR"), "/host_interfaces.rs"));
+
+/// Checks the Wasm produced by building each of `components`. Imports which
+/// Spin may not provide, or which the manifest does not grant the component
+/// access to, are reported as warnings, since the host the application runs
+/// on may differ from this one. An invalid source is an error, as is a
+/// missing one for components in `rebuilt`, whose build command has just run.
+pub(crate) fn verify_components(
+    components: &[ComponentBuildInfo],
+    rebuilt: &HashSet<String>,
+    app_dir: &Path,
+) -> Result<()> {
+    let mut errors = vec![];
+    for component in components {
+        let Some(source) = &component.source else {
+            continue;
+        };
+        let source = app_dir.join(source);
+        if !source.exists() && !rebuilt.contains(&component.id) {
+            continue;
+        }
+        match component_imports(&source) {
+            Ok(imports) => {
+                let unsupported: Vec<_> = imports
+                    .iter()
+                    .filter(|import| {
+                        !is_supported(import) && !is_dependency(import, &component.dependencies)
+                    })
+                    .map(String::as_str)
+                    .collect();
+                if !unsupported.is_empty() {
+                    terminal::warn!(
+                        "Component {} imports interface(s) which this version of Spin does not provide: {}. The application may fail to start.",
+                        component.id,
+                        unsupported.join(", ")
+                    );
+                }
+                if let Some(capabilities) = &component.capabilities {
+                    for import in &imports {
+                        if let Some(field) = missing_capability(import, capabilities) {
+                            terminal::warn!("Component {} imports {import}, but the manifest does not set `{field}` for it. Calls to this interface will fail.", component.id);
+                        }
+                    }
+                }
+            }
+            Err(e) => errors.push(format!("Component {}: {e:#}", component.id)),
+        }
+    }
+
+    if !errors.is_empty() {
+        anyhow::bail!(
+            "Built components failed verification:\n{}",
+            errors.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// Returns the names of the interfaces imported by the Wasm module or
+/// component at `path`.
+fn component_imports(path: &Path) -> Result<Vec<String>> {
+    let bytes = std::fs::read(path).with_context(|| {
+        format!(
+            "build did not produce the component source {}",
+            quoted_path(path)
+        )
+    })?;
+    let component = spin_componentize::componentize_if_necessary(&bytes).with_context(|| {
+        format!(
+            "{} is not a valid Wasm module or component",
+            quoted_path(path)
+        )
+    })?;
+    let DecodedWasm::Component(resolve, world) = wit_component::decode(&component)
+        .with_context(|| format!("cannot read the imports of {}", quoted_path(path)))?
+    else {
+        anyhow::bail!("{} is not a Wasm component", quoted_path(path));
+    };
+
+    Ok(resolve.worlds[world]
+        .imports
+        .iter()
+        .filter_map(|(key, item)| match (key, item) {
+            (_, WorldItem::Type(_)) => None,
+            (WorldKey::Interface(id), _) => resolve.id_of(*id),
+            (WorldKey::Name(name), _) => Some(name.clone()),
+        })
+        .collect())
+}
+
+/// Whether a Spin host provides the interface `import`. Imports of a version
+/// compatible with one the host provides are also accepted.
+fn is_supported(import: &str) -> bool {
+    let import = compatibility_key(import);
+    HOST_INTERFACES
+        .iter()
+        .any(|host| compatibility_key(host) == import)
+}
+
+/// Whether the import `import` is satisfied by one of the component's
+/// `dependencies`, which are composed with it when the application is loaded.
+/// A dependency named without a version satisfies any version of the import.
+fn is_dependency(import: &str, dependencies: &[String]) -> bool {
+    let unversioned = import.split_once('@').map_or(import, |(name, _)| name);
+    dependencies
+        .iter()
+        .any(|dependency| dependency == import || dependency == unversioned)
+}
+
+// Splits an interface name into the unversioned name and the semver range of
+// its version, so that compatible versions compare equal.
+fn compatibility_key(interface: &str) -> (&str, Option<String>) {
+    let Some((name, version)) = interface.split_once('@') else {
+        return (interface, None);
+    };
+    let range = match semver::Version::parse(version) {
+        Ok(v) if v.pre.is_empty() && v.major > 0 => v.major.to_string(),
+        Ok(v) if v.pre.is_empty() => format!("0.{}", v.minor),
+        _ => version.to_owned(),
+    };
+    (name, Some(range))
+}
+
+/// If using the interface `import` requires access which the manifest does
+/// not grant, returns the manifest field which grants it.
+fn missing_capability(import: &str, capabilities: &ComponentCapabilities) -> Option<&'static str> {
+    let name = import.split_once('@').map_or(import, |(name, _)| name);
+    let granted = match name {
+        "fermyon:spin/key-value" => capabilities.key_value_stores,
+        "fermyon:spin/sqlite" => capabilities.sqlite_databases,
+        "fermyon:spin/llm" => capabilities.ai_models,
+        "fermyon:spin/postgres" | "spin:rdbms/postgres" => {
+            capabilities.outbound_hosts || capabilities.postgres_databases
+        }
+        "fermyon:spin/mysql" | "spin:rdbms/mysql" => {
+            capabilities.outbound_hosts || capabilities.mysql_databases
+        }
+        "fermyon:spin/http"
+        | "fermyon:spin/redis"
+        | "fermyon:spin/mqtt"
+        | "wasi:http/outgoing-handler" => capabilities.outbound_hosts,
+        _ => true,
+    };
+    if granted {
+        return None;
+    }
+    Some(match name {
+        "fermyon:spin/key-value" => "key_value_stores",
+        "fermyon:spin/sqlite" => "sqlite_databases",
+        "fermyon:spin/llm" => "ai_models",
+        _ => "allowed_outbound_hosts",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_compatible_versions_of_host_interfaces() {
+        assert!(is_supported("fermyon:spin/key-value@2.0.0"));
+        assert!(is_supported("fermyon:spin/key-value"));
+        assert!(is_supported("wasi:io/streams@0.2.0"));
+        assert!(is_supported("wasi:io/streams@0.2.1"));
+        assert!(!is_supported("wasi:io/streams@0.3.0"));
+        assert!(!is_supported("example:unknown/interface@1.0.0"));
+    }
+
+    #[test]
+    fn accepts_imports_satisfied_by_dependencies() {
+        let dependencies = vec![
+            "example:auth/middleware".to_owned(),
+            "example:kv/store@1.0.0".to_owned(),
+        ];
+        assert!(is_dependency("example:auth/middleware", &dependencies));
+        assert!(is_dependency(
+            "example:auth/middleware@0.1.0",
+            &dependencies
+        ));
+        assert!(is_dependency("example:kv/store@1.0.0", &dependencies));
+        assert!(!is_dependency("example:kv/store@2.0.0", &dependencies));
+        assert!(!is_dependency("example:other/interface", &dependencies));
+    }
+
+    #[test]
+    fn reports_missing_capabilities() {
+        let capabilities = ComponentCapabilities {
+            key_value_stores: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            None,
+            missing_capability("fermyon:spin/key-value@2.0.0", &capabilities)
+        );
+        assert_eq!(
+            Some("sqlite_databases"),
+            missing_capability("fermyon:spin/sqlite@2.0.0", &capabilities)
+        );
+        assert_eq!(
+            Some("allowed_outbound_hosts"),
+            missing_capability("wasi:http/outgoing-handler@0.2.0", &capabilities)
+        );
+        assert_eq!(
+            None,
+            missing_capability("wasi:io/streams@0.2.0", &capabilities)
+        );
+    }
+
+    #[test]
+    fn reports_missing_or_invalid_output() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("invalid.wasm"), "not wasm").unwrap();
+        let component = |id: &str| ComponentBuildInfo {
+            id: id.into(),
+            source: Some(format!("{id}.wasm")),
+            build: None,
+            capabilities: None,
+            dependencies: vec![],
+        };
+
+        let components = [component("missing"), component("invalid")];
+        let rebuilt = ["missing", "invalid"].map(String::from).into();
+
+        let err = verify_components(&components, &rebuilt, dir.path())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("Component missing: build did not produce"),
+            "{err}"
+        );
+        assert!(err.contains("Component invalid: "), "{err}");
+    }
+
+    #[test]
+    fn ignores_missing_output_of_components_not_rebuilt() {
+        let dir = tempfile::tempdir().unwrap();
+        let component = ComponentBuildInfo {
+            id: "missing".into(),
+            source: Some("missing.wasm".into()),
+            build: None,
+            capabilities: None,
+            dependencies: vec![],
+        };
+
+        verify_components(&[component], &HashSet::new(), dir.path()).unwrap();
+    }
+}
@@ -0,0 +1,406 @@
+//! A host-managed pool of outbound database connections, shared by all
+//! instances of an application and keyed by connection string.
+
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use spin_telemetry::metrics::{counter, histogram, monotonic_counter};
+use tokio::{
+    sync::{Notify, OwnedSemaphorePermit, Semaphore},
+    time::Instant,
+};
+
+/// Settings for connection pools.
+#[derive(Clone, Debug)]
+pub struct PoolConfig {
+    /// The maximum number of connections open at once to each address.
+    pub max_size: usize,
+    /// How long a connection may sit unused in the pool before it is closed.
+    pub idle_timeout: Duration,
+    /// How long to wait for a connection when the pool is at `max_size`.
+    pub acquire_timeout: Duration,
+    /// Whether to check that an idle connection is still usable before
+    /// handing it out.
+    pub health_check: bool,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 10,
+            idle_timeout: Duration::from_secs(300),
+            acquire_timeout: Duration::from_secs(30),
+            health_check: true,
+        }
+    }
+}
+
+/// Creates and checks connections for a particular kind of database.
+#[async_trait]
+pub trait Manager: Send + Sync + 'static {
+    type Connection: Send + 'static;
+
+    /// The `db_system` attribute reported with pool metrics.
+    const DB_SYSTEM: &'static str;
+
+    /// Opens a new connection to `address`.
+    async fn connect(&self, address: &str) -> Result<Self::Connection>;
+
+    /// Checks that an idle connection is still usable.
+    async fn check(&self, connection: &mut Self::Connection) -> Result<()>;
+
+    /// Clears any session state, such as an open transaction, left by the
+    /// last user of a released connection, so that it can be reused by
+    /// another. The connection is closed if this fails.
+    async fn reset(&self, connection: &mut Self::Connection) -> Result<()>;
+
+    /// Whether a connection may be returned to the pool once released.
+    fn is_reusable(&self, _connection: &Self::Connection) -> bool {
+        true
+    }
+}
+
+/// A set of connection pools, one per connection string.
+pub struct Pools<M: Manager> {
+    manager: Arc<M>,
+    config: PoolConfig,
+    pools: Mutex<HashMap<String, Arc<Pool<M>>>>,
+}
+
+impl<M: Manager> Pools<M> {
+    pub fn new(manager: M, config: PoolConfig) -> Self {
+        Self {
+            manager: Arc::new(manager),
+            config,
+            pools: Default::default(),
+        }
+    }
+
+    /// Gets a connection to `address`, reusing an idle one if possible.
+    pub async fn get(&self, address: &str) -> Result<Pooled<M>> {
+        let pool = self
+            .pools
+            .lock()
+            .unwrap()
+            .entry(address.to_owned())
+            .or_insert_with(|| {
+                Arc::new(Pool {
+                    address: address.to_owned(),
+                    manager: self.manager.clone(),
+                    config: self.config.clone(),
+                    permits: Arc::new(Semaphore::new(self.config.max_size)),
+                    idle: Default::default(),
+                    resetting: Default::default(),
+                    reset_done: Default::default(),
+                })
+            })
+            .clone();
+        pool.get().await
+    }
+}
+
+struct Pool<M: Manager> {
+    address: String,
+    manager: Arc<M>,
+    config: PoolConfig,
+    // Limits the number of connections in use to `max_size`.
+    permits: Arc<Semaphore>,
+    idle: Mutex<Vec<Idle<M::Connection>>>,
+    // The number of released connections which are still being reset, and
+    // a notification as each one finishes.
+    resetting: AtomicUsize,
+    reset_done: Notify,
+}
+
+struct Idle<C> {
+    connection: C,
+    since: Instant,
+}
+
+impl<M: Manager> Pool<M> {
+    async fn get(self: Arc<Self>) -> Result<Pooled<M>> {
+        let start = Instant::now();
+        let permit = tokio::time::timeout(
+            self.config.acquire_timeout,
+            self.permits.clone().acquire_owned(),
+        )
+        .await
+        .context("timed out waiting for a pooled database connection")?
+        .context("connection pool was closed")?;
+        histogram!(
+            spin.db_pool_wait_time = start.elapsed().as_secs_f64(),
+            db_system = M::DB_SYSTEM
+        );
+
+        let connection = match self.checkout_idle().await {
+            Some(connection) => connection,
+            None => {
+                let connection = self.manager.connect(&self.address).await?;
+                monotonic_counter!(
+                    spin.db_pool_connections_created = 1,
+                    db_system = M::DB_SYSTEM
+                );
+                connection
+            }
+        };
+        counter!(
+            spin.db_pool_connections_in_use = 1,
+            db_system = M::DB_SYSTEM
+        );
+        Ok(Pooled {
+            connection: Some(connection),
+            pool: self,
+            permit: Some(permit),
+        })
+    }
+
+    // Takes the most recently used idle connection which passes its health
+    // check, closing any which have expired or failed along the way. If
+    // there are none but some are still being reset, waits for those.
+    async fn checkout_idle(&self) -> Option<M::Connection> {
+        loop {
+            let reset_done = self.reset_done.notified();
+            let Some(mut connection) = self.take_idle() else {
+                if self.resetting.load(Ordering::SeqCst) == 0 {
+                    return None;
+                }
+                reset_done.await;
+                continue;
+            };
+            if !self.config.health_check {
+                return Some(connection);
+            }
+            match self.manager.check(&mut connection).await {
+                Ok(()) => return Some(connection),
+                Err(e) => tracing::debug!("Discarding unhealthy pooled connection: {e:?}"),
+            }
+        }
+    }
+
+    fn take_idle(&self) -> Option<M::Connection> {
+        let mut idle = self.idle.lock().unwrap();
+        let before = idle.len();
+        idle.retain(|i| i.since.elapsed() < self.config.idle_timeout);
+        let taken = idle.pop();
+        let removed = before - idle.len();
+        if removed > 0 {
+            counter!(
+                spin.db_pool_connections_idle = -(removed as i64),
+                db_system = M::DB_SYSTEM
+            );
+        }
+        taken.map(|i| i.connection)
+    }
+
+    // Resets a released connection in the background and then makes it
+    // available to others. The connection still counts toward `max_size`
+    // until it has been reset.
+    fn release(self: &Arc<Self>, mut connection: M::Connection, permit: OwnedSemaphorePermit) {
+        counter!(
+            spin.db_pool_connections_in_use = -1,
+            db_system = M::DB_SYSTEM
+        );
+        if !self.manager.is_reusable(&connection) {
+            return;
+        }
+        // Without a runtime the connection can't be reset, so it is closed.
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let pool = self.clone();
+        pool.resetting.fetch_add(1, Ordering::SeqCst);
+        runtime.spawn(async move {
+            match pool.manager.reset(&mut connection).await {
+                Ok(()) => {
+                    pool.idle.lock().unwrap().push(Idle {
+                        connection,
+                        since: Instant::now(),
+                    });
+                    counter!(spin.db_pool_connections_idle = 1, db_system = M::DB_SYSTEM);
+                }
+                Err(e) => {
+                    tracing::debug!("Discarding pooled connection which could not be reset: {e:?}")
+                }
+            }
+            pool.resetting.fetch_sub(1, Ordering::SeqCst);
+            pool.reset_done.notify_waiters();
+            drop(permit);
+        });
+    }
+}
+
+/// A connection checked out of a pool. It is returned to the pool when
+/// dropped, unless [`Pooled::discard`] is called.
+pub struct Pooled<M: Manager> {
+    connection: Option<M::Connection>,
+    pool: Arc<Pool<M>>,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<M: Manager> Pooled<M> {
+    /// Closes the connection rather than returning it to the pool.
+    pub fn discard(mut self) {
+        self.connection.take();
+        counter!(
+            spin.db_pool_connections_in_use = -1,
+            db_system = M::DB_SYSTEM
+        );
+    }
+}
+
+impl<M: Manager> Deref for Pooled<M> {
+    type Target = M::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        self.connection.as_ref().unwrap()
+    }
+}
+
+impl<M: Manager> DerefMut for Pooled<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.connection.as_mut().unwrap()
+    }
+}
+
+impl<M: Manager> Drop for Pooled<M> {
+    fn drop(&mut self) {
+        if let (Some(connection), Some(permit)) = (self.connection.take(), self.permit.take()) {
+            self.pool.release(connection, permit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct TestManager {
+        connects: AtomicUsize,
+    }
+
+    struct TestConnection {
+        id: usize,
+        healthy: bool,
+        // Session state which must be cleared before reuse
+        dirty: bool,
+        resettable: bool,
+    }
+
+    #[async_trait]
+    impl Manager for TestManager {
+        type Connection = TestConnection;
+
+        const DB_SYSTEM: &'static str = "test";
+
+        async fn connect(&self, _address: &str) -> Result<Self::Connection> {
+            Ok(TestConnection {
+                id: self.connects.fetch_add(1, Ordering::SeqCst),
+                healthy: true,
+                dirty: false,
+                resettable: true,
+            })
+        }
+
+        async fn check(&self, connection: &mut Self::Connection) -> Result<()> {
+            anyhow::ensure!(connection.healthy, "unhealthy");
+            Ok(())
+        }
+
+        async fn reset(&self, connection: &mut Self::Connection) -> Result<()> {
+            anyhow::ensure!(connection.resettable, "cannot reset");
+            connection.dirty = false;
+            Ok(())
+        }
+    }
+
+    fn pools(config: PoolConfig) -> Pools<TestManager> {
+        Pools::new(TestManager::default(), config)
+    }
+
+    #[tokio::test]
+    async fn reuses_released_connections() {
+        let pools = pools(PoolConfig::default());
+        let first = pools.get("db").await.unwrap().id;
+        let second = pools.get("db").await.unwrap().id;
+        assert_eq!(first, second);
+        assert_eq!(1, pools.manager.connects.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn keys_pools_by_address() {
+        let pools = pools(PoolConfig::default());
+        let first = pools.get("db1").await.unwrap().id;
+        let second = pools.get("db2").await.unwrap().id;
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn discards_unhealthy_connections() {
+        let pools = pools(PoolConfig::default());
+        let mut connection = pools.get("db").await.unwrap();
+        connection.healthy = false;
+        drop(connection);
+        assert_eq!(1, pools.get("db").await.unwrap().id);
+    }
+
+    #[tokio::test]
+    async fn resets_released_connections() {
+        let pools = pools(PoolConfig {
+            max_size: 1,
+            ..Default::default()
+        });
+        pools.get("db").await.unwrap().dirty = true;
+        let connection = pools.get("db").await.unwrap();
+        assert_eq!(0, connection.id);
+        assert!(!connection.dirty);
+    }
+
+    #[tokio::test]
+    async fn discards_connections_which_cannot_be_reset() {
+        let pools = pools(PoolConfig {
+            max_size: 1,
+            ..Default::default()
+        });
+        pools.get("db").await.unwrap().resettable = false;
+        assert_eq!(1, pools.get("db").await.unwrap().id);
+    }
+
+    #[tokio::test]
+    async fn discard_does_not_return_to_pool() {
+        let pools = pools(PoolConfig::default());
+        pools.get("db").await.unwrap().discard();
+        assert_eq!(1, pools.get("db").await.unwrap().id);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn closes_expired_idle_connections() {
+        let pools = pools(PoolConfig {
+            idle_timeout: Duration::from_secs(10),
+            ..Default::default()
+        });
+        drop(pools.get("db").await.unwrap());
+        tokio::time::advance(Duration::from_secs(11)).await;
+        assert_eq!(1, pools.get("db").await.unwrap().id);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn times_out_when_exhausted() {
+        let pools = pools(PoolConfig {
+            max_size: 1,
+            acquire_timeout: Duration::from_secs(1),
+            ..Default::default()
+        });
+        let _held = pools.get("db").await.unwrap();
+        assert!(pools.get("db").await.is_err());
+    }
+}
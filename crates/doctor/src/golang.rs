@@ -0,0 +1,2 @@
+/// Diagnose TinyGo toolchain problems.
+pub mod tinygo;
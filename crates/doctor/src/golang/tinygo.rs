@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use semver::Version;
+
+use crate::toolchain::{any_build_command, manual_install, program, ToolStatus};
+use crate::{Diagnosis, Diagnostic, PatientApp, Treatment};
+
+// The oldest TinyGo release supported by the Spin Go SDK.
+const MIN_TINYGO_VERSION: Version = Version::new(0, 27, 0);
+
+const INSTALL_INSTRUCTIONS: &str =
+    "Install TinyGo by following the instructions at https://tinygo.org/getting-started/install/";
+
+/// TinyGoDiagnostic detects problems with the TinyGo toolchain.
+pub struct TinyGoDiagnostic {
+    // The TinyGo program to probe. Tests substitute a fake.
+    tinygo: PathBuf,
+}
+
+impl Default for TinyGoDiagnostic {
+    fn default() -> Self {
+        Self {
+            tinygo: "tinygo".into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Diagnostic for TinyGoDiagnostic {
+    type Diagnosis = TinyGoDiagnosis;
+
+    async fn diagnose(&self, patient: &PatientApp) -> Result<Vec<Self::Diagnosis>> {
+        if !any_build_command(patient, uses_tinygo)? {
+            return Ok(vec![]);
+        }
+
+        let diagnosis = match ToolStatus::probe(&self.tinygo, &["version"]).await? {
+            ToolStatus::NotInstalled => Some(TinyGoDiagnosis::TinyGoNotInstalled),
+            ToolStatus::Installed(Some(version)) if version < MIN_TINYGO_VERSION => {
+                Some(TinyGoDiagnosis::TinyGoOutdated(version))
+            }
+            ToolStatus::Installed(_) => None,
+        };
+        Ok(diagnosis.into_iter().collect())
+    }
+}
+
+fn uses_tinygo(command: &str) -> bool {
+    program(command) == "tinygo"
+}
+
+/// TinyGoDiagnosis represents a problem with the TinyGo toolchain.
+#[derive(Debug)]
+pub enum TinyGoDiagnosis {
+    /// TinyGo is not installed
+    TinyGoNotInstalled,
+    /// The installed TinyGo is older than the Spin Go SDK supports
+    TinyGoOutdated(Version),
+}
+
+impl Diagnosis for TinyGoDiagnosis {
+    fn description(&self) -> String {
+        match self {
+            Self::TinyGoNotInstalled => "The TinyGo compiler isn't installed".into(),
+            Self::TinyGoOutdated(version) => format!(
+                "TinyGo version {version} is older than the minimum supported version {MIN_TINYGO_VERSION}"
+            ),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::TinyGoNotInstalled => "TinyGoDiagnosis::TinyGoNotInstalled",
+            Self::TinyGoOutdated(_) => "TinyGoDiagnosis::TinyGoOutdated",
+        }
+    }
+
+    fn treatment(&self) -> Option<&dyn Treatment> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl Treatment for TinyGoDiagnosis {
+    fn summary(&self) -> String {
+        match self {
+            Self::TinyGoNotInstalled => "Install TinyGo",
+            Self::TinyGoOutdated(_) => "Upgrade TinyGo",
+        }
+        .into()
+    }
+
+    async fn dry_run(&self, _patient: &PatientApp) -> Result<String> {
+        Ok(INSTALL_INSTRUCTIONS.into())
+    }
+
+    async fn treat(&self, _patient: &mut PatientApp) -> Result<()> {
+        // TinyGo installation varies too much by platform to automate
+        manual_install(format!(
+            "{INSTALL_INSTRUCTIONS}, then re-run `spin doctor`."
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uses_tinygo() {
+        assert!(uses_tinygo(
+            "tinygo build -target=wasi -gc=leaking -no-debug -o main.wasm main.go"
+        ));
+        assert!(!uses_tinygo("go build -o main.wasm"));
+        assert!(!uses_tinygo("cargo build --target wasm32-wasi"));
+    }
+
+    #[cfg(unix)]
+    async fn diagnose(tinygo: PathBuf, patient: &PatientApp) -> Vec<TinyGoDiagnosis> {
+        TinyGoDiagnostic { tinygo }.diagnose(patient).await.unwrap()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_diagnose_tinygo() {
+        use crate::test::{fake_program, patient_built_with};
+
+        let dir = tempfile::tempdir().unwrap();
+        let patient = patient_built_with("tinygo build -target=wasi -o main.wasm main.go");
+
+        let current = fake_program(dir.path(), "current", "tinygo version 0.30.0", 0);
+        assert!(diagnose(current, &patient).await.is_empty());
+
+        let old = fake_program(dir.path(), "old", "tinygo version 0.26.0", 0);
+        assert!(matches!(
+            diagnose(old, &patient).await.as_slice(),
+            [TinyGoDiagnosis::TinyGoOutdated(_)]
+        ));
+
+        assert!(matches!(
+            diagnose(dir.path().join("missing"), &patient)
+                .await
+                .as_slice(),
+            [TinyGoDiagnosis::TinyGoNotInstalled]
+        ));
+
+        let other_app = patient_built_with("cargo build --target wasm32-wasi");
+        let diags = TinyGoDiagnostic {
+            tinygo: dir.path().join("missing"),
+        }
+        .diagnose(&other_app)
+        .await
+        .unwrap();
+        assert!(diags.is_empty());
+    }
+}
@@ -0,0 +1,2 @@
+/// Diagnose Node.js and js2wasm toolchain problems.
+pub mod node;
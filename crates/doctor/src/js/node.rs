@@ -0,0 +1,197 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use semver::Version;
+
+use crate::toolchain::{
+    any_build_command, manual_install, program, run_install_command, ToolStatus,
+};
+use crate::{Diagnosis, Diagnostic, PatientApp, Treatment};
+
+// The oldest Node.js release supported by the js2wasm plugin.
+const MIN_NODE_VERSION: Version = Version::new(18, 0, 0);
+
+const JS2WASM_PLUGIN: &str = "js2wasm";
+
+const NODE_INSTALL_INSTRUCTIONS: &str =
+    "Install Node.js by following the instructions at https://nodejs.org/en/download";
+
+/// NodeDiagnostic detects problems with the JavaScript toolchain: Node.js
+/// and the `js2wasm` Spin plugin.
+pub struct NodeDiagnostic {
+    // The Node.js program to probe. Tests substitute a fake.
+    node: PathBuf,
+}
+
+impl Default for NodeDiagnostic {
+    fn default() -> Self {
+        Self {
+            node: "node".into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Diagnostic for NodeDiagnostic {
+    type Diagnosis = NodeDiagnosis;
+
+    async fn diagnose(&self, patient: &PatientApp) -> Result<Vec<Self::Diagnosis>> {
+        if !any_build_command(patient, uses_js)? {
+            return Ok(vec![]);
+        }
+
+        let mut diagnoses = vec![];
+        match ToolStatus::probe(&self.node, &["--version"]).await? {
+            ToolStatus::NotInstalled => diagnoses.push(NodeDiagnosis::NodeNotInstalled),
+            ToolStatus::Installed(Some(version)) if version < MIN_NODE_VERSION => {
+                diagnoses.push(NodeDiagnosis::NodeOutdated(version))
+            }
+            ToolStatus::Installed(_) => (),
+        }
+        if !js2wasm_installed() {
+            diagnoses.push(NodeDiagnosis::Js2WasmNotInstalled);
+        }
+        Ok(diagnoses)
+    }
+}
+
+// JavaScript and TypeScript templates build through npm scripts which run
+// `spin js2wasm`.
+fn uses_js(command: &str) -> bool {
+    matches!(program(command), "npm" | "npx" | "node" | "yarn") || command.contains(JS2WASM_PLUGIN)
+}
+
+fn js2wasm_installed() -> bool {
+    spin_plugins::PluginStore::try_default()
+        .is_ok_and(|store| store.read_plugin_manifest(JS2WASM_PLUGIN).is_ok())
+}
+
+/// NodeDiagnosis represents a problem with the JavaScript toolchain.
+#[derive(Debug)]
+pub enum NodeDiagnosis {
+    /// Node.js is not installed
+    NodeNotInstalled,
+    /// The installed Node.js is older than js2wasm supports
+    NodeOutdated(Version),
+    /// The js2wasm plugin is not installed
+    Js2WasmNotInstalled,
+}
+
+impl Diagnosis for NodeDiagnosis {
+    fn description(&self) -> String {
+        match self {
+            Self::NodeNotInstalled => "Node.js isn't installed".into(),
+            Self::NodeOutdated(version) => format!(
+                "Node.js version {version} is older than the minimum supported version {MIN_NODE_VERSION}"
+            ),
+            Self::Js2WasmNotInstalled => {
+                format!("The Spin '{JS2WASM_PLUGIN}' plugin isn't installed")
+            }
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::NodeNotInstalled => "NodeDiagnosis::NodeNotInstalled",
+            Self::NodeOutdated(_) => "NodeDiagnosis::NodeOutdated",
+            Self::Js2WasmNotInstalled => "NodeDiagnosis::Js2WasmNotInstalled",
+        }
+    }
+
+    fn treatment(&self) -> Option<&dyn Treatment> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl Treatment for NodeDiagnosis {
+    fn summary(&self) -> String {
+        match self {
+            Self::NodeNotInstalled => "Install Node.js".into(),
+            Self::NodeOutdated(_) => "Upgrade Node.js".into(),
+            Self::Js2WasmNotInstalled => format!("Install the '{JS2WASM_PLUGIN}' plugin"),
+        }
+    }
+
+    async fn dry_run(&self, _patient: &PatientApp) -> Result<String> {
+        let message = match self {
+            Self::NodeNotInstalled | Self::NodeOutdated(_) => NODE_INSTALL_INSTRUCTIONS.into(),
+            Self::Js2WasmNotInstalled => format!(
+                "Run the following command:\n    `spin plugins install {JS2WASM_PLUGIN} --yes`"
+            ),
+        };
+        Ok(message)
+    }
+
+    async fn treat(&self, _patient: &mut PatientApp) -> Result<()> {
+        match self {
+            Self::NodeNotInstalled | Self::NodeOutdated(_) => manual_install(format!(
+                "{NODE_INSTALL_INSTRUCTIONS}, then re-run `spin doctor`."
+            )),
+            Self::Js2WasmNotInstalled => {
+                let spin_bin = std::env::current_exe().context("Couldn't find spin executable")?;
+                run_install_command(spin_bin, &["plugins", "install", JS2WASM_PLUGIN, "--yes"])
+                    .await
+            }
+        }
+    }
+
+    fn requires_confirmation(&self) -> bool {
+        matches!(self, Self::Js2WasmNotInstalled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uses_js() {
+        assert!(uses_js("npm run build"));
+        assert!(uses_js("spin js2wasm -o target/app.wasm dist/spin.js"));
+        assert!(!uses_js("cargo build --target wasm32-wasi"));
+        assert!(!uses_js("tinygo build -o main.wasm main.go"));
+    }
+
+    #[cfg(unix)]
+    async fn diagnose(node: PathBuf, patient: &PatientApp) -> Vec<NodeDiagnosis> {
+        NodeDiagnostic { node }
+            .diagnose(patient)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|d| !matches!(d, NodeDiagnosis::Js2WasmNotInstalled))
+            .collect()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_diagnose_node() {
+        use crate::test::{fake_program, patient_built_with};
+
+        // Whether js2wasm is installed depends on the machine running the
+        // tests, so only the Node.js diagnoses are checked.
+        let dir = tempfile::tempdir().unwrap();
+        let patient = patient_built_with("npm run build");
+
+        let current = fake_program(dir.path(), "current", "v20.11.0", 0);
+        assert!(diagnose(current, &patient).await.is_empty());
+
+        let old = fake_program(dir.path(), "old", "v16.20.2", 0);
+        assert!(matches!(
+            diagnose(old, &patient).await.as_slice(),
+            [NodeDiagnosis::NodeOutdated(_)]
+        ));
+
+        assert!(matches!(
+            diagnose(dir.path().join("missing"), &patient)
+                .await
+                .as_slice(),
+            [NodeDiagnosis::NodeNotInstalled]
+        ));
+
+        assert!(NodeDiagnosis::Js2WasmNotInstalled.requires_confirmation());
+        assert!(!NodeDiagnosis::NodeNotInstalled.requires_confirmation());
+    }
+}
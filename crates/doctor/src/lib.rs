@@ -8,12 +8,21 @@ use async_trait::async_trait;
 use spin_common::ui::quoted_path;
 use toml_edit::Document;
 
+/// Diagnoses for Go-specific problems.
+pub mod golang;
+/// Diagnoses for JavaScript-specific problems.
+pub mod js;
 /// Diagnoses for app manifest format problems.
 pub mod manifest;
+/// Diagnoses for Python-specific problems.
+pub mod python;
+/// Diagnoses for runtime config problems.
+pub mod runtime_config;
 /// Diagnose for Rust-specific problems.
 pub mod rustlang;
 /// Test helpers.
 pub mod test;
+mod toolchain;
 /// Diagnoses for Wasm source problems.
 pub mod wasm;
 
@@ -37,8 +46,14 @@ impl Checkup {
             .add_diagnostic::<manifest::upgrade::UpgradeDiagnostic>()
             .add_diagnostic::<manifest::version::VersionDiagnostic>()
             .add_diagnostic::<manifest::trigger::TriggerDiagnostic>()
+            .add_diagnostic::<manifest::outbound_hosts::OutboundHostsDiagnostic>()
+            .add_diagnostic::<runtime_config::validate::RuntimeConfigDiagnostic>()
             .add_diagnostic::<rustlang::target::TargetDiagnostic>() // Do toolchain checks _before_ build check
-            .add_diagnostic::<wasm::missing::WasmMissingDiagnostic>();
+            .add_diagnostic::<golang::tinygo::TinyGoDiagnostic>()
+            .add_diagnostic::<js::node::NodeDiagnostic>()
+            .add_diagnostic::<python::componentize::ComponentizePyDiagnostic>()
+            .add_diagnostic::<wasm::missing::WasmMissingDiagnostic>()
+            .add_diagnostic::<wasm::capabilities::CapabilitiesDiagnostic>();
         Ok(checkup)
     }
 
@@ -141,6 +156,11 @@ pub trait Diagnosis: Debug + Send + Sync + 'static {
     /// Return a human-friendly description of this problem.
     fn description(&self) -> String;
 
+    /// Return a short, stable identifier for the kind of problem, for
+    /// machine-readable output, such as `TriggerDiagnosis::MissingAppTrigger`.
+    /// This must not change between releases.
+    fn kind(&self) -> &'static str;
+
     /// Return true if this problem is "critical", i.e. if the app's
     /// configuration or environment is invalid. Return false for
     /// "non-critical" problems like deprecations.
@@ -175,6 +195,13 @@ pub trait Treatment: Sync {
     /// Attempt to fix this problem. Return Ok only if the problem is
     /// successfully fixed.
     async fn treat(&self, patient: &mut PatientApp) -> Result<()>;
+
+    /// Return true if this treatment changes the user's system outside the
+    /// app, such as by installing software, so should only be applied once
+    /// the user has confirmed it.
+    fn requires_confirmation(&self) -> bool {
+        false
+    }
 }
 
 /// Error returned by [`Treatment::dry_run`] if dry run isn't supported.
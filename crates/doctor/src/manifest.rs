@@ -3,10 +3,12 @@ use std::fs;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use spin_common::ui::quoted_path;
-use toml_edit::Document;
+use toml_edit::{Document, TableLike};
 
 use crate::Treatment;
 
+/// Diagnose invalid component outbound hosts.
+pub mod outbound_hosts;
 /// Diagnose app manifest trigger config problems.
 pub mod trigger;
 /// Diagnose old app manifest versions.
@@ -52,3 +54,38 @@ impl<T: ManifestTreatment + Sync> Treatment for T {
             .with_context(|| format!("failed to write fixed manifest to {}", quoted_path(path)))
     }
 }
+
+/// Returns the table of the component with the given ID, for either manifest
+/// version. Returns None for components that do not have their own table,
+/// such as inline trigger components.
+pub(crate) fn component_table<'a>(doc: &'a Document, id: &str) -> Option<&'a dyn TableLike> {
+    let components = doc.get("component")?;
+    if let Some(components) = components.as_array_of_tables() {
+        // Manifest V1: [[component]] with an id field
+        let component = components
+            .iter()
+            .find(|c| c.get("id").and_then(|v| v.as_str()) == Some(id))?;
+        Some(component)
+    } else {
+        // Manifest V2: [component.<id>]
+        components.get(id)?.as_table_like()
+    }
+}
+
+/// As [`component_table`], but returns the table mutably.
+pub(crate) fn component_table_mut<'a>(
+    doc: &'a mut Document,
+    id: &str,
+) -> Option<&'a mut dyn TableLike> {
+    let components = doc.get_mut("component")?;
+    if let Some(components) = components.as_array_of_tables_mut() {
+        // Manifest V1: [[component]] with an id field
+        let component = components
+            .iter_mut()
+            .find(|c| c.get("id").and_then(|v| v.as_str()) == Some(id))?;
+        Some(component)
+    } else {
+        // Manifest V2: [component.<id>]
+        components.get_mut(id)?.as_table_like_mut()
+    }
+}
@@ -0,0 +1,193 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use spin_outbound_networking::AllowedHostsConfig;
+use toml_edit::{Array, Document, Item};
+
+use crate::{Diagnosis, Diagnostic, PatientApp, Treatment};
+
+use super::{component_table, component_table_mut, ManifestTreatment};
+
+const ALLOWED_OUTBOUND_HOSTS: &str = "allowed_outbound_hosts";
+const INSECURE_ALLOW_ALL: &str = "insecure:allow-all";
+const ALLOW_ALL: &str = "*://*:*";
+
+/// OutboundHostsDiagnostic detects invalid component `allowed_outbound_hosts` entries.
+#[derive(Default)]
+pub struct OutboundHostsDiagnostic;
+
+#[async_trait]
+impl Diagnostic for OutboundHostsDiagnostic {
+    type Diagnosis = OutboundHostsDiagnosis;
+
+    async fn diagnose(&self, patient: &PatientApp) -> Result<Vec<Self::Diagnosis>> {
+        let manifest = spin_manifest::manifest_from_str(&patient.manifest_doc.to_string())?;
+
+        let mut diags = vec![];
+        for (id, component) in manifest.components {
+            let hosts = &component.allowed_outbound_hosts;
+            let Err(err) = AllowedHostsConfig::validate(hosts) else {
+                continue;
+            };
+            let component_id = id.to_string();
+            let has_table = component_table(&patient.manifest_doc, &component_id)
+                .is_some_and(|c| c.contains_key(ALLOWED_OUTBOUND_HOSTS));
+            let corrected = corrected_hosts(hosts).filter(|_| has_table);
+            diags.push(OutboundHostsDiagnosis {
+                component_id,
+                error: format!("{err:#}"),
+                corrected,
+            });
+        }
+        Ok(diags)
+    }
+}
+
+// Returns a valid version of the given hosts if every invalid entry has an
+// unambiguous correction.
+fn corrected_hosts(hosts: &[String]) -> Option<Vec<String>> {
+    if hosts.len() == 1 && hosts[0] == INSECURE_ALLOW_ALL {
+        return Some(vec![ALLOW_ALL.to_owned()]);
+    }
+    let corrected = hosts
+        .iter()
+        .map(|host| {
+            if AllowedHostsConfig::validate(&[host]).is_ok() {
+                return Some(host.clone());
+            }
+            let host = host.trim();
+            let candidate = match host.split_once("://") {
+                // Strip any path
+                Some((scheme, rest)) => {
+                    let authority = rest.split('/').next().unwrap_or_default();
+                    format!("{scheme}://{authority}")
+                }
+                // Assume HTTPS if no scheme was given
+                None => format!("https://{}", host.trim_end_matches('/')),
+            };
+            AllowedHostsConfig::validate(&[&candidate])
+                .is_ok()
+                .then_some(candidate)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    AllowedHostsConfig::validate(&corrected)
+        .is_ok()
+        .then_some(corrected)
+}
+
+/// OutboundHostsDiagnosis represents a component with invalid `allowed_outbound_hosts`.
+#[derive(Debug)]
+pub struct OutboundHostsDiagnosis {
+    component_id: String,
+    error: String,
+    corrected: Option<Vec<String>>,
+}
+
+impl Diagnosis for OutboundHostsDiagnosis {
+    fn description(&self) -> String {
+        format!(
+            "Component {:?} has invalid allowed_outbound_hosts: {}",
+            self.component_id, self.error
+        )
+    }
+
+    fn kind(&self) -> &'static str {
+        "OutboundHostsDiagnosis::InvalidAllowedOutboundHosts"
+    }
+
+    fn treatment(&self) -> Option<&dyn Treatment> {
+        self.corrected.is_some().then_some(self)
+    }
+}
+
+#[async_trait]
+impl ManifestTreatment for OutboundHostsDiagnosis {
+    fn summary(&self) -> String {
+        format!(
+            "Correct allowed_outbound_hosts for component {:?}",
+            self.component_id
+        )
+    }
+
+    async fn treat_manifest(&self, doc: &mut Document) -> anyhow::Result<()> {
+        let corrected = self.corrected.as_ref().context("no correction available")?;
+        let component = component_table_mut(doc, &self.component_id)
+            .with_context(|| format!("couldn't find component {:?}", self.component_id))?;
+        let hosts: Array = corrected.iter().map(String::as_str).collect();
+        component.insert(ALLOWED_OUTBOUND_HOSTS, Item::Value(hosts.into()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::{assert_single_diagnosis, TestPatient};
+
+    use super::*;
+
+    fn manifest_with_hosts(hosts: &str) -> String {
+        format!(
+            r#"
+            spin_manifest_version = 2
+            [application]
+            name = "outbound-hosts-test"
+            [[trigger.http]]
+            route = "/"
+            component = "test"
+            [component.test]
+            source = "test.wasm"
+            allowed_outbound_hosts = {hosts}
+            "#
+        )
+    }
+
+    #[tokio::test]
+    async fn test_valid_hosts() {
+        let patient = TestPatient::from_toml_str(manifest_with_hosts(
+            r#"["https://example.com", "redis://{{ host }}:6379"]"#,
+        ));
+        let diags = OutboundHostsDiagnostic.diagnose(&patient).await.unwrap();
+        assert!(diags.is_empty(), "expected no diagnoses, got {diags:?}");
+    }
+
+    #[tokio::test]
+    async fn test_fixable_hosts() {
+        let mut patient = TestPatient::from_toml_str(manifest_with_hosts(
+            r#"["example.com", "https://api.example.com/v1", "mysql://db:3306"]"#,
+        ));
+        let diag = assert_single_diagnosis::<OutboundHostsDiagnostic>(&patient).await;
+        diag.treatment()
+            .expect("should be treatable")
+            .treat(&mut patient)
+            .await
+            .unwrap();
+        let hosts = patient.manifest_doc["component"]["test"][ALLOWED_OUTBOUND_HOSTS]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "https://example.com",
+                "https://api.example.com",
+                "mysql://db:3306"
+            ],
+            hosts
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insecure_allow_all() {
+        let patient = TestPatient::from_toml_str(manifest_with_hosts(r#"["insecure:allow-all"]"#));
+        let diag = assert_single_diagnosis::<OutboundHostsDiagnostic>(&patient).await;
+        assert_eq!(Some(vec![ALLOW_ALL.to_owned()]), diag.corrected);
+    }
+
+    #[tokio::test]
+    async fn test_unfixable_hosts() {
+        let patient =
+            TestPatient::from_toml_str(manifest_with_hosts(r#"["https://ex*ample.com"]"#));
+        let diag = assert_single_diagnosis::<OutboundHostsDiagnostic>(&patient).await;
+        assert!(diag.treatment().is_none());
+    }
+}
@@ -125,6 +125,19 @@ impl Diagnosis for TriggerDiagnosis {
         }
     }
 
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::MissingAppTrigger => "TriggerDiagnosis::MissingAppTrigger",
+            Self::InvalidAppTrigger(_) => "TriggerDiagnosis::InvalidAppTrigger",
+            Self::HttpComponentTriggerMissingRoute(..) => {
+                "TriggerDiagnosis::HttpComponentTriggerMissingRoute"
+            }
+            Self::InvalidHttpComponentTrigger(..) => {
+                "TriggerDiagnosis::InvalidHttpComponentTrigger"
+            }
+        }
+    }
+
     fn treatment(&self) -> Option<&dyn Treatment> {
         match self {
             Self::MissingAppTrigger => Some(self),
@@ -222,6 +235,7 @@ mod tests {
         let diag =
             run_broken_test::<TriggerDiagnostic>("manifest_trigger", "missing_app_trigger").await;
         assert!(matches!(diag, TriggerDiagnosis::MissingAppTrigger));
+        assert_eq!("TriggerDiagnosis::MissingAppTrigger", diag.kind());
     }
 
     #[tokio::test]
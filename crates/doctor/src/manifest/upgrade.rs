@@ -33,6 +33,10 @@ impl Diagnosis for UpgradeDiagnosis {
         "Version 1 manifest can be upgraded to version 2".into()
     }
 
+    fn kind(&self) -> &'static str {
+        "UpgradeDiagnosis::UpgradableManifest"
+    }
+
     fn is_critical(&self) -> bool {
         false
     }
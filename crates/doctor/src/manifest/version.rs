@@ -74,6 +74,14 @@ impl Diagnosis for VersionDiagnosis {
         }
     }
 
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::MissingVersion => "VersionDiagnosis::MissingVersion",
+            Self::OldVersionKey => "VersionDiagnosis::OldVersionKey",
+            Self::WrongValue(_) => "VersionDiagnosis::WrongValue",
+        }
+    }
+
     fn is_critical(&self) -> bool {
         !matches!(self, Self::OldVersionKey)
     }
@@ -0,0 +1,2 @@
+/// Diagnose componentize-py toolchain problems.
+pub mod componentize;
@@ -0,0 +1,187 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use semver::Version;
+
+use crate::toolchain::{
+    any_build_command, manual_install, program, run_install_command, ToolStatus,
+};
+use crate::{Diagnosis, Diagnostic, PatientApp, Treatment};
+
+// The oldest componentize-py release supported by the Spin Python SDK.
+const MIN_COMPONENTIZE_PY_VERSION: Version = Version::new(0, 13, 0);
+
+const COMPONENTIZE_PY: &str = "componentize-py";
+
+/// ComponentizePyDiagnostic detects problems with the Python toolchain.
+pub struct ComponentizePyDiagnostic {
+    // The componentize-py program to probe. Tests substitute a fake.
+    componentize_py: PathBuf,
+}
+
+impl Default for ComponentizePyDiagnostic {
+    fn default() -> Self {
+        Self {
+            componentize_py: COMPONENTIZE_PY.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Diagnostic for ComponentizePyDiagnostic {
+    type Diagnosis = ComponentizePyDiagnosis;
+
+    async fn diagnose(&self, patient: &PatientApp) -> Result<Vec<Self::Diagnosis>> {
+        if !any_build_command(patient, uses_componentize_py)? {
+            return Ok(vec![]);
+        }
+
+        let diagnosis = match ToolStatus::probe(&self.componentize_py, &["--version"]).await? {
+            ToolStatus::NotInstalled => Some(ComponentizePyDiagnosis::NotInstalled),
+            ToolStatus::Installed(Some(version)) if version < MIN_COMPONENTIZE_PY_VERSION => {
+                Some(ComponentizePyDiagnosis::Outdated(version))
+            }
+            ToolStatus::Installed(_) => None,
+        };
+        Ok(diagnosis.into_iter().collect())
+    }
+}
+
+fn uses_componentize_py(command: &str) -> bool {
+    program(command) == COMPONENTIZE_PY
+}
+
+/// ComponentizePyDiagnosis represents a problem with the Python toolchain.
+#[derive(Debug)]
+pub enum ComponentizePyDiagnosis {
+    /// componentize-py is not installed
+    NotInstalled,
+    /// The installed componentize-py is older than the Spin Python SDK supports
+    Outdated(Version),
+}
+
+fn python_program() -> &'static str {
+    if cfg!(windows) {
+        "python"
+    } else {
+        "python3"
+    }
+}
+
+impl Diagnosis for ComponentizePyDiagnosis {
+    fn description(&self) -> String {
+        match self {
+            Self::NotInstalled => format!("{COMPONENTIZE_PY} isn't installed"),
+            Self::Outdated(version) => format!(
+                "{COMPONENTIZE_PY} version {version} is older than the minimum supported version {MIN_COMPONENTIZE_PY_VERSION}"
+            ),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::NotInstalled => "ComponentizePyDiagnosis::NotInstalled",
+            Self::Outdated(_) => "ComponentizePyDiagnosis::Outdated",
+        }
+    }
+
+    fn treatment(&self) -> Option<&dyn Treatment> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl Treatment for ComponentizePyDiagnosis {
+    fn summary(&self) -> String {
+        match self {
+            Self::NotInstalled => format!("Install {COMPONENTIZE_PY}"),
+            Self::Outdated(_) => format!("Upgrade {COMPONENTIZE_PY}"),
+        }
+    }
+
+    async fn dry_run(&self, _patient: &PatientApp) -> Result<String> {
+        Ok(format!(
+            "Run the following command:\n    `{} -m pip install --upgrade {COMPONENTIZE_PY}`",
+            python_program()
+        ))
+    }
+
+    async fn treat(&self, _patient: &mut PatientApp) -> Result<()> {
+        if let ToolStatus::NotInstalled =
+            ToolStatus::probe(python_program(), &["--version"]).await?
+        {
+            return manual_install(
+                "Install Python 3 from https://www.python.org/downloads/, then re-run `spin doctor`.",
+            );
+        }
+        run_install_command(
+            python_program(),
+            &["-m", "pip", "install", "--upgrade", COMPONENTIZE_PY],
+        )
+        .await
+    }
+
+    fn requires_confirmation(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uses_componentize_py() {
+        assert!(uses_componentize_py(
+            "componentize-py -w spin-http componentize app -o app.wasm"
+        ));
+        assert!(!uses_componentize_py("npm run build"));
+    }
+
+    #[cfg(unix)]
+    async fn diagnose(
+        componentize_py: PathBuf,
+        patient: &PatientApp,
+    ) -> Vec<ComponentizePyDiagnosis> {
+        ComponentizePyDiagnostic { componentize_py }
+            .diagnose(patient)
+            .await
+            .unwrap()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_diagnose_componentize_py() {
+        use crate::test::{fake_program, patient_built_with};
+
+        let dir = tempfile::tempdir().unwrap();
+        let patient =
+            patient_built_with("componentize-py -w spin-http componentize app -o app.wasm");
+
+        let current = fake_program(dir.path(), "current", "componentize-py 0.13.1", 0);
+        assert!(diagnose(current, &patient).await.is_empty());
+
+        let old = fake_program(dir.path(), "old", "componentize-py 0.12.0", 0);
+        assert!(matches!(
+            diagnose(old, &patient).await.as_slice(),
+            [ComponentizePyDiagnosis::Outdated(_)]
+        ));
+
+        // e.g. a pyenv shim for a Python version without componentize-py
+        let shim = fake_program(
+            dir.path(),
+            "shim",
+            "componentize-py: command not found",
+            127,
+        );
+        assert!(matches!(
+            diagnose(shim, &patient).await.as_slice(),
+            [ComponentizePyDiagnosis::NotInstalled]
+        ));
+
+        let diagnosis = &diagnose(dir.path().join("missing"), &patient).await[0];
+        assert!(matches!(diagnosis, ComponentizePyDiagnosis::NotInstalled));
+        assert!(diagnosis.treatment().unwrap().requires_confirmation());
+    }
+}
@@ -0,0 +1,18 @@
+/// Diagnose invalid runtime config files.
+pub mod validate;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use spin_common::paths::parent_dir;
+
+use crate::PatientApp;
+
+/// The conventional name of the runtime config file, alongside the app manifest.
+pub const RUNTIME_CONFIG_FILE_NAME: &str = "runtime-config.toml";
+
+/// Returns the path of the app's runtime config file, if it has one.
+pub fn runtime_config_path(patient: &PatientApp) -> Result<Option<PathBuf>> {
+    let path = parent_dir(&patient.manifest_path)?.join(RUNTIME_CONFIG_FILE_NAME);
+    Ok(path.is_file().then_some(path))
+}
@@ -0,0 +1,361 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use spin_common::ui::quoted_path;
+use toml_edit::{Document, Item, Table};
+
+use crate::{Diagnosis, Diagnostic, PatientApp, Treatment};
+
+use super::runtime_config_path;
+
+// Labels which are always available without runtime configuration.
+const DEFAULT_LABEL: &str = "default";
+
+/// RuntimeConfigDiagnostic detects problems with the app's runtime config file.
+#[derive(Default)]
+pub struct RuntimeConfigDiagnostic;
+
+#[async_trait]
+impl Diagnostic for RuntimeConfigDiagnostic {
+    type Diagnosis = RuntimeConfigDiagnosis;
+
+    async fn diagnose(&self, patient: &PatientApp) -> Result<Vec<Self::Diagnosis>> {
+        let Some(path) = runtime_config_path(patient)? else {
+            return Ok(vec![]);
+        };
+
+        let opts = match RuntimeConfigStores::parse_file(&path) {
+            Ok(opts) => opts,
+            Err(err) => {
+                return Ok(vec![RuntimeConfigDiagnosis::Invalid {
+                    path,
+                    error: format!("{err:#}"),
+                }])
+            }
+        };
+
+        let manifest = spin_manifest::manifest_from_str(&patient.manifest_doc.to_string())?;
+        let kv_labels = manifest
+            .components
+            .values()
+            .flat_map(|c| c.key_value_stores.iter());
+        let sqlite_labels = manifest
+            .components
+            .values()
+            .flat_map(|c| c.sqlite_databases.iter());
+
+        let undefined_kv = kv_labels
+            .filter(|label| !opts.key_value_stores.contains_key(*label))
+            .map(|label| (StoreKind::KeyValue, label.clone()));
+        let undefined_sqlite = sqlite_labels
+            .filter(|label| !opts.sqlite_databases.contains_key(*label))
+            .map(|label| (StoreKind::Sqlite, label.clone()));
+
+        let diags = undefined_kv
+            .chain(undefined_sqlite)
+            .filter(|(_, label)| label != DEFAULT_LABEL)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|(kind, label)| RuntimeConfigDiagnosis::UndefinedLabel {
+                path: path.clone(),
+                kind,
+                label,
+            })
+            .collect();
+        Ok(diags)
+    }
+}
+
+// The store sections of the runtime config schema, which are all this
+// diagnostic needs. Other sections are left to the Spin runtime to validate.
+#[derive(Deserialize)]
+struct RuntimeConfigStores {
+    #[serde(rename = "key_value_store", default)]
+    key_value_stores: HashMap<String, StoreOpts>,
+    #[serde(rename = "sqlite_database", default)]
+    sqlite_databases: HashMap<String, StoreOpts>,
+}
+
+#[derive(Deserialize)]
+struct StoreOpts {
+    #[serde(rename = "type")]
+    store_type: String,
+}
+
+impl RuntimeConfigStores {
+    fn parse_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("couldn't read {}", quoted_path(path)))?;
+        let stores: Self = toml::from_str(&contents)
+            .with_context(|| format!("couldn't parse {}", quoted_path(path)))?;
+        for (kind, opts) in [
+            (StoreKind::KeyValue, &stores.key_value_stores),
+            (StoreKind::Sqlite, &stores.sqlite_databases),
+        ] {
+            for (label, opts) in opts {
+                anyhow::ensure!(
+                    kind.store_types().contains(&opts.store_type.as_str()),
+                    "[{}.{label}] has unknown type {:?}; expected one of {}",
+                    kind.table_name(),
+                    opts.store_type,
+                    kind.store_types().join(", ")
+                );
+            }
+        }
+        Ok(stores)
+    }
+}
+
+/// A kind of store that is defined in runtime config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StoreKind {
+    /// A key-value store (`[key_value_store.<label>]`)
+    KeyValue,
+    /// A SQLite database (`[sqlite_database.<label>]`)
+    Sqlite,
+}
+
+impl StoreKind {
+    fn table_name(&self) -> &'static str {
+        match self {
+            Self::KeyValue => "key_value_store",
+            Self::Sqlite => "sqlite_database",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::KeyValue => "key-value store",
+            Self::Sqlite => "SQLite database",
+        }
+    }
+
+    // The values of `type` which Spin supports for this kind of store.
+    fn store_types(&self) -> &'static [&'static str] {
+        match self {
+            Self::KeyValue => &["spin", "redis", "azure_cosmos"],
+            Self::Sqlite => &["spin", "libsql"],
+        }
+    }
+
+    fn default_path(&self, label: &str) -> String {
+        match self {
+            Self::KeyValue => format!(".spin/{label}.kv.db"),
+            Self::Sqlite => format!(".spin/{label}.sqlite.db"),
+        }
+    }
+}
+
+/// RuntimeConfigDiagnosis represents a problem with the app's runtime config file.
+#[derive(Debug)]
+pub enum RuntimeConfigDiagnosis {
+    /// The runtime config file does not match the runtime config schema
+    Invalid {
+        /// Path to the runtime config file
+        path: PathBuf,
+        /// The parse error
+        error: String,
+    },
+    /// A component uses a store label that the runtime config file does not define
+    UndefinedLabel {
+        /// Path to the runtime config file
+        path: PathBuf,
+        /// The kind of store
+        kind: StoreKind,
+        /// The undefined label
+        label: String,
+    },
+}
+
+impl RuntimeConfigDiagnosis {
+    fn path(&self) -> &Path {
+        match self {
+            Self::Invalid { path, .. } | Self::UndefinedLabel { path, .. } => path,
+        }
+    }
+
+    fn treat_doc(&self, doc: &mut Document) -> Result<()> {
+        let Self::UndefinedLabel { kind, label, .. } = self else {
+            anyhow::bail!("cannot be fixed");
+        };
+        let stores = doc
+            .entry(kind.table_name())
+            .or_insert_with(|| {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            })
+            .as_table_mut()
+            .with_context(|| format!("existing {} value is not a table", kind.table_name()))?;
+        let mut store = Table::new();
+        store.insert("type", "spin".into());
+        store.insert("path", kind.default_path(label).into());
+        stores.insert(label, Item::Table(store));
+        Ok(())
+    }
+
+    fn read_doc(&self) -> Result<Document> {
+        let path = self.path();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("couldn't read {}", quoted_path(path)))?;
+        contents
+            .parse()
+            .with_context(|| format!("couldn't parse {} as TOML", quoted_path(path)))
+    }
+}
+
+impl Diagnosis for RuntimeConfigDiagnosis {
+    fn description(&self) -> String {
+        match self {
+            Self::Invalid { path, error } => {
+                format!(
+                    "Runtime config file {} is invalid: {error}",
+                    quoted_path(path)
+                )
+            }
+            Self::UndefinedLabel { path, kind, label } => format!(
+                "{} {label:?} is used by a component but not defined in {}",
+                capitalize(kind.description()),
+                quoted_path(path)
+            ),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Invalid { .. } => "RuntimeConfigDiagnosis::Invalid",
+            Self::UndefinedLabel { .. } => "RuntimeConfigDiagnosis::UndefinedLabel",
+        }
+    }
+
+    fn treatment(&self) -> Option<&dyn Treatment> {
+        match self {
+            Self::Invalid { .. } => None,
+            Self::UndefinedLabel { .. } => Some(self),
+        }
+    }
+}
+
+#[async_trait]
+impl Treatment for RuntimeConfigDiagnosis {
+    fn summary(&self) -> String {
+        match self {
+            Self::UndefinedLabel { kind, label, .. } => {
+                format!("Define a local {} {label:?}", kind.description())
+            }
+            _ => "[invalid treatment]".into(),
+        }
+    }
+
+    async fn dry_run(&self, _patient: &PatientApp) -> Result<String> {
+        let before = self.read_doc()?;
+        let mut after = before.clone();
+        self.treat_doc(&mut after)?;
+        let (before, after) = (before.to_string(), after.to_string());
+        let diff = similar::udiff::unified_diff(Default::default(), &before, &after, 1, None);
+        Ok(format!(
+            "Apply the following diff to {}:\n{}",
+            quoted_path(self.path()),
+            diff
+        ))
+    }
+
+    async fn treat(&self, _patient: &mut PatientApp) -> Result<()> {
+        let mut doc = self.read_doc()?;
+        self.treat_doc(&mut doc)?;
+        let path = self.path();
+        std::fs::write(path, doc.to_string()).with_context(|| {
+            format!(
+                "failed to write fixed runtime config to {}",
+                quoted_path(path)
+            )
+        })
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+        spin_manifest_version = 2
+        [application]
+        name = "runtime-config-test"
+        [[trigger.http]]
+        route = "/"
+        component = "test"
+        [component.test]
+        source = "test.wasm"
+        key_value_stores = ["default", "cache"]
+        sqlite_databases = ["default", "orders"]
+    "#;
+
+    fn patient_with_runtime_config(runtime_config: &str) -> (tempfile::TempDir, PatientApp) {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = dir.path().join("spin.toml");
+        std::fs::write(&manifest_path, MANIFEST).unwrap();
+        std::fs::write(dir.path().join("runtime-config.toml"), runtime_config).unwrap();
+        let patient = PatientApp::new(manifest_path).unwrap();
+        (dir, patient)
+    }
+
+    #[tokio::test]
+    async fn test_correct() {
+        let (_dir, patient) = patient_with_runtime_config(
+            r#"
+            [key_value_store.cache]
+            type = "redis"
+            url = "redis://localhost"
+            [sqlite_database.orders]
+            type = "spin"
+            path = "orders.db"
+            "#,
+        );
+        let diags = RuntimeConfigDiagnostic.diagnose(&patient).await.unwrap();
+        assert!(diags.is_empty(), "expected no diagnoses, got {diags:?}");
+    }
+
+    #[tokio::test]
+    async fn test_invalid() {
+        let (_dir, patient) = patient_with_runtime_config(
+            r#"
+            [key_value_store.cache]
+            type = "not-a-store-type"
+            "#,
+        );
+        let diags = RuntimeConfigDiagnostic.diagnose(&patient).await.unwrap();
+        assert_eq!(1, diags.len(), "expected one diagnosis, got {diags:?}");
+        assert!(matches!(diags[0], RuntimeConfigDiagnosis::Invalid { .. }));
+        assert!(diags[0].treatment().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_undefined_labels() {
+        let (_dir, mut patient) = patient_with_runtime_config("");
+        let diags = RuntimeConfigDiagnostic.diagnose(&patient).await.unwrap();
+        assert_eq!(2, diags.len(), "expected two diagnoses, got {diags:?}");
+
+        for diag in &diags {
+            diag.treatment()
+                .expect("should be treatable")
+                .treat(&mut patient)
+                .await
+                .unwrap();
+        }
+        let diags = RuntimeConfigDiagnostic.diagnose(&patient).await.unwrap();
+        assert!(diags.is_empty(), "expected fixed config, got {diags:?}");
+    }
+}
@@ -133,6 +133,13 @@ impl Diagnosis for TargetDiagnosis {
         }
     }
 
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::RustNotInstalled => "TargetDiagnosis::RustNotInstalled",
+            Self::WasmTargetNotInstalled => "TargetDiagnosis::WasmTargetNotInstalled",
+        }
+    }
+
     fn treatment(&self) -> Option<&dyn Treatment> {
         Some(self)
     }
@@ -57,6 +57,41 @@ pub async fn assert_single_diagnosis<D: Diagnostic + Default>(
     diags.into_iter().next().unwrap()
 }
 
+/// Writes an executable script to `dir` which prints `output` and exits with
+/// `exit_code`, for standing in for toolchain programs.
+#[cfg(unix)]
+pub fn fake_program(dir: &Path, name: &str, output: &str, exit_code: i32) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join(name);
+    std::fs::write(
+        &path,
+        format!("#!/bin/sh\necho '{output}'\nexit {exit_code}\n"),
+    )
+    .expect("writing fake program");
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+        .expect("making fake program executable");
+    path
+}
+
+/// A patient with a single component built by `build_command`.
+pub fn patient_built_with(build_command: &str) -> TestPatient {
+    TestPatient::from_toml_str(format!(
+        r#"
+        spin_manifest_version = 2
+        [application]
+        name = "toolchain-test"
+        [[trigger.http]]
+        route = "/"
+        component = "test"
+        [component.test]
+        source = "app.wasm"
+        [component.test.build]
+        command = {build_command:?}
+        "#
+    ))
+}
+
 fn test_file_path(prefix: &str, suffix: &str) -> PathBuf {
     format!("tests/data/{prefix}_{suffix}.toml").into()
 }
@@ -0,0 +1,151 @@
+use std::{ffi::OsStr, path::Path};
+
+use anyhow::{Context, Result};
+use semver::Version;
+
+use crate::{PatientApp, StopDiagnosing};
+
+/// Returns true if the build command of any component in the app satisfies
+/// the given predicate.
+pub(crate) fn any_build_command(
+    patient: &PatientApp,
+    predicate: impl Fn(&str) -> bool,
+) -> Result<bool> {
+    let manifest_str = patient.manifest_doc.to_string();
+    let manifest = spin_manifest::manifest_from_str(&manifest_str)?;
+    Ok(manifest.components.values().any(|c| {
+        c.build
+            .as_ref()
+            .is_some_and(|b| predicate(b.command.trim()))
+    }))
+}
+
+/// Returns the program run by a build command.
+pub(crate) fn program(command: &str) -> &str {
+    command.split_whitespace().next().unwrap_or_default()
+}
+
+/// Whether a toolchain binary is installed, and if so its version.
+pub(crate) enum ToolStatus {
+    NotInstalled,
+    Installed(Option<Version>),
+}
+
+impl ToolStatus {
+    /// Runs the program with the given arguments (which should make it print
+    /// its version) and parses the version from its output. A program which
+    /// exits unsuccessfully, such as a version manager shim for a tool that
+    /// isn't installed in the active environment, is treated as not installed.
+    pub(crate) async fn probe(program: impl AsRef<Path>, args: &[&str]) -> Result<Self> {
+        let program = program.as_ref();
+        let output = tokio::process::Command::new(program)
+            .args(args)
+            .output()
+            .await;
+        match output {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::NotInstalled),
+            Err(e) => anyhow::bail!(
+                "Failed to run `{} {}`: {e:#}",
+                program.display(),
+                args.join(" ")
+            ),
+            Ok(output) if !output.status.success() => {
+                tracing::debug!(
+                    "`{} {}` failed with {}: {}",
+                    program.display(),
+                    args.join(" "),
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                Ok(Self::NotInstalled)
+            }
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                Ok(Self::Installed(parse_version(&stdout)))
+            }
+        }
+    }
+}
+
+/// Finds the first version number, such as `v18.17.0` or `1.21`, in the given text.
+pub(crate) fn parse_version(text: &str) -> Option<Version> {
+    text.split_whitespace().find_map(|word| {
+        let word = word.trim_start_matches('v');
+        Version::parse(word)
+            .or_else(|_| Version::parse(&format!("{word}.0")))
+            .ok()
+    })
+}
+
+/// Runs an install command, failing if it does not succeed.
+pub(crate) async fn run_install_command(program: impl AsRef<OsStr>, args: &[&str]) -> Result<()> {
+    let program = program.as_ref();
+    let display = format!("`{} {}`", program.to_string_lossy(), args.join(" "));
+    let status = tokio::process::Command::new(program)
+        .args(args)
+        .status()
+        .await
+        .with_context(|| format!("Failed to run installation command {display}"))?;
+    anyhow::ensure!(
+        status.success(),
+        "Installation command {display} failed: {status}"
+    );
+    Ok(())
+}
+
+/// Stops diagnosing so that the user can follow manual install instructions.
+pub(crate) fn manual_install(instructions: impl Into<String>) -> Result<()> {
+    Err(anyhow::anyhow!(StopDiagnosing::new(instructions)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            Some(Version::new(0, 30, 0)),
+            parse_version("tinygo version 0.30.0 linux/amd64 (using go version go1.21.1)")
+        );
+        assert_eq!(Some(Version::new(18, 17, 1)), parse_version("v18.17.1\n"));
+        assert_eq!(
+            Some(Version::new(0, 13, 0)),
+            parse_version("componentize-py 0.13")
+        );
+        assert_eq!(None, parse_version("command not understood"));
+    }
+
+    #[test]
+    fn test_program() {
+        assert_eq!("npm", program("npm run build"));
+        assert_eq!("", program(""));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_probe() {
+        use crate::test::fake_program;
+
+        let dir = tempfile::tempdir().unwrap();
+
+        let installed = fake_program(dir.path(), "installed", "tool 1.2.3", 0);
+        assert!(matches!(
+            ToolStatus::probe(&installed, &["--version"]).await.unwrap(),
+            ToolStatus::Installed(Some(v)) if v == Version::new(1, 2, 3)
+        ));
+
+        let failing = fake_program(dir.path(), "failing", "tool 1.2.3", 1);
+        assert!(matches!(
+            ToolStatus::probe(&failing, &["--version"]).await.unwrap(),
+            ToolStatus::NotInstalled
+        ));
+
+        assert!(matches!(
+            ToolStatus::probe(dir.path().join("missing"), &["--version"])
+                .await
+                .unwrap(),
+            ToolStatus::NotInstalled
+        ));
+    }
+}
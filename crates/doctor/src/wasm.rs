@@ -1,3 +1,5 @@
+/// Diagnose components using capabilities they have not been granted.
+pub mod capabilities;
 /// Diagnose missing Wasm sources.
 pub mod missing;
 
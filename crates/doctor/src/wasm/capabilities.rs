@@ -0,0 +1,316 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use toml_edit::{Array, Document, Item, Value};
+use wasmparser::{Parser, Payload};
+
+use crate::{
+    manifest::{component_table, component_table_mut},
+    Diagnosis, PatientApp, Treatment,
+};
+
+use super::{PatientWasm, WasmDiagnostic};
+
+/// CapabilitiesDiagnostic detects components which import Spin host
+/// interfaces without being granted access to the corresponding resources.
+#[derive(Default)]
+pub struct CapabilitiesDiagnostic;
+
+#[async_trait]
+impl WasmDiagnostic for CapabilitiesDiagnostic {
+    type Diagnosis = MissingCapability;
+
+    async fn diagnose_wasm(
+        &self,
+        app: &PatientApp,
+        wasm: PatientWasm,
+    ) -> anyhow::Result<Vec<Self::Diagnosis>> {
+        // Missing or unreadable sources are diagnosed elsewhere
+        let Some(path) = wasm.abs_source_path() else {
+            return Ok(vec![]);
+        };
+        let Ok(bytes) = tokio::fs::read(&path).await else {
+            return Ok(vec![]);
+        };
+        let imports = match imported_interfaces(&bytes) {
+            Ok(imports) => imports,
+            Err(e) => {
+                tracing::debug!("Couldn't read imports of {}: {e:#}", path.display());
+                return Ok(vec![]);
+            }
+        };
+
+        let has_table = component_table(&app.manifest_doc, wasm.component_id()).is_some();
+        let used = imports
+            .iter()
+            .filter_map(|name| Capability::for_import(name))
+            .collect::<BTreeSet<_>>();
+        let diags = used
+            .into_iter()
+            .filter(|capability| !capability.is_granted(&wasm))
+            .map(|capability| MissingCapability {
+                component_id: wasm.component_id().to_owned(),
+                capability,
+                fixable: has_table && capability.default_label().is_some(),
+            })
+            .collect();
+        Ok(diags)
+    }
+}
+
+/// A Spin host capability which requires a manifest grant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Capability {
+    /// Key-value storage, granted by `key_value_stores`
+    KeyValue,
+    /// SQLite databases, granted by `sqlite_databases`
+    Sqlite,
+    /// Large language models, granted by `ai_models`
+    Llm,
+}
+
+impl Capability {
+    // Matches imports of Spin's own interfaces (e.g. `fermyon:spin/key-value@2.0.0`)
+    // only, so that unrelated interfaces which happen to share a name are not
+    // mistaken for them.
+    fn for_import(name: &str) -> Option<Self> {
+        let name = name.split('@').next().unwrap_or_default();
+        match name {
+            "fermyon:spin/key-value" => Some(Self::KeyValue),
+            "fermyon:spin/sqlite" => Some(Self::Sqlite),
+            "fermyon:spin/llm" => Some(Self::Llm),
+            _ => None,
+        }
+    }
+
+    fn manifest_key(&self) -> &'static str {
+        match self {
+            Self::KeyValue => "key_value_stores",
+            Self::Sqlite => "sqlite_databases",
+            Self::Llm => "ai_models",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::KeyValue => "key-value storage",
+            Self::Sqlite => "SQLite databases",
+            Self::Llm => "AI models",
+        }
+    }
+
+    // There is no sensible default for AI models, as they depend on the
+    // models the component was written for.
+    fn default_label(&self) -> Option<&'static str> {
+        match self {
+            Self::KeyValue | Self::Sqlite => Some("default"),
+            Self::Llm => None,
+        }
+    }
+
+    fn is_granted(&self, wasm: &PatientWasm) -> bool {
+        match self {
+            Self::KeyValue => !wasm.component.key_value_stores.is_empty(),
+            Self::Sqlite => !wasm.component.sqlite_databases.is_empty(),
+            Self::Llm => !wasm.component.ai_models.is_empty(),
+        }
+    }
+}
+
+/// Returns the names of the interfaces (or, for core modules, modules) that
+/// a Wasm binary imports.
+fn imported_interfaces(wasm: &[u8]) -> Result<BTreeSet<String>> {
+    let mut names = BTreeSet::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    names.insert(import?.module.to_owned());
+                }
+            }
+            Payload::ComponentImportSection(reader) => {
+                for import in reader {
+                    names.insert(import?.name.0.to_owned());
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(names)
+}
+
+/// MissingCapability represents a component which uses a Spin capability it
+/// has not been granted.
+#[derive(Debug)]
+pub struct MissingCapability {
+    component_id: String,
+    capability: Capability,
+    fixable: bool,
+}
+
+impl MissingCapability {
+    /// The capability the component is missing.
+    pub fn capability(&self) -> Capability {
+        self.capability
+    }
+}
+
+impl Diagnosis for MissingCapability {
+    fn description(&self) -> String {
+        format!(
+            "Component {:?} uses {} but has no `{}` entry",
+            self.component_id,
+            self.capability.description(),
+            self.capability.manifest_key(),
+        )
+    }
+
+    fn kind(&self) -> &'static str {
+        match self.capability {
+            Capability::KeyValue => "MissingCapability::KeyValue",
+            Capability::Sqlite => "MissingCapability::Sqlite",
+            Capability::Llm => "MissingCapability::Llm",
+        }
+    }
+
+    fn treatment(&self) -> Option<&dyn Treatment> {
+        self.fixable.then_some(self)
+    }
+}
+
+#[async_trait]
+impl crate::manifest::ManifestTreatment for MissingCapability {
+    fn summary(&self) -> String {
+        format!(
+            "Grant component {:?} access to the default {}",
+            self.component_id,
+            self.capability.description()
+        )
+    }
+
+    async fn treat_manifest(&self, doc: &mut Document) -> anyhow::Result<()> {
+        let label = self
+            .capability
+            .default_label()
+            .context("no default label for capability")?;
+        let component = component_table_mut(doc, &self.component_id)
+            .with_context(|| format!("couldn't find component {:?}", self.component_id))?;
+        let key = self.capability.manifest_key();
+        let labels = component
+            .entry(key)
+            .or_insert(Item::Value(Array::new().into()));
+        let labels = labels
+            .as_array_mut()
+            .with_context(|| format!("existing {key} value is not an array"))?;
+        if !labels.iter().any(|l| l.as_str() == Some(label)) {
+            labels.push(Value::from(label));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::{
+        test::{assert_single_diagnosis, TestPatient},
+        Diagnostic,
+    };
+
+    use super::*;
+
+    // A core module with a single function import from the given module.
+    fn module_importing(module: &str) -> Vec<u8> {
+        let mut import_section = vec![1, module.len() as u8];
+        import_section.extend(module.as_bytes());
+        import_section.extend([3, b'g', b'e', b't', 0, 0]);
+
+        let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+        wasm.extend([1, 4, 1, 0x60, 0, 0]);
+        wasm.extend([2, import_section.len() as u8]);
+        wasm.extend(import_section);
+        wasm
+    }
+
+    fn patient_importing(module: &str, grants: &str) -> (TestPatient, tempfile::NamedTempFile) {
+        let mut wasm_file = tempfile::NamedTempFile::new().unwrap();
+        wasm_file.write_all(&module_importing(module)).unwrap();
+        let source = wasm_file.path().display().to_string();
+        let patient = TestPatient::from_toml_str(format!(
+            r#"
+            spin_manifest_version = 2
+            [application]
+            name = "capabilities-test"
+            [[trigger.http]]
+            route = "/"
+            component = "test"
+            [component.test]
+            source = {source:?}
+            {grants}
+            "#
+        ));
+        (patient, wasm_file)
+    }
+
+    #[test]
+    fn test_capability_for_import() {
+        assert_eq!(
+            Some(Capability::KeyValue),
+            Capability::for_import("fermyon:spin/key-value@2.0.0")
+        );
+        assert_eq!(
+            Some(Capability::Sqlite),
+            Capability::for_import("fermyon:spin/sqlite@2.0.0")
+        );
+        assert_eq!(None, Capability::for_import("sqlite"));
+        assert_eq!(
+            Some(Capability::Llm),
+            Capability::for_import("fermyon:spin/llm")
+        );
+        assert_eq!(
+            None,
+            Capability::for_import("wasi:http/outgoing-handler@0.2.0")
+        );
+        assert_eq!(
+            None,
+            Capability::for_import("example:cache/key-value@1.0.0")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_granted_capability() {
+        let (patient, _wasm) = patient_importing(
+            "fermyon:spin/key-value",
+            r#"key_value_stores = ["default"]"#,
+        );
+        let diags = CapabilitiesDiagnostic.diagnose(&patient).await.unwrap();
+        assert!(diags.is_empty(), "expected no diagnoses, got {diags:?}");
+    }
+
+    #[tokio::test]
+    async fn test_missing_key_value_store() {
+        let (mut patient, _wasm) = patient_importing("fermyon:spin/key-value@2.0.0", "");
+        let diag = assert_single_diagnosis::<CapabilitiesDiagnostic>(&patient).await;
+        assert_eq!(Capability::KeyValue, diag.capability());
+        assert_eq!("MissingCapability::KeyValue", diag.kind());
+        diag.treatment()
+            .expect("should be treatable")
+            .treat(&mut patient)
+            .await
+            .unwrap();
+        let stores = patient.manifest_doc["component"]["test"]["key_value_stores"]
+            .as_array()
+            .unwrap();
+        assert_eq!(Some("default"), stores.get(0).and_then(|v| v.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_missing_ai_model_has_no_treatment() {
+        let (patient, _wasm) = patient_importing("fermyon:spin/llm@2.0.0", "");
+        let diag = assert_single_diagnosis::<CapabilitiesDiagnostic>(&patient).await;
+        assert_eq!(Capability::Llm, diag.capability());
+        assert!(diag.treatment().is_none());
+    }
+}
@@ -59,6 +59,10 @@ impl Diagnosis for WasmMissing {
         )
     }
 
+    fn kind(&self) -> &'static str {
+        "WasmMissing::SourceNotFound"
+    }
+
     fn treatment(&self) -> Option<&dyn Treatment> {
         self.0.has_build().then_some(self)
     }
@@ -2,8 +2,12 @@ use anyhow::{Context, Result};
 use spin_app::MetadataKey;
 use spin_core::{async_trait, wasmtime::component::Resource};
 use spin_world::v2::key_value;
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use table::Table;
+use tracing::{field::Empty, instrument};
 
 mod host_component;
 mod util;
@@ -36,6 +40,8 @@ pub struct KeyValueDispatch {
     allowed_stores: HashSet<String>,
     manager: Arc<dyn StoreManager>,
     stores: Table<Arc<dyn Store>>,
+    // Names of open stores, by resource rep, for tracing
+    store_names: HashMap<u32, String>,
 }
 
 impl KeyValueDispatch {
@@ -48,6 +54,7 @@ impl KeyValueDispatch {
             allowed_stores: HashSet::new(),
             manager: Arc::new(EmptyStoreManager),
             stores: Table::new(capacity),
+            store_names: HashMap::new(),
         }
     }
 
@@ -59,6 +66,17 @@ impl KeyValueDispatch {
     pub fn get_store(&self, store: Resource<key_value::Store>) -> anyhow::Result<&Arc<dyn Store>> {
         self.stores.get(store.rep()).context("invalid store")
     }
+
+    // Gets the store for a host call, recording its name on the current span.
+    fn get_traced_store(
+        &self,
+        store: Resource<key_value::Store>,
+    ) -> anyhow::Result<&Arc<dyn Store>> {
+        if let Some(name) = self.store_names.get(&store.rep()) {
+            tracing::Span::current().record("db.name", name.as_str());
+        }
+        self.get_store(store)
+    }
 }
 
 impl Default for KeyValueDispatch {
@@ -72,72 +90,109 @@ impl key_value::Host for KeyValueDispatch {}
 
 #[async_trait]
 impl key_value::HostStore for KeyValueDispatch {
+    #[instrument(name = "spin_key_value.open", skip(self, name), fields(otel.kind = "client", db.system = "spin_key_value", db.operation = "open", db.name = name, error.type = Empty))]
     async fn open(&mut self, name: String) -> Result<Result<Resource<key_value::Store>, Error>> {
+        record_call("open");
         Ok(async {
             if self.allowed_stores.contains(&name) {
                 let store = self
                     .stores
                     .push(self.manager.get(&name).await?)
                     .map_err(|()| Error::StoreTableFull)?;
+                self.store_names.insert(store, name);
                 Ok(Resource::new_own(store))
             } else {
                 Err(Error::AccessDenied)
             }
         }
-        .await)
+        .await
+        .inspect_err(spin_telemetry::record_error_type))
     }
 
+    #[instrument(name = "spin_key_value.get", skip(self, store, key), fields(otel.kind = "client", db.system = "spin_key_value", db.operation = "get", db.name = Empty, error.type = Empty))]
     async fn get(
         &mut self,
         store: Resource<key_value::Store>,
         key: String,
     ) -> Result<Result<Option<Vec<u8>>, Error>> {
-        let store = self.get_store(store)?;
-        Ok(store.get(&key).await)
+        record_call("get");
+        let store = self.get_traced_store(store)?;
+        Ok(store
+            .get(&key)
+            .await
+            .inspect_err(spin_telemetry::record_error_type))
     }
 
+    #[instrument(name = "spin_key_value.set", skip(self, store, key, value), fields(otel.kind = "client", db.system = "spin_key_value", db.operation = "set", db.name = Empty, error.type = Empty))]
     async fn set(
         &mut self,
         store: Resource<key_value::Store>,
         key: String,
         value: Vec<u8>,
     ) -> Result<Result<(), Error>> {
-        let store = self.get_store(store)?;
-        Ok(store.set(&key, &value).await)
+        record_call("set");
+        let store = self.get_traced_store(store)?;
+        Ok(store
+            .set(&key, &value)
+            .await
+            .inspect_err(spin_telemetry::record_error_type))
     }
 
+    #[instrument(name = "spin_key_value.delete", skip(self, store, key), fields(otel.kind = "client", db.system = "spin_key_value", db.operation = "delete", db.name = Empty, error.type = Empty))]
     async fn delete(
         &mut self,
         store: Resource<key_value::Store>,
         key: String,
     ) -> Result<Result<(), Error>> {
-        let store = self.get_store(store)?;
-        Ok(store.delete(&key).await)
+        record_call("delete");
+        let store = self.get_traced_store(store)?;
+        Ok(store
+            .delete(&key)
+            .await
+            .inspect_err(spin_telemetry::record_error_type))
     }
 
+    #[instrument(name = "spin_key_value.exists", skip(self, store, key), fields(otel.kind = "client", db.system = "spin_key_value", db.operation = "exists", db.name = Empty, error.type = Empty))]
     async fn exists(
         &mut self,
         store: Resource<key_value::Store>,
         key: String,
     ) -> Result<Result<bool, Error>> {
-        let store = self.get_store(store)?;
-        Ok(store.exists(&key).await)
+        record_call("exists");
+        let store = self.get_traced_store(store)?;
+        Ok(store
+            .exists(&key)
+            .await
+            .inspect_err(spin_telemetry::record_error_type))
     }
 
+    #[instrument(name = "spin_key_value.get_keys", skip(self, store), fields(otel.kind = "client", db.system = "spin_key_value", db.operation = "get_keys", db.name = Empty, error.type = Empty))]
     async fn get_keys(
         &mut self,
         store: Resource<key_value::Store>,
     ) -> Result<Result<Vec<String>, Error>> {
-        let store = self.get_store(store)?;
-        Ok(store.get_keys().await)
+        record_call("get_keys");
+        let store = self.get_traced_store(store)?;
+        Ok(store
+            .get_keys()
+            .await
+            .inspect_err(spin_telemetry::record_error_type))
     }
 
     fn drop(&mut self, store: Resource<key_value::Store>) -> Result<()> {
+        self.store_names.remove(&store.rep());
         self.stores.remove(store.rep());
         Ok(())
     }
 }
 
+fn record_call(operation: &'static str) {
+    spin_telemetry::metrics::monotonic_counter!(
+        spin.key_value_call_count = 1,
+        operation = operation
+    );
+}
+
 pub fn log_error(err: impl std::fmt::Debug) -> Error {
     tracing::warn!("key-value error: {err:?}");
     Error::Other(format!("{err:?}"))
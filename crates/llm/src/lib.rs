@@ -5,6 +5,7 @@ use spin_core::async_trait;
 use spin_world::v1::llm::{self as v1};
 use spin_world::v2::llm::{self as v2};
 use std::collections::HashSet;
+use tracing::{field::Empty, instrument, Level};
 
 pub use crate::host_component::LlmComponent;
 
@@ -34,6 +35,7 @@ pub struct LlmDispatch {
 
 #[async_trait]
 impl v2::Host for LlmDispatch {
+    #[instrument(name = "spin_llm.infer", skip(self, prompt), err(level = Level::INFO), fields(otel.kind = "client", gen_ai.request.model = model, gen_ai.usage.prompt_tokens = Empty, gen_ai.usage.completion_tokens = Empty, error.type = Empty))]
     async fn infer(
         &mut self,
         model: v2::InferencingModel,
@@ -41,8 +43,9 @@ impl v2::Host for LlmDispatch {
         params: Option<v2::InferencingParams>,
     ) -> Result<v2::InferencingResult, v2::Error> {
         if !self.allowed_models.contains(&model) {
-            return Err(access_denied_error(&model));
+            return Err(access_denied_error(&model)).inspect_err(spin_telemetry::record_error_type);
         }
+        let span = tracing::Span::current();
         self.engine
             .infer(
                 model,
@@ -57,17 +60,39 @@ impl v2::Host for LlmDispatch {
                 }),
             )
             .await
+            .inspect(|result| {
+                span.record(
+                    "gen_ai.usage.prompt_tokens",
+                    result.usage.prompt_token_count,
+                );
+                span.record(
+                    "gen_ai.usage.completion_tokens",
+                    result.usage.generated_token_count,
+                );
+            })
+            .inspect_err(spin_telemetry::record_error_type)
     }
 
+    #[instrument(name = "spin_llm.generate_embeddings", skip(self, data), err(level = Level::INFO), fields(otel.kind = "client", gen_ai.request.model = m, gen_ai.usage.prompt_tokens = Empty, error.type = Empty))]
     async fn generate_embeddings(
         &mut self,
         m: v1::EmbeddingModel,
         data: Vec<String>,
     ) -> Result<v2::EmbeddingsResult, v2::Error> {
         if !self.allowed_models.contains(&m) {
-            return Err(access_denied_error(&m));
+            return Err(access_denied_error(&m)).inspect_err(spin_telemetry::record_error_type);
         }
-        self.engine.generate_embeddings(m, data).await
+        let span = tracing::Span::current();
+        self.engine
+            .generate_embeddings(m, data)
+            .await
+            .inspect(|result| {
+                span.record(
+                    "gen_ai.usage.prompt_tokens",
+                    result.usage.prompt_token_count,
+                );
+            })
+            .inspect_err(spin_telemetry::record_error_type)
     }
 
     fn convert_error(&mut self, error: v2::Error) -> anyhow::Result<v2::Error> {
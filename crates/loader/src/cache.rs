@@ -3,8 +3,10 @@
 use anyhow::{ensure, Context, Result};
 
 use std::{
-    path::PathBuf,
+    collections::HashSet,
+    path::{Path, PathBuf},
     sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
 };
 
 use crate::fs::{create_dir_all, write_file};
@@ -14,6 +16,9 @@ const REGISTRY_CACHE_DIR: &str = "registry";
 const MANIFESTS_DIR: &str = "manifests";
 const WASM_DIR: &str = "wasm";
 const DATA_DIR: &str = "data";
+const STANDALONE_DIR: &str = "standalone";
+const MANIFEST_FILE: &str = "manifest.json";
+const CONFIG_FILE: &str = "config.json";
 
 /// Cache for registry entities.
 #[derive(Debug)]
@@ -57,6 +62,11 @@ impl Cache {
         self.root.join(DATA_DIR)
     }
 
+    /// The directory recording blobs which applications load directly.
+    fn standalone_dir(&self) -> PathBuf {
+        self.root.join(STANDALONE_DIR)
+    }
+
     /// Return the path to a wasm file given its digest.
     pub fn wasm_file(&self, digest: impl AsRef<str>) -> Result<PathBuf> {
         // Check the expected wasm directory first; else check the data directory as a fallback.
@@ -72,6 +82,7 @@ impl Cache {
             "cannot find wasm file for digest {}",
             digest.as_ref()
         );
+        touch(&path);
         Ok(path)
     }
 
@@ -83,6 +94,7 @@ impl Cache {
             "cannot find data file for digest {}",
             digest.as_ref()
         );
+        touch(&path);
         Ok(path)
     }
 
@@ -100,6 +112,20 @@ impl Cache {
         Ok(())
     }
 
+    /// Record that an application loaded the blob with the given digest
+    /// directly, rather than through a cached manifest. Pruning keeps such
+    /// blobs unless they are the least recently used entries in the cache.
+    pub fn mark_standalone(&self, digest: impl AsRef<str>) -> Result<()> {
+        let dir = self.standalone_dir();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create directory `{}`", dir.display()))?;
+        // The record's modification time is when the blob was last used.
+        let path = dir.join(safe_name(digest).as_ref());
+        std::fs::write(&path, b"")
+            .with_context(|| format!("failed to write `{}`", path.display()))?;
+        Ok(())
+    }
+
     /// The path of contents in the cache's wasm directory, which may or may not exist.
     pub fn wasm_path(&self, digest: impl AsRef<str>) -> PathBuf {
         self.wasm_dir().join(safe_name(digest).as_ref())
@@ -152,6 +178,368 @@ impl Cache {
 
         Ok(())
     }
+
+    /// The applications whose manifests are in the cache.
+    pub fn manifests(&self) -> Result<Vec<CachedManifest>> {
+        let mut manifests: Vec<_> = self
+            .cached_apps()?
+            .into_iter()
+            .map(|app| {
+                // Look up paths directly rather than through `wasm_file` so as
+                // not to count as a use of the blob.
+                let size = app
+                    .digests
+                    .iter()
+                    .flat_map(|digest| [self.wasm_path(digest), self.data_path(digest)])
+                    .filter_map(|path| path.metadata().ok())
+                    .map(|metadata| metadata.len())
+                    .sum();
+                CachedManifest {
+                    reference: app.reference,
+                    size,
+                }
+            })
+            .collect();
+        manifests.sort_by(|a, b| a.reference.cmp(&b.reference));
+        Ok(manifests)
+    }
+
+    /// The Wasm and data blobs in the cache.
+    pub fn blobs(&self) -> Result<Vec<CachedBlob>> {
+        let referenced: HashSet<String> = self
+            .cached_apps()?
+            .into_iter()
+            .flat_map(|app| app.digests)
+            .map(|digest| safe_name_string(&digest))
+            .chain(self.standalone_uses()?.into_iter().map(|s| s.name))
+            .collect();
+
+        let mut blobs = vec![];
+        for dir in [self.wasm_dir(), self.data_dir()] {
+            if !dir.is_dir() {
+                continue;
+            }
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let metadata = entry.metadata()?;
+                if !metadata.is_file() {
+                    continue;
+                }
+                let name = entry.file_name().to_string_lossy().into_owned();
+                blobs.push(CachedBlob {
+                    path: entry.path(),
+                    size: metadata.len(),
+                    last_used: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    referenced: referenced.contains(&name),
+                });
+            }
+        }
+        Ok(blobs)
+    }
+
+    /// The total size in bytes of everything in the cache.
+    pub fn usage(&self) -> Result<u64> {
+        dir_size(&self.root)
+    }
+
+    /// Remove blobs which are not referenced by any cached manifest, nor
+    /// loaded directly by an application. Then, if `max_size` is given,
+    /// remove the least recently used applications and directly loaded
+    /// blobs, along with the blobs only they use, until the whole cache is
+    /// no more than `max_size` bytes. Removed applications must be pulled
+    /// again before they can be run from the cache.
+    pub fn prune(&self, max_size: Option<u64>) -> Result<PruneSummary> {
+        let mut summary = PruneSummary::default();
+        self.remove_unreferenced_blobs(&mut summary)?;
+
+        if let Some(max_size) = max_size {
+            let mut size = self.usage()?;
+            let mut entries: Vec<_> = self
+                .cached_apps()?
+                .into_iter()
+                .map(CacheEntry::App)
+                .chain(
+                    self.standalone_uses()?
+                        .into_iter()
+                        .map(CacheEntry::Standalone),
+                )
+                .collect();
+            // Oldest last, so that they can be popped off.
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_used()));
+            while size > max_size {
+                let Some(entry) = entries.pop() else {
+                    break;
+                };
+                let freed_before = summary.freed;
+                match entry {
+                    CacheEntry::App(app) => summary.remove_app(app)?,
+                    CacheEntry::Standalone(standalone) => remove_record(&standalone.record)?,
+                }
+                // A blob may be shared with applications which are still
+                // cached, so only remove those no longer referenced at all.
+                self.remove_unreferenced_blobs(&mut summary)?;
+                size = size.saturating_sub(summary.freed - freed_before);
+            }
+        }
+        Ok(summary)
+    }
+
+    fn remove_unreferenced_blobs(&self, summary: &mut PruneSummary) -> Result<()> {
+        for blob in self.blobs()? {
+            if !blob.referenced {
+                summary.remove(blob)?;
+            }
+        }
+        // Forget directly loaded blobs which are no longer in the cache.
+        for standalone in self.standalone_uses()? {
+            let name = &standalone.name;
+            if !self.wasm_dir().join(name).exists() && !self.data_dir().join(name).exists() {
+                remove_record(&standalone.record)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Finds the blobs which applications have loaded directly.
+    fn standalone_uses(&self) -> Result<Vec<StandaloneUse>> {
+        let dir = self.standalone_dir();
+        if !dir.is_dir() {
+            return Ok(vec![]);
+        }
+        let mut uses = vec![];
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+            uses.push(StandaloneUse {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                record: entry.path(),
+                last_used: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+        Ok(uses)
+    }
+
+    // Finds the cached manifests and the digests of the blobs they use.
+    fn cached_apps(&self) -> Result<Vec<CachedApp>> {
+        let manifests_dir = self.manifests_dir();
+        if !manifests_dir.is_dir() {
+            return Ok(vec![]);
+        }
+        let mut apps = vec![];
+        for entry in walkdir::WalkDir::new(&manifests_dir) {
+            let entry = entry?;
+            if entry.file_name() != MANIFEST_FILE {
+                continue;
+            }
+            let Some(dir) = entry.path().parent() else {
+                continue;
+            };
+            // Manifests are stored in `<registry>/<repository>/<tag>/`.
+            let Ok(name) = dir.strip_prefix(&manifests_dir) else {
+                continue;
+            };
+            let mut components: Vec<_> = name
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            let Some(tag) = components.pop() else {
+                continue;
+            };
+            let digests = referenced_digests(dir);
+            let last_used = digests
+                .iter()
+                .flat_map(|digest| [self.wasm_path(digest), self.data_path(digest)])
+                .chain([entry.path().to_owned()])
+                .filter_map(|path| path.metadata().ok()?.modified().ok())
+                .max()
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            apps.push(CachedApp {
+                reference: format!("{}:{tag}", components.join("/")),
+                dir: dir.to_owned(),
+                digests,
+                last_used,
+            });
+        }
+        Ok(apps)
+    }
+
+    /// Remove everything from the cache, returning the number of bytes freed.
+    pub fn clear(&self) -> Result<u64> {
+        let size = self.usage()?;
+        for dir in [
+            self.manifests_dir(),
+            self.wasm_dir(),
+            self.data_dir(),
+            self.standalone_dir(),
+        ] {
+            if dir.is_dir() {
+                std::fs::remove_dir_all(&dir)
+                    .with_context(|| format!("failed to remove `{}`", dir.display()))?;
+            }
+        }
+        self.dirs_ensured_once.store(false, Ordering::Relaxed);
+        Ok(size)
+    }
+}
+
+/// An application whose manifest is in the cache.
+#[derive(Debug)]
+pub struct CachedManifest {
+    /// The registry reference of the application.
+    pub reference: String,
+    /// The total size of the cached blobs which the application uses.
+    pub size: u64,
+}
+
+// A cached application manifest, with the digests of the blobs it uses.
+struct CachedApp {
+    reference: String,
+    dir: PathBuf,
+    digests: HashSet<String>,
+    // When the manifest or any of its blobs was last written or looked up.
+    last_used: SystemTime,
+}
+
+// A blob which an application loaded directly rather than through a cached
+// manifest, such as a component downloaded from a URL.
+struct StandaloneUse {
+    // The blob's file name in the cache.
+    name: String,
+    // The file recording the use.
+    record: PathBuf,
+    last_used: SystemTime,
+}
+
+// Something which keeps blobs in the cache.
+enum CacheEntry {
+    App(CachedApp),
+    Standalone(StandaloneUse),
+}
+
+impl CacheEntry {
+    fn last_used(&self) -> SystemTime {
+        match self {
+            CacheEntry::App(app) => app.last_used,
+            CacheEntry::Standalone(standalone) => standalone.last_used,
+        }
+    }
+}
+
+/// A Wasm or data blob in the cache.
+#[derive(Debug)]
+pub struct CachedBlob {
+    /// The path of the blob.
+    pub path: PathBuf,
+    /// The size of the blob in bytes.
+    pub size: u64,
+    /// When the blob was last written or looked up.
+    pub last_used: SystemTime,
+    /// Whether any cached manifest refers to the blob, or an application
+    /// loaded it directly.
+    pub referenced: bool,
+}
+
+/// What was removed by [`Cache::prune`].
+#[derive(Debug, Default)]
+pub struct PruneSummary {
+    /// The number of application manifests removed.
+    pub apps_removed: usize,
+    /// The number of blobs removed.
+    pub removed: usize,
+    /// The number of bytes freed.
+    pub freed: u64,
+}
+
+impl PruneSummary {
+    fn remove_app(&mut self, app: CachedApp) -> Result<()> {
+        let size = dir_size(&app.dir)?;
+        std::fs::remove_dir_all(&app.dir)
+            .with_context(|| format!("failed to remove cached manifest for {}", app.reference))?;
+        self.apps_removed += 1;
+        self.freed += size;
+        Ok(())
+    }
+
+    fn remove(&mut self, blob: CachedBlob) -> Result<()> {
+        std::fs::remove_file(&blob.path)
+            .with_context(|| format!("failed to remove `{}`", blob.path.display()))?;
+        self.removed += 1;
+        self.freed += blob.size;
+        Ok(())
+    }
+}
+
+fn remove_record(path: &Path) -> Result<()> {
+    std::fs::remove_file(path).with_context(|| format!("failed to remove `{}`", path.display()))
+}
+
+fn dir_size(dir: &Path) -> Result<u64> {
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    let mut size = 0;
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+/// Collect every digest mentioned by the OCI manifest and locked app config
+/// cached in `dir`. This includes layer digests from the manifest as well as
+/// the digests of individual files unpacked from archive layers, which are
+/// only recorded in the locked app.
+fn referenced_digests(dir: &Path) -> HashSet<String> {
+    let mut digests = HashSet::new();
+    for file in [MANIFEST_FILE, CONFIG_FILE] {
+        let Ok(bytes) = std::fs::read(dir.join(file)) else {
+            continue;
+        };
+        if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&bytes) {
+            collect_digests(&json, &mut digests);
+        }
+    }
+    digests
+}
+
+fn collect_digests(value: &serde_json::Value, digests: &mut HashSet<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                match value {
+                    serde_json::Value::String(digest) if key == "digest" => {
+                        digests.insert(digest.clone());
+                    }
+                    _ => collect_digests(value, digests),
+                }
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for value in values {
+                collect_digests(value, digests);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Updates the modification time of a blob so that least-recently-used pruning
+// keeps blobs which are still in use. Failure only affects pruning order.
+fn touch(path: &Path) {
+    if let Ok(file) = std::fs::File::options().append(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+fn safe_name_string(digest: &str) -> String {
+    let name = safe_name(digest);
+    let name: &Path = name.as_ref().as_ref();
+    name.to_string_lossy().into_owned()
 }
 
 #[cfg(windows)]
@@ -187,4 +575,170 @@ mod test {
 
         Ok(())
     }
+
+    async fn cache_with_app(temp_dir: &Path) -> anyhow::Result<(Cache, String, String)> {
+        let cache = Cache::new(Some(temp_dir.to_owned())).await?;
+        cache.ensure_dirs().await?;
+
+        let used = b"used wasm";
+        let used_digest = format!("sha256:{}", hex_digest_from_bytes(used));
+        cache.write_wasm(used, &used_digest).await?;
+        let unused = b"unused data";
+        let unused_digest = format!("sha256:{}", hex_digest_from_bytes(unused));
+        cache.write_data(unused, &unused_digest).await?;
+
+        let app_dir = cache.manifests_dir().join("ghcr.io/fermyon/app/v1");
+        std::fs::create_dir_all(&app_dir)?;
+        std::fs::write(
+            app_dir.join(MANIFEST_FILE),
+            format!(r#"{{"layers":[{{"digest":"{used_digest}"}}]}}"#),
+        )?;
+        Ok((cache, used_digest, unused_digest))
+    }
+
+    #[tokio::test]
+    async fn lists_cached_manifests() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let (cache, _, _) = cache_with_app(temp_dir.path()).await?;
+
+        let manifests = cache.manifests()?;
+        assert_eq!(1, manifests.len());
+        assert_eq!("ghcr.io/fermyon/app:v1", manifests[0].reference);
+        assert_eq!(b"used wasm".len() as u64, manifests[0].size);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn prunes_unreferenced_blobs() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let (cache, used_digest, unused_digest) = cache_with_app(temp_dir.path()).await?;
+
+        let summary = cache.prune(None)?;
+        assert_eq!(1, summary.removed);
+        assert_eq!(b"unused data".len() as u64, summary.freed);
+        assert!(cache.wasm_file(&used_digest).is_ok());
+        assert!(cache.data_file(&unused_digest).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn prunes_apps_with_their_blobs_down_to_max_size() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let (cache, used_digest, _) = cache_with_app(temp_dir.path()).await?;
+
+        let summary = cache.prune(Some(0))?;
+        assert_eq!(1, summary.apps_removed);
+        assert_eq!(2, summary.removed);
+        assert!(cache.wasm_file(&used_digest).is_err());
+        assert!(cache.manifests()?.is_empty());
+        assert_eq!(0, cache.usage()?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn prunes_least_recently_used_apps_but_keeps_shared_blobs() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let cache = Cache::new(Some(temp_dir.path().to_owned())).await?;
+        cache.ensure_dirs().await?;
+
+        let mut digests = vec![];
+        for blob in ["old only", "shared", "new only"] {
+            let digest = format!("sha256:{}", hex_digest_from_bytes(blob));
+            cache.write_wasm(blob, &digest).await?;
+            digests.push(digest);
+        }
+        let hour_ago = SystemTime::now() - std::time::Duration::from_secs(3600);
+        for (tag, layers, modified) in [
+            ("old", &digests[..2], hour_ago),
+            ("new", &digests[1..], SystemTime::now()),
+        ] {
+            let app_dir = cache.manifests_dir().join("ghcr.io/fermyon/app").join(tag);
+            std::fs::create_dir_all(&app_dir)?;
+            let layers = layers
+                .iter()
+                .map(|digest| format!(r#"{{"digest":"{digest}"}}"#))
+                .collect::<Vec<_>>()
+                .join(",");
+            std::fs::write(
+                app_dir.join(MANIFEST_FILE),
+                format!(r#"{{"layers":[{layers}]}}"#),
+            )?;
+            std::fs::File::options()
+                .append(true)
+                .open(app_dir.join(MANIFEST_FILE))?
+                .set_modified(modified)?;
+        }
+        // Backdate the old app's blobs so that it is the least recently used.
+        for digest in &digests[..2] {
+            std::fs::File::options()
+                .append(true)
+                .open(cache.wasm_path(digest))?
+                .set_modified(hour_ago)?;
+        }
+
+        let summary = cache.prune(Some(cache.usage()? - 1))?;
+        assert_eq!(1, summary.apps_removed);
+        assert_eq!(1, summary.removed);
+        let manifests = cache.manifests()?;
+        assert_eq!(1, manifests.len());
+        assert_eq!("ghcr.io/fermyon/app:new", manifests[0].reference);
+        assert!(cache.wasm_file(&digests[0]).is_err());
+        assert!(cache.wasm_file(&digests[1]).is_ok());
+        assert!(cache.wasm_file(&digests[2]).is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn prune_keeps_directly_loaded_blobs() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let (cache, _, _) = cache_with_app(temp_dir.path()).await?;
+
+        // As for a component source downloaded from a URL
+        let url_source = b"url source";
+        let url_digest = format!("sha256:{}", hex_digest_from_bytes(url_source));
+        cache.write_wasm(url_source, &url_digest).await?;
+        cache.mark_standalone(&url_digest)?;
+
+        let summary = cache.prune(None)?;
+        assert_eq!(1, summary.removed);
+        assert!(cache.wasm_file(&url_digest).is_ok());
+        assert!(cache.blobs()?.iter().all(|blob| blob.referenced));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn prunes_least_recently_loaded_blobs_down_to_max_size() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let (cache, used_digest, _) = cache_with_app(temp_dir.path()).await?;
+
+        let url_source = b"url source";
+        let url_digest = format!("sha256:{}", hex_digest_from_bytes(url_source));
+        cache.write_wasm(url_source, &url_digest).await?;
+        cache.mark_standalone(&url_digest)?;
+        let hour_ago = SystemTime::now() - std::time::Duration::from_secs(3600);
+        std::fs::File::options()
+            .append(true)
+            .open(cache.standalone_dir().join(safe_name(&url_digest).as_ref()))?
+            .set_modified(hour_ago)?;
+
+        cache.prune(None)?;
+        let summary = cache.prune(Some(cache.usage()? - 1))?;
+        assert_eq!(0, summary.apps_removed);
+        assert_eq!(1, summary.removed);
+        assert!(cache.wasm_file(&url_digest).is_err());
+        assert!(cache.wasm_file(&used_digest).is_ok());
+        assert!(cache.standalone_uses()?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn clear_removes_everything() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let (cache, _, _) = cache_with_app(temp_dir.path()).await?;
+
+        assert!(cache.clear()? > 0);
+        assert_eq!(0, cache.usage()?);
+        assert!(cache.manifests()?.is_empty());
+        Ok(())
+    }
 }
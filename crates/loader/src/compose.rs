@@ -0,0 +1,27 @@
+//! Composition of components with the components which satisfy their imports.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use wasm_compose::{
+    composer::ComponentComposer,
+    config::{Config, Dependency},
+};
+
+/// Composes the component at `component` with `dependencies`, which map
+/// import names to the paths of components exporting those interfaces,
+/// returning the composed component. Imports not satisfied by a dependency
+/// remain imports of the composed component.
+pub(crate) fn compose(
+    component: &Path,
+    dependencies: impl IntoIterator<Item = (String, PathBuf)>,
+) -> Result<Vec<u8>> {
+    let config = Config {
+        dependencies: dependencies
+            .into_iter()
+            .map(|(import, path)| (import, Dependency { path }))
+            .collect(),
+        ..Default::default()
+    };
+    ComponentComposer::new(component, &config).compose()
+}
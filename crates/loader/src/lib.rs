@@ -10,18 +10,24 @@
 
 #![deny(missing_docs)]
 
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::{Context, Result};
 use local::LocalLoader;
+use oci::OciRegistry;
 use spin_common::paths::parent_dir;
 use spin_locked_app::locked::LockedApp;
 
 pub mod cache;
+mod compose;
 mod fs;
 #[cfg(feature = "async-io")]
 mod http;
 mod local;
+pub mod oci;
 
 /// Maximum number of files to copy (or download) concurrently
 pub(crate) const MAX_FILE_LOADING_CONCURRENCY: usize = 16;
@@ -40,6 +46,22 @@ pub async fn from_file(
     loader.load_file(path).await
 }
 
+/// Load a Spin locked app from a spin.toml manifest file, as [`from_file`],
+/// pulling components whose source is an OCI Wasm artifact from `oci_registry`.
+pub async fn from_file_with_oci_registry(
+    manifest_path: impl AsRef<Path>,
+    files_mount_strategy: FilesMountStrategy,
+    cache_root: Option<PathBuf>,
+    oci_registry: Arc<dyn OciRegistry>,
+) -> Result<LockedApp> {
+    let path = manifest_path.as_ref();
+    let app_root = parent_dir(path).context("manifest path has no parent directory")?;
+    let loader = LocalLoader::new(&app_root, files_mount_strategy, cache_root)
+        .await?
+        .with_oci_registry(oci_registry);
+    loader.load_file(path).await
+}
+
 /// Load a Spin locked app from a standalone Wasm file.
 pub async fn from_wasm_file(wasm_path: impl AsRef<Path>) -> Result<LockedApp> {
     let app_root = std::env::current_dir()?;
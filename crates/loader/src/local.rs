@@ -1,9 +1,13 @@
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::{anyhow, bail, ensure, Context, Result};
 use futures::{future::try_join_all, StreamExt};
+use indexmap::IndexMap;
 use reqwest::Url;
-use spin_common::{paths::parent_dir, sloth, ui::quoted_path};
+use spin_common::{paths::parent_dir, sha256, sloth, ui::quoted_path, url::parse_file_url};
 use spin_locked_app::{
     locked::{
         self, ContentPath, ContentRef, LockedApp, LockedComponent, LockedComponentSource,
@@ -15,14 +19,36 @@ use spin_manifest::schema::v2::{self, AppManifest, KebabId, WasiFilesMount};
 use spin_outbound_networking::SERVICE_CHAINING_DOMAIN_SUFFIX;
 use tokio::{io::AsyncWriteExt, sync::Semaphore};
 
-use crate::{cache::Cache, FilesMountStrategy};
+use crate::{
+    cache::Cache,
+    oci::{OciLayer, OciRegistry},
+    FilesMountStrategy,
+};
+
+/// Media types of the Wasm layer of an OCI Wasm artifact, as published by
+/// tools following the CNCF Wasm OCI artifact layout or by `wasm-to-oci`.
+const WASM_LAYER_MEDIA_TYPES: &[&str] = &[
+    "application/wasm",
+    "application/vnd.wasm.content.layer.v1+wasm",
+];
 
-#[derive(Debug)]
 pub struct LocalLoader {
     app_root: PathBuf,
     files_mount_strategy: FilesMountStrategy,
     cache: Cache,
     file_loading_permits: Semaphore,
+    oci_registry: Option<Arc<dyn OciRegistry>>,
+}
+
+impl std::fmt::Debug for LocalLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalLoader")
+            .field("app_root", &self.app_root)
+            .field("files_mount_strategy", &self.files_mount_strategy)
+            .field("cache", &self.cache)
+            .field("file_loading_permits", &self.file_loading_permits)
+            .finish_non_exhaustive()
+    }
 }
 
 impl LocalLoader {
@@ -39,9 +65,17 @@ impl LocalLoader {
             cache: Cache::new(cache_root).await?,
             // Limit concurrency to avoid hitting system resource limits
             file_loading_permits: Semaphore::new(crate::MAX_FILE_LOADING_CONCURRENCY),
+            oci_registry: None,
         })
     }
 
+    // Pull components whose source is an OCI Wasm artifact from `oci_registry`.
+    // Without one, such components can't be loaded.
+    pub fn with_oci_registry(mut self, oci_registry: Arc<dyn OciRegistry>) -> Self {
+        self.oci_registry = Some(oci_registry);
+        self
+    }
+
     // Load the manifest file (spin.toml) at the given path into a LockedApp,
     // preparing all its content for execution.
     pub async fn load_file(&self, path: impl AsRef<Path>) -> Result<LockedApp> {
@@ -146,19 +180,38 @@ impl LocalLoader {
         spin_outbound_networking::AllowedHostsConfig::validate(&allowed_outbound_hosts)
             .context("`allowed_outbound_hosts` is malformed")?;
 
+        // Where a non-local source came from is otherwise lost once it is
+        // downloaded, so record it for supply-chain metadata.
+        let remote_source = match &component.source {
+            v2::ComponentSource::Local(_) => None,
+            source => Some(source),
+        };
+
         let metadata = ValuesMapBuilder::new()
             .string("description", component.description)
             .string_array("allowed_outbound_hosts", allowed_outbound_hosts)
             .string_array("key_value_stores", component.key_value_stores)
             .string_array("databases", component.sqlite_databases)
+            .string_array("postgres_databases", component.postgres_databases)
+            .string_array("mysql_databases", component.mysql_databases)
             .string_array("ai_models", component.ai_models)
             .serializable("build", component.build)?
+            .serializable("source", remote_source)?
             .take();
 
         let source = self
             .load_component_source(component.source.clone())
             .await
             .with_context(|| format!("Failed to load Wasm source {}", component.source))?;
+        let source = if component.dependencies.is_empty() {
+            source
+        } else {
+            self.compose_dependencies(source, &component.dependencies)
+                .await
+                .with_context(|| {
+                    format!("Failed to compose component {id} with its dependencies")
+                })?
+        };
 
         let env = component.environment.into_iter().collect();
 
@@ -230,6 +283,9 @@ impl LocalLoader {
                 self.load_registry_source(registry.as_ref(), &package, &version)
                     .await?
             }
+            v2::ComponentSource::Oci { oci, digest } => {
+                self.load_oci_source(&oci, digest.as_deref()).await?
+            }
         };
         Ok(LockedComponentSource {
             content_type: "application/wasm".into(),
@@ -237,6 +293,71 @@ impl LocalLoader {
         })
     }
 
+    // Compose the loaded Wasm `source` with the components which satisfy its
+    // imports, returning a source for the composed component.
+    async fn compose_dependencies(
+        &self,
+        source: LockedComponentSource,
+        dependencies: &IndexMap<String, v2::ComponentSource>,
+    ) -> Result<LockedComponentSource> {
+        let component_path = self
+            .componentized_path(&content_path(&source.content)?)
+            .await?;
+        let dependency_paths =
+            try_join_all(dependencies.iter().map(|(import, dependency)| async move {
+                let dependency_source = self
+                    .load_component_source(dependency.clone())
+                    .await
+                    .with_context(|| {
+                        format!("Failed to load dependency {import:?} from {dependency}")
+                    })?;
+                let dependency_path = self
+                    .componentized_path(&content_path(&dependency_source.content)?)
+                    .await
+                    .with_context(|| format!("Invalid dependency {import:?} from {dependency}"))?;
+                anyhow::Ok((import.clone(), dependency_path))
+            }))
+            .await?;
+
+        let composed = crate::compose::compose(&component_path, dependency_paths)?;
+        let digest = format!("sha256:{}", sha256::hex_digest_from_bytes(&composed));
+        self.cache.write_wasm(&composed, &digest).await?;
+        Ok(LockedComponentSource {
+            content_type: source.content_type,
+            content: self.cached_content_ref(self.cache.wasm_path(&digest), &digest)?,
+        })
+    }
+
+    // Only components can be composed, so a Wasm module at `path` (as many
+    // toolchains still produce) is converted to a component in the cache, as
+    // Spin would do when running it. Components are used as they are.
+    async fn componentized_path(&self, path: &Path) -> Result<PathBuf> {
+        let bytes = tokio::fs::read(path)
+            .await
+            .with_context(|| format!("Failed to read {}", quoted_path(path)))?;
+        let component =
+            spin_componentize::componentize_if_necessary(&bytes).with_context(|| {
+                format!(
+                    "Failed to convert Wasm module {} to a component for composition",
+                    quoted_path(path)
+                )
+            })?;
+        let std::borrow::Cow::Owned(component) = component else {
+            return Ok(path.to_owned());
+        };
+        let digest = format!("sha256:{}", sha256::hex_digest_from_bytes(&component));
+        self.cache.write_wasm(&component, &digest).await?;
+        self.cache.mark_standalone(&digest)?;
+        Ok(self.cache.wasm_path(&digest))
+    }
+
+    // Return a ContentRef to a blob in the cache which this application
+    // loads directly, recording the use so that pruning the cache keeps it.
+    fn cached_content_ref(&self, path: PathBuf, digest: &str) -> Result<ContentRef> {
+        self.cache.mark_standalone(digest)?;
+        file_content_ref(path)
+    }
+
     // Load a Wasm source from the given HTTP ContentRef source URL and
     // return a ContentRef an absolute path to the local copy.
     async fn load_http_source(&self, url: &str, digest: &str) -> Result<ContentRef> {
@@ -256,7 +377,7 @@ impl LocalLoader {
                 .with_context(|| format!("Error fetching source URL {url:?}"))?;
             dest
         };
-        file_content_ref(path)
+        self.cached_content_ref(path, digest)
     }
 
     async fn load_registry_source(
@@ -303,7 +424,60 @@ impl LocalLoader {
             dest
         };
 
-        file_content_ref(path)
+        self.cached_content_ref(path, &digest)
+    }
+
+    // Load a Wasm source from the given OCI Wasm artifact reference and return
+    // a ContentRef to the cached copy. If `digest` is given, the artifact's Wasm
+    // layer must match it, and a cached copy is used without contacting the registry.
+    async fn load_oci_source(&self, oci: &str, digest: Option<&str>) -> Result<ContentRef> {
+        if let Some(digest) = digest {
+            ensure!(
+                digest.starts_with("sha256:"),
+                "invalid `digest` {digest:?}; must start with 'sha256:'"
+            );
+            if let Ok(cached_path) = self.cache.wasm_file(digest) {
+                return self.cached_content_ref(cached_path, digest);
+            }
+        }
+
+        let registry = self
+            .oci_registry
+            .as_ref()
+            .with_context(|| format!("Cannot load {oci:?}: OCI sources need registry access"))?;
+        let layers = registry
+            .layers(oci)
+            .await
+            .with_context(|| format!("Error fetching OCI artifact {oci:?}"))?;
+        let layer = wasm_layer(oci, layers)?;
+        if let Some(digest) = digest {
+            ensure!(
+                layer.digest == digest,
+                "OCI artifact {oci:?} has content digest {}, but the manifest pins {digest}",
+                layer.digest
+            );
+        }
+
+        let path = if let Ok(cached_path) = self.cache.wasm_file(&layer.digest) {
+            cached_path
+        } else {
+            let _loading_permit = self.file_loading_permits.acquire().await?;
+
+            let bytes = registry
+                .pull_layer(oci, &layer)
+                .await
+                .with_context(|| format!("Error fetching Wasm layer of OCI artifact {oci:?}"))?;
+            let actual_digest = format!("sha256:{}", sha256::hex_digest_from_bytes(&bytes));
+            ensure!(
+                actual_digest == layer.digest,
+                "invalid content digest; expected {}, downloaded {actual_digest}",
+                layer.digest
+            );
+
+            self.cache.write_wasm(&bytes, &layer.digest).await?;
+            self.cache.wasm_path(&layer.digest)
+        };
+        self.cached_content_ref(path, &layer.digest)
     }
 
     // Copy content(s) from the given `mount`
@@ -634,6 +808,26 @@ fn file_content_ref(path: impl AsRef<Path>) -> Result<ContentRef> {
     })
 }
 
+// Returns the single Wasm layer of the OCI artifact `oci`.
+fn wasm_layer(oci: &str, layers: Vec<OciLayer>) -> Result<OciLayer> {
+    let mut wasm_layers = layers
+        .into_iter()
+        .filter(|layer| WASM_LAYER_MEDIA_TYPES.contains(&layer.media_type.as_str()));
+    match (wasm_layers.next(), wasm_layers.next()) {
+        (Some(layer), None) => Ok(layer),
+        (None, _) => bail!("OCI artifact {oci:?} does not contain a Wasm layer"),
+        (Some(_), Some(_)) => bail!("OCI artifact {oci:?} contains more than one Wasm layer"),
+    }
+}
+
+fn content_path(content: &ContentRef) -> Result<PathBuf> {
+    let url = content
+        .source
+        .as_deref()
+        .context("loaded Wasm content has no source")?;
+    parse_file_url(url)
+}
+
 fn file_url(path: impl AsRef<Path>) -> Result<String> {
     let path = path.as_ref();
     let abs_path = safe_canonicalize(path)
@@ -674,8 +868,154 @@ fn warn_if_component_load_slothful() -> sloth::SlothGuard {
 
 #[cfg(test)]
 mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
+
     use super::*;
 
+    const WASM: &[u8] = b"\0asm\x0d\0\x01\0";
+    const OCI: &str = "ghcr.io/example/component:1.0";
+
+    struct FakeRegistry {
+        layers: Vec<OciLayer>,
+        content: Vec<u8>,
+        requests: AtomicUsize,
+    }
+
+    impl FakeRegistry {
+        fn new(media_types: &[&str], content: &[u8]) -> Arc<Self> {
+            let layers = media_types
+                .iter()
+                .map(|media_type| OciLayer {
+                    media_type: media_type.to_string(),
+                    digest: digest(WASM),
+                    size: WASM.len() as i64,
+                })
+                .collect();
+            Arc::new(Self {
+                layers,
+                content: content.to_vec(),
+                requests: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl OciRegistry for FakeRegistry {
+        async fn layers(&self, _reference: &str) -> Result<Vec<OciLayer>> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            Ok(self.layers.clone())
+        }
+
+        async fn pull_layer(&self, _reference: &str, _layer: &OciLayer) -> Result<Vec<u8>> {
+            Ok(self.content.clone())
+        }
+    }
+
+    fn digest(content: &[u8]) -> String {
+        format!("sha256:{}", sha256::hex_digest_from_bytes(content))
+    }
+
+    async fn oci_loader(dir: &Path, registry: Arc<FakeRegistry>) -> LocalLoader {
+        LocalLoader::new(dir, FilesMountStrategy::Direct, Some(dir.join("cache")))
+            .await
+            .unwrap()
+            .with_oci_registry(registry)
+    }
+
+    #[tokio::test]
+    async fn loads_oci_source_into_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = FakeRegistry::new(&["application/wasm", "text/plain"], WASM);
+        let loader = oci_loader(dir.path(), registry.clone()).await;
+
+        let content = loader
+            .load_oci_source(OCI, Some(&digest(WASM)))
+            .await
+            .unwrap();
+        assert_eq!(
+            WASM,
+            std::fs::read(content_path(&content).unwrap()).unwrap()
+        );
+
+        // Once cached, a pinned source is loaded without contacting the registry.
+        loader
+            .load_oci_source(OCI, Some(&digest(WASM)))
+            .await
+            .unwrap();
+        assert_eq!(1, registry.requests.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn rejects_oci_source_not_matching_pinned_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = oci_loader(dir.path(), FakeRegistry::new(&["application/wasm"], WASM)).await;
+
+        let err = loader
+            .load_oci_source(OCI, Some(&digest(b"other")))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("but the manifest pins"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn rejects_oci_layer_not_matching_its_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = oci_loader(
+            dir.path(),
+            FakeRegistry::new(&["application/wasm"], b"other"),
+        )
+        .await;
+
+        let err = loader.load_oci_source(OCI, None).await.unwrap_err();
+        assert!(
+            err.to_string().contains("invalid content digest"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn requires_a_single_oci_wasm_layer() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let loader = oci_loader(dir.path(), FakeRegistry::new(&["text/plain"], WASM)).await;
+        let err = loader.load_oci_source(OCI, None).await.unwrap_err();
+        assert!(
+            err.to_string().contains("does not contain a Wasm layer"),
+            "{err:#}"
+        );
+
+        let media_types = [
+            "application/wasm",
+            "application/vnd.wasm.content.layer.v1+wasm",
+        ];
+        let loader = oci_loader(dir.path(), FakeRegistry::new(&media_types, WASM)).await;
+        let err = loader.load_oci_source(OCI, None).await.unwrap_err();
+        assert!(
+            err.to_string().contains("more than one Wasm layer"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn oci_source_requires_a_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = LocalLoader::new(
+            dir.path(),
+            FilesMountStrategy::Direct,
+            Some(dir.path().join("cache")),
+        )
+        .await
+        .unwrap();
+
+        let err = loader.load_oci_source(OCI, None).await.unwrap_err();
+        assert!(
+            err.to_string().contains("OCI sources need registry access"),
+            "{err:#}"
+        );
+    }
+
     #[tokio::test]
     async fn bad_destination_filename_is_explained() -> anyhow::Result<()> {
         let app_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -700,4 +1040,120 @@ mod test {
         );
         Ok(())
     }
+
+    // Returns the names of the imports of the component itself, not those
+    // of the components and modules nested in it.
+    fn component_imports(bytes: &[u8]) -> Vec<String> {
+        let mut depth = 0;
+        let mut imports = vec![];
+        for payload in wasmparser::Parser::new(0).parse_all(bytes) {
+            match payload.unwrap() {
+                wasmparser::Payload::Version { .. } => depth += 1,
+                wasmparser::Payload::End(_) => depth -= 1,
+                wasmparser::Payload::ComponentImportSection(reader) if depth == 1 => {
+                    for import in reader {
+                        imports.push(import.unwrap().name.0.to_owned());
+                    }
+                }
+                _ => {}
+            }
+        }
+        imports
+    }
+
+    #[tokio::test]
+    async fn reports_modules_which_cannot_be_composed() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_root = dir.path();
+        // A module whose imports Spin cannot provide, so it can't be
+        // converted to a component
+        let module = wat::parse_str(r#"(module (import "nowhere" "f" (func)))"#).unwrap();
+        std::fs::write(app_root.join("module.wasm"), module).unwrap();
+
+        let loader = LocalLoader::new(
+            app_root,
+            FilesMountStrategy::Direct,
+            Some(app_root.join("cache")),
+        )
+        .await
+        .unwrap();
+        let source = loader
+            .load_component_source(v2::ComponentSource::Local("module.wasm".into()))
+            .await
+            .unwrap();
+        let dependencies = [(
+            "example:dep/greet".to_owned(),
+            v2::ComponentSource::Local("module.wasm".into()),
+        )]
+        .into_iter()
+        .collect();
+
+        let err = loader
+            .compose_dependencies(source, &dependencies)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Failed to convert Wasm module"), "{err}");
+        assert!(err.contains("module.wasm"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn composes_component_with_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_root = dir.path();
+        let component = wat::parse_str(
+            r#"(component
+                (import "example:dep/greet" (instance (export "hello" (func (result u32)))))
+                (import "example:other/thing" (instance (export "run" (func))))
+            )"#,
+        )
+        .unwrap();
+        let dependency = wat::parse_str(
+            r#"(component
+                (core module $m (func (export "hello") (result i32) i32.const 42))
+                (core instance $i (instantiate $m))
+                (func $hello (result u32) (canon lift (core func $i "hello")))
+                (instance $greet (export "hello" (func $hello)))
+                (export "example:dep/greet" (instance $greet))
+            )"#,
+        )
+        .unwrap();
+        std::fs::write(app_root.join("component.wasm"), &component).unwrap();
+        std::fs::write(app_root.join("dep.wasm"), dependency).unwrap();
+        assert_eq!(
+            vec!["example:dep/greet", "example:other/thing"],
+            component_imports(&component)
+        );
+
+        let loader = LocalLoader::new(
+            app_root,
+            FilesMountStrategy::Direct,
+            Some(app_root.join("cache")),
+        )
+        .await
+        .unwrap();
+        let source = loader
+            .load_component_source(v2::ComponentSource::Local("component.wasm".into()))
+            .await
+            .unwrap();
+        let dependencies = [(
+            "example:dep/greet".to_owned(),
+            v2::ComponentSource::Local("dep.wasm".into()),
+        )]
+        .into_iter()
+        .collect();
+
+        let composed = loader
+            .compose_dependencies(source, &dependencies)
+            .await
+            .unwrap();
+
+        let composed_path = content_path(&composed.content).unwrap();
+        assert!(
+            composed_path.starts_with(safe_canonicalize(&app_root.join("cache")).unwrap()),
+            "{composed_path:?}"
+        );
+        let composed = std::fs::read(composed_path).unwrap();
+        assert_eq!(vec!["example:other/thing"], component_imports(&composed));
+    }
 }
@@ -0,0 +1,30 @@
+//! Access to OCI registries for components whose source is an OCI Wasm
+//! artifact. The loader does not talk to registries itself; callers which
+//! support such sources provide an [`OciRegistry`], which is responsible for
+//! the registry connection and credentials.
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// A layer of an OCI artifact.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OciLayer {
+    /// The media type of the layer.
+    pub media_type: String,
+    /// The digest of the layer content, such as `sha256:abc123...`.
+    pub digest: String,
+    /// The size of the layer content in bytes.
+    pub size: i64,
+}
+
+/// Pulls OCI artifacts from registries.
+#[async_trait]
+pub trait OciRegistry: Send + Sync {
+    /// Returns the layers of the artifact at `reference`, as listed in its
+    /// manifest.
+    async fn layers(&self, reference: &str) -> Result<Vec<OciLayer>>;
+
+    /// Returns the content of `layer` of the artifact at `reference`. The
+    /// content is checked against the layer digest by the loader.
+    async fn pull_layer(&self, reference: &str, layer: &OciLayer) -> Result<Vec<u8>>;
+}
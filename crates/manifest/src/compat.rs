@@ -69,7 +69,10 @@ pub fn v1_to_v2_app(manifest: v1::AppManifestV1) -> Result<v2::AppManifest, Erro
                 exclude_files: component.exclude_files,
                 key_value_stores: component.key_value_stores,
                 sqlite_databases: component.sqlite_databases,
+                postgres_databases: Vec::new(),
+                mysql_databases: Vec::new(),
                 ai_models,
+                dependencies: Default::default(),
                 build: component.build,
                 tool: Default::default(),
                 allowed_outbound_hosts,
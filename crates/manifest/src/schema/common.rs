@@ -24,6 +24,9 @@ pub struct Variable {
 #[serde(deny_unknown_fields, untagged)]
 pub enum ComponentSource {
     /// `"local.wasm"`
+    ///
+    /// Local files can't be pinned to a digest; use a `url` or `oci` source
+    /// with a `digest` for content which must not change.
     Local(String),
     /// `{ ... }`
     Remote {
@@ -41,6 +44,14 @@ pub enum ComponentSource {
         /// `version = "1.2.3"`
         version: semver::Version,
     },
+    /// `{ ... }`
+    Oci {
+        /// `oci = "ghcr.io/example/component:1.2.3"`
+        oci: String,
+        /// `digest = "sha256:abc123..."`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        digest: Option<String>,
+    },
 }
 
 impl Display for ComponentSource {
@@ -59,6 +70,10 @@ impl Display for ComponentSource {
                 };
                 write!(f, "\"{package}@{version}\" from {registry_suffix}")
             }
+            ComponentSource::Oci { oci, digest } => match digest {
+                Some(digest) => write!(f, "OCI artifact {oci:?} with digest {digest:?}"),
+                None => write!(f, "OCI artifact {oci:?}"),
+            },
         }
     }
 }
@@ -90,6 +105,9 @@ pub struct ComponentBuildConfig {
     /// watch = ["src/**/*.rs"]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub watch: Vec<String>,
+    /// depends_on = ["other-component"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
 }
 
 fn is_false(v: &bool) -> bool {
@@ -136,9 +136,28 @@ pub struct Component {
         skip_serializing_if = "Vec::is_empty"
     )]
     pub sqlite_databases: Vec<String>,
+    /// `postgres_databases = ["orders"]`
+    #[serde(
+        default,
+        with = "kebab_or_snake_case",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub postgres_databases: Vec<String>,
+    /// `mysql_databases = ["orders"]`
+    #[serde(
+        default,
+        with = "kebab_or_snake_case",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub mysql_databases: Vec<String>,
     /// `ai_models = ["llama2-chat"]`
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub ai_models: Vec<KebabId>,
+    /// `dependencies = { "example:auth/middleware" = "auth.wasm" }`
+    ///
+    /// Components which satisfy imports of this component, keyed by import name.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub dependencies: Map<String, ComponentSource>,
     /// Build configuration
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub build: Option<ComponentBuildConfig>,
@@ -321,6 +340,8 @@ mod tests {
             source = "dummy"
             key_value_stores = ["default", "snake_case", "kebab-case"]
             sqlite_databases = ["default", "snake_case", "kebab-case"]
+            postgres_databases = ["snake_case", "kebab-case"]
+            mysql_databases = ["snake_case", "kebab-case"]
         })
         .unwrap();
     }
@@ -352,7 +373,10 @@ mod tests {
             allowed_outbound_hosts: vec![],
             key_value_stores: labels.clone(),
             sqlite_databases: labels,
+            postgres_databases: vec![],
+            mysql_databases: vec![],
             ai_models: vec![],
+            dependencies: Map::new(),
             build: None,
             tool: Map::new(),
         }
@@ -1,13 +1,17 @@
+//! Credentials for OCI registries.
+
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
 };
 
 use anyhow::{bail, Context, Result};
-use oci_distribution::secrets::RegistryAuth;
+use docker_credential::DockerCredential;
+use oci_distribution::{secrets::RegistryAuth, Reference};
 use serde::{Deserialize, Serialize};
 use spin_common::ui::quoted_path;
 
+/// Registry credentials saved by `spin registry login`.
 #[derive(Serialize, Deserialize)]
 pub struct AuthConfig {
     /// Map between registry server and base64 encoded username:password credential set.
@@ -99,3 +103,33 @@ impl AuthConfig {
             .with_context(|| format!("cannot save authentication file {}", quoted_path(p)))
     }
 }
+
+/// Construct the registry authentication based on the reference, using the
+/// credentials saved by `spin registry login` or, failing that, by Docker.
+pub(crate) async fn registry_auth(reference: &Reference) -> Result<RegistryAuth> {
+    let server = reference
+        .resolve_registry()
+        .strip_suffix('/')
+        .unwrap_or_else(|| reference.resolve_registry());
+
+    match AuthConfig::get_auth_from_default(server).await {
+        Ok(c) => Ok(c),
+        Err(_) => {
+            match docker_credential::get_credential(server) {
+                Err(e) => {
+                    tracing::trace!("Cannot retrieve credentials from Docker, attempting to use anonymous auth: {}", e);
+                    Ok(RegistryAuth::Anonymous)
+                }
+
+                Ok(DockerCredential::UsernamePassword(username, password)) => {
+                    tracing::trace!("Found Docker credentials");
+                    Ok(RegistryAuth::Basic(username, password))
+                }
+                Ok(DockerCredential::IdentityToken(_)) => {
+                    tracing::trace!("Cannot use contents of Docker config, identity token not supported. Using anonymous auth");
+                    Ok(RegistryAuth::Anonymous)
+                }
+            }
+        }
+    }
+}
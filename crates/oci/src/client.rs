@@ -2,17 +2,23 @@
 
 use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use anyhow::{bail, Context, Result};
-use docker_credential::DockerCredential;
 use futures_util::future;
 use futures_util::stream::{self, StreamExt, TryStreamExt};
 use itertools::Itertools;
 use oci_distribution::{
-    client::ImageLayer, config::ConfigFile, manifest::OciImageManifest, secrets::RegistryAuth,
-    token_cache::RegistryTokenType, Reference, RegistryOperation,
+    client::ImageLayer,
+    config::ConfigFile,
+    errors::{OciDistributionError, OciErrorCode},
+    manifest::{OciDescriptor, OciImageManifest},
+    secrets::RegistryAuth,
+    token_cache::RegistryTokenType,
+    Reference, RegistryOperation,
 };
 use reqwest::Url;
+use serde::Serialize;
 use spin_common::sha256;
 use spin_common::ui::quoted_path;
 use spin_common::url::parse_file_url;
@@ -22,7 +28,11 @@ use spin_locked_app::locked::{ContentPath, ContentRef, LockedApp};
 use tokio::fs;
 use walkdir::WalkDir;
 
-use crate::auth::AuthConfig;
+use crate::auth::{registry_auth, AuthConfig};
+use crate::component_registry::ComponentRegistry;
+use crate::layout::OciLayout;
+use crate::sbom::{self, Sbom, SBOM_MEDIA_TYPE};
+use crate::signing::{self, Signer, Verifier, SIGNATURE_ANNOTATION, SIGNATURE_MEDIA_TYPE};
 
 // TODO: the media types for application, data and archive layer are not final
 /// Media type for a layer representing a locked Spin application configuration
@@ -75,6 +85,26 @@ pub struct Client {
     oci: oci_distribution::Client,
     /// Client options
     pub opts: ClientOpts,
+    /// Key with which to sign pushed applications.
+    signer: Option<Signer>,
+    /// Keys against which to verify the signatures of pulled applications.
+    verifier: Option<Verifier>,
+}
+
+/// A description of a Spin application in an OCI registry.
+#[derive(Debug, Serialize)]
+pub struct Inspection {
+    /// The full reference of the application.
+    pub reference: String,
+    /// The digest of the application manifest.
+    pub digest: String,
+    /// The application manifest.
+    pub manifest: OciImageManifest,
+    /// Whether a signature is attached to the application. This does not
+    /// mean that the signature is valid.
+    pub signed: bool,
+    /// The bill of materials attached to the application, if any.
+    pub sbom: Option<Sbom>,
 }
 
 #[derive(Clone)]
@@ -97,9 +127,25 @@ impl Client {
             oci: client,
             cache,
             opts,
+            signer: None,
+            verifier: None,
         })
     }
 
+    /// Sign the manifest of every application pushed by this client.
+    pub fn with_signer(mut self, signer: Signer) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Require every application pulled by this client to be signed by one
+    /// of the verifier's keys. Nothing is written to the cache for an
+    /// application which fails verification.
+    pub fn with_verifier(mut self, verifier: Verifier) -> Self {
+        self.verifier = Some(verifier);
+        self
+    }
+
     /// Push a Spin application to an OCI registry and return the digest (or None
     /// if the digest cannot be determined).
     pub async fn push(
@@ -118,10 +164,11 @@ impl Client {
         // Create a locked application from the application manifest.
         // TODO: We don't need an extra copy here for each asset to prepare the application.
         // We should be able to use assets::collect instead when constructing the locked app.
-        let locked = spin_loader::from_file(
+        let locked = spin_loader::from_file_with_oci_registry(
             manifest_path,
             FilesMountStrategy::Copy(working_dir.path().into()),
             None,
+            Arc::new(ComponentRegistry::with_client(self.oci.clone())),
         )
         .await?;
 
@@ -196,7 +243,7 @@ impl Client {
         // Construct empty/default OCI config file. Data may be parsed according to
         // the expected config structure per the image spec, so we want to ensure it conforms.
         // (See https://github.com/opencontainers/image-spec/blob/main/config.md)
-        // TODO: Explore adding data applicable to the Spin app being published.
+        // Supply-chain metadata is attached as a separate artifact instead; see `push_sbom`.
         let oci_config_file = ConfigFile {
             architecture: oci_distribution::config::Architecture::Wasm,
             os: oci_distribution::config::Os::Wasip1,
@@ -210,6 +257,8 @@ impl Client {
         let oci_config =
             oci_distribution::client::Config::oci_v1_from_config_file(oci_config_file, None)?;
         let manifest = OciImageManifest::build(&layers, &oci_config, annotations);
+        let manifest_bytes =
+            serde_json::to_vec(&manifest).context("could not serialize manifest")?;
 
         let response = self
             .oci
@@ -221,9 +270,187 @@ impl Client {
         tracing::info!("Pushed {:?}", response);
 
         let digest = digest_from_url(&response);
+        // The pushed manifest is only known to be the one serialized here if
+        // the digests match; otherwise attached artifacts are found by tag only.
+        let subject = digest
+            .as_deref()
+            .and_then(|digest| manifest_descriptor(&manifest_bytes, digest));
+        match &digest {
+            Some(digest) => {
+                if let Err(e) = self
+                    .push_sbom(&locked_app, &reference, digest, subject.clone(), &auth)
+                    .await
+                {
+                    tracing::warn!("Could not push bill of materials for Spin application: {e:#}");
+                }
+            }
+            None => tracing::warn!(
+                "Not attaching bill of materials: the registry did not return the digest"
+            ),
+        }
+        self.sign_pushed(&reference, digest.as_deref(), subject, &auth)
+            .await?;
         Ok(digest)
     }
 
+    /// Push a bill of materials for the application with the given digest.
+    async fn push_sbom(
+        &self,
+        locked_app: &LockedApp,
+        reference: &Reference,
+        digest: &str,
+        subject: Option<OciDescriptor>,
+        auth: &RegistryAuth,
+    ) -> Result<()> {
+        let sbom = serde_json::to_vec(&Sbom::from_locked_app(locked_app))
+            .context("could not serialize bill of materials")?;
+        let sbom_reference = sbom::sbom_reference(reference, digest)?;
+        self.push_attached(
+            &sbom_reference,
+            ImageLayer::new(sbom, SBOM_MEDIA_TYPE.to_string(), None),
+            None,
+            subject,
+            auth,
+        )
+        .await?;
+        tracing::info!("Pushed bill of materials {sbom_reference}");
+        Ok(())
+    }
+
+    /// Push a Spin application from an OCI image layout (a directory or `.tar`
+    /// archive, as written by [`Client::save`]) to an OCI registry and return
+    /// the digest of the pushed manifest. If the layout contains more than one
+    /// application, the one saved under `reference` is pushed.
+    pub async fn push_layout(
+        &mut self,
+        layout: &Path,
+        reference: impl AsRef<str>,
+    ) -> Result<Option<String>> {
+        let reference: Reference = reference
+            .as_ref()
+            .parse()
+            .with_context(|| format!("cannot parse reference {}", reference.as_ref()))?;
+        let auth = Self::auth(&reference).await?;
+
+        let layout = OciLayout::open(layout).await?;
+        let (manifest_bytes, digest, _) = layout.manifest(Some(reference.whole().as_str())).await?;
+        let subject = manifest_descriptor(&manifest_bytes, &digest);
+        let manifest: OciImageManifest =
+            serde_json::from_slice(&manifest_bytes).context("cannot parse manifest")?;
+
+        self.oci
+            .auth(&reference, &auth, RegistryOperation::Push)
+            .await
+            .context("cannot authenticate to registry")?;
+        for descriptor in std::iter::once(&manifest.config).chain(&manifest.layers) {
+            let bytes = layout.read_blob(&descriptor.digest).await?;
+            self.oci
+                .push_blob(&reference, &bytes, &descriptor.digest)
+                .await
+                .with_context(|| format!("cannot push blob {}", descriptor.digest))?;
+        }
+
+        // The manifest is pushed exactly as saved, so that it keeps its digest
+        // and any signature made for it remains valid.
+        let media_type = manifest
+            .media_type
+            .as_deref()
+            .unwrap_or(oci_distribution::manifest::OCI_IMAGE_MEDIA_TYPE);
+        let response = self
+            .oci
+            .push_manifest_raw(
+                &reference,
+                manifest_bytes,
+                http::HeaderValue::from_str(media_type)
+                    .with_context(|| format!("invalid manifest media type {media_type:?}"))?,
+            )
+            .await
+            .context("cannot push Spin application")?;
+
+        tracing::info!("Pushed {:?}", response);
+
+        self.sign_pushed(&reference, Some(&digest), subject, &auth)
+            .await?;
+        Ok(Some(digest))
+    }
+
+    /// Push a signature for a just-pushed manifest if this client signs.
+    async fn sign_pushed(
+        &self,
+        reference: &Reference,
+        digest: Option<&str>,
+        subject: Option<OciDescriptor>,
+        auth: &RegistryAuth,
+    ) -> Result<()> {
+        let Some(signer) = &self.signer else {
+            return Ok(());
+        };
+        let digest = digest
+            .context("cannot sign Spin application: the registry did not return its digest")?;
+        self.push_signature(signer, reference, digest, subject, auth)
+            .await
+            .context("cannot push signature for Spin application")
+    }
+
+    /// Push a signature artifact for the manifest with the given digest.
+    async fn push_signature(
+        &self,
+        signer: &Signer,
+        reference: &Reference,
+        digest: &str,
+        subject: Option<OciDescriptor>,
+        auth: &RegistryAuth,
+    ) -> Result<()> {
+        let (payload, signature) = signer.sign(digest)?;
+        let annotations = BTreeMap::from([(SIGNATURE_ANNOTATION.to_string(), signature)]);
+        let signature_reference = signing::signature_reference(reference, digest)?;
+        self.push_attached(
+            &signature_reference,
+            ImageLayer::new(payload, SIGNATURE_MEDIA_TYPE.to_string(), None),
+            Some(annotations),
+            subject,
+            auth,
+        )
+        .await?;
+        tracing::info!("Pushed signature {signature_reference}");
+        Ok(())
+    }
+
+    /// Push a single-layer artifact attached to an application manifest. The
+    /// artifact is tagged so that it can be found on any registry, and also
+    /// names the application manifest as its subject, if known, so that
+    /// registries supporting the referrers API can list it.
+    async fn push_attached(
+        &self,
+        attached_reference: &Reference,
+        layer: ImageLayer,
+        annotations: Option<BTreeMap<String, String>>,
+        subject: Option<OciDescriptor>,
+        auth: &RegistryAuth,
+    ) -> Result<()> {
+        let layers = vec![layer];
+        let oci_config = oci_distribution::client::Config::oci_v1_from_config_file(
+            ConfigFile {
+                architecture: oci_distribution::config::Architecture::Wasm,
+                os: oci_distribution::config::Os::Wasip1,
+                ..Default::default()
+            },
+            None,
+        )?;
+        let mut manifest = OciImageManifest::build(&layers, &oci_config, annotations);
+        manifest.subject = subject;
+        self.oci
+            .push(
+                attached_reference,
+                &layers,
+                oci_config,
+                auth,
+                Some(manifest),
+            )
+            .await?;
+        Ok(())
+    }
+
     /// Assemble ImageLayers for a locked application using the provided
     /// AssemblyMode and return the resulting Vec<ImageLayer>.
     async fn assemble_layers(
@@ -369,8 +596,15 @@ impl Client {
         let reference: Reference = reference.parse().context("cannot parse reference")?;
         let auth = Self::auth(&reference).await?;
 
-        // Pull the manifest from the registry.
-        let (manifest, digest) = self.oci.pull_image_manifest(&reference, &auth).await?;
+        // Pull the manifest from the registry, checking its signature first if required.
+        let (manifest, digest) = match &self.verifier {
+            Some(verifier) => self
+                .pull_verified_manifest(&reference, &auth, verifier)
+                .await
+                .with_context(|| format!("cannot verify signature of {reference}"))?,
+            None => self.oci.pull_image_manifest(&reference, &auth).await?,
+        };
+        let verify_digests = self.verifier.is_some();
 
         let manifest_json = serde_json::to_string(&manifest)?;
         tracing::debug!("Pulled manifest: {}", manifest_json);
@@ -386,6 +620,9 @@ impl Client {
         self.oci
             .pull_blob(&reference, &manifest.config, &mut cfg_bytes)
             .await?;
+        if verify_digests {
+            check_digest(&cfg_bytes, &manifest.config.digest)?;
+        }
         self.write_locked_app_config(&reference.to_string(), &cfg_bytes)
             .await
             .context("unable to write locked app config to cache")?;
@@ -408,23 +645,11 @@ impl Client {
                     tracing::debug!("Pulling layer {}", &layer.digest);
                     let mut bytes = Vec::with_capacity(layer.size.try_into()?);
                     this.oci.pull_blob(&reference, &layer, &mut bytes).await?;
-                    match layer.media_type.as_str() {
-                        SPIN_APPLICATION_MEDIA_TYPE => {
-                            this.write_locked_app_config(&reference.to_string(), &bytes)
-                                .await
-                                .with_context(|| "unable to write locked app config to cache")?;
-                        }
-                        WASM_LAYER_MEDIA_TYPE => {
-                            this.cache.write_wasm(&bytes, &layer.digest).await?;
-                        }
-                        ARCHIVE_MEDIATYPE => {
-                            unpack_archive_layer(&this.cache, &bytes, &layer.digest).await?;
-                        }
-                        _ => {
-                            this.cache.write_data(&bytes, &layer.digest).await?;
-                        }
+                    if verify_digests {
+                        check_digest(&bytes, &layer.digest)?;
                     }
-                    Ok(())
+                    this.write_layer(&reference.to_string(), &layer, &bytes)
+                        .await
                 }
             })
             .buffer_unordered(MAX_PARALLEL_PULL)
@@ -435,6 +660,313 @@ impl Client {
         Ok(())
     }
 
+    /// Write a pulled layer to the cache: a Wasm module in the Wasm directory,
+    /// and anything else in the data directory (after unpacking if an archive layer).
+    async fn write_layer(
+        &self,
+        reference: &str,
+        layer: &OciDescriptor,
+        bytes: &[u8],
+    ) -> Result<()> {
+        match layer.media_type.as_str() {
+            SPIN_APPLICATION_MEDIA_TYPE => {
+                self.write_locked_app_config(reference, bytes)
+                    .await
+                    .with_context(|| "unable to write locked app config to cache")?;
+            }
+            WASM_LAYER_MEDIA_TYPE => {
+                self.cache.write_wasm(bytes, &layer.digest).await?;
+            }
+            ARCHIVE_MEDIATYPE => {
+                unpack_archive_layer(&self.cache, bytes, &layer.digest).await?;
+            }
+            _ => {
+                self.cache.write_data(bytes, &layer.digest).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Save a Spin application from an OCI registry as an OCI image layout at
+    /// `dest`, for transfer to machines without access to the registry. If
+    /// `dest` ends in `.tar` the layout is written as a tar archive, otherwise
+    /// as a directory, adding to any layout already in that directory.
+    /// Returns the digest of the saved manifest.
+    pub async fn save(&mut self, reference: &str, dest: &Path) -> Result<String> {
+        let reference: Reference = reference.parse().context("cannot parse reference")?;
+        let auth = Self::auth(&reference).await?;
+
+        let (manifest_bytes, manifest, digest) = self.pull_raw_manifest(&reference, &auth).await?;
+        if let Some(verifier) = &self.verifier {
+            self.verify_signature(&reference, &digest, &auth, verifier)
+                .await
+                .with_context(|| format!("cannot verify signature of {reference}"))?;
+        }
+
+        let staging_dir = tempfile::tempdir()?;
+        let layout_dir = if dest.extension().is_some_and(|ext| ext == "tar") {
+            staging_dir.path()
+        } else {
+            dest
+        };
+        let layout = OciLayout::open_or_create(layout_dir).await?;
+
+        for descriptor in std::iter::once(&manifest.config).chain(&manifest.layers) {
+            tracing::debug!("Saving blob {}", &descriptor.digest);
+            let mut bytes = Vec::with_capacity(descriptor.size.try_into()?);
+            self.oci
+                .pull_blob(&reference, descriptor, &mut bytes)
+                .await?;
+            check_digest(&bytes, &descriptor.digest)?;
+            layout.write_blob(&bytes).await?;
+        }
+        let media_type = manifest
+            .media_type
+            .as_deref()
+            .unwrap_or(oci_distribution::manifest::OCI_IMAGE_MEDIA_TYPE);
+        layout
+            .add_manifest(&manifest_bytes, media_type, &reference.whole())
+            .await?;
+
+        if layout_dir != dest {
+            layout.write_tar(dest).await?;
+        }
+        tracing::info!("Saved {}@{} to {}", reference, digest, quoted_path(dest));
+
+        Ok(digest)
+    }
+
+    /// Load a Spin application from an OCI image layout (a directory or `.tar`
+    /// archive) into the cache, as if it had been pulled from a registry.
+    /// The application is cached under `reference` if given, or else under
+    /// the reference it was saved from. If the layout contains more than one
+    /// application, the one saved under `reference` is loaded. Returns the
+    /// reference used.
+    pub async fn load(&mut self, layout: &Path, reference: Option<&str>) -> Result<String> {
+        let layout = OciLayout::open(layout).await?;
+        let (manifest_bytes, digest, saved_reference) = layout.manifest(reference).await?;
+        let reference: Reference = reference
+            .or(saved_reference.as_deref())
+            .context(
+                "the image layout does not record a reference for the application; specify one",
+            )?
+            .parse()
+            .context("cannot parse reference")?;
+        let manifest: OciImageManifest =
+            serde_json::from_slice(&manifest_bytes).context("cannot parse manifest")?;
+
+        let m = self.manifest_path(&reference.to_string()).await?;
+        fs::write(&m, &manifest_bytes).await?;
+
+        // As with pulling, the config may be the locked app config for older
+        // published Spin apps.
+        let cfg_bytes = layout.read_blob(&manifest.config.digest).await?;
+        self.write_locked_app_config(&reference.to_string(), &cfg_bytes)
+            .await
+            .context("unable to write locked app config to cache")?;
+
+        for layer in &manifest.layers {
+            let bytes = layout.read_blob(&layer.digest).await?;
+            self.write_layer(&reference.to_string(), layer, &bytes)
+                .await?;
+        }
+        tracing::info!("Loaded {}@{}", reference, digest);
+
+        Ok(reference.to_string())
+    }
+
+    /// Pull the manifest for `reference` and check that its digest is signed
+    /// by one of the verifier's keys.
+    async fn pull_verified_manifest(
+        &self,
+        reference: &Reference,
+        auth: &RegistryAuth,
+        verifier: &Verifier,
+    ) -> Result<(OciImageManifest, String)> {
+        let (_, manifest, digest) = self.pull_raw_manifest(reference, auth).await?;
+        self.verify_signature(reference, &digest, auth, verifier)
+            .await?;
+        Ok((manifest, digest))
+    }
+
+    /// Pull the manifest for `reference`, returning its exact bytes as well as
+    /// the parsed manifest. The digest is computed from the manifest bytes
+    /// rather than trusted from the registry.
+    async fn pull_raw_manifest(
+        &self,
+        reference: &Reference,
+        auth: &RegistryAuth,
+    ) -> Result<(Vec<u8>, OciImageManifest, String)> {
+        let (manifest_bytes, _) = self
+            .oci
+            .pull_manifest_raw(
+                reference,
+                auth,
+                &[
+                    oci_distribution::manifest::OCI_IMAGE_MEDIA_TYPE,
+                    oci_distribution::manifest::IMAGE_MANIFEST_MEDIA_TYPE,
+                ],
+            )
+            .await?;
+        let digest = format!("sha256:{}", sha256::hex_digest_from_bytes(&manifest_bytes));
+        if let Some(expected) = reference.digest() {
+            if expected != digest {
+                bail!("digest mismatch for manifest: expected {expected}, got {digest}");
+            }
+        }
+        let manifest: OciImageManifest =
+            serde_json::from_slice(&manifest_bytes).context("cannot parse manifest")?;
+        Ok((manifest_bytes, manifest, digest))
+    }
+
+    /// Check that the manifest with `digest` is signed by one of the
+    /// verifier's keys.
+    async fn verify_signature(
+        &self,
+        reference: &Reference,
+        digest: &str,
+        auth: &RegistryAuth,
+        verifier: &Verifier,
+    ) -> Result<()> {
+        let signature_reference = signing::signature_reference(reference, digest)?;
+        let (signature_manifest, _) = self
+            .oci
+            .pull_image_manifest(&signature_reference, auth)
+            .await
+            .with_context(|| format!("no signature found at {signature_reference}"))?;
+        let signature = signature_manifest
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations.get(SIGNATURE_ANNOTATION))
+            .with_context(|| format!("{signature_reference} does not contain a signature"))?;
+        let payload_layer = signature_manifest
+            .layers
+            .iter()
+            .find(|layer| layer.media_type == SIGNATURE_MEDIA_TYPE)
+            .with_context(|| format!("{signature_reference} does not contain a signed payload"))?;
+        let mut payload = Vec::new();
+        self.oci
+            .pull_blob(&signature_reference, payload_layer, &mut payload)
+            .await?;
+        check_digest(&payload, &payload_layer.digest)?;
+        verifier.verify(digest, &payload, signature)?;
+
+        tracing::info!("Verified signature of {}@{}", reference, digest);
+        Ok(())
+    }
+
+    /// Describe a Spin application in an OCI registry without pulling its
+    /// content: its manifest, whether a signature is attached, and the bill of
+    /// materials attached when it was pushed, if any. The signature is not
+    /// checked; use [`Client::with_verifier`] and [`Client::pull`] for that.
+    pub async fn inspect(&mut self, reference: &str) -> Result<Inspection> {
+        let reference: Reference = reference.parse().context("cannot parse reference")?;
+        let auth = Self::auth(&reference).await?;
+
+        let (_, manifest, digest) = self.pull_raw_manifest(&reference, &auth).await?;
+
+        let signature_reference = signing::signature_reference(&reference, &digest)?;
+        let signed = match self
+            .oci
+            .pull_image_manifest(&signature_reference, &auth)
+            .await
+        {
+            Ok(_) => true,
+            Err(e) if is_not_found(&e) => false,
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("cannot check for a signature at {signature_reference}")
+                })
+            }
+        };
+
+        let sbom = self
+            .pull_sbom(&reference, &digest, &auth)
+            .await
+            .context("cannot pull bill of materials")?;
+
+        Ok(Inspection {
+            reference: reference.whole(),
+            digest,
+            manifest,
+            signed,
+            sbom,
+        })
+    }
+
+    /// Pull the bill of materials attached to the manifest with the given
+    /// digest, or None if there isn't one.
+    async fn pull_sbom(
+        &self,
+        reference: &Reference,
+        digest: &str,
+        auth: &RegistryAuth,
+    ) -> Result<Option<Sbom>> {
+        let sbom_reference = sbom::sbom_reference(reference, digest)?;
+        let manifest = match self.oci.pull_image_manifest(&sbom_reference, auth).await {
+            Ok((manifest, _)) => manifest,
+            Err(e) if is_not_found(&e) => {
+                tracing::debug!("No bill of materials at {sbom_reference}: {e:?}");
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let layer = manifest
+            .layers
+            .iter()
+            .find(|layer| layer.media_type == SBOM_MEDIA_TYPE)
+            .with_context(|| format!("{sbom_reference} does not contain a bill of materials"))?;
+        let mut bytes = Vec::new();
+        self.oci
+            .pull_blob(&sbom_reference, layer, &mut bytes)
+            .await?;
+        check_digest(&bytes, &layer.digest)?;
+        let sbom = serde_json::from_slice(&bytes).context("invalid bill of materials")?;
+        Ok(Some(sbom))
+    }
+
+    /// Pull an artifact made up of compressed archive layers (such as a set of
+    /// templates) from an OCI registry, and unpack its layers, in order, into `dest`.
+    /// Layers of other media types are ignored. Returns the digest of the pulled
+    /// manifest.
+    pub async fn pull_archive_artifact(&mut self, reference: &str, dest: &Path) -> Result<String> {
+        let reference: Reference = reference.parse().context("cannot parse reference")?;
+        let auth = Self::auth(&reference).await?;
+
+        let (manifest, digest) = self.oci.pull_image_manifest(&reference, &auth).await?;
+
+        let archive_layers = manifest
+            .layers
+            .iter()
+            .filter(|layer| {
+                layer.media_type == ARCHIVE_MEDIATYPE
+                    || layer.media_type == oci_distribution::manifest::IMAGE_LAYER_GZIP_MEDIA_TYPE
+            })
+            .collect::<Vec<_>>();
+        if archive_layers.is_empty() {
+            bail!("{reference} does not contain any archive layers");
+        }
+
+        let staging_dir = tempfile::tempdir()?;
+        for (index, layer) in archive_layers.into_iter().enumerate() {
+            tracing::debug!("Pulling archive layer {}", &layer.digest);
+            let mut bytes = Vec::with_capacity(layer.size.try_into()?);
+            self.oci.pull_blob(&reference, layer, &mut bytes).await?;
+
+            check_digest(&bytes, &layer.digest)
+                .with_context(|| format!("invalid layer in {reference}"))?;
+
+            let archive_path = staging_dir.path().join(format!("layer{index}.tar.gz"));
+            fs::write(&archive_path, &bytes).await?;
+            crate::utils::unarchive(&archive_path, dest)
+                .await
+                .with_context(|| format!("unable to unpack layer {}", layer.digest))?;
+        }
+        tracing::info!("Pulled {}@{}", reference, digest);
+
+        Ok(digest)
+    }
+
     /// Get the file path to an OCI manifest given a reference.
     /// If the directory for the manifest does not exist, this will create it.
     async fn manifest_path(&self, reference: impl AsRef<str>) -> Result<PathBuf> {
@@ -581,33 +1113,11 @@ impl Client {
 
     /// Construct the registry authentication based on the reference.
     async fn auth(reference: &Reference) -> Result<RegistryAuth> {
-        let server = reference
-            .resolve_registry()
-            .strip_suffix('/')
-            .unwrap_or_else(|| reference.resolve_registry());
-
-        match AuthConfig::get_auth_from_default(server).await {
-            Ok(c) => Ok(c),
-            Err(_) => match docker_credential::get_credential(server) {
-                Err(e) => {
-                    tracing::trace!("Cannot retrieve credentials from Docker, attempting to use anonymous auth: {}", e);
-                    Ok(RegistryAuth::Anonymous)
-                }
-
-                Ok(DockerCredential::UsernamePassword(username, password)) => {
-                    tracing::trace!("Found Docker credentials");
-                    Ok(RegistryAuth::Basic(username, password))
-                }
-                Ok(DockerCredential::IdentityToken(_)) => {
-                    tracing::trace!("Cannot use contents of Docker config, identity token not supported. Using anonymous auth");
-                    Ok(RegistryAuth::Anonymous)
-                }
-            },
-        }
+        registry_auth(reference).await
     }
 
     /// Build the OCI client configuration given the insecure option.
-    fn build_config(insecure: bool) -> oci_distribution::client::ClientConfig {
+    pub(crate) fn build_config(insecure: bool) -> oci_distribution::client::ClientConfig {
         let protocol = if insecure {
             oci_distribution::client::ClientProtocol::Http
         } else {
@@ -662,6 +1172,15 @@ pub async fn unpack_archive_layer(
     Ok(())
 }
 
+/// Check that `bytes` have the given sha256 digest.
+fn check_digest(bytes: &[u8], expected: &str) -> Result<()> {
+    let actual = format!("sha256:{}", sha256::hex_digest_from_bytes(bytes));
+    if actual != expected {
+        bail!("digest mismatch for layer: expected {expected}, got {actual}");
+    }
+    Ok(())
+}
+
 fn digest_from_url(manifest_url: &str) -> Option<String> {
     // The URL is in the form "https://host/v2/refname/manifests/sha256:..."
     let manifest_url = Url::parse(manifest_url).ok()?;
@@ -674,6 +1193,43 @@ fn digest_from_url(manifest_url: &str) -> Option<String> {
     }
 }
 
+/// Returns a descriptor for the manifest serialized as `manifest_bytes`, or
+/// None if the bytes don't have the given digest.
+fn manifest_descriptor(manifest_bytes: &[u8], digest: &str) -> Option<OciDescriptor> {
+    let actual = format!("sha256:{}", sha256::hex_digest_from_bytes(manifest_bytes));
+    if actual != digest {
+        tracing::debug!("Manifest digest {actual} does not match pushed digest {digest}");
+        return None;
+    }
+    let media_type = serde_json::from_slice::<OciImageManifest>(manifest_bytes)
+        .ok()
+        .and_then(|manifest| manifest.media_type)
+        .unwrap_or_else(|| oci_distribution::manifest::OCI_IMAGE_MEDIA_TYPE.to_owned());
+    Some(OciDescriptor {
+        media_type,
+        digest: actual,
+        size: manifest_bytes.len() as i64,
+        urls: None,
+        annotations: None,
+    })
+}
+
+/// Returns true if `err` means that the requested manifest does not exist,
+/// rather than that the registry could not be reached or refused the request.
+fn is_not_found(err: &OciDistributionError) -> bool {
+    match err {
+        OciDistributionError::ImageManifestNotFoundError(_) => true,
+        OciDistributionError::RegistryError { envelope, .. } => envelope.errors.iter().any(|e| {
+            matches!(
+                e.code,
+                OciErrorCode::ManifestUnknown | OciErrorCode::NameUnknown
+            )
+        }),
+        OciDistributionError::ServerError { code, .. } => *code == 404,
+        _ => false,
+    }
+}
+
 fn registry_from_input(server: impl AsRef<str>) -> String {
     // We want to allow a user to login to both https://ghcr.io and ghcr.io.
     let server = server.as_ref();
@@ -702,6 +1258,44 @@ mod test {
         );
     }
 
+    #[test]
+    fn manifest_descriptor_requires_matching_digest() {
+        let manifest = OciImageManifest::default();
+        let bytes = serde_json::to_vec(&manifest).unwrap();
+        let digest = format!("sha256:{}", sha256::hex_digest_from_bytes(&bytes));
+
+        let descriptor = manifest_descriptor(&bytes, &digest).unwrap();
+        assert_eq!(digest, descriptor.digest);
+        assert_eq!(bytes.len() as i64, descriptor.size);
+        assert_eq!(
+            oci_distribution::manifest::OCI_IMAGE_MEDIA_TYPE,
+            descriptor.media_type
+        );
+
+        let other = format!("sha256:{}", sha256::hex_digest_from_bytes(b"other"));
+        assert!(manifest_descriptor(&bytes, &other).is_none());
+    }
+
+    #[test]
+    fn only_missing_manifests_are_not_found() {
+        assert!(is_not_found(
+            &OciDistributionError::ImageManifestNotFoundError("example.com/app".into())
+        ));
+        assert!(is_not_found(&OciDistributionError::ServerError {
+            code: 404,
+            url: "https://example.com/v2/app/manifests/sha256-0.sig".into(),
+            message: "not found".into(),
+        }));
+        assert!(!is_not_found(&OciDistributionError::ServerError {
+            code: 500,
+            url: "https://example.com/v2/app/manifests/sha256-0.sig".into(),
+            message: "internal error".into(),
+        }));
+        assert!(!is_not_found(&OciDistributionError::UnauthorizedError {
+            url: "https://example.com/v2/app/manifests/sha256-0.sig".into(),
+        }));
+    }
+
     #[test]
     fn can_derive_registry_from_input() {
         #[derive(Clone)]
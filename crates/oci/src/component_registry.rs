@@ -0,0 +1,63 @@
+//! Pulling component Wasm from OCI registries for the Spin loader.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use oci_distribution::{manifest::OciDescriptor, Reference};
+use spin_loader::oci::{OciLayer, OciRegistry};
+
+use crate::auth::registry_auth;
+use crate::client::Client;
+
+/// Pulls components whose source is an OCI Wasm artifact, using the same
+/// registry credentials and connection settings as [`Client`].
+pub struct ComponentRegistry {
+    oci: oci_distribution::Client,
+}
+
+impl ComponentRegistry {
+    /// Create a new instance, connecting to registries over HTTP rather than
+    /// HTTPS if `insecure` is set.
+    pub fn new(insecure: bool) -> Self {
+        Self::with_client(oci_distribution::Client::new(Client::build_config(
+            insecure,
+        )))
+    }
+
+    pub(crate) fn with_client(oci: oci_distribution::Client) -> Self {
+        Self { oci }
+    }
+}
+
+#[async_trait]
+impl OciRegistry for ComponentRegistry {
+    async fn layers(&self, reference: &str) -> Result<Vec<OciLayer>> {
+        let reference: Reference = reference.parse().context("cannot parse reference")?;
+        let auth = registry_auth(&reference).await?;
+        let (manifest, _) = self.oci.pull_image_manifest(&reference, &auth).await?;
+        Ok(manifest
+            .layers
+            .into_iter()
+            .map(|layer| OciLayer {
+                media_type: layer.media_type,
+                digest: layer.digest,
+                size: layer.size,
+            })
+            .collect())
+    }
+
+    async fn pull_layer(&self, reference: &str, layer: &OciLayer) -> Result<Vec<u8>> {
+        let reference: Reference = reference.parse().context("cannot parse reference")?;
+        let descriptor = OciDescriptor {
+            media_type: layer.media_type.clone(),
+            digest: layer.digest.clone(),
+            size: layer.size,
+            urls: None,
+            annotations: None,
+        };
+        let mut bytes = vec![];
+        self.oci
+            .pull_blob(&reference, &descriptor, &mut bytes)
+            .await?;
+        Ok(bytes)
+    }
+}
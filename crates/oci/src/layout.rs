@@ -0,0 +1,369 @@
+//! Reading and writing Spin applications as OCI image layouts, for moving
+//! applications between machines without a registry.
+//!
+//! See https://github.com/opencontainers/image-spec/blob/main/image-layout.md
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use spin_common::sha256;
+use spin_common::ui::quoted_path;
+use tokio::fs;
+
+const OCI_LAYOUT_FILE: &str = "oci-layout";
+const OCI_LAYOUT_VERSION: &str = "1.0.0";
+const INDEX_FILE: &str = "index.json";
+const BLOBS_DIR: &str = "blobs";
+const INDEX_MEDIA_TYPE: &str = "application/vnd.oci.image.index.v1+json";
+/// Annotation naming the reference of a manifest in an image layout index
+pub const REF_NAME_ANNOTATION: &str = "org.opencontainers.image.ref.name";
+
+/// Returns whether `path` looks like an OCI image layout: either a directory
+/// containing an `oci-layout` file or a `.tar` archive of one.
+pub fn is_oci_layout(path: &Path) -> bool {
+    path.join(OCI_LAYOUT_FILE).is_file() || (path.is_file() && is_tar(path))
+}
+
+fn is_tar(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "tar")
+}
+
+/// An OCI image layout directory.
+pub struct OciLayout {
+    root: PathBuf,
+    // Holds the directory a tar archive was unpacked into.
+    _unpacked: Option<tempfile::TempDir>,
+}
+
+impl OciLayout {
+    /// Creates an empty image layout in `dir`.
+    pub async fn create(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir.join(BLOBS_DIR).join("sha256"))
+            .await
+            .with_context(|| format!("cannot create image layout in {}", quoted_path(dir)))?;
+        let layout = Self {
+            root: dir.to_owned(),
+            _unpacked: None,
+        };
+        fs::write(
+            dir.join(OCI_LAYOUT_FILE),
+            serde_json::to_vec(&LayoutFile {
+                image_layout_version: OCI_LAYOUT_VERSION.to_owned(),
+            })?,
+        )
+        .await?;
+        layout.write_index(&Index::default()).await?;
+        Ok(layout)
+    }
+
+    /// Opens the image layout in `dir` if there is one, so that manifests can
+    /// be added alongside those already there, or else creates an empty one.
+    pub async fn open_or_create(dir: &Path) -> Result<Self> {
+        if dir.join(OCI_LAYOUT_FILE).is_file() {
+            Self::open(dir).await
+        } else {
+            Self::create(dir).await
+        }
+    }
+
+    /// Opens the image layout at `path`, which may be a directory or a `.tar`
+    /// archive.
+    pub async fn open(path: &Path) -> Result<Self> {
+        let (root, unpacked) = if path.is_file() && is_tar(path) {
+            let dir = tempfile::tempdir()?;
+            let archive = async_tar::Archive::new(
+                tokio_util::compat::TokioAsyncReadCompatExt::compat(fs::File::open(path).await?),
+            );
+            archive
+                .unpack(dir.path())
+                .await
+                .with_context(|| format!("cannot unpack {}", quoted_path(path)))?;
+            (dir.path().to_owned(), Some(dir))
+        } else {
+            (path.to_owned(), None)
+        };
+
+        let layout_file = fs::read(root.join(OCI_LAYOUT_FILE))
+            .await
+            .with_context(|| format!("{} is not an OCI image layout", quoted_path(path)))?;
+        let layout_file: LayoutFile = serde_json::from_slice(&layout_file)
+            .with_context(|| format!("invalid {OCI_LAYOUT_FILE} file in {}", quoted_path(path)))?;
+        if layout_file.image_layout_version != OCI_LAYOUT_VERSION {
+            anyhow::bail!(
+                "unsupported OCI image layout version {}",
+                layout_file.image_layout_version
+            );
+        }
+
+        Ok(Self {
+            root,
+            _unpacked: unpacked,
+        })
+    }
+
+    /// Writes a blob into the layout, returning its digest.
+    pub async fn write_blob(&self, bytes: &[u8]) -> Result<String> {
+        let digest = format!("sha256:{}", sha256::hex_digest_from_bytes(bytes));
+        let path = self.blob_path(&digest)?;
+        if !path.exists() {
+            fs::write(&path, bytes)
+                .await
+                .with_context(|| format!("cannot write blob {digest}"))?;
+        }
+        Ok(digest)
+    }
+
+    /// Reads a blob from the layout, checking that it has the expected digest.
+    pub async fn read_blob(&self, digest: &str) -> Result<Vec<u8>> {
+        let bytes = fs::read(self.blob_path(digest)?)
+            .await
+            .with_context(|| format!("image layout does not contain blob {digest}"))?;
+        let actual = format!("sha256:{}", sha256::hex_digest_from_bytes(&bytes));
+        if actual != digest {
+            anyhow::bail!("digest mismatch for blob: expected {digest}, got {actual}");
+        }
+        Ok(bytes)
+    }
+
+    /// Writes a manifest into the layout and adds it to the index under
+    /// `ref_name`, replacing any manifest previously added under that name.
+    pub async fn add_manifest(
+        &self,
+        manifest: &[u8],
+        media_type: &str,
+        ref_name: &str,
+    ) -> Result<String> {
+        let digest = self.write_blob(manifest).await?;
+        let mut index = self.read_index().await?;
+        index.manifests.retain(|m| m.ref_name() != Some(ref_name));
+        index.manifests.push(IndexEntry {
+            media_type: media_type.to_owned(),
+            digest: digest.clone(),
+            size: manifest.len() as u64,
+            annotations: [(REF_NAME_ANNOTATION.to_owned(), ref_name.to_owned())]
+                .into_iter()
+                .collect(),
+        });
+        self.write_index(&index).await?;
+        Ok(digest)
+    }
+
+    /// Finds a manifest in the index, returning its bytes, digest and reference
+    /// name, if it has one. If `ref_name` is given, the manifest added under
+    /// that name is used if there is one; otherwise the index must contain
+    /// exactly one manifest.
+    pub async fn manifest(
+        &self,
+        ref_name: Option<&str>,
+    ) -> Result<(Vec<u8>, String, Option<String>)> {
+        let index = self.read_index().await?;
+        let named = ref_name.and_then(|ref_name| {
+            index
+                .manifests
+                .iter()
+                .find(|m| m.ref_name() == Some(ref_name))
+        });
+        let entry = match (named, index.manifests.as_slice()) {
+            (Some(entry), _) | (None, [entry]) => entry,
+            (None, []) => anyhow::bail!("image layout does not contain any manifests"),
+            (None, _) => match ref_name {
+                Some(ref_name) => anyhow::bail!("image layout does not contain {ref_name:?}"),
+                None => anyhow::bail!(
+                    "image layout contains more than one manifest; specify which to use"
+                ),
+            },
+        };
+        let bytes = self.read_blob(&entry.digest).await?;
+        Ok((
+            bytes,
+            entry.digest.clone(),
+            entry.ref_name().map(str::to_owned),
+        ))
+    }
+
+    /// Writes the layout as an uncompressed tar archive at `dest`.
+    pub async fn write_tar(&self, dest: &Path) -> Result<()> {
+        let file = fs::File::create(dest)
+            .await
+            .with_context(|| format!("cannot create {}", quoted_path(dest)))?;
+        let mut builder = async_tar::Builder::new(
+            tokio_util::compat::TokioAsyncWriteCompatExt::compat_write(file),
+        );
+        builder.append_dir_all(".", &self.root).await?;
+        builder.finish().await?;
+        use tokio::io::AsyncWriteExt;
+        builder.into_inner().await?.into_inner().shutdown().await?;
+        Ok(())
+    }
+
+    fn blob_path(&self, digest: &str) -> Result<PathBuf> {
+        let (algorithm, hex) = digest
+            .split_once(':')
+            .with_context(|| format!("invalid digest {digest:?}"))?;
+        anyhow::ensure!(
+            algorithm == "sha256" && hex.chars().all(|c| c.is_ascii_hexdigit()),
+            "unsupported digest {digest:?}"
+        );
+        Ok(self.root.join(BLOBS_DIR).join(algorithm).join(hex))
+    }
+
+    async fn read_index(&self) -> Result<Index> {
+        let bytes = fs::read(self.root.join(INDEX_FILE))
+            .await
+            .context("image layout does not contain an index")?;
+        serde_json::from_slice(&bytes).context("invalid image layout index")
+    }
+
+    async fn write_index(&self, index: &Index) -> Result<()> {
+        fs::write(self.root.join(INDEX_FILE), serde_json::to_vec(index)?)
+            .await
+            .context("cannot write image layout index")
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayoutFile {
+    image_layout_version: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Index {
+    schema_version: u32,
+    #[serde(default)]
+    media_type: Option<String>,
+    manifests: Vec<IndexEntry>,
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Self {
+            schema_version: 2,
+            media_type: Some(INDEX_MEDIA_TYPE.to_owned()),
+            manifests: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexEntry {
+    media_type: String,
+    digest: String,
+    size: u64,
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    annotations: std::collections::BTreeMap<String, String>,
+}
+
+impl IndexEntry {
+    fn ref_name(&self) -> Option<&str> {
+        self.annotations
+            .get(REF_NAME_ANNOTATION)
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn round_trips_through_tar() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = OciLayout::create(&dir.path().join("layout")).await.unwrap();
+        let blob = layout.write_blob(b"hello").await.unwrap();
+        layout
+            .add_manifest(b"{}", INDEX_MEDIA_TYPE, "example.com/app:v1")
+            .await
+            .unwrap();
+        let tar = dir.path().join("app.tar");
+        layout.write_tar(&tar).await.unwrap();
+
+        assert!(is_oci_layout(&dir.path().join("layout")));
+        assert!(is_oci_layout(&tar));
+
+        let opened = OciLayout::open(&tar).await.unwrap();
+        assert_eq!(b"hello".to_vec(), opened.read_blob(&blob).await.unwrap());
+        let (manifest, _, ref_name) = opened.manifest(None).await.unwrap();
+        assert_eq!(b"{}".to_vec(), manifest);
+        assert_eq!(Some("example.com/app:v1"), ref_name.as_deref());
+    }
+
+    #[tokio::test]
+    async fn replaces_manifest_with_same_ref_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = OciLayout::create(dir.path()).await.unwrap();
+        layout
+            .add_manifest(b"{\"a\":1}", INDEX_MEDIA_TYPE, "app:v1")
+            .await
+            .unwrap();
+        layout
+            .add_manifest(b"{\"a\":2}", INDEX_MEDIA_TYPE, "app:v1")
+            .await
+            .unwrap();
+        layout
+            .add_manifest(b"{\"a\":3}", INDEX_MEDIA_TYPE, "app:v2")
+            .await
+            .unwrap();
+
+        let (manifest, _, _) = layout.manifest(Some("app:v1")).await.unwrap();
+        assert_eq!(b"{\"a\":2}".to_vec(), manifest);
+        assert!(layout.manifest(None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn finds_manifest_without_ref_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = OciLayout::create(dir.path()).await.unwrap();
+        let digest = layout.write_blob(b"{}").await.unwrap();
+        let mut index = layout.read_index().await.unwrap();
+        index.manifests.push(IndexEntry {
+            media_type: INDEX_MEDIA_TYPE.to_owned(),
+            digest: digest.clone(),
+            size: 2,
+            annotations: Default::default(),
+        });
+        layout.write_index(&index).await.unwrap();
+
+        let (_, found, ref_name) = layout.manifest(None).await.unwrap();
+        assert_eq!(digest, found);
+        assert_eq!(None, ref_name);
+        let (_, found, _) = layout.manifest(Some("app:v1")).await.unwrap();
+        assert_eq!(digest, found);
+    }
+
+    #[tokio::test]
+    async fn open_or_create_keeps_existing_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        OciLayout::open_or_create(dir.path())
+            .await
+            .unwrap()
+            .add_manifest(b"{\"a\":1}", INDEX_MEDIA_TYPE, "app:v1")
+            .await
+            .unwrap();
+        OciLayout::open_or_create(dir.path())
+            .await
+            .unwrap()
+            .add_manifest(b"{\"a\":2}", INDEX_MEDIA_TYPE, "app:v2")
+            .await
+            .unwrap();
+
+        let layout = OciLayout::open(dir.path()).await.unwrap();
+        let (manifest, _, _) = layout.manifest(Some("app:v1")).await.unwrap();
+        assert_eq!(b"{\"a\":1}".to_vec(), manifest);
+        let (manifest, _, _) = layout.manifest(Some("app:v2")).await.unwrap();
+        assert_eq!(b"{\"a\":2}".to_vec(), manifest);
+        assert!(layout.manifest(Some("app:v3")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_corrupted_blobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = OciLayout::create(dir.path()).await.unwrap();
+        let digest = layout.write_blob(b"hello").await.unwrap();
+        std::fs::write(layout.blob_path(&digest).unwrap(), b"goodbye").unwrap();
+        assert!(layout.read_blob(&digest).await.is_err());
+    }
+}
@@ -3,11 +3,17 @@
 
 mod auth;
 pub mod client;
+mod component_registry;
+pub mod layout;
 mod loader;
+pub mod sbom;
+pub mod signing;
 pub mod utils;
 
 pub use client::Client;
+pub use component_registry::ComponentRegistry;
 pub use loader::OciLoader;
+pub use signing::{Signer, Verifier};
 
 /// URL scheme used for the locked app "origin" metadata field for OCI-sourced apps.
 pub const ORIGIN_URL_SCHEME: &str = "vnd.fermyon.origin-oci";
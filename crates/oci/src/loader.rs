@@ -38,6 +38,24 @@ impl OciLoader {
             .await
     }
 
+    /// Loads an OCI image layout (a directory or `.tar` archive) into the
+    /// cache and returns a LockedApp
+    pub async fn load_layout(&self, client: &mut Client, layout: &Path) -> Result<LockedApp> {
+        let reference = client.load(layout, None).await.with_context(|| {
+            format!(
+                "cannot load Spin application from image layout {}",
+                quoted_path(layout)
+            )
+        })?;
+
+        let lockfile_path = client
+            .lockfile_path(&reference)
+            .await
+            .context("cannot get path to spin.lock")?;
+        self.load_from_cache(lockfile_path, &reference, &client.cache)
+            .await
+    }
+
     /// Loads an OCI Artifact from the given cache and returns a LockedApp with the given reference
     pub async fn load_from_cache(
         &self,
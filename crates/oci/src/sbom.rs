@@ -0,0 +1,201 @@
+//! Supply-chain metadata for pushed Spin applications.
+//!
+//! A bill of materials describing where each component came from and how it
+//! was built is pushed as a separate artifact alongside the application,
+//! tagged `sha256-<hex digest>.sbom` in the same repository.
+
+use anyhow::Result;
+use oci_distribution::Reference;
+use serde::{Deserialize, Serialize};
+use spin_locked_app::locked::{LockedApp, LockedComponent};
+
+/// Media type for the layer of a Spin application bill of materials artifact
+pub const SBOM_MEDIA_TYPE: &str = "application/vnd.fermyon.spin.sbom.v1+json";
+
+const SBOM_TAG_SUFFIX: &str = ".sbom";
+
+/// A bill of materials for a Spin application.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Sbom {
+    /// The application name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The application version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The application components.
+    pub components: Vec<SbomComponent>,
+}
+
+/// A component in a bill of materials.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SbomComponent {
+    /// The component ID.
+    pub id: String,
+    /// The digest of the component Wasm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    /// The URL the component Wasm was downloaded from, if it was not built locally.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The OCI Wasm artifact the component Wasm was pulled from, if it was
+    /// not built locally.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oci: Option<String>,
+    /// The registry package the component Wasm was fetched from, as
+    /// `<package>@<version>`, if it was not built locally.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    /// The registry the package was fetched from, if not the default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// The command used to build the component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_command: Option<String>,
+    /// The files mounted into the component and their digests.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<SbomFile>,
+}
+
+/// A file mounted into a component.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SbomFile {
+    /// The path of the file in the component.
+    pub path: String,
+    /// The digest of the file content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+}
+
+impl Sbom {
+    /// Builds a bill of materials for a locked application whose content has
+    /// been assembled into layers, so that content refs carry digests.
+    pub fn from_locked_app(locked: &LockedApp) -> Self {
+        let metadata_string = |key: &str| {
+            locked
+                .metadata
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::to_owned)
+        };
+        Self {
+            name: metadata_string("name"),
+            version: metadata_string("version"),
+            components: locked
+                .components
+                .iter()
+                .map(SbomComponent::from_locked_component)
+                .collect(),
+        }
+    }
+}
+
+impl SbomComponent {
+    fn from_locked_component(component: &LockedComponent) -> Self {
+        let source = component.metadata.get("source");
+        let source_string = |key: &str| {
+            source
+                .and_then(|s| s.get(key))
+                .and_then(|v| v.as_str())
+                .map(str::to_owned)
+        };
+        let package = source_string("package").map(|package| match source_string("version") {
+            Some(version) => format!("{package}@{version}"),
+            None => package,
+        });
+        let build_command = component
+            .metadata
+            .get("build")
+            .and_then(|build| build.get("command"))
+            .and_then(|command| command.as_str())
+            .map(str::to_owned);
+
+        Self {
+            id: component.id.clone(),
+            digest: component.source.content.digest.clone(),
+            url: source_string("url"),
+            oci: source_string("oci"),
+            package,
+            registry: source_string("registry"),
+            build_command,
+            files: component
+                .files
+                .iter()
+                .map(|f| SbomFile {
+                    path: f.path.to_string_lossy().into_owned(),
+                    digest: f.content.digest.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Returns the reference at which the bill of materials for the manifest with
+/// `digest` in the same repository as `reference` is stored.
+pub(crate) fn sbom_reference(reference: &Reference, digest: &str) -> Result<Reference> {
+    crate::utils::attached_reference(reference, digest, SBOM_TAG_SUFFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_component_origins() {
+        let locked = LockedApp::from_json(
+            serde_json::json!({
+                "spin_lock_version": 1,
+                "metadata": { "name": "app", "version": "1.0.0" },
+                "triggers": [],
+                "components": [
+                    {
+                        "id": "built",
+                        "metadata": { "build": { "command": "cargo build --release" } },
+                        "source": { "content_type": "application/wasm", "digest": "sha256:aaa" },
+                        "files": [{ "path": "static/index.html", "digest": "sha256:bbb" }],
+                    },
+                    {
+                        "id": "downloaded",
+                        "metadata": { "source": { "url": "https://example.com/c.wasm", "digest": "sha256:ccc" } },
+                        "source": { "content_type": "application/wasm", "digest": "sha256:ccc" },
+                    },
+                    {
+                        "id": "oci",
+                        "metadata": { "source": { "oci": "ghcr.io/example/comp:1.0", "digest": "sha256:eee" } },
+                        "source": { "content_type": "application/wasm", "digest": "sha256:eee" },
+                    },
+                    {
+                        "id": "package",
+                        "metadata": { "source": { "registry": null, "package": "example:comp", "version": "1.2.3" } },
+                        "source": { "content_type": "application/wasm", "digest": "sha256:ddd" },
+                    },
+                ],
+            })
+            .to_string()
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let sbom = Sbom::from_locked_app(&locked);
+        assert_eq!(Some("app"), sbom.name.as_deref());
+        assert_eq!(Some("1.0.0"), sbom.version.as_deref());
+
+        let [built, downloaded, oci, package] = sbom.components.as_slice() else {
+            panic!("expected four components");
+        };
+        assert_eq!(Some("sha256:aaa"), built.digest.as_deref());
+        assert_eq!(
+            Some("cargo build --release"),
+            built.build_command.as_deref()
+        );
+        assert_eq!("static/index.html", built.files[0].path);
+        assert_eq!(Some("sha256:bbb"), built.files[0].digest.as_deref());
+        assert_eq!(
+            Some("https://example.com/c.wasm"),
+            downloaded.url.as_deref()
+        );
+        assert_eq!(Some("ghcr.io/example/comp:1.0"), oci.oci.as_deref());
+        assert_eq!(Some("example:comp@1.2.3"), package.package.as_deref());
+        assert_eq!(None, package.registry);
+    }
+}
@@ -0,0 +1,185 @@
+//! Signing of pushed Spin applications and verification of pulled ones.
+//!
+//! A signature is pushed as a separate artifact alongside the signed
+//! application, tagged `sha256-<hex digest>.sig` in the same repository. Its
+//! single layer is a JSON payload naming the digest of the signed manifest,
+//! and the Ed25519 signature over that payload is carried in the manifest
+//! annotations.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    Signature, Signer as _, SigningKey, Verifier as _, VerifyingKey,
+};
+use oci_distribution::Reference;
+use serde::{Deserialize, Serialize};
+use spin_common::ui::quoted_path;
+
+/// Media type for the payload layer of a Spin application signature artifact
+pub const SIGNATURE_MEDIA_TYPE: &str = "application/vnd.fermyon.spin.signature.v1+json";
+/// Manifest annotation holding the base64-encoded signature over the payload
+pub const SIGNATURE_ANNOTATION: &str = "com.fermyon.spin.signature";
+
+const SIGNATURE_TAG_SUFFIX: &str = ".sig";
+
+/// Signs the manifest digests of pushed applications with a private key.
+#[derive(Clone)]
+pub struct Signer {
+    key: SigningKey,
+}
+
+impl Signer {
+    /// Loads an Ed25519 private key from a PKCS#8 PEM file, such as one
+    /// created by `openssl genpkey -algorithm ed25519`.
+    pub fn from_pem_file(path: &Path) -> Result<Self> {
+        let pem = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read signing key {}", quoted_path(path)))?;
+        let key = SigningKey::from_pkcs8_pem(&pem).map_err(|e| {
+            anyhow::anyhow!(
+                "{} is not an Ed25519 private key in PKCS#8 PEM format: {e}",
+                quoted_path(path)
+            )
+        })?;
+        Ok(Self { key })
+    }
+
+    /// Returns the signature payload for `digest` and the base64-encoded
+    /// signature over it.
+    pub(crate) fn sign(&self, digest: &str) -> Result<(Vec<u8>, String)> {
+        let payload = serde_json::to_vec(&SignaturePayload {
+            digest: digest.to_owned(),
+        })?;
+        let signature = self.key.sign(&payload);
+        Ok((payload, BASE64.encode(signature.to_bytes())))
+    }
+}
+
+/// Checks signatures of pulled applications against a set of trusted public keys.
+#[derive(Clone)]
+pub struct Verifier {
+    keys: Vec<VerifyingKey>,
+}
+
+impl Verifier {
+    /// Loads Ed25519 public keys from SPKI PEM files, such as ones created by
+    /// `openssl pkey -pubout`. A signature made by any of them is accepted.
+    pub fn from_pem_files(paths: &[impl AsRef<Path>]) -> Result<Self> {
+        anyhow::ensure!(
+            !paths.is_empty(),
+            "at least one public key is required to verify signatures"
+        );
+        let keys = paths
+            .iter()
+            .map(|path| {
+                let path = path.as_ref();
+                let pem = std::fs::read_to_string(path)
+                    .with_context(|| format!("cannot read public key {}", quoted_path(path)))?;
+                VerifyingKey::from_public_key_pem(&pem).map_err(|e| {
+                    anyhow::anyhow!(
+                        "{} is not an Ed25519 public key in PEM format: {e}",
+                        quoted_path(path)
+                    )
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { keys })
+    }
+
+    /// Checks that `signature` is a valid signature by a trusted key over
+    /// `payload`, and that `payload` names `digest`.
+    pub(crate) fn verify(&self, digest: &str, payload: &[u8], signature: &str) -> Result<()> {
+        let signature = BASE64
+            .decode(signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .context("signature is malformed")?;
+        if !self
+            .keys
+            .iter()
+            .any(|key| key.verify(payload, &signature).is_ok())
+        {
+            anyhow::bail!("signature was not made by any of the trusted keys");
+        }
+        let payload: SignaturePayload =
+            serde_json::from_slice(payload).context("signature payload is malformed")?;
+        if payload.digest != digest {
+            anyhow::bail!("signature is for {}, not {digest}", payload.digest);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignaturePayload {
+    digest: String,
+}
+
+/// Returns the reference at which the signature for the manifest with
+/// `digest` in the same repository as `reference` is stored.
+pub(crate) fn signature_reference(reference: &Reference, digest: &str) -> Result<Reference> {
+    crate::utils::attached_reference(reference, digest, SIGNATURE_TAG_SUFFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "sha256:0a867093096e0ef01ef749b12b6e7a90e4952eda107f89a676eeedce63a8361f";
+
+    fn signer(seed: u8) -> Signer {
+        Signer {
+            key: SigningKey::from_bytes(&[seed; 32]),
+        }
+    }
+
+    fn verifier(seeds: &[u8]) -> Verifier {
+        Verifier {
+            keys: seeds
+                .iter()
+                .map(|seed| signer(*seed).key.verifying_key())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn accepts_signature_by_any_trusted_key() {
+        let (payload, signature) = signer(2).sign(DIGEST).unwrap();
+        verifier(&[1, 2])
+            .verify(DIGEST, &payload, &signature)
+            .unwrap();
+    }
+
+    #[test]
+    fn rejects_signature_by_untrusted_key() {
+        let (payload, signature) = signer(3).sign(DIGEST).unwrap();
+        let err = verifier(&[1, 2])
+            .verify(DIGEST, &payload, &signature)
+            .unwrap_err();
+        assert!(err.to_string().contains("trusted keys"), "{err}");
+    }
+
+    #[test]
+    fn rejects_signature_for_other_digest() {
+        let other = DIGEST.replace("0a86", "ffff");
+        let (payload, signature) = signer(1).sign(&other).unwrap();
+        let err = verifier(&[1])
+            .verify(DIGEST, &payload, &signature)
+            .unwrap_err();
+        assert!(err.to_string().contains("not sha256:"), "{err}");
+    }
+
+    #[test]
+    fn signature_is_tagged_by_digest() {
+        let reference: Reference = "ghcr.io/fermyon/app:v1".parse().unwrap();
+        let sig_ref = signature_reference(&reference, DIGEST).unwrap();
+        assert_eq!("ghcr.io", sig_ref.registry());
+        assert_eq!("fermyon/app", sig_ref.repository());
+        assert_eq!(
+            Some("sha256-0a867093096e0ef01ef749b12b6e7a90e4952eda107f89a676eeedce63a8361f.sig"),
+            sig_ref.tag()
+        );
+    }
+}
@@ -4,6 +4,7 @@ use anyhow::{Context, Result};
 use async_compression::tokio::bufread::GzipDecoder;
 use async_compression::tokio::write::GzipEncoder;
 use async_tar::Archive;
+use oci_distribution::Reference;
 use spin_common::ui::quoted_path;
 use std::path::{Path, PathBuf};
 
@@ -59,3 +60,22 @@ pub async fn unarchive(source: &Path, dest: &Path) -> Result<()> {
     };
     Ok(())
 }
+
+/// Returns the reference at which an artifact attached to the manifest with
+/// `digest` in the same repository as `reference` is stored. Attached artifacts
+/// are tagged `<algorithm>-<hex digest><suffix>`, so that they can be found
+/// on registries which do not support the OCI referrers API.
+pub(crate) fn attached_reference(
+    reference: &Reference,
+    digest: &str,
+    suffix: &str,
+) -> Result<Reference> {
+    let (algorithm, hex) = digest
+        .split_once(':')
+        .with_context(|| format!("invalid digest {digest:?}"))?;
+    Ok(Reference::with_tag(
+        reference.registry().to_owned(),
+        reference.repository().to_owned(),
+        format!("{algorithm}-{hex}{suffix}"),
+    ))
+}
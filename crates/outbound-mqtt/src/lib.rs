@@ -8,7 +8,7 @@ use spin_core::{async_trait, wasmtime::component::Resource};
 use spin_world::v2::mqtt::{self as v2, Connection as MqttConnection, Error, Qos};
 
 pub use host_component::OutboundMqttComponent;
-use tracing::{instrument, Level};
+use tracing::{field::Empty, instrument, Level};
 
 pub struct OutboundMqtt {
     allowed_hosts: spin_outbound_networking::AllowedHostsConfig,
@@ -61,7 +61,7 @@ impl v2::Host for OutboundMqtt {
 
 #[async_trait]
 impl v2::HostConnection for OutboundMqtt {
-    #[instrument(name = "spin_outbound_mqtt.open_connection", skip(self, password), err(level = Level::INFO), fields(otel.kind = "client"))]
+    #[instrument(name = "spin_outbound_mqtt.open_connection", skip(self, password), err(level = Level::INFO), fields(otel.kind = "client", error.type = Empty))]
     async fn open(
         &mut self,
         address: String,
@@ -69,18 +69,22 @@ impl v2::HostConnection for OutboundMqtt {
         password: String,
         keep_alive_interval: u64,
     ) -> Result<Resource<MqttConnection>, Error> {
-        if !self.is_address_allowed(&address) {
-            return Err(v2::Error::ConnectionFailed(format!(
-                "address {address} is not permitted"
-            )));
+        async {
+            if !self.is_address_allowed(&address) {
+                return Err(v2::Error::ConnectionFailed(format!(
+                    "address {address} is not permitted"
+                )));
+            }
+            self.establish_connection(
+                address,
+                username,
+                password,
+                Duration::from_secs(keep_alive_interval),
+            )
+            .await
         }
-        self.establish_connection(
-            address,
-            username,
-            password,
-            Duration::from_secs(keep_alive_interval),
-        )
         .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
     /// Publish a message to the MQTT broker.
@@ -90,7 +94,7 @@ impl v2::HostConnection for OutboundMqtt {
     /// https://w3c.github.io/trace-context-mqtt/#mqtt-v3-recommendation.
     #[instrument(name = "spin_outbound_mqtt.publish", skip(self, connection, payload), err(level = Level::INFO),
         fields(otel.kind = "producer", otel.name = format!("{} publish", topic), messaging.operation = "publish",
-        messaging.system = "mqtt"))]
+        messaging.system = "mqtt", error.type = Empty))]
     async fn publish(
         &mut self,
         connection: Resource<MqttConnection>,
@@ -98,32 +102,36 @@ impl v2::HostConnection for OutboundMqtt {
         payload: Vec<u8>,
         qos: Qos,
     ) -> Result<(), Error> {
-        let (client, eventloop) = self.get_conn(connection).await.map_err(other_error)?;
-        let qos = convert_to_mqtt_qos_value(qos);
-
-        // Message published to EventLoop (not MQTT Broker)
-        client
-            .publish_bytes(topic, qos, false, payload.into())
-            .await
-            .map_err(other_error)?;
+        async {
+            let (client, eventloop) = self.get_conn(connection).await.map_err(other_error)?;
+            let qos = convert_to_mqtt_qos_value(qos);
 
-        // Poll event loop until outgoing publish event is iterated over to send the message to MQTT broker or capture/throw error.
-        // We may revisit this later to manage long running connections, high throughput use cases and their issues in the connection pool.
-        loop {
-            let event = eventloop
-                .poll()
+            // Message published to EventLoop (not MQTT Broker)
+            client
+                .publish_bytes(topic, qos, false, payload.into())
                 .await
-                .map_err(|err| v2::Error::ConnectionFailed(err.to_string()))?;
-
-            match (qos, event) {
-                (QoS::AtMostOnce, Event::Outgoing(Outgoing::Publish(_)))
-                | (QoS::AtLeastOnce, Event::Incoming(Incoming::PubAck(_)))
-                | (QoS::ExactlyOnce, Event::Incoming(Incoming::PubComp(_))) => break,
-
-                (_, _) => continue,
+                .map_err(other_error)?;
+
+            // Poll event loop until outgoing publish event is iterated over to send the message to MQTT broker or capture/throw error.
+            // We may revisit this later to manage long running connections, high throughput use cases and their issues in the connection pool.
+            loop {
+                let event = eventloop
+                    .poll()
+                    .await
+                    .map_err(|err| v2::Error::ConnectionFailed(err.to_string()))?;
+
+                match (qos, event) {
+                    (QoS::AtMostOnce, Event::Outgoing(Outgoing::Publish(_)))
+                    | (QoS::AtLeastOnce, Event::Incoming(Incoming::PubAck(_)))
+                    | (QoS::ExactlyOnce, Event::Incoming(Incoming::PubComp(_))) => break,
+
+                    (_, _) => continue,
+                }
             }
+            Ok(())
         }
-        Ok(())
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
     fn drop(&mut self, connection: Resource<MqttConnection>) -> anyhow::Result<()> {
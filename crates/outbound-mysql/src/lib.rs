@@ -1,46 +1,238 @@
 use anyhow::{Context, Result};
+use chrono::{Datelike, Timelike};
 use mysql_async::{consts::ColumnType, from_value_opt, prelude::*, Opts, OptsBuilder, SslOpts};
-use spin_app::DynamicHostComponent;
+use spin_app::{DynamicHostComponent, MetadataKey};
 use spin_core::wasmtime::component::Resource;
 use spin_core::{async_trait, HostComponent};
+use spin_db_pool::{Manager, PoolConfig, Pooled, Pools};
 use spin_world::v1::mysql as v1;
-use spin_world::v2::mysql::{self as v2, Connection};
+use spin_world::v2::mysql as v2;
 use spin_world::v2::rdbms_types as v2_types;
-use spin_world::v2::rdbms_types::{Column, DbDataType, DbValue, ParameterValue};
+use spin_world::v3::mysql::{self as v3, Connection};
+use spin_world::v3::rdbms_types as v3_types;
+use spin_world::v3::rdbms_types::{Column, DbDataType, DbValue, ParameterValue};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tracing::{instrument, Level};
+use tracing::{field::Empty, instrument, Level};
 use url::Url;
 
+/// The manifest key listing the runtime config databases a component may open.
+pub const DATABASES_KEY: MetadataKey<HashSet<String>> = MetadataKey::new("mysql_databases");
+
 /// A simple implementation to support outbound mysql connection
 pub struct OutboundMysqlComponent {
     pub resolver: spin_expressions::SharedPreparedResolver,
+    pools: Arc<Pools<MysqlManager>>,
+    // Connection strings of the databases defined in runtime config, by label
+    databases: Arc<HashMap<String, String>>,
+}
+
+impl OutboundMysqlComponent {
+    pub fn new(
+        resolver: spin_expressions::SharedPreparedResolver,
+        pool_config: PoolConfig,
+        databases: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            resolver,
+            pools: Arc::new(Pools::new(MysqlManager, pool_config)),
+            databases: Arc::new(databases),
+        }
+    }
 }
 
-#[derive(Default)]
 pub struct OutboundMysql {
     allowed_hosts: spin_outbound_networking::AllowedHostsConfig,
-    pub connections: table::Table<mysql_async::Conn>,
+    pools: Arc<Pools<MysqlManager>>,
+    databases: Arc<HashMap<String, String>>,
+    allowed_databases: HashSet<String>,
+    pub connections: table::Table<Pooled<MysqlManager>>,
+    // Database names of open connections, by resource rep, for tracing
+    database_names: HashMap<u32, String>,
+    statements: table::Table<PreparedStatement>,
+    // The connection rep of each transaction, until it is committed or rolled back
+    transactions: table::Table<Option<u32>>,
+    // The state of the transaction open on each connection, by connection rep
+    open_transactions: HashMap<u32, TransactionState>,
+}
+
+struct PreparedStatement {
+    connection: u32,
+    sql: String,
+    statement: mysql_async::Statement,
+}
+
+#[derive(Debug, PartialEq)]
+enum TransactionState {
+    Active,
+    // The transaction resource was dropped without being committed or rolled
+    // back, so it must be rolled back before the connection is next used.
+    Abandoned,
+}
+
+impl Drop for OutboundMysql {
+    fn drop(&mut self) {
+        // Connections are returned to the pool when they are dropped, so any
+        // with a transaction still open must be closed instead, which rolls
+        // the transaction back.
+        for (connection, _) in self.open_transactions.drain() {
+            if let Some(conn) = self.connections.remove(connection) {
+                conn.discard();
+            }
+        }
+    }
 }
 
 impl OutboundMysql {
-    async fn open_connection(&mut self, address: &str) -> Result<Resource<Connection>, v2::Error> {
-        self.connections
+    async fn open_connection<T: 'static>(
+        &mut self,
+        address: &str,
+    ) -> Result<Resource<T>, v3::Error> {
+        let rep = self
+            .connections
             .push(
-                build_conn(address)
+                self.pools
+                    .get(address)
                     .await
-                    .map_err(|e| v2::Error::ConnectionFailed(format!("{e:?}")))?,
+                    .map_err(|e| v3::Error::ConnectionFailed(format!("{e:?}")))?,
             )
-            .map_err(|_| v2::Error::ConnectionFailed("too many connections".into()))
-            .map(Resource::new_own)
+            .map_err(|_| v3::Error::ConnectionFailed("too many connections".into()))?;
+        let database = Opts::from_url(address)
+            .ok()
+            .and_then(|opts| opts.db_name().map(str::to_owned));
+        if let Some(database) = database {
+            tracing::Span::current().record("db.name", database.as_str());
+            self.database_names.insert(rep, database);
+        }
+        Ok(Resource::new_own(rep))
+    }
+
+    // Records the database name of a connection on the current span.
+    fn record_db_name(&self, connection: u32) {
+        if let Some(database) = self.database_names.get(&connection) {
+            tracing::Span::current().record("db.name", database.as_str());
+        }
     }
 
-    async fn get_conn(
+    // Gets a connection, first rolling back any transaction which was
+    // abandoned on it.
+    async fn get_conn(&mut self, connection: u32) -> Result<&mut mysql_async::Conn, v3::Error> {
+        let conn = self
+            .connections
+            .get_mut(connection)
+            .map(|conn| &mut **conn)
+            .ok_or_else(|| v3::Error::ConnectionFailed("no connection found".into()))?;
+        if self.open_transactions.get(&connection) == Some(&TransactionState::Abandoned) {
+            self.open_transactions.remove(&connection);
+            conn.query_drop("ROLLBACK")
+                .await
+                .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))?;
+        }
+        Ok(conn)
+    }
+
+    async fn execute_on<S: StatementLike>(
         &mut self,
-        connection: Resource<Connection>,
-    ) -> Result<&mut mysql_async::Conn, v2::Error> {
-        self.connections
-            .get_mut(connection.rep())
-            .ok_or_else(|| v2::Error::ConnectionFailed("no connection found".into()))
+        connection: u32,
+        statement: S,
+        params: Vec<ParameterValue>,
+    ) -> Result<(), v3::Error> {
+        let db_params = params
+            .into_iter()
+            .map(to_sql_parameter)
+            .collect::<Result<Vec<_>, _>>()?;
+        let parameters = mysql_async::Params::Positional(db_params);
+
+        self.get_conn(connection)
+            .await?
+            .exec_batch(statement, &[parameters])
+            .await
+            .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))?;
+
+        Ok(())
+    }
+
+    async fn query_on<S: StatementLike>(
+        &mut self,
+        connection: u32,
+        statement: S,
+        params: Vec<ParameterValue>,
+    ) -> Result<v3_types::RowSet, v3::Error> {
+        let db_params = params
+            .into_iter()
+            .map(to_sql_parameter)
+            .collect::<Result<Vec<_>, _>>()?;
+        let parameters = mysql_async::Params::Positional(db_params);
+
+        let mut query_result = self
+            .get_conn(connection)
+            .await?
+            .exec_iter(statement, parameters)
+            .await
+            .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))?;
+
+        // We have to get these before collect() destroys them
+        let columns = convert_columns(query_result.columns());
+
+        match query_result.collect::<mysql_async::Row>().await {
+            Err(e) => Err(v3::Error::Other(e.to_string())),
+            Ok(result_set) => {
+                let rows = result_set
+                    .into_iter()
+                    .map(|row| convert_row(row, &columns))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                tracing::Span::current().record("db.response.returned_rows", rows.len());
+                Ok(v3_types::RowSet { columns, rows })
+            }
+        }
+    }
+
+    fn get_statement(
+        &self,
+        statement: &Resource<v3::Statement>,
+    ) -> Result<(u32, mysql_async::Statement), v3::Error> {
+        let prepared = self
+            .statements
+            .get(statement.rep())
+            .ok_or_else(|| v3::Error::Other("no prepared statement found".into()))?;
+        self.record_db_name(prepared.connection);
+        tracing::Span::current().record("otel.name", prepared.sql.as_str());
+        Ok((prepared.connection, prepared.statement.clone()))
+    }
+
+    fn get_transaction_connection(
+        &self,
+        transaction: &Resource<v3::Transaction>,
+    ) -> Result<u32, v3::Error> {
+        let connection = self
+            .transactions
+            .get(transaction.rep())
+            .copied()
+            .flatten()
+            .ok_or_else(|| v3::Error::Other("transaction has already finished".into()))?;
+        self.record_db_name(connection);
+        Ok(connection)
+    }
+
+    // Ends a transaction with `COMMIT` or `ROLLBACK`.
+    async fn finish_transaction(
+        &mut self,
+        transaction: Resource<v3::Transaction>,
+        command: &str,
+    ) -> Result<(), v3::Error> {
+        let connection = self
+            .transactions
+            .get_mut(transaction.rep())
+            .and_then(Option::take)
+            .ok_or_else(|| v3::Error::Other("transaction has already finished".into()))?;
+        self.record_db_name(connection);
+        self.open_transactions.remove(&connection);
+        self.get_conn(connection)
+            .await?
+            .query_drop(command)
+            .await
+            .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))
     }
 
     fn is_address_allowed(&self, address: &str) -> bool {
@@ -55,12 +247,23 @@ impl HostComponent for OutboundMysqlComponent {
         linker: &mut spin_core::Linker<T>,
         get: impl Fn(&mut spin_core::Data<T>) -> &mut Self::Data + Send + Sync + Copy + 'static,
     ) -> anyhow::Result<()> {
+        v3::add_to_linker(linker, get)?;
         v2::add_to_linker(linker, get)?;
         v1::add_to_linker(linker, get)
     }
 
     fn build_data(&self) -> Self::Data {
-        Default::default()
+        OutboundMysql {
+            allowed_hosts: Default::default(),
+            pools: self.pools.clone(),
+            databases: self.databases.clone(),
+            allowed_databases: Default::default(),
+            connections: Default::default(),
+            database_names: Default::default(),
+            statements: Default::default(),
+            transactions: Default::default(),
+            open_transactions: Default::default(),
+        }
     }
 }
 
@@ -78,79 +281,325 @@ impl DynamicHostComponent for OutboundMysqlComponent {
             self.resolver.get().unwrap(),
         )
         .context("`allowed_outbound_hosts` contained an invalid url")?;
+        data.allowed_databases = component.get_metadata(DATABASES_KEY)?.unwrap_or_default();
         Ok(())
     }
+
+    fn validate_app(&self, app: &spin_app::App) -> anyhow::Result<()> {
+        let mut errors = vec![];
+
+        for component in app.components() {
+            for label in component.get_metadata(DATABASES_KEY)?.unwrap_or_default() {
+                if !self.databases.contains_key(&label) {
+                    errors.push(format!(
+                        "- Component {} uses database '{label}'",
+                        component.id()
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            let prologue = [
+                "One or more components use MySQL databases which are not defined.",
+                "Check the spelling, or pass a runtime configuration file that defines these databases.",
+                "Details:",
+            ];
+            let lines: Vec<_> = prologue
+                .into_iter()
+                .map(|s| s.to_owned())
+                .chain(errors)
+                .collect();
+            Err(anyhow::anyhow!(lines.join("\n")))
+        }
+    }
 }
 
-impl v2::Host for OutboundMysql {}
+impl v3::Host for OutboundMysql {}
 
 #[async_trait]
-impl v2::HostConnection for OutboundMysql {
-    #[instrument(name = "spin_outbound_mysql.open_connection", skip(self), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql"))]
-    async fn open(&mut self, address: String) -> Result<Resource<Connection>, v2::Error> {
-        if !self.is_address_allowed(&address) {
-            return Err(v2::Error::ConnectionFailed(format!(
-                "address {address} is not permitted"
-            )));
+impl v3::HostConnection for OutboundMysql {
+    #[instrument(name = "spin_outbound_mysql.open_connection", skip(self, address), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", db.name = Empty, error.type = Empty))]
+    async fn open(&mut self, address: String) -> Result<Resource<Connection>, v3::Error> {
+        async {
+            if !self.is_address_allowed(&address) {
+                return Err(v3::Error::ConnectionFailed(format!(
+                    "address {address} is not permitted"
+                )));
+            }
+            self.open_connection(&address).await
         }
-        self.open_connection(&address).await
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_mysql.execute", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", otel.name = statement))]
+    #[instrument(name = "spin_outbound_mysql.open_connection", skip(self), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", db.name = Empty, error.type = Empty))]
+    async fn open_database(&mut self, label: String) -> Result<Resource<Connection>, v3::Error> {
+        async {
+            if !self.allowed_databases.contains(&label) {
+                return Err(v3::Error::ConnectionFailed(format!(
+                    "access to database '{label}' is not granted; add it to `mysql_databases` in the component manifest"
+                )));
+            }
+            let address = self.databases.get(&label).cloned().ok_or_else(|| {
+                v3::Error::ConnectionFailed(format!("no database '{label}' is defined"))
+            })?;
+            self.open_connection(&address).await
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    #[instrument(name = "spin_outbound_mysql.execute", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", otel.name = statement, db.name = Empty, error.type = Empty))]
     async fn execute(
         &mut self,
         connection: Resource<Connection>,
         statement: String,
         params: Vec<ParameterValue>,
-    ) -> Result<(), v2::Error> {
-        let db_params = params.into_iter().map(to_sql_parameter).collect::<Vec<_>>();
-        let parameters = mysql_async::Params::Positional(db_params);
+    ) -> Result<(), v3::Error> {
+        self.record_db_name(connection.rep());
+        self.execute_on(connection.rep(), &statement, params)
+            .await
+            .inspect_err(spin_telemetry::record_error_type)
+    }
 
-        self.get_conn(connection)
-            .await?
-            .exec_batch(&statement, &[parameters])
+    #[instrument(name = "spin_outbound_mysql.query", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", otel.name = statement, db.name = Empty, db.response.returned_rows = Empty, error.type = Empty))]
+    async fn query(
+        &mut self,
+        connection: Resource<Connection>,
+        statement: String,
+        params: Vec<ParameterValue>,
+    ) -> Result<v3_types::RowSet, v3::Error> {
+        self.record_db_name(connection.rep());
+        self.query_on(connection.rep(), &statement, params)
             .await
-            .map_err(|e| v2::Error::QueryFailed(format!("{:?}", e)))?;
+            .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    #[instrument(name = "spin_outbound_mysql.prepare", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", otel.name = statement, db.name = Empty, error.type = Empty))]
+    async fn prepare(
+        &mut self,
+        connection: Resource<Connection>,
+        statement: String,
+    ) -> Result<Resource<v3::Statement>, v3::Error> {
+        self.record_db_name(connection.rep());
+        async {
+            let prepared = self
+                .get_conn(connection.rep())
+                .await?
+                .prep(&statement)
+                .await
+                .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))?;
+            let rep = self
+                .statements
+                .push(PreparedStatement {
+                    connection: connection.rep(),
+                    sql: statement,
+                    statement: prepared,
+                })
+                .map_err(|_| v3::Error::Other("too many prepared statements".into()))?;
+            Ok(Resource::new_own(rep))
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    #[instrument(name = "spin_outbound_mysql.begin", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", db.name = Empty, error.type = Empty))]
+    async fn begin(
+        &mut self,
+        connection: Resource<Connection>,
+    ) -> Result<Resource<v3::Transaction>, v3::Error> {
+        let connection = connection.rep();
+        self.record_db_name(connection);
+        async {
+            if self.open_transactions.get(&connection) == Some(&TransactionState::Active) {
+                return Err(v3::Error::Other(
+                    "a transaction is already open on this connection".into(),
+                ));
+            }
+            self.get_conn(connection)
+                .await?
+                .query_drop("START TRANSACTION")
+                .await
+                .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))?;
+            match self.transactions.push(Some(connection)) {
+                Ok(rep) => {
+                    self.open_transactions
+                        .insert(connection, TransactionState::Active);
+                    Ok(Resource::new_own(rep))
+                }
+                Err(()) => {
+                    self.open_transactions
+                        .insert(connection, TransactionState::Abandoned);
+                    Err(v3::Error::Other("too many transactions".into()))
+                }
+            }
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    fn drop(&mut self, connection: Resource<Connection>) -> Result<()> {
+        let connection = connection.rep();
+        self.database_names.remove(&connection);
+        let in_transaction = self.open_transactions.remove(&connection).is_some();
+        if let Some(conn) = self.connections.remove(connection) {
+            // Closing the connection rolls back its open transaction, so it
+            // is not returned to the pool.
+            if in_transaction {
+                conn.discard();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl v3::HostStatement for OutboundMysql {
+    #[instrument(name = "spin_outbound_mysql.execute", skip(self, statement), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", otel.name = Empty, db.name = Empty, error.type = Empty))]
+    async fn execute(
+        &mut self,
+        statement: Resource<v3::Statement>,
+        params: Vec<ParameterValue>,
+    ) -> Result<(), v3::Error> {
+        async {
+            let (connection, statement) = self.get_statement(&statement)?;
+            self.execute_on(connection, statement, params).await
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
 
+    #[instrument(name = "spin_outbound_mysql.query", skip(self, statement), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", otel.name = Empty, db.name = Empty, db.response.returned_rows = Empty, error.type = Empty))]
+    async fn query(
+        &mut self,
+        statement: Resource<v3::Statement>,
+        params: Vec<ParameterValue>,
+    ) -> Result<v3_types::RowSet, v3::Error> {
+        async {
+            let (connection, statement) = self.get_statement(&statement)?;
+            self.query_on(connection, statement, params).await
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    fn drop(&mut self, statement: Resource<v3::Statement>) -> Result<()> {
+        self.statements.remove(statement.rep());
         Ok(())
     }
+}
+
+#[async_trait]
+impl v3::HostTransaction for OutboundMysql {
+    #[instrument(name = "spin_outbound_mysql.execute", skip(self, transaction), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", otel.name = statement, db.name = Empty, error.type = Empty))]
+    async fn execute(
+        &mut self,
+        transaction: Resource<v3::Transaction>,
+        statement: String,
+        params: Vec<ParameterValue>,
+    ) -> Result<(), v3::Error> {
+        async {
+            let connection = self.get_transaction_connection(&transaction)?;
+            self.execute_on(connection, &statement, params).await
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
 
-    #[instrument(name = "spin_outbound_mysql.query", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", otel.name = statement))]
+    #[instrument(name = "spin_outbound_mysql.query", skip(self, transaction), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", otel.name = statement, db.name = Empty, db.response.returned_rows = Empty, error.type = Empty))]
     async fn query(
         &mut self,
-        connection: Resource<Connection>,
+        transaction: Resource<v3::Transaction>,
         statement: String,
         params: Vec<ParameterValue>,
-    ) -> Result<v2_types::RowSet, v2::Error> {
-        let db_params = params.into_iter().map(to_sql_parameter).collect::<Vec<_>>();
-        let parameters = mysql_async::Params::Positional(db_params);
+    ) -> Result<v3_types::RowSet, v3::Error> {
+        async {
+            let connection = self.get_transaction_connection(&transaction)?;
+            self.query_on(connection, &statement, params).await
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
 
-        let mut query_result = self
-            .get_conn(connection)
-            .await?
-            .exec_iter(&statement, parameters)
+    #[instrument(name = "spin_outbound_mysql.commit", skip(self, transaction), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", db.name = Empty, error.type = Empty))]
+    async fn commit(&mut self, transaction: Resource<v3::Transaction>) -> Result<(), v3::Error> {
+        self.finish_transaction(transaction, "COMMIT")
             .await
-            .map_err(|e| v2::Error::QueryFailed(format!("{:?}", e)))?;
-
-        // We have to get these before collect() destroys them
-        let columns = convert_columns(query_result.columns());
+            .inspect_err(spin_telemetry::record_error_type)
+    }
 
-        match query_result.collect::<mysql_async::Row>().await {
-            Err(e) => Err(v2::Error::Other(e.to_string())),
-            Ok(result_set) => {
-                let rows = result_set
-                    .into_iter()
-                    .map(|row| convert_row(row, &columns))
-                    .collect::<Result<Vec<_>, _>>()?;
+    #[instrument(name = "spin_outbound_mysql.rollback", skip(self, transaction), err(level = Level::INFO), fields(otel.kind = "client", db.system = "mysql", db.name = Empty, error.type = Empty))]
+    async fn rollback(&mut self, transaction: Resource<v3::Transaction>) -> Result<(), v3::Error> {
+        self.finish_transaction(transaction, "ROLLBACK")
+            .await
+            .inspect_err(spin_telemetry::record_error_type)
+    }
 
-                Ok(v2_types::RowSet { columns, rows })
+    fn drop(&mut self, transaction: Resource<v3::Transaction>) -> Result<()> {
+        if let Some(Some(connection)) = self.transactions.remove(transaction.rep()) {
+            // The connection may already have been dropped, discarding the
+            // transaction along with it.
+            if let Some(state) = self.open_transactions.get_mut(&connection) {
+                *state = TransactionState::Abandoned;
             }
         }
+        Ok(())
     }
+}
 
-    fn drop(&mut self, connection: Resource<Connection>) -> Result<()> {
-        self.connections.remove(connection.rep());
-        Ok(())
+impl v3_types::Host for OutboundMysql {
+    fn convert_error(&mut self, error: v3::Error) -> Result<v3::Error> {
+        Ok(error)
+    }
+}
+
+impl v2::Host for OutboundMysql {}
+
+/// The v2 interface is implemented by delegating to the v3 implementation;
+/// both versions share the same table of connections.
+#[async_trait]
+impl v2::HostConnection for OutboundMysql {
+    async fn open(&mut self, address: String) -> Result<Resource<v2::Connection>, v2::Error> {
+        let connection = <Self as v3::HostConnection>::open(self, address).await?;
+        Ok(Resource::new_own(connection.rep()))
+    }
+
+    async fn execute(
+        &mut self,
+        connection: Resource<v2::Connection>,
+        statement: String,
+        params: Vec<v2_types::ParameterValue>,
+    ) -> Result<(), v2::Error> {
+        Ok(<Self as v3::HostConnection>::execute(
+            self,
+            Resource::new_borrow(connection.rep()),
+            statement,
+            params.into_iter().map(Into::into).collect(),
+        )
+        .await?)
+    }
+
+    async fn query(
+        &mut self,
+        connection: Resource<v2::Connection>,
+        statement: String,
+        params: Vec<v2_types::ParameterValue>,
+    ) -> Result<v2_types::RowSet, v2::Error> {
+        Ok(<Self as v3::HostConnection>::query(
+            self,
+            Resource::new_borrow(connection.rep()),
+            statement,
+            params.into_iter().map(Into::into).collect(),
+        )
+        .await?
+        .into())
+    }
+
+    fn drop(&mut self, connection: Resource<v2::Connection>) -> Result<()> {
+        <Self as v3::HostConnection>::drop(self, Resource::new_own(connection.rep()))
     }
 }
 
@@ -168,13 +617,22 @@ macro_rules! delegate {
                 "address {} is not permitted", $address
             )));
         }
-        let connection = match $self.open_connection(&$address).await {
-            Ok(c) => c,
-            Err(e) => return Err(e.into()),
+        let connection = match $self.open_connection::<v2::Connection>(&$address).await {
+            Ok(c) => c.rep(),
+            Err(e) => return Err(v2::Error::from(e).into()),
         };
-        <Self as v2::HostConnection>::$name($self, connection, $($arg),*)
-            .await
-            .map_err(Into::into)
+        let result = <Self as v2::HostConnection>::$name(
+            $self,
+            Resource::new_borrow(connection),
+            $($arg),*
+        )
+        .await;
+        // The connection is only used for this call, so return it to the pool
+        // rather than holding it until the instance ends.
+        if let Err(e) = <Self as v2::HostConnection>::drop($self, Resource::new_own(connection)) {
+            tracing::debug!("Failed to release connection: {e:?}");
+        }
+        result.map_err(Into::into)
     }};
 }
 
@@ -212,8 +670,8 @@ impl v1::Host for OutboundMysql {
     }
 }
 
-fn to_sql_parameter(value: ParameterValue) -> mysql_async::Value {
-    match value {
+fn to_sql_parameter(value: ParameterValue) -> Result<mysql_async::Value, v3::Error> {
+    Ok(match value {
         ParameterValue::Boolean(v) => mysql_async::Value::from(v),
         ParameterValue::Int32(v) => mysql_async::Value::from(v),
         ParameterValue::Int64(v) => mysql_async::Value::from(v),
@@ -227,8 +685,74 @@ fn to_sql_parameter(value: ParameterValue) -> mysql_async::Value {
         ParameterValue::Uint64(v) => mysql_async::Value::from(v),
         ParameterValue::Str(v) => mysql_async::Value::from(v),
         ParameterValue::Binary(v) => mysql_async::Value::from(v),
+        ParameterValue::Date(date) => date_time_value(&date, &MIDNIGHT)?,
+        ParameterValue::Time(time) => mysql_async::Value::Time(
+            false,
+            0,
+            time.hour,
+            time.minute,
+            time.second,
+            time.nanosecond / 1_000,
+        ),
+        ParameterValue::Datetime(v) => date_time_value(&v.date, &v.time)?,
+        ParameterValue::Timestamp(v) => {
+            // MySQL has no time zone aware type, so timestamps are sent as UTC datetimes.
+            let utc = chrono::DateTime::from_timestamp(v.seconds, v.nanoseconds)
+                .ok_or_else(|| v3::Error::BadParameter(format!("invalid timestamp {v:?}")))?
+                .naive_utc();
+            mysql_async::Value::Date(
+                utc.year()
+                    .try_into()
+                    .map_err(|_| v3::Error::BadParameter(format!("invalid timestamp {v:?}")))?,
+                utc.month() as u8,
+                utc.day() as u8,
+                utc.hour() as u8,
+                utc.minute() as u8,
+                utc.second() as u8,
+                utc.nanosecond() / 1_000,
+            )
+        }
+        // MySQL has no native UUID type, and accepts JSON and DECIMAL values in string form.
+        ParameterValue::Uuid(v) | ParameterValue::Json(v) | ParameterValue::Decimal(v) => {
+            mysql_async::Value::from(v)
+        }
+        ParameterValue::ArrayBoolean(_)
+        | ParameterValue::ArrayInt32(_)
+        | ParameterValue::ArrayInt64(_)
+        | ParameterValue::ArrayFloating64(_)
+        | ParameterValue::ArrayStr(_) => {
+            return Err(v3::Error::BadParameter(
+                "MySQL does not support array parameters".into(),
+            ))
+        }
         ParameterValue::DbNull => mysql_async::Value::NULL,
-    }
+    })
+}
+
+const MIDNIGHT: v3_types::Time = v3_types::Time {
+    hour: 0,
+    minute: 0,
+    second: 0,
+    nanosecond: 0,
+};
+
+fn date_time_value(
+    date: &v3_types::Date,
+    time: &v3_types::Time,
+) -> Result<mysql_async::Value, v3::Error> {
+    let year = date
+        .year
+        .try_into()
+        .map_err(|_| v3::Error::BadParameter(format!("year {} is out of range", date.year)))?;
+    Ok(mysql_async::Value::Date(
+        year,
+        date.month,
+        date.day,
+        time.hour,
+        time.minute,
+        time.second,
+        time.nanosecond / 1_000,
+    ))
 }
 
 fn convert_columns(columns: Option<Arc<[mysql_async::Column]>>) -> Vec<Column> {
@@ -248,7 +772,9 @@ fn convert_column(column: &mysql_async::Column) -> Column {
 fn convert_data_type(column: &mysql_async::Column) -> DbDataType {
     let column_type = column.column_type();
 
-    if column_type.is_numeric_type() {
+    if let Some(data_type) = convert_rich_type(column_type) {
+        data_type
+    } else if column_type.is_numeric_type() {
         convert_numeric_type(column)
     } else if column_type.is_character_type() {
         convert_character_type(column)
@@ -257,6 +783,23 @@ fn convert_data_type(column: &mysql_async::Column) -> DbDataType {
     }
 }
 
+fn convert_rich_type(column_type: ColumnType) -> Option<DbDataType> {
+    match column_type {
+        ColumnType::MYSQL_TYPE_DATE | ColumnType::MYSQL_TYPE_NEWDATE => Some(DbDataType::Date),
+        ColumnType::MYSQL_TYPE_TIME => Some(DbDataType::Time),
+        // MySQL returns TIMESTAMP values in the session time zone rather than as
+        // an instant, so they are surfaced as datetimes too.
+        ColumnType::MYSQL_TYPE_DATETIME | ColumnType::MYSQL_TYPE_TIMESTAMP => {
+            Some(DbDataType::Datetime)
+        }
+        ColumnType::MYSQL_TYPE_JSON => Some(DbDataType::Json),
+        ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL => {
+            Some(DbDataType::Decimal)
+        }
+        _ => None,
+    }
+}
+
 fn convert_character_type(column: &mysql_async::Column) -> DbDataType {
     match (column.column_type(), is_binary(column)) {
         (ColumnType::MYSQL_TYPE_BLOB, false) => DbDataType::Str, // TEXT type
@@ -301,7 +844,7 @@ fn is_binary(column: &mysql_async::Column) -> bool {
         .contains(mysql_async::consts::ColumnFlags::BINARY_FLAG)
 }
 
-fn convert_row(mut row: mysql_async::Row, columns: &[Column]) -> Result<Vec<DbValue>, v2::Error> {
+fn convert_row(mut row: mysql_async::Row, columns: &[Column]) -> Result<Vec<DbValue>, v3::Error> {
     let mut result = Vec::with_capacity(row.len());
     for index in 0..row.len() {
         result.push(convert_entry(&mut row, index, columns)?);
@@ -313,10 +856,10 @@ fn convert_entry(
     row: &mut mysql_async::Row,
     index: usize,
     columns: &[Column],
-) -> Result<DbValue, v2::Error> {
+) -> Result<DbValue, v3::Error> {
     match (row.take(index), columns.get(index)) {
         (None, _) => Ok(DbValue::DbNull), // TODO: is this right or is this an "index out of range" thing
-        (_, None) => Err(v2::Error::Other(format!(
+        (_, None) => Err(v3::Error::Other(format!(
             "Can't get column at index {}",
             index
         ))),
@@ -325,7 +868,7 @@ fn convert_entry(
     }
 }
 
-fn convert_value(value: mysql_async::Value, column: &Column) -> Result<DbValue, v2::Error> {
+fn convert_value(value: mysql_async::Value, column: &Column) -> Result<DbValue, v3::Error> {
     match column.data_type {
         DbDataType::Binary => convert_value_to::<Vec<u8>>(value).map(DbValue::Binary),
         DbDataType::Boolean => convert_value_to::<bool>(value).map(DbValue::Boolean),
@@ -340,10 +883,79 @@ fn convert_value(value: mysql_async::Value, column: &Column) -> Result<DbValue,
         DbDataType::Uint16 => convert_value_to::<u16>(value).map(DbValue::Uint16),
         DbDataType::Uint32 => convert_value_to::<u32>(value).map(DbValue::Uint32),
         DbDataType::Uint64 => convert_value_to::<u64>(value).map(DbValue::Uint64),
-        DbDataType::Other => Err(v2::Error::ValueConversionFailed(format!(
-            "Cannot convert value {:?} in column {} data type {:?}",
-            value, column.name, column.data_type
-        ))),
+        DbDataType::Date => match value {
+            mysql_async::Value::Date(year, month, day, ..) => Ok(DbValue::Date(v3_types::Date {
+                year: year.into(),
+                month,
+                day,
+            })),
+            value => Err(conversion_error(value, column)),
+        },
+        DbDataType::Time => match value {
+            mysql_async::Value::Time(false, 0, hour, minute, second, micros) => {
+                Ok(DbValue::Time(v3_types::Time {
+                    hour,
+                    minute,
+                    second,
+                    nanosecond: micros * 1_000,
+                }))
+            }
+            // Negative or multi-day TIME values are durations rather than times of day.
+            value => Err(conversion_error(value, column)),
+        },
+        DbDataType::Datetime => match value {
+            mysql_async::Value::Date(year, month, day, hour, minute, second, micros) => {
+                Ok(DbValue::Datetime(v3_types::Datetime {
+                    date: v3_types::Date {
+                        year: year.into(),
+                        month,
+                        day,
+                    },
+                    time: v3_types::Time {
+                        hour,
+                        minute,
+                        second,
+                        nanosecond: micros * 1_000,
+                    },
+                }))
+            }
+            value => Err(conversion_error(value, column)),
+        },
+        DbDataType::Json => convert_value_to::<String>(value).map(DbValue::Json),
+        DbDataType::Decimal => convert_value_to::<String>(value).map(DbValue::Decimal),
+        DbDataType::Timestamp | DbDataType::Uuid | DbDataType::Array | DbDataType::Other => {
+            Err(conversion_error(value, column))
+        }
+    }
+}
+
+fn conversion_error(value: mysql_async::Value, column: &Column) -> v3::Error {
+    v3::Error::ValueConversionFailed(format!(
+        "Cannot convert value {:?} in column {} data type {:?}",
+        value, column.name, column.data_type
+    ))
+}
+
+/// Opens and checks pooled MySQL connections.
+pub struct MysqlManager;
+
+#[async_trait]
+impl Manager for MysqlManager {
+    type Connection = mysql_async::Conn;
+
+    const DB_SYSTEM: &'static str = "mysql";
+
+    async fn connect(&self, address: &str) -> Result<mysql_async::Conn> {
+        Ok(build_conn(address).await?)
+    }
+
+    async fn check(&self, conn: &mut mysql_async::Conn) -> Result<()> {
+        Ok(conn.ping().await?)
+    }
+
+    async fn reset(&self, conn: &mut mysql_async::Conn) -> Result<()> {
+        conn.reset().await?;
+        Ok(())
     }
 }
 
@@ -352,9 +964,7 @@ async fn build_conn(address: &str) -> Result<mysql_async::Conn, mysql_async::Err
 
     let opts = build_opts(address)?;
 
-    let connection_pool = mysql_async::Pool::new(opts);
-
-    connection_pool.get_conn().await
+    mysql_async::Conn::new(opts).await
 }
 
 fn is_ssl_param(s: &str) -> bool {
@@ -394,8 +1004,8 @@ fn build_opts(address: &str) -> Result<Opts, mysql_async::Error> {
         .into())
 }
 
-fn convert_value_to<T: FromValue>(value: mysql_async::Value) -> Result<T, v2::Error> {
-    from_value_opt::<T>(value).map_err(|e| v2::Error::ValueConversionFailed(format!("{}", e)))
+fn convert_value_to<T: FromValue>(value: mysql_async::Value) -> Result<T, v3::Error> {
+    from_value_opt::<T>(value).map_err(|e| v3::Error::ValueConversionFailed(format!("{}", e)))
 }
 
 #[cfg(test)]
@@ -439,4 +1049,45 @@ mod test {
             10
         )
     }
+
+    #[test]
+    fn test_mysql_datetime_parameter() {
+        let datetime = v3_types::Datetime {
+            date: v3_types::Date {
+                year: 2024,
+                month: 2,
+                day: 29,
+            },
+            time: v3_types::Time {
+                hour: 13,
+                minute: 45,
+                second: 30,
+                nanosecond: 250_000_000,
+            },
+        };
+        assert_eq!(
+            mysql_async::Value::Date(2024, 2, 29, 13, 45, 30, 250_000),
+            to_sql_parameter(ParameterValue::Datetime(datetime)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_mysql_timestamp_parameter_is_utc() {
+        let timestamp = v3_types::Timestamp {
+            seconds: 86_400,
+            nanoseconds: 0,
+        };
+        assert_eq!(
+            mysql_async::Value::Date(1970, 1, 2, 0, 0, 0, 0),
+            to_sql_parameter(ParameterValue::Timestamp(timestamp)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_mysql_rejects_array_parameters() {
+        assert!(matches!(
+            to_sql_parameter(ParameterValue::ArrayInt32(vec![Some(1), None])),
+            Err(v3::Error::BadParameter(_))
+        ));
+    }
 }
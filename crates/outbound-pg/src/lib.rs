@@ -1,48 +1,245 @@
 use anyhow::{anyhow, Context, Result};
+use chrono::{Datelike, Timelike};
 use native_tls::TlsConnector;
 use postgres_native_tls::MakeTlsConnector;
-use spin_app::DynamicHostComponent;
+use spin_app::{DynamicHostComponent, MetadataKey};
 use spin_core::{async_trait, wasmtime::component::Resource, HostComponent};
+use spin_db_pool::{Manager, PoolConfig, Pooled, Pools};
 use spin_world::v1::postgres as v1;
 use spin_world::v1::rdbms_types as v1_types;
-use spin_world::v2::postgres::{self as v2, Connection};
-use spin_world::v2::rdbms_types;
-use spin_world::v2::rdbms_types::{Column, DbDataType, DbValue, ParameterValue, RowSet};
+use spin_world::v2::postgres as v2;
+use spin_world::v2::rdbms_types as v2_types;
+use spin_world::v3::postgres::{self as v3, Connection};
+use spin_world::v3::rdbms_types;
+use spin_world::v3::rdbms_types::{Column, DbDataType, DbValue, ParameterValue, RowSet};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use tokio_postgres::{
     config::SslMode,
-    types::{ToSql, Type},
-    Client, NoTls, Row, Socket,
+    types::{FromSql, ToSql, Type},
+    Client, NoTls, Row, Socket, ToStatement,
 };
-use tracing::instrument;
 use tracing::Level;
+use tracing::{field::Empty, instrument};
+
+/// The manifest key listing the runtime config databases a component may open.
+pub const DATABASES_KEY: MetadataKey<HashSet<String>> = MetadataKey::new("postgres_databases");
 
 pub struct OutboundPgComponent {
     pub resolver: spin_expressions::SharedPreparedResolver,
+    pools: Arc<Pools<PgManager>>,
+    // Connection strings of the databases defined in runtime config, by label
+    databases: Arc<HashMap<String, String>>,
+}
+
+impl OutboundPgComponent {
+    pub fn new(
+        resolver: spin_expressions::SharedPreparedResolver,
+        pool_config: PoolConfig,
+        databases: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            resolver,
+            pools: Arc::new(Pools::new(PgManager, pool_config)),
+            databases: Arc::new(databases),
+        }
+    }
 }
 
 /// A simple implementation to support outbound pg connection
-#[derive(Default)]
 pub struct OutboundPg {
     allowed_hosts: spin_outbound_networking::AllowedHostsConfig,
-    pub connections: table::Table<Client>,
+    pools: Arc<Pools<PgManager>>,
+    databases: Arc<HashMap<String, String>>,
+    allowed_databases: HashSet<String>,
+    pub connections: table::Table<Pooled<PgManager>>,
+    // Database names of open connections, by resource rep, for tracing
+    database_names: HashMap<u32, String>,
+    statements: table::Table<PreparedStatement>,
+    // The connection rep of each transaction, until it is committed or rolled back
+    transactions: table::Table<Option<u32>>,
+    // The state of the transaction open on each connection, by connection rep
+    open_transactions: HashMap<u32, TransactionState>,
+}
+
+struct PreparedStatement {
+    connection: u32,
+    sql: String,
+    statement: tokio_postgres::Statement,
+}
+
+#[derive(Debug, PartialEq)]
+enum TransactionState {
+    Active,
+    // The transaction resource was dropped without being committed or rolled
+    // back, so it must be rolled back before the connection is next used.
+    Abandoned,
+}
+
+impl Drop for OutboundPg {
+    fn drop(&mut self) {
+        // Connections are returned to the pool when they are dropped, so any
+        // with a transaction still open must be closed instead, which rolls
+        // the transaction back.
+        for (connection, _) in self.open_transactions.drain() {
+            if let Some(client) = self.connections.remove(connection) {
+                client.discard();
+            }
+        }
+    }
 }
 
 impl OutboundPg {
-    async fn open_connection(&mut self, address: &str) -> Result<Resource<Connection>, v2::Error> {
-        self.connections
+    async fn open_connection<T: 'static>(
+        &mut self,
+        address: &str,
+    ) -> Result<Resource<T>, v3::Error> {
+        let rep = self
+            .connections
             .push(
-                build_client(address)
+                self.pools
+                    .get(address)
                     .await
-                    .map_err(|e| v2::Error::ConnectionFailed(format!("{e:?}")))?,
+                    .map_err(|e| v3::Error::ConnectionFailed(format!("{e:?}")))?,
             )
-            .map_err(|_| v2::Error::ConnectionFailed("too many connections".into()))
-            .map(Resource::new_own)
+            .map_err(|_| v3::Error::ConnectionFailed("too many connections".into()))?;
+        let database = address
+            .parse::<tokio_postgres::Config>()
+            .ok()
+            .and_then(|config| config.get_dbname().map(str::to_owned));
+        if let Some(database) = database {
+            tracing::Span::current().record("db.name", database.as_str());
+            self.database_names.insert(rep, database);
+        }
+        Ok(Resource::new_own(rep))
+    }
+
+    // Records the database name of a connection on the current span.
+    fn record_db_name(&self, connection: u32) {
+        if let Some(database) = self.database_names.get(&connection) {
+            tracing::Span::current().record("db.name", database.as_str());
+        }
+    }
+
+    // Gets the client for a connection, first rolling back any transaction
+    // which was abandoned on it.
+    async fn get_client(&mut self, connection: u32) -> Result<&Client, v3::Error> {
+        let client = self
+            .connections
+            .get(connection)
+            .map(|client| &**client)
+            .ok_or_else(|| v3::Error::ConnectionFailed("no connection found".into()))?;
+        if self.open_transactions.get(&connection) == Some(&TransactionState::Abandoned) {
+            self.open_transactions.remove(&connection);
+            client
+                .batch_execute("ROLLBACK")
+                .await
+                .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))?;
+        }
+        Ok(client)
+    }
+
+    async fn execute_on<T: ?Sized + ToStatement + Sync>(
+        &mut self,
+        connection: u32,
+        statement: &T,
+        params: Vec<ParameterValue>,
+    ) -> Result<u64, v3::Error> {
+        let params = params
+            .iter()
+            .map(to_sql_parameter)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|e| v3::Error::ValueConversionFailed(format!("{:?}", e)))?;
+
+        let nrow = self
+            .get_client(connection)
+            .await?
+            .execute(statement, &param_refs(&params))
+            .await
+            .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))?;
+
+        tracing::Span::current().record("db.response.affected_rows", nrow);
+        Ok(nrow)
+    }
+
+    async fn query_on<T: ?Sized + ToStatement + Sync>(
+        &mut self,
+        connection: u32,
+        statement: &T,
+        params: Vec<ParameterValue>,
+    ) -> Result<RowSet, v3::Error> {
+        let params = params
+            .iter()
+            .map(to_sql_parameter)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|e| v3::Error::BadParameter(format!("{:?}", e)))?;
+
+        let results = self
+            .get_client(connection)
+            .await?
+            .query(statement, &param_refs(&params))
+            .await
+            .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))?;
+
+        tracing::Span::current().record("db.response.returned_rows", results.len());
+        if results.is_empty() {
+            return Ok(RowSet {
+                columns: vec![],
+                rows: vec![],
+            });
+        }
+
+        let columns = infer_columns(&results[0]);
+        let rows = results.iter().map(convert_row).collect();
+
+        Ok(RowSet { columns, rows })
     }
 
-    async fn get_client(&mut self, connection: Resource<Connection>) -> Result<&Client, v2::Error> {
-        self.connections
-            .get(connection.rep())
-            .ok_or_else(|| v2::Error::ConnectionFailed("no connection found".into()))
+    fn get_statement(
+        &self,
+        statement: &Resource<v3::Statement>,
+    ) -> Result<(u32, tokio_postgres::Statement), v3::Error> {
+        let prepared = self
+            .statements
+            .get(statement.rep())
+            .ok_or_else(|| v3::Error::Other("no prepared statement found".into()))?;
+        self.record_db_name(prepared.connection);
+        tracing::Span::current().record("otel.name", prepared.sql.as_str());
+        Ok((prepared.connection, prepared.statement.clone()))
+    }
+
+    fn get_transaction_connection(
+        &self,
+        transaction: &Resource<v3::Transaction>,
+    ) -> Result<u32, v3::Error> {
+        let connection = self
+            .transactions
+            .get(transaction.rep())
+            .copied()
+            .flatten()
+            .ok_or_else(|| v3::Error::Other("transaction has already finished".into()))?;
+        self.record_db_name(connection);
+        Ok(connection)
+    }
+
+    // Ends a transaction with `COMMIT` or `ROLLBACK`.
+    async fn finish_transaction(
+        &mut self,
+        transaction: Resource<v3::Transaction>,
+        command: &str,
+    ) -> Result<(), v3::Error> {
+        let connection = self
+            .transactions
+            .get_mut(transaction.rep())
+            .and_then(Option::take)
+            .ok_or_else(|| v3::Error::Other("transaction has already finished".into()))?;
+        self.record_db_name(connection);
+        self.open_transactions.remove(&connection);
+        self.get_client(connection)
+            .await?
+            .batch_execute(command)
+            .await
+            .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))
     }
 
     fn is_address_allowed(&self, address: &str) -> bool {
@@ -82,11 +279,22 @@ impl HostComponent for OutboundPgComponent {
         get: impl Fn(&mut spin_core::Data<T>) -> &mut Self::Data + Send + Sync + Copy + 'static,
     ) -> anyhow::Result<()> {
         v1::add_to_linker(linker, get)?;
-        v2::add_to_linker(linker, get)
+        v2::add_to_linker(linker, get)?;
+        v3::add_to_linker(linker, get)
     }
 
     fn build_data(&self) -> Self::Data {
-        Default::default()
+        OutboundPg {
+            allowed_hosts: Default::default(),
+            pools: self.pools.clone(),
+            databases: self.databases.clone(),
+            allowed_databases: Default::default(),
+            connections: Default::default(),
+            database_names: Default::default(),
+            statements: Default::default(),
+            transactions: Default::default(),
+            open_transactions: Default::default(),
+        }
     }
 }
 
@@ -104,113 +312,419 @@ impl DynamicHostComponent for OutboundPgComponent {
             self.resolver.get().unwrap(),
         )
         .context("`allowed_outbound_hosts` contained an invalid url")?;
+        data.allowed_databases = component.get_metadata(DATABASES_KEY)?.unwrap_or_default();
         Ok(())
     }
+
+    fn validate_app(&self, app: &spin_app::App) -> anyhow::Result<()> {
+        let mut errors = vec![];
+
+        for component in app.components() {
+            for label in component.get_metadata(DATABASES_KEY)?.unwrap_or_default() {
+                if !self.databases.contains_key(&label) {
+                    errors.push(format!(
+                        "- Component {} uses database '{label}'",
+                        component.id()
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            let prologue = [
+                "One or more components use Postgres databases which are not defined.",
+                "Check the spelling, or pass a runtime configuration file that defines these databases.",
+                "Details:",
+            ];
+            let lines: Vec<_> = prologue
+                .into_iter()
+                .map(|s| s.to_owned())
+                .chain(errors)
+                .collect();
+            Err(anyhow::anyhow!(lines.join("\n")))
+        }
+    }
 }
 
 #[async_trait]
-impl v2::Host for OutboundPg {}
+impl v3::Host for OutboundPg {}
 
 #[async_trait]
-impl v2::HostConnection for OutboundPg {
-    #[instrument(name = "spin_outbound_pg.open_connection", skip(self), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql"))]
-    async fn open(&mut self, address: String) -> Result<Resource<Connection>, v2::Error> {
-        if !self.is_address_allowed(&address) {
-            return Err(v2::Error::ConnectionFailed(format!(
-                "address {address} is not permitted"
-            )));
+impl v3::HostConnection for OutboundPg {
+    #[instrument(name = "spin_outbound_pg.open_connection", skip(self, address), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", db.name = Empty, error.type = Empty))]
+    async fn open(&mut self, address: String) -> Result<Resource<Connection>, v3::Error> {
+        async {
+            if !self.is_address_allowed(&address) {
+                return Err(v3::Error::ConnectionFailed(format!(
+                    "address {address} is not permitted"
+                )));
+            }
+            self.open_connection(&address).await
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    #[instrument(name = "spin_outbound_pg.open_connection", skip(self), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", db.name = Empty, error.type = Empty))]
+    async fn open_database(&mut self, label: String) -> Result<Resource<Connection>, v3::Error> {
+        async {
+            if !self.allowed_databases.contains(&label) {
+                return Err(v3::Error::ConnectionFailed(format!(
+                    "access to database '{label}' is not granted; add it to `postgres_databases` in the component manifest"
+                )));
+            }
+            let address = self.databases.get(&label).cloned().ok_or_else(|| {
+                v3::Error::ConnectionFailed(format!("no database '{label}' is defined"))
+            })?;
+            self.open_connection(&address).await
         }
-        self.open_connection(&address).await
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_pg.execute", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", otel.name = statement))]
+    #[instrument(name = "spin_outbound_pg.execute", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", otel.name = statement, db.name = Empty, db.response.affected_rows = Empty, error.type = Empty))]
     async fn execute(
         &mut self,
         connection: Resource<Connection>,
         statement: String,
         params: Vec<ParameterValue>,
-    ) -> Result<u64, v2::Error> {
-        let params: Vec<&(dyn ToSql + Sync)> = params
-            .iter()
-            .map(to_sql_parameter)
-            .collect::<anyhow::Result<Vec<_>>>()
-            .map_err(|e| v2::Error::ValueConversionFailed(format!("{:?}", e)))?;
-
-        let nrow = self
-            .get_client(connection)
-            .await?
-            .execute(&statement, params.as_slice())
+    ) -> Result<u64, v3::Error> {
+        self.record_db_name(connection.rep());
+        self.execute_on(connection.rep(), statement.as_str(), params)
             .await
-            .map_err(|e| v2::Error::QueryFailed(format!("{:?}", e)))?;
-
-        Ok(nrow)
+            .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_pg.query", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", otel.name = statement))]
+    #[instrument(name = "spin_outbound_pg.query", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", otel.name = statement, db.name = Empty, db.response.returned_rows = Empty, error.type = Empty))]
     async fn query(
         &mut self,
         connection: Resource<Connection>,
         statement: String,
         params: Vec<ParameterValue>,
-    ) -> Result<RowSet, v2::Error> {
-        let params: Vec<&(dyn ToSql + Sync)> = params
-            .iter()
-            .map(to_sql_parameter)
-            .collect::<anyhow::Result<Vec<_>>>()
-            .map_err(|e| v2::Error::BadParameter(format!("{:?}", e)))?;
-
-        let results = self
-            .get_client(connection)
-            .await?
-            .query(&statement, params.as_slice())
+    ) -> Result<RowSet, v3::Error> {
+        self.record_db_name(connection.rep());
+        self.query_on(connection.rep(), statement.as_str(), params)
             .await
-            .map_err(|e| v2::Error::QueryFailed(format!("{:?}", e)))?;
+            .inspect_err(spin_telemetry::record_error_type)
+    }
 
-        if results.is_empty() {
-            return Ok(RowSet {
-                columns: vec![],
-                rows: vec![],
-            });
+    #[instrument(name = "spin_outbound_pg.prepare", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", otel.name = statement, db.name = Empty, error.type = Empty))]
+    async fn prepare(
+        &mut self,
+        connection: Resource<Connection>,
+        statement: String,
+    ) -> Result<Resource<v3::Statement>, v3::Error> {
+        self.record_db_name(connection.rep());
+        async {
+            let prepared = self
+                .get_client(connection.rep())
+                .await?
+                .prepare(&statement)
+                .await
+                .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))?;
+            let rep = self
+                .statements
+                .push(PreparedStatement {
+                    connection: connection.rep(),
+                    sql: statement,
+                    statement: prepared,
+                })
+                .map_err(|_| v3::Error::Other("too many prepared statements".into()))?;
+            Ok(Resource::new_own(rep))
         }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
 
-        let columns = infer_columns(&results[0]);
-        let rows = results
-            .iter()
-            .map(convert_row)
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| v2::Error::QueryFailed(format!("{:?}", e)))?;
-
-        Ok(RowSet { columns, rows })
+    #[instrument(name = "spin_outbound_pg.begin", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", db.name = Empty, error.type = Empty))]
+    async fn begin(
+        &mut self,
+        connection: Resource<Connection>,
+    ) -> Result<Resource<v3::Transaction>, v3::Error> {
+        let connection = connection.rep();
+        self.record_db_name(connection);
+        async {
+            if self.open_transactions.get(&connection) == Some(&TransactionState::Active) {
+                return Err(v3::Error::Other(
+                    "a transaction is already open on this connection".into(),
+                ));
+            }
+            self.get_client(connection)
+                .await?
+                .batch_execute("BEGIN")
+                .await
+                .map_err(|e| v3::Error::QueryFailed(format!("{:?}", e)))?;
+            match self.transactions.push(Some(connection)) {
+                Ok(rep) => {
+                    self.open_transactions
+                        .insert(connection, TransactionState::Active);
+                    Ok(Resource::new_own(rep))
+                }
+                Err(()) => {
+                    self.open_transactions
+                        .insert(connection, TransactionState::Abandoned);
+                    Err(v3::Error::Other("too many transactions".into()))
+                }
+            }
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
     fn drop(&mut self, connection: Resource<Connection>) -> anyhow::Result<()> {
-        self.connections.remove(connection.rep());
+        let connection = connection.rep();
+        self.database_names.remove(&connection);
+        let in_transaction = self.open_transactions.remove(&connection).is_some();
+        if let Some(client) = self.connections.remove(connection) {
+            // Closing the connection rolls back its open transaction, so it
+            // is not returned to the pool.
+            if in_transaction {
+                client.discard();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl v3::HostStatement for OutboundPg {
+    #[instrument(name = "spin_outbound_pg.execute", skip(self, statement), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", otel.name = Empty, db.name = Empty, db.response.affected_rows = Empty, error.type = Empty))]
+    async fn execute(
+        &mut self,
+        statement: Resource<v3::Statement>,
+        params: Vec<ParameterValue>,
+    ) -> Result<u64, v3::Error> {
+        async {
+            let (connection, statement) = self.get_statement(&statement)?;
+            self.execute_on(connection, &statement, params).await
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    #[instrument(name = "spin_outbound_pg.query", skip(self, statement), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", otel.name = Empty, db.name = Empty, db.response.returned_rows = Empty, error.type = Empty))]
+    async fn query(
+        &mut self,
+        statement: Resource<v3::Statement>,
+        params: Vec<ParameterValue>,
+    ) -> Result<RowSet, v3::Error> {
+        async {
+            let (connection, statement) = self.get_statement(&statement)?;
+            self.query_on(connection, &statement, params).await
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    fn drop(&mut self, statement: Resource<v3::Statement>) -> anyhow::Result<()> {
+        self.statements.remove(statement.rep());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl v3::HostTransaction for OutboundPg {
+    #[instrument(name = "spin_outbound_pg.execute", skip(self, transaction), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", otel.name = statement, db.name = Empty, db.response.affected_rows = Empty, error.type = Empty))]
+    async fn execute(
+        &mut self,
+        transaction: Resource<v3::Transaction>,
+        statement: String,
+        params: Vec<ParameterValue>,
+    ) -> Result<u64, v3::Error> {
+        async {
+            let connection = self.get_transaction_connection(&transaction)?;
+            self.execute_on(connection, statement.as_str(), params)
+                .await
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    #[instrument(name = "spin_outbound_pg.query", skip(self, transaction), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", otel.name = statement, db.name = Empty, db.response.returned_rows = Empty, error.type = Empty))]
+    async fn query(
+        &mut self,
+        transaction: Resource<v3::Transaction>,
+        statement: String,
+        params: Vec<ParameterValue>,
+    ) -> Result<RowSet, v3::Error> {
+        async {
+            let connection = self.get_transaction_connection(&transaction)?;
+            self.query_on(connection, statement.as_str(), params).await
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    #[instrument(name = "spin_outbound_pg.commit", skip(self, transaction), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", db.name = Empty, error.type = Empty))]
+    async fn commit(&mut self, transaction: Resource<v3::Transaction>) -> Result<(), v3::Error> {
+        self.finish_transaction(transaction, "COMMIT")
+            .await
+            .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    #[instrument(name = "spin_outbound_pg.rollback", skip(self, transaction), err(level = Level::INFO), fields(otel.kind = "client", db.system = "postgresql", db.name = Empty, error.type = Empty))]
+    async fn rollback(&mut self, transaction: Resource<v3::Transaction>) -> Result<(), v3::Error> {
+        self.finish_transaction(transaction, "ROLLBACK")
+            .await
+            .inspect_err(spin_telemetry::record_error_type)
+    }
+
+    fn drop(&mut self, transaction: Resource<v3::Transaction>) -> anyhow::Result<()> {
+        if let Some(Some(connection)) = self.transactions.remove(transaction.rep()) {
+            // The connection may already have been dropped, discarding the
+            // transaction along with it.
+            if let Some(state) = self.open_transactions.get_mut(&connection) {
+                *state = TransactionState::Abandoned;
+            }
+        }
         Ok(())
     }
 }
 
 impl rdbms_types::Host for OutboundPg {
+    fn convert_error(&mut self, error: v3::Error) -> Result<v3::Error> {
+        Ok(error)
+    }
+}
+
+#[async_trait]
+impl v2::Host for OutboundPg {}
+
+/// The v2 interface is implemented by delegating to the v3 implementation;
+/// both versions share the same table of connections.
+#[async_trait]
+impl v2::HostConnection for OutboundPg {
+    async fn open(&mut self, address: String) -> Result<Resource<v2::Connection>, v2::Error> {
+        let connection = <Self as v3::HostConnection>::open(self, address).await?;
+        Ok(Resource::new_own(connection.rep()))
+    }
+
+    async fn execute(
+        &mut self,
+        connection: Resource<v2::Connection>,
+        statement: String,
+        params: Vec<v2_types::ParameterValue>,
+    ) -> Result<u64, v2::Error> {
+        Ok(<Self as v3::HostConnection>::execute(
+            self,
+            Resource::new_borrow(connection.rep()),
+            statement,
+            params.into_iter().map(Into::into).collect(),
+        )
+        .await?)
+    }
+
+    async fn query(
+        &mut self,
+        connection: Resource<v2::Connection>,
+        statement: String,
+        params: Vec<v2_types::ParameterValue>,
+    ) -> Result<v2_types::RowSet, v2::Error> {
+        Ok(<Self as v3::HostConnection>::query(
+            self,
+            Resource::new_borrow(connection.rep()),
+            statement,
+            params.into_iter().map(Into::into).collect(),
+        )
+        .await?
+        .into())
+    }
+
+    fn drop(&mut self, connection: Resource<v2::Connection>) -> anyhow::Result<()> {
+        <Self as v3::HostConnection>::drop(self, Resource::new_own(connection.rep()))
+    }
+}
+
+impl v2_types::Host for OutboundPg {
     fn convert_error(&mut self, error: v2::Error) -> Result<v2::Error> {
         Ok(error)
     }
 }
 
-fn to_sql_parameter(value: &ParameterValue) -> anyhow::Result<&(dyn ToSql + Sync)> {
+type SqlParameter = Box<dyn ToSql + Send + Sync>;
+
+fn to_sql_parameter(value: &ParameterValue) -> anyhow::Result<SqlParameter> {
     match value {
-        ParameterValue::Boolean(v) => Ok(v),
-        ParameterValue::Int32(v) => Ok(v),
-        ParameterValue::Int64(v) => Ok(v),
-        ParameterValue::Int8(v) => Ok(v),
-        ParameterValue::Int16(v) => Ok(v),
-        ParameterValue::Floating32(v) => Ok(v),
-        ParameterValue::Floating64(v) => Ok(v),
+        ParameterValue::Boolean(v) => Ok(Box::new(*v)),
+        ParameterValue::Int32(v) => Ok(Box::new(*v)),
+        ParameterValue::Int64(v) => Ok(Box::new(*v)),
+        ParameterValue::Int8(v) => Ok(Box::new(*v)),
+        ParameterValue::Int16(v) => Ok(Box::new(*v)),
+        ParameterValue::Floating32(v) => Ok(Box::new(*v)),
+        ParameterValue::Floating64(v) => Ok(Box::new(*v)),
         ParameterValue::Uint8(_)
         | ParameterValue::Uint16(_)
         | ParameterValue::Uint32(_)
         | ParameterValue::Uint64(_) => Err(anyhow!("Postgres does not support unsigned integers")),
-        ParameterValue::Str(v) => Ok(v),
-        ParameterValue::Binary(v) => Ok(v),
-        ParameterValue::DbNull => Ok(&PgNull),
+        ParameterValue::Str(v) => Ok(Box::new(v.clone())),
+        ParameterValue::Binary(v) => Ok(Box::new(v.clone())),
+        ParameterValue::Date(v) => Ok(Box::new(to_naive_date(v)?)),
+        ParameterValue::Time(v) => Ok(Box::new(to_naive_time(v)?)),
+        ParameterValue::Datetime(v) => Ok(Box::new(chrono::NaiveDateTime::new(
+            to_naive_date(&v.date)?,
+            to_naive_time(&v.time)?,
+        ))),
+        ParameterValue::Timestamp(v) => Ok(Box::new(
+            chrono::DateTime::from_timestamp(v.seconds, v.nanoseconds)
+                .with_context(|| format!("invalid timestamp {v:?}"))?,
+        )),
+        ParameterValue::Uuid(v) => Ok(Box::new(
+            uuid::Uuid::parse_str(v).with_context(|| format!("invalid UUID {v:?}"))?,
+        )),
+        ParameterValue::Json(v) => Ok(Box::new(
+            serde_json::from_str::<serde_json::Value>(v).context("invalid JSON")?,
+        )),
+        ParameterValue::Decimal(v) => Ok(Box::new(
+            rust_decimal::Decimal::from_str_exact(v)
+                .with_context(|| format!("invalid decimal {v:?}"))?,
+        )),
+        ParameterValue::ArrayBoolean(v) => Ok(Box::new(v.clone())),
+        ParameterValue::ArrayInt32(v) => Ok(Box::new(v.clone())),
+        ParameterValue::ArrayInt64(v) => Ok(Box::new(v.clone())),
+        ParameterValue::ArrayFloating64(v) => Ok(Box::new(v.clone())),
+        ParameterValue::ArrayStr(v) => Ok(Box::new(v.clone())),
+        ParameterValue::DbNull => Ok(Box::new(PgNull)),
+    }
+}
+
+fn param_refs(params: &[SqlParameter]) -> Vec<&(dyn ToSql + Sync)> {
+    params
+        .iter()
+        .map(|p| p.as_ref() as &(dyn ToSql + Sync))
+        .collect()
+}
+
+fn to_naive_date(date: &rdbms_types::Date) -> anyhow::Result<chrono::NaiveDate> {
+    chrono::NaiveDate::from_ymd_opt(date.year, date.month.into(), date.day.into())
+        .with_context(|| format!("invalid date {date:?}"))
+}
+
+fn to_naive_time(time: &rdbms_types::Time) -> anyhow::Result<chrono::NaiveTime> {
+    chrono::NaiveTime::from_hms_nano_opt(
+        time.hour.into(),
+        time.minute.into(),
+        time.second.into(),
+        time.nanosecond,
+    )
+    .with_context(|| format!("invalid time {time:?}"))
+}
+
+fn from_naive_date(date: chrono::NaiveDate) -> rdbms_types::Date {
+    rdbms_types::Date {
+        year: date.year(),
+        month: date.month() as u8,
+        day: date.day() as u8,
+    }
+}
+
+fn from_naive_time(time: chrono::NaiveTime) -> rdbms_types::Time {
+    rdbms_types::Time {
+        hour: time.hour() as u8,
+        minute: time.minute() as u8,
+        second: time.second() as u8,
+        nanosecond: time.nanosecond(),
     }
 }
 
@@ -239,6 +753,19 @@ fn convert_data_type(pg_type: &Type) -> DbDataType {
         Type::INT4 => DbDataType::Int32,
         Type::INT8 => DbDataType::Int64,
         Type::TEXT | Type::VARCHAR | Type::BPCHAR => DbDataType::Str,
+        Type::DATE => DbDataType::Date,
+        Type::TIME => DbDataType::Time,
+        Type::TIMESTAMP => DbDataType::Datetime,
+        Type::TIMESTAMPTZ => DbDataType::Timestamp,
+        Type::UUID => DbDataType::Uuid,
+        Type::JSON | Type::JSONB => DbDataType::Json,
+        Type::NUMERIC => DbDataType::Decimal,
+        Type::BOOL_ARRAY
+        | Type::INT4_ARRAY
+        | Type::INT8_ARRAY
+        | Type::FLOAT8_ARRAY
+        | Type::TEXT_ARRAY
+        | Type::VARCHAR_ARRAY => DbDataType::Array,
         _ => {
             tracing::debug!("Couldn't convert Postgres type {} to WIT", pg_type.name(),);
             DbDataType::Other
@@ -246,12 +773,20 @@ fn convert_data_type(pg_type: &Type) -> DbDataType {
     }
 }
 
-fn convert_row(row: &Row) -> Result<Vec<DbValue>, tokio_postgres::Error> {
-    let mut result = Vec::with_capacity(row.len());
-    for index in 0..row.len() {
-        result.push(convert_entry(row, index)?);
-    }
-    Ok(result)
+fn convert_row(row: &Row) -> Vec<DbValue> {
+    (0..row.len())
+        .map(|index| {
+            // A value that can't be decoded, such as an infinite timestamp, shouldn't fail the
+            // whole query
+            convert_entry(row, index).unwrap_or_else(|e| {
+                tracing::debug!(
+                    "Couldn't convert value in column {}: {e}",
+                    row.columns()[index].name()
+                );
+                DbValue::Unsupported
+            })
+        })
+        .collect()
 }
 
 fn convert_entry(row: &Row, index: usize) -> Result<DbValue, tokio_postgres::Error> {
@@ -313,6 +848,96 @@ fn convert_entry(row: &Row, index: usize) -> Result<DbValue, tokio_postgres::Err
                 None => DbValue::DbNull,
             }
         }
+        &Type::DATE => {
+            let value: Option<chrono::NaiveDate> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::Date(from_naive_date(v)),
+                None => DbValue::DbNull,
+            }
+        }
+        &Type::TIME => {
+            let value: Option<chrono::NaiveTime> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::Time(from_naive_time(v)),
+                None => DbValue::DbNull,
+            }
+        }
+        &Type::TIMESTAMP => {
+            let value: Option<chrono::NaiveDateTime> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::Datetime(rdbms_types::Datetime {
+                    date: from_naive_date(v.date()),
+                    time: from_naive_time(v.time()),
+                }),
+                None => DbValue::DbNull,
+            }
+        }
+        &Type::TIMESTAMPTZ => {
+            let value: Option<chrono::DateTime<chrono::Utc>> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::Timestamp(rdbms_types::Timestamp {
+                    seconds: v.timestamp(),
+                    nanoseconds: v.timestamp_subsec_nanos(),
+                }),
+                None => DbValue::DbNull,
+            }
+        }
+        &Type::UUID => {
+            let value: Option<uuid::Uuid> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::Uuid(v.hyphenated().to_string()),
+                None => DbValue::DbNull,
+            }
+        }
+        &Type::JSON | &Type::JSONB => {
+            let value: Option<serde_json::Value> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::Json(v.to_string()),
+                None => DbValue::DbNull,
+            }
+        }
+        &Type::NUMERIC => {
+            let value: Option<PgNumeric> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::Decimal(v.0),
+                None => DbValue::DbNull,
+            }
+        }
+        &Type::BOOL_ARRAY => {
+            let value: Option<Vec<Option<bool>>> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::ArrayBoolean(v),
+                None => DbValue::DbNull,
+            }
+        }
+        &Type::INT4_ARRAY => {
+            let value: Option<Vec<Option<i32>>> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::ArrayInt32(v),
+                None => DbValue::DbNull,
+            }
+        }
+        &Type::INT8_ARRAY => {
+            let value: Option<Vec<Option<i64>>> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::ArrayInt64(v),
+                None => DbValue::DbNull,
+            }
+        }
+        &Type::FLOAT8_ARRAY => {
+            let value: Option<Vec<Option<f64>>> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::ArrayFloating64(v),
+                None => DbValue::DbNull,
+            }
+        }
+        &Type::TEXT_ARRAY | &Type::VARCHAR_ARRAY => {
+            let value: Option<Vec<Option<String>>> = row.try_get(index)?;
+            match value {
+                Some(v) => DbValue::ArrayStr(v),
+                None => DbValue::DbNull,
+            }
+        }
         t => {
             tracing::debug!(
                 "Couldn't convert Postgres type {} in column {}",
@@ -325,6 +950,38 @@ fn convert_entry(row: &Row, index: usize) -> Result<DbValue, tokio_postgres::Err
     Ok(value)
 }
 
+/// Opens and checks pooled Postgres connections.
+pub struct PgManager;
+
+#[async_trait]
+impl Manager for PgManager {
+    type Connection = Client;
+
+    const DB_SYSTEM: &'static str = "postgresql";
+
+    async fn connect(&self, address: &str) -> anyhow::Result<Client> {
+        build_client(address).await
+    }
+
+    async fn check(&self, client: &mut Client) -> anyhow::Result<()> {
+        client.simple_query("").await?;
+        Ok(())
+    }
+
+    async fn reset(&self, client: &mut Client) -> anyhow::Result<()> {
+        // `DISCARD ALL` can't run inside a transaction, so end any first.
+        client.batch_execute("ROLLBACK").await?;
+        client.batch_execute("DISCARD ALL").await?;
+        // `DISCARD ALL` drops temporary types along with everything else.
+        client.clear_type_cache();
+        Ok(())
+    }
+
+    fn is_reusable(&self, client: &Client) -> bool {
+        !client.is_closed()
+    }
+}
+
 async fn build_client(address: &str) -> anyhow::Result<Client> {
     let config = address.parse::<tokio_postgres::Config>()?;
 
@@ -408,6 +1065,82 @@ impl std::fmt::Debug for PgNull {
     }
 }
 
+/// The text form of a Postgres NUMERIC value. Unlike `rust_decimal::Decimal`,
+/// this can represent every NUMERIC value, including NaN, the infinities and
+/// values with more than 28 significant digits.
+#[derive(Debug, PartialEq)]
+struct PgNumeric(String);
+
+const NUMERIC_POS: u16 = 0x0000;
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+
+impl<'a> FromSql<'a> for PgNumeric {
+    fn from_sql(
+        _ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        // The binary format is a header of digit count, weight, sign and
+        // display scale, followed by the digits in base 10000, most
+        // significant first. The weight is the power of 10000 of the first
+        // digit, and digits not sent are zero.
+        let word = |i: usize| {
+            raw.get(i * 2..i * 2 + 2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                .ok_or("invalid NUMERIC value")
+        };
+        let ndigits = word(0)? as usize;
+        let weight = word(1)? as i16 as i64;
+        let sign = word(2)?;
+        let dscale = word(3)? as usize;
+        let digits = (0..ndigits)
+            .map(|i| word(4 + i))
+            .collect::<Result<Vec<_>, _>>()?;
+        let digit = |i: i64| {
+            usize::try_from(i)
+                .ok()
+                .and_then(|i| digits.get(i))
+                .copied()
+                .unwrap_or_default()
+        };
+
+        let mut text = match sign {
+            NUMERIC_NAN => return Ok(Self("NaN".into())),
+            NUMERIC_PINF => return Ok(Self("Infinity".into())),
+            NUMERIC_NINF => return Ok(Self("-Infinity".into())),
+            NUMERIC_POS => String::new(),
+            NUMERIC_NEG => "-".to_owned(),
+            _ => return Err(format!("invalid NUMERIC sign {sign:#x}").into()),
+        };
+        if weight < 0 {
+            text.push('0');
+        } else {
+            text.push_str(&digit(0).to_string());
+            for i in 1..=weight {
+                text.push_str(&format!("{:04}", digit(i)));
+            }
+        }
+        if dscale > 0 {
+            let mut fraction = String::with_capacity(dscale + 4);
+            let mut i = weight + 1;
+            while fraction.len() < dscale {
+                fraction.push_str(&format!("{:04}", digit(i)));
+                i += 1;
+            }
+            fraction.truncate(dscale);
+            text.push('.');
+            text.push_str(&fraction);
+        }
+        Ok(Self(text))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+}
+
 /// Delegate a function call to the v2::HostConnection implementation
 macro_rules! delegate {
     ($self:ident.$name:ident($address:expr, $($arg:expr),*)) => {{
@@ -416,13 +1149,22 @@ macro_rules! delegate {
                 "address {} is not permitted", $address
             )));
         }
-        let connection = match $self.open_connection(&$address).await {
-            Ok(c) => c,
-            Err(e) => return Err(e.into()),
+        let connection = match $self.open_connection::<v2::Connection>(&$address).await {
+            Ok(c) => c.rep(),
+            Err(e) => return Err(v2::Error::from(e).into()),
         };
-        <Self as v2::HostConnection>::$name($self, connection, $($arg),*)
-            .await
-            .map_err(|e| e.into())
+        let result = <Self as v2::HostConnection>::$name(
+            $self,
+            Resource::new_borrow(connection),
+            $($arg),*
+        )
+        .await;
+        // The connection is only used for this call, so return it to the pool
+        // rather than holding it until the instance ends.
+        if let Err(e) = <Self as v2::HostConnection>::drop($self, Resource::new_own(connection)) {
+            tracing::debug!("Failed to release connection: {e:?}");
+        }
+        result.map_err(|e| e.into())
     }};
 }
 
@@ -459,3 +1201,57 @@ impl v1::Host for OutboundPg {
         Ok(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numeric(weight: i16, sign: u16, dscale: u16, digits: &[u16]) -> Vec<u8> {
+        [digits.len() as u16, weight as u16, sign, dscale]
+            .iter()
+            .chain(digits)
+            .flat_map(|w| w.to_be_bytes())
+            .collect()
+    }
+
+    fn decode(raw: &[u8]) -> String {
+        PgNumeric::from_sql(&Type::NUMERIC, raw).unwrap().0
+    }
+
+    #[test]
+    fn numeric_decodes_finite_values() {
+        assert_eq!("0", decode(&numeric(0, NUMERIC_POS, 0, &[])));
+        assert_eq!(
+            "12345.678",
+            decode(&numeric(1, NUMERIC_POS, 3, &[1, 2345, 6780]))
+        );
+        assert_eq!("-42.50", decode(&numeric(0, NUMERIC_NEG, 2, &[42, 5000])));
+        assert_eq!("0.00001", decode(&numeric(-2, NUMERIC_POS, 5, &[1000])));
+        assert_eq!("120000", decode(&numeric(1, NUMERIC_POS, 0, &[12])));
+    }
+
+    #[test]
+    fn numeric_decodes_values_beyond_decimal_precision() {
+        let value = decode(&numeric(
+            10,
+            NUMERIC_POS,
+            4,
+            &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        ));
+        assert_eq!(format!("1{}.0001", "0".repeat(40)), value);
+    }
+
+    #[test]
+    fn numeric_decodes_special_values() {
+        assert_eq!("NaN", decode(&numeric(0, NUMERIC_NAN, 0, &[])));
+        assert_eq!("Infinity", decode(&numeric(0, NUMERIC_PINF, 0, &[])));
+        assert_eq!("-Infinity", decode(&numeric(0, NUMERIC_NINF, 0, &[])));
+    }
+
+    #[test]
+    fn numeric_rejects_truncated_values() {
+        let mut raw = numeric(0, NUMERIC_POS, 0, &[1, 2]);
+        raw.truncate(raw.len() - 1);
+        assert!(PgNumeric::from_sql(&Type::NUMERIC, &raw).is_err());
+    }
+}
@@ -9,7 +9,7 @@ use spin_world::v2::redis::{
 };
 
 pub use host_component::OutboundRedisComponent;
-use tracing::{instrument, Level};
+use tracing::{field::Empty, instrument, Level};
 
 struct RedisResults(Vec<RedisResult>);
 
@@ -74,137 +74,177 @@ impl v2::Host for OutboundRedis {
 
 #[async_trait]
 impl v2::HostConnection for OutboundRedis {
-    #[instrument(name = "spin_outbound_redis.open_connection", skip(self), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis"))]
+    #[instrument(name = "spin_outbound_redis.open_connection", skip(self, address), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", error.type = Empty))]
     async fn open(&mut self, address: String) -> Result<Resource<RedisConnection>, Error> {
-        if !self.is_address_allowed(&address) {
-            return Err(Error::InvalidAddress);
-        }
+        async {
+            if !self.is_address_allowed(&address) {
+                return Err(Error::InvalidAddress);
+            }
 
-        self.establish_connection(address).await
+            self.establish_connection(address).await
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_redis.publish", skip(self, connection, payload), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("PUBLISH {}", channel)))]
+    #[instrument(name = "spin_outbound_redis.publish", skip(self, connection, payload), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("PUBLISH {}", channel), error.type = Empty))]
     async fn publish(
         &mut self,
         connection: Resource<RedisConnection>,
         channel: String,
         payload: Vec<u8>,
     ) -> Result<(), Error> {
-        let conn = self.get_conn(connection).await.map_err(other_error)?;
-        conn.publish(&channel, &payload)
-            .await
-            .map_err(other_error)?;
-        Ok(())
+        async {
+            let conn = self.get_conn(connection).await.map_err(other_error)?;
+            conn.publish(&channel, &payload)
+                .await
+                .map_err(other_error)?;
+            Ok(())
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_redis.get", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("GET {}", key)))]
+    #[instrument(name = "spin_outbound_redis.get", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("GET {}", key), error.type = Empty))]
     async fn get(
         &mut self,
         connection: Resource<RedisConnection>,
         key: String,
     ) -> Result<Option<Vec<u8>>, Error> {
-        let conn = self.get_conn(connection).await.map_err(other_error)?;
-        let value = conn.get(&key).await.map_err(other_error)?;
-        Ok(value)
+        async {
+            let conn = self.get_conn(connection).await.map_err(other_error)?;
+            let value = conn.get(&key).await.map_err(other_error)?;
+            Ok(value)
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_redis.set", skip(self, connection, value), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("SET {}", key)))]
+    #[instrument(name = "spin_outbound_redis.set", skip(self, connection, value), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("SET {}", key), error.type = Empty))]
     async fn set(
         &mut self,
         connection: Resource<RedisConnection>,
         key: String,
         value: Vec<u8>,
     ) -> Result<(), Error> {
-        let conn = self.get_conn(connection).await.map_err(other_error)?;
-        conn.set(&key, &value).await.map_err(other_error)?;
-        Ok(())
+        async {
+            let conn = self.get_conn(connection).await.map_err(other_error)?;
+            conn.set(&key, &value).await.map_err(other_error)?;
+            Ok(())
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_redis.incr", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("INCRBY {} 1", key)))]
+    #[instrument(name = "spin_outbound_redis.incr", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("INCRBY {} 1", key), error.type = Empty))]
     async fn incr(
         &mut self,
         connection: Resource<RedisConnection>,
         key: String,
     ) -> Result<i64, Error> {
-        let conn = self.get_conn(connection).await.map_err(other_error)?;
-        let value = conn.incr(&key, 1).await.map_err(other_error)?;
-        Ok(value)
+        async {
+            let conn = self.get_conn(connection).await.map_err(other_error)?;
+            let value = conn.incr(&key, 1).await.map_err(other_error)?;
+            Ok(value)
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_redis.del", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("DEL {}", keys.join(" "))))]
+    #[instrument(name = "spin_outbound_redis.del", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("DEL {}", keys.join(" ")), error.type = Empty))]
     async fn del(
         &mut self,
         connection: Resource<RedisConnection>,
         keys: Vec<String>,
     ) -> Result<u32, Error> {
-        let conn = self.get_conn(connection).await.map_err(other_error)?;
-        let value = conn.del(&keys).await.map_err(other_error)?;
-        Ok(value)
+        async {
+            let conn = self.get_conn(connection).await.map_err(other_error)?;
+            let value = conn.del(&keys).await.map_err(other_error)?;
+            Ok(value)
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_redis.sadd", skip(self, connection, values), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("SADD {} {}", key, values.join(" "))))]
+    #[instrument(name = "spin_outbound_redis.sadd", skip(self, connection, values), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("SADD {} {}", key, values.join(" ")), error.type = Empty))]
     async fn sadd(
         &mut self,
         connection: Resource<RedisConnection>,
         key: String,
         values: Vec<String>,
     ) -> Result<u32, Error> {
-        let conn = self.get_conn(connection).await.map_err(other_error)?;
-        let value = conn.sadd(&key, &values).await.map_err(|e| {
-            if e.kind() == redis::ErrorKind::TypeError {
-                Error::TypeError
-            } else {
-                Error::Other(e.to_string())
-            }
-        })?;
-        Ok(value)
+        async {
+            let conn = self.get_conn(connection).await.map_err(other_error)?;
+            let value = conn.sadd(&key, &values).await.map_err(|e| {
+                if e.kind() == redis::ErrorKind::TypeError {
+                    Error::TypeError
+                } else {
+                    Error::Other(e.to_string())
+                }
+            })?;
+            Ok(value)
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_redis.smembers", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("SMEMBERS {}", key)))]
+    #[instrument(name = "spin_outbound_redis.smembers", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("SMEMBERS {}", key), error.type = Empty))]
     async fn smembers(
         &mut self,
         connection: Resource<RedisConnection>,
         key: String,
     ) -> Result<Vec<String>, Error> {
-        let conn = self.get_conn(connection).await.map_err(other_error)?;
-        let value = conn.smembers(&key).await.map_err(other_error)?;
-        Ok(value)
+        async {
+            let conn = self.get_conn(connection).await.map_err(other_error)?;
+            let value = conn.smembers(&key).await.map_err(other_error)?;
+            Ok(value)
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_redis.srem", skip(self, connection, values), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("SREM {} {}", key, values.join(" "))))]
+    #[instrument(name = "spin_outbound_redis.srem", skip(self, connection, values), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("SREM {} {}", key, values.join(" ")), error.type = Empty))]
     async fn srem(
         &mut self,
         connection: Resource<RedisConnection>,
         key: String,
         values: Vec<String>,
     ) -> Result<u32, Error> {
-        let conn = self.get_conn(connection).await.map_err(other_error)?;
-        let value = conn.srem(&key, &values).await.map_err(other_error)?;
-        Ok(value)
+        async {
+            let conn = self.get_conn(connection).await.map_err(other_error)?;
+            let value = conn.srem(&key, &values).await.map_err(other_error)?;
+            Ok(value)
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
-    #[instrument(name = "spin_outbound_redis.execute", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("{}", command)))]
+    #[instrument(name = "spin_outbound_redis.execute", skip(self, connection), err(level = Level::INFO), fields(otel.kind = "client", db.system = "redis", otel.name = format!("{}", command), error.type = Empty))]
     async fn execute(
         &mut self,
         connection: Resource<RedisConnection>,
         command: String,
         arguments: Vec<RedisParameter>,
     ) -> Result<Vec<RedisResult>, Error> {
-        let conn = self.get_conn(connection).await?;
-        let mut cmd = redis::cmd(&command);
-        arguments.iter().for_each(|value| match value {
-            RedisParameter::Int64(v) => {
-                cmd.arg(v);
-            }
-            RedisParameter::Binary(v) => {
-                cmd.arg(v);
-            }
-        });
-
-        cmd.query_async::<_, RedisResults>(conn)
-            .await
-            .map(|values| values.0)
-            .map_err(other_error)
+        async {
+            let conn = self.get_conn(connection).await?;
+            let mut cmd = redis::cmd(&command);
+            arguments.iter().for_each(|value| match value {
+                RedisParameter::Int64(v) => {
+                    cmd.arg(v);
+                }
+                RedisParameter::Binary(v) => {
+                    cmd.arg(v);
+                }
+            });
+
+            cmd.query_async::<_, RedisResults>(conn)
+                .await
+                .map(|values| values.0)
+                .map_err(other_error)
+        }
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
     fn drop(&mut self, connection: Resource<RedisConnection>) -> anyhow::Result<()> {
@@ -167,8 +167,8 @@ impl BadgerEvaluator {
     async fn available_upgrades(&self) -> anyhow::Result<AvailableUpgrades> {
         let store = self.plugin_manager.store();
 
+        let latest_lookup = crate::lookup::PluginLookup::new(&self.plugin_name, None);
         let latest_version = {
-            let latest_lookup = crate::lookup::PluginLookup::new(&self.plugin_name, None);
             let latest_manifest = latest_lookup
                 .resolve_manifest_exact(store.get_plugins_directory())
                 .await
@@ -176,10 +176,9 @@ impl BadgerEvaluator {
             latest_manifest.and_then(|m| semver::Version::parse(m.version()).ok())
         };
 
-        let manifests = store.catalogue_manifests()?;
-        let relevant_manifests = manifests
-            .into_iter()
-            .filter(|m| m.name() == self.plugin_name);
+        let relevant_manifests = latest_lookup
+            .catalogue_manifests(store.get_plugins_directory())?
+            .into_iter();
         let compatible_manifests = relevant_manifests.filter(|m| {
             m.has_compatible_package() && m.is_compatible_spin_version(self.spin_version)
         });
@@ -1,10 +1,13 @@
 pub mod badger;
 pub mod error;
 mod git;
+pub mod lockfile;
 pub mod lookup;
 pub mod manager;
 pub mod manifest;
+pub mod repos;
 mod store;
+pub mod trust;
 pub use store::PluginStore;
 
 /// List of Spin internal subcommands
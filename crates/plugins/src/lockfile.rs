@@ -0,0 +1,311 @@
+use anyhow::{anyhow, Context, Result};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    lookup::PluginLookup,
+    manifest::{PluginManifest, PluginPackage},
+    store::PluginStore,
+};
+
+/// Name of the file, alongside the application manifest, which pins the
+/// plugins required by the application.
+pub const PLUGINS_LOCKFILE_NAME: &str = "spin-plugins.lock";
+
+const LOCKFILE_VERSION: u32 = 1;
+
+const LOCKFILE_HEADER: &str =
+    "# This file is generated by `spin plugins sync`. Do not edit it by hand.\n\n";
+
+/// A plugin that an application requires, with the range of versions it accepts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PluginRequirement {
+    pub name: String,
+    pub version: VersionReq,
+}
+
+impl PluginRequirement {
+    /// Parses a requirement from a plugin name and a semver range such as `"^0.5"`.
+    pub fn parse(name: &str, version: &str) -> Result<Self> {
+        let version = VersionReq::parse(version).with_context(|| {
+            format!("Plugin '{name}' has invalid version requirement '{version}'")
+        })?;
+        Ok(Self {
+            name: name.to_lowercase(),
+            version,
+        })
+    }
+}
+
+/// Exact plugin versions and checksums resolved from an application's plugin requirements.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct PluginLockfile {
+    version: u32,
+    #[serde(default, rename = "plugin")]
+    pub plugins: Vec<LockedPlugin>,
+}
+
+/// A plugin pinned to an exact version in a lockfile.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct LockedPlugin {
+    /// Name of the plugin.
+    pub name: String,
+    /// The plugin repository from which the plugin must be installed, if the
+    /// requirement named one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// The exact version that was resolved.
+    pub version: String,
+    /// The requirement from which the version was resolved. This is used to
+    /// detect when the application's requirements have changed.
+    pub requirement: String,
+    /// The packages of the resolved version, including their checksums.
+    pub packages: Vec<PluginPackage>,
+}
+
+impl PluginLockfile {
+    /// Gets the path of the lockfile for the application in `app_dir`.
+    pub fn path(app_dir: &Path) -> PathBuf {
+        app_dir.join(PLUGINS_LOCKFILE_NAME)
+    }
+
+    /// Loads the lockfile for the application in `app_dir`, or returns `None`
+    /// if the application has not been synced.
+    pub fn load(app_dir: &Path) -> Result<Option<Self>> {
+        let path = Self::path(app_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let lockfile: Self = toml::from_str(&text)
+            .with_context(|| format!("Invalid lockfile {}", path.display()))?;
+        if lockfile.version != LOCKFILE_VERSION {
+            anyhow::bail!(
+                "Lockfile {} has unsupported version {}",
+                path.display(),
+                lockfile.version
+            );
+        }
+        Ok(Some(lockfile))
+    }
+
+    /// Saves the lockfile for the application in `app_dir`.
+    pub fn save(&self, app_dir: &Path) -> Result<()> {
+        let path = Self::path(app_dir);
+        let text = toml::to_string_pretty(self).context("Failed to serialise plugins lockfile")?;
+        std::fs::write(&path, format!("{LOCKFILE_HEADER}{text}"))
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Resolves each requirement to the highest version in the local catalogue
+    /// snapshot that satisfies it and is compatible with this Spin and platform.
+    /// The catalogue should be updated before calling this.
+    pub fn resolve(
+        requirements: &[PluginRequirement],
+        plugins_dir: &Path,
+        spin_version: &str,
+    ) -> Result<Self> {
+        let plugins = requirements
+            .iter()
+            .map(|req| {
+                let lookup = PluginLookup::new(&req.name, None);
+                let version = lookup
+                    .catalogue_manifests(plugins_dir)?
+                    .iter()
+                    .filter(|m| m.has_compatible_package())
+                    .filter(|m| m.is_compatible_spin_version(spin_version))
+                    .filter_map(|m| m.try_version().ok())
+                    .filter(|v| req.version.matches(v))
+                    .max()
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "No version of plugin '{}' matching '{}' is available for this version of Spin and platform",
+                            req.name,
+                            req.version
+                        )
+                    })?;
+                let lookup = PluginLookup {
+                    version: Some(version),
+                    ..lookup
+                };
+                let manifest = lookup.resolve_manifest_exact_from_good_repo(plugins_dir)?;
+                Ok(LockedPlugin::new(req, lookup.repo, manifest))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            version: LOCKFILE_VERSION,
+            plugins,
+        })
+    }
+
+    /// Whether the lockfile pins every requirement, with the requirement
+    /// unchanged since the lockfile was generated, and pins nothing else.
+    pub fn satisfies(&self, requirements: &[PluginRequirement]) -> bool {
+        let all_pinned = requirements.iter().all(|req| {
+            self.get(&req.name).is_some_and(|locked| {
+                locked.requirement == req.version.to_string()
+                    && Version::parse(&locked.version).is_ok_and(|v| req.version.matches(&v))
+            })
+        });
+        let none_stale = self.plugins.iter().all(|locked| {
+            requirements
+                .iter()
+                .any(|req| req.name == locked.name || req.name == locked.qualified_name())
+        });
+        all_pinned && none_stale
+    }
+
+    /// Gets the pinned entry for the named plugin. The name may be qualified
+    /// with a repository name.
+    pub fn get(&self, name: &str) -> Option<&LockedPlugin> {
+        self.plugins
+            .iter()
+            .find(|p| p.name == name || p.qualified_name() == name)
+    }
+}
+
+impl LockedPlugin {
+    fn new(
+        requirement: &PluginRequirement,
+        repo: Option<String>,
+        manifest: PluginManifest,
+    ) -> Self {
+        Self {
+            name: manifest.name(),
+            repo,
+            version: manifest.version,
+            requirement: requirement.version.to_string(),
+            packages: manifest.packages,
+        }
+    }
+
+    /// The plugin name, qualified with the repository name if the plugin is
+    /// pinned to a repository.
+    pub fn qualified_name(&self) -> String {
+        match &self.repo {
+            Some(repo) => format!("{repo}/{}", self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// A lookup for the pinned version of the plugin in the catalogue.
+    pub fn lookup(&self) -> PluginLookup {
+        PluginLookup::new(&self.qualified_name(), Version::parse(&self.version).ok())
+    }
+
+    /// Whether the pinned version of the plugin is installed.
+    pub fn is_installed_in(&self, store: &PluginStore) -> bool {
+        store
+            .read_plugin_manifest(&self.name)
+            .is_ok_and(|m| m.version == self.version)
+    }
+
+    /// Checks that a manifest fetched for the plugin matches what was pinned,
+    /// so that a changed catalogue entry cannot substitute a different binary.
+    pub fn verify(&self, manifest: &PluginManifest) -> Result<()> {
+        if manifest.name() != self.name || manifest.version != self.version {
+            anyhow::bail!(
+                "Catalogue returned {}@{} but the lockfile pins {}@{}",
+                manifest.name(),
+                manifest.version,
+                self.name,
+                self.version
+            );
+        }
+        let current = manifest
+            .packages
+            .iter()
+            .find(|p| p.matches_current_os_arch());
+        let locked = self.packages.iter().find(|p| p.matches_current_os_arch());
+        match (current, locked) {
+            (Some(current), Some(locked)) if current.sha256 == locked.sha256 => Ok(()),
+            (_, None) => anyhow::bail!(
+                "The lockfile has no package of plugin '{}' for this platform",
+                self.name
+            ),
+            _ => anyhow::bail!(
+                "Checksum of plugin '{}' version {} does not match the lockfile. The catalogue entry may have been changed.",
+                self.name,
+                self.version
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_NAME: &str = "some-spin-ver-some-not";
+
+    fn tests_store_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests")
+    }
+
+    fn requirement(version: &str) -> PluginRequirement {
+        PluginRequirement::parse(TEST_NAME, version).unwrap()
+    }
+
+    #[test]
+    fn resolves_highest_matching_compatible_version() {
+        let lockfile =
+            PluginLockfile::resolve(&[requirement(">=98")], &tests_store_dir(), "99.0.0").unwrap();
+        assert_eq!("99.0.1", lockfile.get(TEST_NAME).unwrap().version);
+
+        // 98.1.0 is excluded (see note on lookup tests) and 99.x does not match
+        let lockfile =
+            PluginLockfile::resolve(&[requirement("~98")], &tests_store_dir(), "99.0.0").unwrap();
+        assert_eq!("98.0.0", lockfile.get(TEST_NAME).unwrap().version);
+    }
+
+    #[test]
+    fn resolve_respects_spin_compatibility() {
+        // Versions 99.x require Spin 99
+        PluginLockfile::resolve(&[requirement("^99")], &tests_store_dir(), "98.0.0")
+            .expect_err("should not resolve a version incompatible with Spin");
+    }
+
+    #[test]
+    fn lockfile_detects_changed_requirements() {
+        let lockfile =
+            PluginLockfile::resolve(&[requirement("~98")], &tests_store_dir(), "99.0.0").unwrap();
+        assert!(lockfile.satisfies(&[requirement("~98")]));
+        assert!(!lockfile.satisfies(&[requirement(">=98")]));
+        assert!(!lockfile.satisfies(&[PluginRequirement::parse("other", "*").unwrap()]));
+        // A plugin which is no longer required is a stale entry
+        assert!(!lockfile.satisfies(&[]));
+    }
+
+    #[test]
+    fn lockfile_round_trips() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let lockfile =
+            PluginLockfile::resolve(&[requirement("~98")], &tests_store_dir(), "99.0.0").unwrap();
+        lockfile.save(temp_dir.path()).unwrap();
+        let loaded = PluginLockfile::load(temp_dir.path()).unwrap().unwrap();
+        assert_eq!(lockfile, loaded);
+    }
+
+    #[test]
+    fn locked_plugin_rejects_changed_checksum() {
+        let lockfile =
+            PluginLockfile::resolve(&[requirement("~98")], &tests_store_dir(), "99.0.0").unwrap();
+        let locked = lockfile.get(TEST_NAME).unwrap();
+
+        let mut manifest = locked
+            .lookup()
+            .resolve_manifest_exact_from_good_repo(&tests_store_dir())
+            .unwrap();
+        locked.verify(&manifest).unwrap();
+
+        for package in manifest.packages.iter_mut() {
+            package.sha256 = "22222222".to_owned();
+        }
+        locked
+            .verify(&manifest)
+            .expect_err("should reject a manifest with a different checksum");
+    }
+}
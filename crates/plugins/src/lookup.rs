@@ -1,4 +1,9 @@
-use crate::{error::*, git::GitSource, manifest::PluginManifest, store::manifest_file_name};
+use crate::{
+    error::*,
+    manifest::PluginManifest,
+    repos::{PluginRepo, PluginRepos},
+    store::{manifest_file_name, PluginStore},
+};
 use semver::Version;
 use std::{
     fs::File,
@@ -6,26 +11,37 @@ use std::{
 };
 use url::Url;
 
-// Name of directory that contains the cloned centralized Spin plugins
-// repository
-const PLUGINS_REPO_LOCAL_DIRECTORY: &str = ".spin-plugins";
-
 // Name of directory containing the installed manifests
 pub(crate) const PLUGINS_REPO_MANIFESTS_DIRECTORY: &str = "manifests";
 
 pub(crate) const SPIN_PLUGINS_REPO: &str = "https://github.com/fermyon/spin-plugins/";
 
-/// Looks up plugin manifests in centralized spin plugin repository.
+// Separates the repository name from the plugin name in a qualified name
+// such as `internal/my-trigger`.
+const REPO_QUALIFIER_SEPARATOR: char = '/';
+
+/// Looks up plugin manifests in the configured plugin repositories.
 pub struct PluginLookup {
     pub name: String,
     pub version: Option<Version>,
+    /// The repository to look in. If `None`, repositories are searched in
+    /// priority order.
+    pub repo: Option<String>,
 }
 
 impl PluginLookup {
+    /// Creates a lookup for the named plugin. The name may be qualified with
+    /// a repository name, e.g. `internal/my-trigger`.
     pub fn new(name: &str, version: Option<Version>) -> Self {
+        let name = name.to_lowercase();
+        let (repo, name) = match name.split_once(REPO_QUALIFIER_SEPARATOR) {
+            Some((repo, name)) => (Some(repo.to_owned()), name.to_owned()),
+            None => (None, name),
+        };
         Self {
-            name: name.to_lowercase(),
+            name,
             version,
+            repo,
         }
     }
 
@@ -35,7 +51,8 @@ impl PluginLookup {
         skip_compatibility_check: bool,
         spin_version: &str,
     ) -> PluginLookupResult<PluginManifest> {
-        let exact = self.resolve_manifest_exact(plugins_dir).await?;
+        self.fetch_repos(plugins_dir).await?;
+        let (repo, exact) = self.resolve_with_repo(plugins_dir)?;
         if skip_compatibility_check
             || self.version.is_some()
             || exact.is_compatible_spin_version(spin_version)
@@ -43,10 +60,13 @@ impl PluginLookup {
             return Ok(exact);
         }
 
-        let store = crate::store::PluginStore::new(plugins_dir.to_owned());
+        let store = PluginStore::new(plugins_dir.to_owned());
 
+        // Fall back only to versions from the repository that provided the
+        // plugin, so that a lower-priority repository cannot substitute a
+        // different plugin of the same name.
         // TODO: This is very similar to some logic in the badger module - look for consolidation opportunities.
-        let manifests = store.catalogue_manifests()?;
+        let manifests = store.catalogue_manifests_in(&repo)?;
         let relevant_manifests = manifests.into_iter().filter(|m| m.name() == self.name);
         let compatible_manifests = relevant_manifests
             .filter(|m| m.has_compatible_package() && m.is_compatible_spin_version(spin_version));
@@ -60,25 +80,111 @@ impl PluginLookup {
         &self,
         plugins_dir: &Path,
     ) -> PluginLookupResult<PluginManifest> {
-        let url = plugins_repo_url()?;
-        tracing::info!("Pulling manifest for plugin {} from {url}", self.name);
-        fetch_plugins_repo(&url, plugins_dir, false)
-            .await
-            .map_err(|e| {
-                Error::ConnectionFailed(ConnectionFailedError::new(url.to_string(), e.to_string()))
-            })?;
-
+        self.fetch_repos(plugins_dir).await?;
         self.resolve_manifest_exact_from_good_repo(plugins_dir)
     }
 
-    // This is split from resolve_manifest_exact because it may recurse (once) and that makes
-    // Rust async sad. So we move the potential recursion to a sync helper.
-    #[allow(clippy::let_and_return)]
+    /// Looks up the manifest in the local copies of the repositories, without
+    /// fetching them.
     pub fn resolve_manifest_exact_from_good_repo(
         &self,
         plugins_dir: &Path,
     ) -> PluginLookupResult<PluginManifest> {
-        let expected_path = spin_plugins_repo_manifest_path(&self.name, &self.version, plugins_dir);
+        self.resolve_with_repo(plugins_dir).map(|(_, m)| m)
+    }
+
+    /// The catalogue manifests of all versions of the plugin, from the
+    /// highest-priority repository that has the plugin.
+    pub fn catalogue_manifests(
+        &self,
+        plugins_dir: &Path,
+    ) -> PluginLookupResult<Vec<PluginManifest>> {
+        let store = PluginStore::new(plugins_dir.to_owned());
+        for repo in self.candidate_repos(plugins_dir)? {
+            let manifests: Vec<_> = store
+                .catalogue_manifests_in(&repo)?
+                .into_iter()
+                .filter(|m| m.name() == self.name)
+                .collect();
+            if !manifests.is_empty() {
+                return Ok(manifests);
+            }
+        }
+        Ok(vec![])
+    }
+
+    fn candidate_repos(&self, plugins_dir: &Path) -> PluginLookupResult<Vec<PluginRepo>> {
+        let repos = PluginRepos::load(&PluginStore::new(plugins_dir))?;
+        match &self.repo {
+            None => Ok(repos.all()),
+            Some(name) => match repos.get(name) {
+                Some(repo) => Ok(vec![repo]),
+                None => Err(Error::Other(anyhow::anyhow!(
+                    "No plugin repository named '{name}'. Run `spin plugins repo list` to see configured repositories."
+                ))),
+            },
+        }
+    }
+
+    async fn fetch_repos(&self, plugins_dir: &Path) -> PluginLookupResult<()> {
+        let candidates = self.candidate_repos(plugins_dir)?;
+        let mut last_error = None;
+        let mut any_fetched = false;
+        for repo in &candidates {
+            tracing::info!(
+                "Pulling manifest for plugin {} from {}",
+                self.name,
+                repo.source_text()
+            );
+            match repo.fetch(plugins_dir, false).await {
+                Ok(()) => any_fetched = true,
+                Err(e) => {
+                    tracing::warn!("Failed to fetch plugin repository '{}': {e:#}", repo.name);
+                    last_error = Some(Error::ConnectionFailed(ConnectionFailedError::new(
+                        repo.source_text(),
+                        e.to_string(),
+                    )));
+                }
+            }
+        }
+        // An unreachable repository should not prevent lookups in the others.
+        match last_error {
+            Some(e) if !any_fetched => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn resolve_with_repo(
+        &self,
+        plugins_dir: &Path,
+    ) -> PluginLookupResult<(PluginRepo, PluginManifest)> {
+        let mut first_error = None;
+        for repo in self.candidate_repos(plugins_dir)? {
+            match self.resolve_in_repo(&repo, plugins_dir) {
+                Ok(manifest) => return Ok((repo, manifest)),
+                Err(Error::NotFound(e)) => {
+                    first_error.get_or_insert(Error::NotFound(e));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(first_error.unwrap_or_else(|| {
+            Error::NotFound(NotFoundError::new(
+                Some(self.name.clone()),
+                plugins_dir.display().to_string(),
+                "no plugin repositories are configured".to_owned(),
+            ))
+        }))
+    }
+
+    // This may recurse (once), so it is kept synchronous.
+    #[allow(clippy::let_and_return)]
+    fn resolve_in_repo(
+        &self,
+        repo: &PluginRepo,
+        plugins_dir: &Path,
+    ) -> PluginLookupResult<PluginManifest> {
+        let expected_path = repo_manifest_path(&self.name, &self.version, repo, plugins_dir);
 
         let not_found = |e: std::io::Error| {
             Err(Error::NotFound(NotFoundError::new(
@@ -99,8 +205,12 @@ impl PluginLookup {
             Err(e) if e.kind() == std::io::ErrorKind::NotFound && self.version.is_some() => {
                 // If a user has asked for a version by number, and the path doesn't exist,
                 // it _might_ be because it's the latest version. This checks for that case.
-                let latest = Self::new(&self.name, None);
-                match latest.resolve_manifest_exact_from_good_repo(plugins_dir) {
+                let latest = Self {
+                    name: self.name.clone(),
+                    version: None,
+                    repo: self.repo.clone(),
+                };
+                match latest.resolve_in_repo(repo, plugins_dir) {
                     Ok(manifest) if manifest.try_version().ok() == self.version => Ok(manifest),
                     _ => not_found(e),
                 }
@@ -117,36 +227,34 @@ pub fn plugins_repo_url() -> Result<Url, url::ParseError> {
 }
 
 #[cfg(not(test))]
-fn accept_as_repo(git_root: &Path) -> bool {
+pub(crate) fn accept_as_repo(git_root: &Path) -> bool {
     git_root.join(".git").exists()
 }
 
 #[cfg(test)]
-fn accept_as_repo(git_root: &Path) -> bool {
+pub(crate) fn accept_as_repo(git_root: &Path) -> bool {
     git_root.join(".git").exists() || git_root.join("_spin_test_dot_git").exists()
 }
 
-pub async fn fetch_plugins_repo(
-    repo_url: &Url,
-    plugins_dir: &Path,
-    update: bool,
-) -> anyhow::Result<()> {
-    let git_root = plugin_manifests_repo_path(plugins_dir);
-    let git_source = GitSource::new(repo_url, None, &git_root);
-    if accept_as_repo(&git_root) {
-        if update {
-            git_source.pull().await?;
+/// Fetches all configured plugin repositories. If `update` is true, existing
+/// clones are updated to the latest.
+pub async fn fetch_plugins_repos(plugins_dir: &Path, update: bool) -> anyhow::Result<()> {
+    let repos = PluginRepos::load(&PluginStore::new(plugins_dir))?;
+    let mut failures = vec![];
+    for repo in repos.all() {
+        if let Err(e) = repo.fetch(plugins_dir, update).await {
+            failures.push(format!("{}: {e:#}", repo.name));
         }
-    } else {
-        git_source.clone_repo().await?;
+    }
+    if !failures.is_empty() {
+        anyhow::bail!(
+            "Failed to fetch plugin repositories:\n{}",
+            failures.join("\n")
+        );
     }
     Ok(())
 }
 
-fn plugin_manifests_repo_path(plugins_dir: &Path) -> PathBuf {
-    plugins_dir.join(PLUGINS_REPO_LOCAL_DIRECTORY)
-}
-
 // Given a name and option version, outputs expected file name for the plugin.
 fn manifest_file_name_version(plugin_name: &str, version: &Option<semver::Version>) -> String {
     match version {
@@ -156,23 +264,18 @@ fn manifest_file_name_version(plugin_name: &str, version: &Option<semver::Versio
 }
 
 /// Get expected path to the manifest of a plugin with a given name
-/// and version within the spin-plugins repository
-fn spin_plugins_repo_manifest_path(
+/// and version within a plugins repository
+fn repo_manifest_path(
     plugin_name: &str,
     plugin_version: &Option<Version>,
+    repo: &PluginRepo,
     plugins_dir: &Path,
 ) -> PathBuf {
-    spin_plugins_repo_manifest_dir(plugins_dir)
+    repo.manifests_dir(plugins_dir)
         .join(plugin_name)
         .join(manifest_file_name_version(plugin_name, plugin_version))
 }
 
-pub fn spin_plugins_repo_manifest_dir(plugins_dir: &Path) -> PathBuf {
-    plugins_dir
-        .join(PLUGINS_REPO_LOCAL_DIRECTORY)
-        .join(PLUGINS_REPO_MANIFESTS_DIRECTORY)
-}
-
 fn null_version() -> semver::Version {
     semver::Version::new(0, 0, 0)
 }
@@ -251,4 +354,80 @@ mod tests {
             .expect_err("Should have errored because plugin v177.7.7 does not exist");
         Ok(())
     }
+
+    fn store_with_internal_repo(first: bool) -> (tempfile::TempDir, PathBuf) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let plugins_dir = temp_dir.path().to_owned();
+        let store = PluginStore::new(&plugins_dir);
+        let mut repos = PluginRepos::default();
+        repos
+            .add(
+                PluginRepo {
+                    name: "spin-copy".to_owned(),
+                    source: crate::repos::PluginRepoSource::Dir(
+                        tests_store_dir().join(".spin-plugins"),
+                    ),
+                },
+                false,
+            )
+            .unwrap();
+        repos
+            .add(
+                PluginRepo {
+                    name: "internal".to_owned(),
+                    source: crate::repos::PluginRepoSource::Dir(
+                        tests_store_dir().join("internal-plugins"),
+                    ),
+                },
+                first,
+            )
+            .unwrap();
+        repos.save(&store).unwrap();
+        (temp_dir, plugins_dir)
+    }
+
+    #[tokio::test]
+    async fn qualified_name_looks_only_in_named_repo() -> PluginLookupResult<()> {
+        let (_temp_dir, plugins_dir) = store_with_internal_repo(false);
+
+        let lookup = PluginLookup::new("internal/my-trigger", None);
+        assert_eq!("my-trigger", lookup.name);
+        let resolved = lookup
+            .resolve_manifest(&plugins_dir, false, "1.0.0")
+            .await?;
+        assert_eq!("0.1.0", resolved.version);
+
+        let lookup = PluginLookup::new(&format!("internal/{TEST_NAME}"), None);
+        let resolved = lookup.resolve_manifest(&plugins_dir, true, "1.0.0").await?;
+        assert_eq!("1.0.0", resolved.version);
+
+        PluginLookup::new("spin-copy/my-trigger", None)
+            .resolve_manifest(&plugins_dir, true, "1.0.0")
+            .await
+            .expect_err("my-trigger is not in the spin-copy repository");
+        PluginLookup::new("nope/my-trigger", None)
+            .resolve_manifest(&plugins_dir, true, "1.0.0")
+            .await
+            .expect_err("there is no repository named nope");
+        Ok(())
+    }
+
+    #[test]
+    fn unqualified_name_uses_highest_priority_repo() -> PluginLookupResult<()> {
+        let (_temp_dir, plugins_dir) = store_with_internal_repo(false);
+        let resolved = PluginLookup::new(TEST_NAME, None)
+            .resolve_manifest_exact_from_good_repo(&plugins_dir)?;
+        assert_eq!("99.0.1", resolved.version);
+
+        let (_temp_dir, plugins_dir) = store_with_internal_repo(true);
+        let resolved = PluginLookup::new(TEST_NAME, None)
+            .resolve_manifest_exact_from_good_repo(&plugins_dir)?;
+        assert_eq!("1.0.0", resolved.version);
+
+        // Plugins in only one repository are found regardless of priority.
+        let resolved = PluginLookup::new("my-trigger", None)
+            .resolve_manifest_exact_from_good_repo(&plugins_dir)?;
+        assert_eq!("0.1.0", resolved.version);
+        Ok(())
+    }
 }
@@ -3,6 +3,7 @@ use crate::{
     lookup::PluginLookup,
     manifest::{warn_unsupported_version, PluginManifest, PluginPackage},
     store::PluginStore,
+    trust::TrustPolicy,
     SPIN_INTERNAL_COMMANDS,
 };
 
@@ -62,13 +63,18 @@ pub(crate) enum RawInstallRecord {
 /// Provides accesses to functionality to inspect and manage the installation of plugins.
 pub struct PluginManager {
     store: PluginStore,
+    trust: TrustPolicy,
 }
 
 impl PluginManager {
     /// Creates a `PluginManager` with the default install location.
     pub fn try_default() -> anyhow::Result<Self> {
         let store = PluginStore::try_default()?;
-        Ok(Self { store })
+        let mut trust = TrustPolicy::load(&store)?;
+        if crate::trust::require_signatures_from_env() {
+            trust.require_signatures = true;
+        }
+        Ok(Self { store, trust })
     }
 
     /// Returns the underlying store object
@@ -80,7 +86,8 @@ impl PluginManager {
     /// Spin plugins repository, it fetches the latest contents of the repository and searches for
     /// the appropriately named and versioned plugin manifest. Parses the plugin manifest to get the
     /// appropriate source for the machine OS and architecture. Verifies the checksum of the source,
-    /// unpacks and installs it into the plugins directory.
+    /// and its signature according to the trust policy, then unpacks and installs it into the
+    /// plugins directory.
     /// Returns name of plugin that was successfully installed.
     pub async fn install(
         &self,
@@ -109,6 +116,16 @@ impl PluginManager {
         };
         verify_checksum(&plugin_tarball_path, &plugin_package.sha256)?;
 
+        let signature = match &plugin_package.signature {
+            Some(signature_url) => Some(download_signature(signature_url).await?),
+            None => None,
+        };
+        self.trust.verify_package(
+            &plugin_manifest.name(),
+            &plugin_tarball_path,
+            signature.as_deref(),
+        )?;
+
         self.store
             .untar_plugin(&plugin_tarball_path, &plugin_manifest.name())
             .with_context(|| format!("Failed to untar {}", plugin_tarball_path.display()))?;
@@ -353,6 +370,24 @@ async fn download_plugin(name: &str, temp_dir: &TempDir, target_url: &str) -> Re
     Ok(plugin_file)
 }
 
+async fn download_signature(signature_url: &str) -> Result<String> {
+    let url = Url::parse(signature_url)
+        .with_context(|| format!("Invalid plugin signature URL {signature_url}"))?;
+    if url.scheme() == URL_FILE_SCHEME {
+        let path = url
+            .to_file_path()
+            .map_err(|_| anyhow!("Invalid file URL: {url:?}"))?;
+        return fs::read_to_string(&path)
+            .with_context(|| format!("Cannot read plugin signature {}", path.display()));
+    }
+    tracing::trace!("Trying to get plugin signature from {url}");
+    let response = reqwest::get(url.as_str())
+        .await?
+        .error_for_status()
+        .with_context(|| format!("Failed to download plugin signature from {url}"))?;
+    Ok(response.text().await?)
+}
+
 fn verify_checksum(plugin_file: &Path, expected_sha256: &str) -> Result<()> {
     let actual_sha256 = sha256::hex_digest_from_file(plugin_file)
         .with_context(|| format!("Cannot get digest for {}", plugin_file.display()))?;
@@ -372,7 +407,10 @@ mod tests {
     async fn good_error_when_tarball_404s() -> anyhow::Result<()> {
         let temp_dir = tempdir()?;
         let store = PluginStore::new(temp_dir.path());
-        let manager = PluginManager { store };
+        let manager = PluginManager {
+            store,
+            trust: TrustPolicy::default(),
+        };
 
         let bad_manifest: PluginManifest = serde_json::from_str(include_str!(
             "../tests/nonexistent-url/nonexistent-url.json"
@@ -396,4 +434,50 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn refuses_unsigned_package_when_signatures_required() -> anyhow::Result<()> {
+        let temp_dir = tempdir()?;
+        let store = PluginStore::new(temp_dir.path());
+        let manager = PluginManager {
+            store,
+            trust: TrustPolicy {
+                require_signatures: true,
+                ..Default::default()
+            },
+        };
+
+        let package_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/signed/package.bin");
+        let package_url = Url::from_file_path(&package_path).unwrap();
+        let sha256 = sha256::hex_digest_from_file(&package_path)?;
+        let manifest: PluginManifest = serde_json::from_value(serde_json::json!({
+            "name": "unsigned",
+            "version": "1.0.0",
+            "spinCompatibility": ">=0.1",
+            "license": "Apache-2.0",
+            "packages": [{
+                "os": "linux",
+                "arch": "amd64",
+                "url": package_url.as_str(),
+                "sha256": sha256,
+            }]
+        }))?;
+
+        let install_result = manager
+            .install(
+                &manifest,
+                &manifest.packages[0],
+                &ManifestLocation::Local(package_path),
+            )
+            .await;
+
+        let err = format!("{:#}", install_result.unwrap_err());
+        assert!(
+            err.contains("not signed"),
+            "Expected error to contain 'not signed' but was '{err}'"
+        );
+        assert!(!manager.store.installed_manifest_path("unsigned").exists());
+
+        Ok(())
+    }
 }
@@ -83,12 +83,18 @@ pub struct PluginPackage {
     pub(crate) url: String,
     /// Checksum to verify the plugin before installation.
     pub(crate) sha256: String,
+    /// Address to fetch a detached minisign signature of the plugin source tar file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) signature: Option<String>,
 }
 
 impl PluginPackage {
     pub fn url(&self) -> String {
         self.url.clone()
     }
+    pub fn is_signed(&self) -> bool {
+        self.signature.is_some()
+    }
     pub fn matches_current_os_arch(&self) -> bool {
         self.os.rust_name() == std::env::consts::OS
             && self.arch.rust_name() == std::env::consts::ARCH
@@ -0,0 +1,317 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use crate::{
+    git::GitSource,
+    lookup::{plugins_repo_url, PLUGINS_REPO_MANIFESTS_DIRECTORY},
+    store::PluginStore,
+};
+
+/// Name of the default (public) plugin catalogue.
+pub const DEFAULT_REPO_NAME: &str = "spin";
+
+// Name of directory that contains the cloned centralized Spin plugins
+// repository
+const DEFAULT_REPO_LOCAL_DIRECTORY: &str = ".spin-plugins";
+
+// Name of directory that contains clones of additional catalogue repositories
+const ADDITIONAL_REPOS_LOCAL_DIRECTORY: &str = ".spin-plugins-repos";
+
+/// A catalogue of plugin manifests.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PluginRepo {
+    /// The name of the catalogue, used to qualify plugin names (`name/plugin`).
+    pub name: String,
+    /// Where the catalogue comes from.
+    pub source: PluginRepoSource,
+}
+
+/// The location of a plugin catalogue.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PluginRepoSource {
+    /// The catalogue is a Git repository, which is cloned into the plugins directory.
+    Git(Url),
+    /// The catalogue is a local directory, which is used in place.
+    Dir(PathBuf),
+}
+
+/// The configured plugin catalogues, in priority order. The default
+/// catalogue is always present, with the lowest priority.
+#[derive(Debug, Default)]
+pub struct PluginRepos {
+    additional: Vec<PluginRepo>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RawReposConfig {
+    #[serde(default, rename = "repo")]
+    repos: Vec<RawRepo>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RawRepo {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<Url>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<PathBuf>,
+}
+
+impl PluginRepos {
+    /// Loads the configured catalogues from the plugin store.
+    pub fn load(store: &PluginStore) -> Result<Self> {
+        let path = store.repos_config_file();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let raw: RawReposConfig = toml::from_str(&text)
+            .with_context(|| format!("Invalid plugin repositories file {}", path.display()))?;
+        let additional = raw
+            .repos
+            .into_iter()
+            .map(PluginRepo::try_from)
+            .collect::<Result<_>>()
+            .with_context(|| format!("Invalid plugin repositories file {}", path.display()))?;
+        Ok(Self { additional })
+    }
+
+    /// Saves the configured catalogues to the plugin store.
+    pub fn save(&self, store: &PluginStore) -> Result<()> {
+        let path = store.repos_config_file();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let raw = RawReposConfig {
+            repos: self.additional.iter().map(RawRepo::from).collect(),
+        };
+        let text =
+            toml::to_string_pretty(&raw).context("Failed to serialise plugin repositories")?;
+        std::fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// All catalogues, highest priority first.
+    pub fn all(&self) -> Vec<PluginRepo> {
+        self.additional
+            .iter()
+            .cloned()
+            .chain(std::iter::once(PluginRepo::default_repo()))
+            .collect()
+    }
+
+    /// Gets the named catalogue.
+    pub fn get(&self, name: &str) -> Option<PluginRepo> {
+        self.all().into_iter().find(|r| r.name == name)
+    }
+
+    /// Adds a catalogue. If `highest_priority` is true, it is consulted before
+    /// all other catalogues; otherwise, after all catalogues except the default.
+    pub fn add(&mut self, repo: PluginRepo, highest_priority: bool) -> Result<()> {
+        validate_repo_name(&repo.name)?;
+        if self.get(&repo.name).is_some() {
+            anyhow::bail!("A plugin repository named '{}' already exists", repo.name);
+        }
+        if highest_priority {
+            self.additional.insert(0, repo);
+        } else {
+            self.additional.push(repo);
+        }
+        Ok(())
+    }
+
+    /// Removes the named catalogue. Returns the removed catalogue, or `None` if
+    /// there was no such catalogue.
+    pub fn remove(&mut self, name: &str) -> Result<Option<PluginRepo>> {
+        if name == DEFAULT_REPO_NAME {
+            anyhow::bail!("The default plugin repository cannot be removed");
+        }
+        let index = self.additional.iter().position(|r| r.name == name);
+        Ok(index.map(|i| self.additional.remove(i)))
+    }
+}
+
+impl PluginRepo {
+    /// The default (public) plugin catalogue.
+    pub fn default_repo() -> Self {
+        Self {
+            name: DEFAULT_REPO_NAME.to_owned(),
+            source: PluginRepoSource::Git(
+                plugins_repo_url().expect("default plugins repository URL should be valid"),
+            ),
+        }
+    }
+
+    /// The local directory containing the catalogue.
+    pub fn local_dir(&self, plugins_dir: &Path) -> PathBuf {
+        match &self.source {
+            PluginRepoSource::Dir(path) => path.clone(),
+            PluginRepoSource::Git(_) if self.name == DEFAULT_REPO_NAME => {
+                plugins_dir.join(DEFAULT_REPO_LOCAL_DIRECTORY)
+            }
+            PluginRepoSource::Git(_) => plugins_dir
+                .join(ADDITIONAL_REPOS_LOCAL_DIRECTORY)
+                .join(&self.name),
+        }
+    }
+
+    /// The directory containing the catalogue's plugin manifests.
+    pub fn manifests_dir(&self, plugins_dir: &Path) -> PathBuf {
+        self.local_dir(plugins_dir)
+            .join(PLUGINS_REPO_MANIFESTS_DIRECTORY)
+    }
+
+    /// Makes sure the catalogue is available locally, cloning it if necessary.
+    /// If `update` is true, an existing clone is updated to the latest.
+    pub async fn fetch(&self, plugins_dir: &Path, update: bool) -> Result<()> {
+        match &self.source {
+            PluginRepoSource::Git(url) => {
+                let git_root = self.local_dir(plugins_dir);
+                let git_source = GitSource::new(url, None, &git_root);
+                if crate::lookup::accept_as_repo(&git_root) {
+                    if update {
+                        git_source.pull().await?;
+                    }
+                } else {
+                    git_source.clone_repo().await?;
+                }
+                Ok(())
+            }
+            PluginRepoSource::Dir(path) => {
+                if !path.is_dir() {
+                    anyhow::bail!(
+                        "Plugin repository '{}' directory {} does not exist",
+                        self.name,
+                        path.display()
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// A displayable description of the catalogue source.
+    pub fn source_text(&self) -> String {
+        match &self.source {
+            PluginRepoSource::Git(url) => url.to_string(),
+            PluginRepoSource::Dir(path) => path.display().to_string(),
+        }
+    }
+}
+
+impl TryFrom<RawRepo> for PluginRepo {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawRepo) -> Result<Self> {
+        validate_repo_name(&raw.name)?;
+        let source = match (raw.url, raw.path) {
+            (Some(url), None) => PluginRepoSource::Git(url),
+            (None, Some(path)) => PluginRepoSource::Dir(path),
+            _ => anyhow::bail!(
+                "Plugin repository '{}' must have exactly one of 'url' or 'path'",
+                raw.name
+            ),
+        };
+        Ok(Self {
+            name: raw.name,
+            source,
+        })
+    }
+}
+
+impl From<&PluginRepo> for RawRepo {
+    fn from(repo: &PluginRepo) -> Self {
+        let (url, path) = match &repo.source {
+            PluginRepoSource::Git(url) => (Some(url.clone()), None),
+            PluginRepoSource::Dir(path) => (None, Some(path.clone())),
+        };
+        Self {
+            name: repo.name.clone(),
+            url,
+            path,
+        }
+    }
+}
+
+fn validate_repo_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        anyhow::bail!(
+            "Plugin repository name '{name}' must contain only letters, numbers, '-' and '_'"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir_repo(name: &str) -> PluginRepo {
+        PluginRepo {
+            name: name.to_owned(),
+            source: PluginRepoSource::Dir(PathBuf::from(format!("/repos/{name}"))),
+        }
+    }
+
+    #[test]
+    fn default_repo_has_lowest_priority() {
+        let mut repos = PluginRepos::default();
+        repos.add(dir_repo("second"), false).unwrap();
+        repos.add(dir_repo("first"), true).unwrap();
+        repos.add(dir_repo("third"), false).unwrap();
+
+        let names: Vec<_> = repos.all().into_iter().map(|r| r.name).collect();
+        assert_eq!(vec!["first", "second", "third", DEFAULT_REPO_NAME], names);
+    }
+
+    #[test]
+    fn cannot_add_duplicate_or_remove_default() {
+        let mut repos = PluginRepos::default();
+        repos.add(dir_repo("internal"), false).unwrap();
+        repos
+            .add(dir_repo("internal"), false)
+            .expect_err("should reject duplicate name");
+        repos
+            .add(dir_repo(DEFAULT_REPO_NAME), false)
+            .expect_err("should reject default name");
+        repos
+            .add(dir_repo("not/valid"), false)
+            .expect_err("should reject name containing separator");
+        repos
+            .remove(DEFAULT_REPO_NAME)
+            .expect_err("should not remove default");
+        assert!(repos.remove("internal").unwrap().is_some());
+        assert!(repos.remove("internal").unwrap().is_none());
+    }
+
+    #[test]
+    fn repos_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = PluginStore::new(temp_dir.path());
+        let mut repos = PluginRepos::default();
+        repos.add(dir_repo("internal"), false).unwrap();
+        repos
+            .add(
+                PluginRepo {
+                    name: "partner".to_owned(),
+                    source: PluginRepoSource::Git(
+                        Url::parse("https://example.com/plugins.git").unwrap(),
+                    ),
+                },
+                false,
+            )
+            .unwrap();
+        repos.save(&store).unwrap();
+
+        let loaded = PluginRepos::load(&store).unwrap();
+        assert_eq!(repos.all(), loaded.all());
+    }
+}
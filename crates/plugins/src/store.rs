@@ -8,11 +8,17 @@ use std::{
 };
 use tar::Archive;
 
-use crate::{error::*, manifest::PluginManifest};
+use crate::{
+    error::*,
+    manifest::PluginManifest,
+    repos::{PluginRepo, PluginRepos},
+};
 
 /// Directory where the manifests of installed plugins are stored.
 pub const PLUGIN_MANIFESTS_DIRECTORY_NAME: &str = "manifests";
 const INSTALLATION_RECORD_FILE_NAME: &str = ".install.json";
+const TRUST_POLICY_FILE_NAME: &str = "trust.toml";
+const REPOS_CONFIG_FILE_NAME: &str = "repos.toml";
 
 /// Houses utilities for getting the path to Spin plugin directories.
 pub struct PluginStore {
@@ -63,6 +69,16 @@ impl PluginStore {
             .join(INSTALLATION_RECORD_FILE_NAME)
     }
 
+    /// Get the path to the file listing the keys trusted to sign plugins.
+    pub fn trust_policy_file(&self) -> PathBuf {
+        self.root.join(TRUST_POLICY_FILE_NAME)
+    }
+
+    /// The file listing the configured plugin repositories.
+    pub fn repos_config_file(&self) -> PathBuf {
+        self.root.join(REPOS_CONFIG_FILE_NAME)
+    }
+
     pub fn installed_manifests(&self) -> Result<Vec<PluginManifest>> {
         let manifests_dir = self.installed_manifests_directory();
         let manifest_paths = Self::json_files_in(&manifests_dir);
@@ -73,18 +89,28 @@ impl PluginStore {
         Ok(manifests)
     }
 
-    // TODO: report errors on individuals
+    /// The manifests in all configured plugin repositories, in repository
+    /// priority order.
     pub fn catalogue_manifests(&self) -> Result<Vec<PluginManifest>> {
+        let repos = PluginRepos::load(self)?;
+        let mut manifests = vec![];
+        for repo in repos.all() {
+            manifests.extend(self.catalogue_manifests_in(&repo)?);
+        }
+        Ok(manifests)
+    }
+
+    // TODO: report errors on individuals
+    pub fn catalogue_manifests_in(&self, repo: &PluginRepo) -> Result<Vec<PluginManifest>> {
         // Structure:
-        // CATALOGUE_DIR (spin/plugins/.spin-plugins/manifests)
+        // CATALOGUE_DIR (e.g. spin/plugins/.spin-plugins/manifests)
         // |- foo
         // |  |- foo@0.1.2.json
         // |  |- foo@1.2.3.json
         // |  |- foo.json
         // |- bar
         //    |- bar.json
-        let catalogue_dir =
-            crate::lookup::spin_plugins_repo_manifest_dir(self.get_plugins_directory());
+        let catalogue_dir = repo.manifests_dir(self.get_plugins_directory());
 
         // Catalogue directory doesn't exist so likely nothing has been installed.
         if !catalogue_dir.exists() {
@@ -93,7 +119,7 @@ impl PluginStore {
 
         let plugin_dirs = catalogue_dir
             .read_dir()
-            .context("reading manifest catalogue at {catalogue_dir:?}")?
+            .with_context(|| format!("reading manifest catalogue at {catalogue_dir:?}"))?
             .filter_map(|d| d.ok())
             .map(|d| d.path())
             .filter(|p| p.is_dir());
@@ -0,0 +1,213 @@
+use anyhow::{anyhow, Context, Result};
+use minisign_verify::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::store::PluginStore;
+
+// Setting this environment variable to a non-empty value requires all plugins to
+// be signed by a trusted key, regardless of the trust policy file. This is intended
+// for CI, where there is no one to notice a warning.
+const REQUIRE_SIGNATURES_ENV: &str = "SPIN_PLUGINS_REQUIRE_SIGNATURES";
+
+/// The keys which are trusted to sign plugin packages, and whether
+/// plugins must be signed to be installed.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrustPolicy {
+    /// If true, unsigned plugins are refused. Signed plugins are refused
+    /// unless a key in the policy verifies them whether or not this is set.
+    #[serde(default)]
+    pub require_signatures: bool,
+    /// The keys trusted to sign plugins.
+    #[serde(default, rename = "key", skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<TrustedKey>,
+}
+
+/// A public key trusted to sign plugin packages.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrustedKey {
+    /// A name to identify the key, e.g. the publisher.
+    pub name: String,
+    /// The base64-encoded minisign public key.
+    pub public_key: String,
+}
+
+impl TrustedKey {
+    /// Creates a trusted key entry, checking that `public_key` is a valid
+    /// minisign public key.
+    pub fn new(name: impl Into<String>, public_key: impl Into<String>) -> Result<Self> {
+        let key = Self {
+            name: name.into(),
+            public_key: public_key.into(),
+        };
+        key.decode()?;
+        Ok(key)
+    }
+
+    fn decode(&self) -> Result<PublicKey> {
+        PublicKey::from_base64(&self.public_key)
+            .map_err(|e| anyhow!("Invalid public key for '{}': {e}", self.name))
+    }
+}
+
+impl TrustPolicy {
+    /// Loads the trust policy from the plugin store. If there is no policy
+    /// file, the default policy (no trusted keys, signatures not required)
+    /// is used.
+    pub fn load(store: &PluginStore) -> Result<Self> {
+        let path = store.trust_policy_file();
+        let policy = if path.exists() {
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let policy: Self = toml::from_str(&text)
+                .with_context(|| format!("Invalid plugin trust policy {}", path.display()))?;
+            for key in &policy.keys {
+                key.decode()
+                    .with_context(|| format!("Invalid plugin trust policy {}", path.display()))?;
+            }
+            policy
+        } else {
+            Self::default()
+        };
+        Ok(policy)
+    }
+
+    /// Saves the trust policy to the plugin store.
+    pub fn save(&self, store: &PluginStore) -> Result<()> {
+        let path = store.trust_policy_file();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string_pretty(self).context("Failed to serialise trust policy")?;
+        std::fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Checks a downloaded plugin package against its detached signature (if any).
+    /// Fails if the package is signed but no trusted key verifies it, or if the
+    /// policy requires signatures and the package is unsigned.
+    pub(crate) fn verify_package(
+        &self,
+        plugin_name: &str,
+        package_file: &Path,
+        signature: Option<&str>,
+    ) -> Result<()> {
+        let Some(signature) = signature else {
+            if self.require_signatures {
+                anyhow::bail!("Plugin '{plugin_name}' is not signed, and the plugin trust policy requires signed plugins. Installation aborted.");
+            }
+            tracing::info!("Plugin '{plugin_name}' is not signed");
+            return Ok(());
+        };
+
+        if self.keys.is_empty() && !self.require_signatures {
+            tracing::info!("Plugin '{plugin_name}' is signed but no keys are trusted: skipping signature check");
+            return Ok(());
+        }
+
+        let signature = Signature::decode(signature)
+            .map_err(|e| anyhow!("Signature of plugin '{plugin_name}' is not valid: {e}"))?;
+        let content = std::fs::read(package_file)
+            .with_context(|| format!("Cannot read {}", package_file.display()))?;
+
+        for key in &self.keys {
+            if key.decode()?.verify(&content, &signature, false).is_ok() {
+                tracing::info!(
+                    "Plugin '{plugin_name}' signature verified with key '{}'",
+                    key.name
+                );
+                return Ok(());
+            }
+        }
+
+        anyhow::bail!("Plugin '{plugin_name}' is not signed by any key in the plugin trust policy. Installation aborted.")
+    }
+}
+
+pub(crate) fn require_signatures_from_env() -> bool {
+    std::env::var(REQUIRE_SIGNATURES_ENV).is_ok_and(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn signed_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/signed")
+    }
+
+    fn read_public_key(name: &str) -> String {
+        let text = std::fs::read_to_string(signed_dir().join(name)).unwrap();
+        text.lines().last().unwrap().to_owned()
+    }
+
+    fn read_signature() -> String {
+        std::fs::read_to_string(signed_dir().join("package.bin.minisig")).unwrap()
+    }
+
+    fn policy(require_signatures: bool, key_file: &str) -> TrustPolicy {
+        TrustPolicy {
+            require_signatures,
+            keys: vec![TrustedKey::new("test", read_public_key(key_file)).unwrap()],
+        }
+    }
+
+    #[test]
+    fn accepts_package_signed_by_trusted_key() {
+        let package = signed_dir().join("package.bin");
+        policy(true, "trusted.pub")
+            .verify_package("test", &package, Some(&read_signature()))
+            .unwrap();
+    }
+
+    #[test]
+    fn refuses_untrusted_signer() {
+        let package = signed_dir().join("package.bin");
+        let signature = read_signature();
+        for require_signatures in [true, false] {
+            policy(require_signatures, "untrusted.pub")
+                .verify_package("test", &package, Some(&signature))
+                .expect_err("should refuse a package signed by an untrusted key");
+        }
+    }
+
+    #[test]
+    fn refuses_tampered_package() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let package = temp_dir.path().join("package.bin");
+        std::fs::write(&package, "This is a different package").unwrap();
+        for require_signatures in [true, false] {
+            policy(require_signatures, "trusted.pub")
+                .verify_package("test", &package, Some(&read_signature()))
+                .expect_err("should refuse a package which does not match its signature");
+        }
+    }
+
+    #[test]
+    fn refuses_unsigned_package_when_required() {
+        let package = signed_dir().join("package.bin");
+        policy(true, "trusted.pub")
+            .verify_package("test", &package, None)
+            .expect_err("should refuse an unsigned package");
+        policy(false, "trusted.pub")
+            .verify_package("test", &package, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_public_keys() {
+        TrustedKey::new("bad", "not a key").expect_err("should reject invalid key");
+    }
+
+    #[test]
+    fn policy_round_trips() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = PluginStore::new(temp_dir.path());
+        policy(true, "trusted.pub").save(&store).unwrap();
+        let loaded = TrustPolicy::load(&store).unwrap();
+        assert!(loaded.require_signatures);
+        assert_eq!(read_public_key("trusted.pub"), loaded.keys[0].public_key);
+    }
+}
@@ -4,7 +4,11 @@ use spin_app::{async_trait, MetadataKey};
 use spin_core::wasmtime::component::Resource;
 use spin_world::v1::sqlite::Error as V1SqliteError;
 use spin_world::v2::sqlite;
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tracing::{field::Empty, instrument, Level};
 
 pub use host_component::SqliteComponent;
 
@@ -38,6 +42,8 @@ pub trait Connection: Send + Sync {
 pub struct SqliteDispatch {
     allowed_databases: HashSet<String>,
     connections: table::Table<Arc<dyn Connection>>,
+    // Names of the databases of open connections, by resource rep, for tracing
+    connection_names: HashMap<u32, String>,
     connections_store: Arc<dyn ConnectionsStore>,
 }
 
@@ -45,6 +51,7 @@ impl SqliteDispatch {
     pub fn new(connections_store: Arc<dyn ConnectionsStore>) -> Self {
         Self {
             connections: table::Table::new(256),
+            connection_names: HashMap::new(),
             allowed_databases: HashSet::new(),
             connections_store,
         }
@@ -79,39 +86,58 @@ impl sqlite::Host for SqliteDispatch {
 
 #[async_trait]
 impl sqlite::HostConnection for SqliteDispatch {
+    #[instrument(name = "spin_sqlite.open", skip(self, database), err(level = Level::INFO), fields(otel.kind = "client", db.system = "sqlite", db.operation = "open", db.name = database, error.type = Empty))]
     async fn open(
         &mut self,
         database: String,
     ) -> Result<Resource<sqlite::Connection>, sqlite::Error> {
-        if !self.allowed_databases.contains(&database) {
-            return Err(sqlite::Error::AccessDenied);
+        record_call("open");
+        async {
+            if !self.allowed_databases.contains(&database) {
+                return Err(sqlite::Error::AccessDenied);
+            }
+            let rep = self
+                .connections_store
+                .get_connection(&database)
+                .await
+                .and_then(|conn| conn.ok_or(sqlite::Error::NoSuchDatabase))
+                .and_then(|conn| {
+                    self.connections
+                        .push(conn)
+                        .map_err(|()| sqlite::Error::Io("too many connections opened".to_string()))
+                })?;
+            self.connection_names.insert(rep, database);
+            Ok(Resource::new_own(rep))
         }
-        self.connections_store
-            .get_connection(&database)
-            .await
-            .and_then(|conn| conn.ok_or(sqlite::Error::NoSuchDatabase))
-            .and_then(|conn| {
-                self.connections
-                    .push(conn)
-                    .map_err(|()| sqlite::Error::Io("too many connections opened".to_string()))
-            })
-            .map(Resource::new_own)
+        .await
+        .inspect_err(spin_telemetry::record_error_type)
     }
 
+    #[instrument(name = "spin_sqlite.execute", skip(self, connection, parameters), err(level = Level::INFO), fields(otel.kind = "client", db.system = "sqlite", otel.name = query, db.name = Empty, db.response.returned_rows = Empty, error.type = Empty))]
     async fn execute(
         &mut self,
         connection: Resource<sqlite::Connection>,
         query: String,
         parameters: Vec<sqlite::Value>,
     ) -> Result<sqlite::QueryResult, sqlite::Error> {
-        let conn = match self.get_connection(connection) {
-            Ok(c) => c,
-            Err(err) => return Err(err),
-        };
-        conn.query(&query, parameters).await
+        record_call("execute");
+        let span = tracing::Span::current();
+        if let Some(name) = self.connection_names.get(&connection.rep()) {
+            span.record("db.name", name.as_str());
+        }
+        let conn = self
+            .get_connection(connection)
+            .inspect_err(spin_telemetry::record_error_type)?;
+        conn.query(&query, parameters)
+            .await
+            .inspect(|result| {
+                span.record("db.response.returned_rows", result.rows.len());
+            })
+            .inspect_err(spin_telemetry::record_error_type)
     }
 
     fn drop(&mut self, connection: Resource<sqlite::Connection>) -> anyhow::Result<()> {
+        self.connection_names.remove(&connection.rep());
         let _ = self.connections.remove(connection.rep());
         Ok(())
     }
@@ -149,8 +175,13 @@ impl spin_world::v1::sqlite::Host for SqliteDispatch {
         Ok(error)
     }
 }
+
 use spin_world::v1::sqlite as v1;
 
+fn record_call(operation: &'static str) {
+    spin_telemetry::metrics::monotonic_counter!(spin.sqlite_call_count = 1, operation = operation);
+}
+
 fn to_legacy_error(error: sqlite::Error) -> v1::Error {
     match error {
         sqlite::Error::NoSuchDatabase => v1::Error::NoSuchDatabase,
@@ -11,19 +11,22 @@ use tracing_subscriber::{fmt, prelude::*, registry, EnvFilter, Layer};
 
 pub mod detector;
 mod env;
+mod error_type;
 pub mod logs;
 pub mod metrics;
 mod propagation;
 mod traces;
 
+pub use propagation::current_trace_id;
 pub use propagation::extract_trace_context;
 pub use propagation::inject_trace_context;
+pub use traces::{record_error_type, ErrorType};
 
 /// Initializes telemetry for Spin using the [tracing] library.
 ///
 /// Under the hood this involves initializing a [tracing::Subscriber] with multiple [Layer]s. One
 /// [Layer] emits [tracing] events to stderr, another sends spans to an OTel collector, and another
-/// sends metrics to an OTel collector.
+/// records metrics for Prometheus scraping and (optionally) sends them to an OTel collector.
 ///
 /// Configuration for the OTel layers is pulled from the environment.
 ///
@@ -76,16 +79,14 @@ pub fn init(spin_version: String) -> anyhow::Result<ShutdownGuard> {
         None
     };
 
-    let otel_metrics_layer = if otel_metrics_enabled() {
-        Some(metrics::otel_metrics_layer(spin_version.clone())?)
-    } else {
-        None
-    };
+    // Metrics are always recorded so that they can be scraped by Prometheus, but are only
+    // exported over OTLP if configured.
+    let metrics_layer = metrics::metrics_layer(spin_version.clone(), otel_metrics_enabled())?;
 
     // Build a registry subscriber with the layers we want to use.
     registry()
         .with(otel_tracing_layer)
-        .with(otel_metrics_layer)
+        .with(metrics_layer)
         .with(fmt_layer)
         .init();
 
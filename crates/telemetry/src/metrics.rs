@@ -1,6 +1,6 @@
-use std::time::Duration;
+use std::{sync::OnceLock, time::Duration};
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use opentelemetry_otlp::MetricsExporterBuilder;
 use opentelemetry_sdk::{
     metrics::{
@@ -16,13 +16,21 @@ use tracing_subscriber::{registry::LookupSpan, Layer};
 
 use crate::{detector::SpinResourceDetector, env::OtlpProtocol};
 
-/// Constructs a layer for the tracing subscriber that sends metrics to an OTEL collector.
+// The registry backing the Prometheus endpoint. It is set once metrics are
+// initialized, and aggregates in memory whether or not it is ever scraped.
+static PROMETHEUS_REGISTRY: OnceLock<prometheus::Registry> = OnceLock::new();
+
+/// Constructs a layer for the tracing subscriber that records metrics.
+///
+/// Metrics are always aggregated in memory for the Prometheus endpoint (see [prometheus_text]).
+/// If `otlp` is set they are also sent to an OTEL collector.
 ///
 /// It pulls OTEL configuration from the environment based on the variables defined
 /// [here](https://opentelemetry.io/docs/specs/otel/protocol/exporter/) and
 /// [here](https://opentelemetry.io/docs/specs/otel/configuration/sdk-environment-variables/#general-sdk-configuration).
-pub(crate) fn otel_metrics_layer<S: Subscriber + for<'span> LookupSpan<'span>>(
+pub(crate) fn metrics_layer<S: Subscriber + for<'span> LookupSpan<'span>>(
     spin_version: String,
+    otlp: bool,
 ) -> Result<impl Layer<S>> {
     let resource = Resource::from_detectors(
         Duration::from_secs(5),
@@ -37,6 +45,22 @@ pub(crate) fn otel_metrics_layer<S: Subscriber + for<'span> LookupSpan<'span>>(
         ],
     );
 
+    let registry = PROMETHEUS_REGISTRY.get_or_init(prometheus::Registry::new);
+    let prometheus_exporter = opentelemetry_prometheus::exporter()
+        .with_registry(registry.clone())
+        .build()?;
+
+    let mut meter_provider = SdkMeterProvider::builder()
+        .with_reader(prometheus_exporter)
+        .with_resource(resource);
+    if otlp {
+        meter_provider = meter_provider.with_reader(otlp_reader()?);
+    }
+
+    Ok(MetricsLayer::new(meter_provider.build()))
+}
+
+fn otlp_reader() -> Result<PeriodicReader> {
     // This will configure the exporter based on the OTEL_EXPORTER_* environment variables. We
     // currently default to using the HTTP exporter but in the future we could select off of the
     // combination of OTEL_EXPORTER_OTLP_PROTOCOL and OTEL_EXPORTER_OTLP_TRACES_PROTOCOL to
@@ -51,15 +75,28 @@ pub(crate) fn otel_metrics_layer<S: Subscriber + for<'span> LookupSpan<'span>>(
         Box::new(DefaultAggregationSelector::new()),
     )?;
 
-    let reader = PeriodicReader::builder(exporter, runtime::Tokio).build();
-    let meter_provider = SdkMeterProvider::builder()
-        .with_reader(reader)
-        .with_resource(resource)
-        .build();
+    Ok(PeriodicReader::builder(exporter, runtime::Tokio).build())
+}
 
-    Ok(MetricsLayer::new(meter_provider))
+/// Returns the current value of all metrics in the Prometheus text exposition format.
+///
+/// Returns an error if metrics have not been initialized.
+pub fn prometheus_text() -> Result<String> {
+    let registry = PROMETHEUS_REGISTRY
+        .get()
+        .context("metrics have not been initialized")?;
+    let mut buf = vec![];
+    prometheus::Encoder::encode(
+        &prometheus::TextEncoder::new(),
+        &registry.gather(),
+        &mut buf,
+    )?;
+    Ok(String::from_utf8(buf)?)
 }
 
+/// The content type of [prometheus_text] responses.
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
 #[macro_export]
 /// Records an increment to the named counter with the given attributes.
 ///
@@ -108,3 +145,28 @@ macro_rules! monotonic_counter {
 pub use counter;
 pub use histogram;
 pub use monotonic_counter;
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn prometheus_text_reports_recorded_metrics() {
+        let layer = metrics_layer::<tracing_subscriber::Registry>("test".to_owned(), false);
+        let subscriber = tracing_subscriber::registry().with(layer.unwrap());
+        tracing::subscriber::with_default(subscriber, || {
+            monotonic_counter!(spin.test_call_count = 1, operation = "open");
+            monotonic_counter!(spin.test_call_count = 1, operation = "open");
+        });
+
+        let text = prometheus_text().unwrap();
+        let line = text
+            .lines()
+            .find(|line| line.starts_with("spin_test_call_count_total{"))
+            .unwrap_or_else(|| panic!("counter missing from exposition:\n{text}"));
+        assert!(line.contains(r#"operation="open""#), "{line}");
+        assert!(line.ends_with(" 2"), "{line}");
+    }
+}
@@ -1,6 +1,7 @@
 use opentelemetry::{
     global,
     propagation::{Extractor, Injector},
+    trace::TraceContextExt,
 };
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
@@ -22,6 +23,15 @@ pub fn extract_trace_context<'a>(req: impl Into<HeaderExtractor<'a>>) {
     tracing::Span::current().set_parent(parent_context);
 }
 
+/// Returns the trace ID of the current span, if it belongs to a valid trace.
+pub fn current_trace_id() -> Option<String> {
+    let context = tracing::Span::current().context();
+    let span_context = context.span().span_context().clone();
+    span_context
+        .is_valid()
+        .then(|| span_context.trace_id().to_string())
+}
+
 pub enum HeaderInjector<'a> {
     Http0(&'a mut http0::HeaderMap),
     Http1(&'a mut http1::HeaderMap),
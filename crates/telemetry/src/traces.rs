@@ -60,3 +60,31 @@ pub(crate) fn otel_tracing_layer<S: Subscriber + for<'span> LookupSpan<'span>>(
         .with_threads(false)
         .with_filter(env_filter))
 }
+
+/// An error returned from a host call that can be grouped by kind in telemetry.
+pub trait ErrorType {
+    /// Returns the name of the kind of error, such as `QueryFailed`. This is recorded as the
+    /// `error.type` span attribute, so must not include details such as error messages.
+    fn error_type(&self) -> &'static str;
+}
+
+/// Records the kind of an error returned from a host call as the `error.type` attribute of the
+/// current span, so that failures can be grouped by kind. The span must declare an empty
+/// `error.type` field.
+///
+/// ```no_run
+/// # use spin_telemetry::ErrorType;
+/// # #[derive(Debug)]
+/// # enum Error { Timeout }
+/// # impl ErrorType for Error {
+/// #     fn error_type(&self) -> &'static str { "Timeout" }
+/// # }
+/// # fn call() -> Result<(), Error> { Ok(()) }
+/// #[tracing::instrument(fields(error.type = tracing::field::Empty))]
+/// fn host_call() -> Result<(), Error> {
+///     call().inspect_err(spin_telemetry::record_error_type)
+/// }
+/// ```
+pub fn record_error_type<E: ErrorType>(err: &E) {
+    tracing::Span::current().record("error.type", err.error_type());
+}
@@ -15,3 +15,68 @@ impl StringConstraints {
         Ok(text)
     }
 }
+
+#[derive(Clone, Debug)]
+pub(crate) struct IntegerConstraints {
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+impl IntegerConstraints {
+    pub fn validate(&self, text: String) -> anyhow::Result<String> {
+        let Ok(value) = text.trim().parse::<i64>() else {
+            anyhow::bail!("Input '{}' is not a whole number", text);
+        };
+        if let Some(min) = self.min {
+            if value < min {
+                anyhow::bail!("Input '{}' is less than the minimum of {}", text, min);
+            }
+        }
+        if let Some(max) = self.max {
+            if value > max {
+                anyhow::bail!("Input '{}' is greater than the maximum of {}", text, max);
+            }
+        }
+        Ok(value.to_string())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ChoiceConstraints {
+    pub allowed_values: Vec<String>,
+}
+
+impl ChoiceConstraints {
+    pub fn validate(&self, text: String) -> anyhow::Result<String> {
+        if !self.allowed_values.contains(&text) {
+            anyhow::bail!(
+                "Input '{}' is not one of the allowed values ({})",
+                text,
+                self.allowed_values.join(", ")
+            );
+        }
+        Ok(text)
+    }
+}
+
+pub(crate) fn validate_bool(text: String) -> anyhow::Result<String> {
+    match text.trim().to_lowercase().as_str() {
+        "true" | "yes" | "y" => Ok("true".to_owned()),
+        "false" | "no" | "n" => Ok("false".to_owned()),
+        _ => anyhow::bail!("Input '{}' is not a yes/no value", text),
+    }
+}
+
+pub(crate) fn split_list(text: &str) -> impl Iterator<Item = &str> {
+    text.split(',').map(|s| s.trim()).filter(|s| !s.is_empty())
+}
+
+pub(crate) fn validate_list(
+    item_constraints: &StringConstraints,
+    text: String,
+) -> anyhow::Result<String> {
+    let items = split_list(&text)
+        .map(|item| item_constraints.validate(item.to_owned()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(items.join(","))
+}
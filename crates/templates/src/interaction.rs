@@ -1,4 +1,4 @@
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, path::Path, sync::Mutex};
 
 use crate::{
     cancellable::Cancellable,
@@ -8,10 +8,11 @@ use crate::{
 
 use anyhow::anyhow;
 // use console::style;
-use dialoguer::{Confirm, Input};
+use dialoguer::{Confirm, Input, Select};
 
 pub(crate) trait InteractionStrategy {
     fn allow_generate_into(&self, target_dir: &Path) -> Cancellable<(), anyhow::Error>;
+    fn allow_run_commands(&self, run: &Run, commands: &[String]) -> anyhow::Result<bool>;
     fn populate_parameters(
         &self,
         run: &Run,
@@ -38,6 +39,22 @@ pub(crate) trait InteractionStrategy {
 pub(crate) struct Interactive;
 pub(crate) struct Silent;
 
+/// Runs a template without user interaction, as [`Silent`] does, but never
+/// runs commands. The commands the template would have run are recorded so
+/// that verification can report them.
+#[derive(Default)]
+pub(crate) struct Verification {
+    skipped_commands: Mutex<Vec<String>>,
+}
+
+impl Verification {
+    pub(crate) fn into_skipped_commands(self) -> Vec<String> {
+        self.skipped_commands
+            .into_inner()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+}
+
 impl InteractionStrategy for Interactive {
     fn allow_generate_into(&self, target_dir: &Path) -> Cancellable<(), anyhow::Error> {
         if !is_directory_empty(target_dir) {
@@ -55,15 +72,26 @@ impl InteractionStrategy for Interactive {
         }
     }
 
+    fn allow_run_commands(&self, run: &Run, commands: &[String]) -> anyhow::Result<bool> {
+        if run.options.allow_run_commands {
+            return Ok(true);
+        }
+        println!("This template runs the following commands after generating files:");
+        for command in commands {
+            println!("  {command}");
+        }
+        Ok(crate::interaction::confirm("Run these commands?")?)
+    }
+
     fn populate_parameter(
         &self,
         run: &Run,
         parameter: &TemplateParameter,
     ) -> Cancellable<String, anyhow::Error> {
         match run.options.values.get(parameter.id()) {
-            Some(s) => Cancellable::Ok(s.clone()),
+            Some(s) => validated(parameter, s),
             None => match (run.options.accept_defaults, parameter.default_value()) {
-                (true, Some(v)) => Cancellable::Ok(v.to_string()),
+                (true, Some(v)) => validated(parameter, v),
                 _ => match crate::interaction::prompt_parameter(parameter) {
                     Some(v) => Cancellable::Ok(v),
                     None => Cancellable::Cancelled,
@@ -86,21 +114,56 @@ impl InteractionStrategy for Silent {
         }
     }
 
+    fn allow_run_commands(&self, run: &Run, _commands: &[String]) -> anyhow::Result<bool> {
+        // Templates may only run commands silently if the user explicitly
+        // allowed it in the run options.
+        Ok(run.options.allow_run_commands)
+    }
+
     fn populate_parameter(
         &self,
         run: &Run,
         parameter: &TemplateParameter,
     ) -> Cancellable<String, anyhow::Error> {
         match run.options.values.get(parameter.id()) {
-            Some(s) => Cancellable::Ok(s.clone()),
+            Some(s) => validated(parameter, s),
             None => match (run.options.accept_defaults, parameter.default_value()) {
-                (true, Some(v)) => Cancellable::Ok(v.to_string()),
+                (true, Some(v)) => validated(parameter, v),
                 _ => Cancellable::Err(anyhow!("Parameter '{}' not provided", parameter.id())),
             },
         }
     }
 }
 
+impl InteractionStrategy for &Verification {
+    fn allow_generate_into(&self, target_dir: &Path) -> Cancellable<(), anyhow::Error> {
+        Silent.allow_generate_into(target_dir)
+    }
+
+    fn allow_run_commands(&self, _run: &Run, commands: &[String]) -> anyhow::Result<bool> {
+        self.skipped_commands
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(commands);
+        Ok(false)
+    }
+
+    fn populate_parameter(
+        &self,
+        run: &Run,
+        parameter: &TemplateParameter,
+    ) -> Cancellable<String, anyhow::Error> {
+        Silent.populate_parameter(run, parameter)
+    }
+}
+
+fn validated(parameter: &TemplateParameter, value: &str) -> Cancellable<String, anyhow::Error> {
+    match parameter.validate_value(value) {
+        Ok(v) => Cancellable::Ok(v),
+        Err(e) => Cancellable::Err(e.context(format!("Invalid value for '{}'", parameter.id()))),
+    }
+}
+
 pub(crate) fn confirm(text: &str) -> std::io::Result<bool> {
     Confirm::new().with_prompt(text).interact()
 }
@@ -111,7 +174,16 @@ pub(crate) fn prompt_parameter(parameter: &TemplateParameter) -> Option<String>
 
     loop {
         let input = match parameter.data_type() {
-            TemplateParameterDataType::String(_) => ask_free_text(prompt, default_value),
+            TemplateParameterDataType::String(_) | TemplateParameterDataType::Integer(_) => {
+                ask_free_text(prompt, default_value)
+            }
+            TemplateParameterDataType::List(_) => {
+                ask_free_text(&format!("{prompt} (comma-separated)"), default_value)
+            }
+            TemplateParameterDataType::Bool => ask_yes_no(prompt, default_value),
+            TemplateParameterDataType::Choice(constraints) => {
+                ask_choice(prompt, &constraints.allowed_values, default_value)
+            }
         };
 
         match input {
@@ -138,6 +210,36 @@ fn ask_free_text(prompt: &str, default_value: &Option<String>) -> anyhow::Result
     Ok(result)
 }
 
+fn ask_yes_no(prompt: &str, default_value: &Option<String>) -> anyhow::Result<String> {
+    let mut confirm = Confirm::new();
+    confirm.with_prompt(prompt);
+    if let Some(default) = default_value
+        .as_ref()
+        .and_then(|s| crate::constraints::validate_bool(s.to_owned()).ok())
+    {
+        confirm.default(default == "true");
+    }
+    let result = confirm.interact()?;
+    Ok(result.to_string())
+}
+
+fn ask_choice(
+    prompt: &str,
+    allowed_values: &[String],
+    default_value: &Option<String>,
+) -> anyhow::Result<String> {
+    let mut select = Select::new();
+    select.with_prompt(prompt).items(allowed_values);
+    if let Some(index) = default_value
+        .as_ref()
+        .and_then(|d| allowed_values.iter().position(|v| v == d))
+    {
+        select.default(index);
+    }
+    let index = select.interact()?;
+    Ok(allowed_values[index].clone())
+}
+
 fn is_directory_empty(path: &Path) -> bool {
     if !path.exists() {
         return true;
@@ -18,12 +18,15 @@ mod source;
 mod store;
 mod template;
 mod toml;
+mod verify;
 mod writer;
 
 pub use manager::*;
+pub use reader::ParameterValueText;
 pub use run::{Run, RunOptions};
 pub use source::TemplateSource;
 pub use template::{Template, TemplateVariantInfo};
+pub use verify::{verify_source, VerificationResult, VerifyOptions};
 
 #[cfg(test)]
 mod test_built_ins;
@@ -6,6 +6,7 @@ use crate::{
     source::TemplateSource,
     store::{TemplateLayout, TemplateStore},
     template::Template,
+    verify::{verify_layouts, VerificationResult, VerifyOptions},
 };
 
 /// Provides access to and operations on the set of installed
@@ -268,6 +269,22 @@ impl TemplateManager {
             .map(|l| Template::load_from(&l))
             .transpose()
     }
+
+    /// Verifies installed templates by generating from each of their sample
+    /// parameter sets into a temporary directory and checking the result.
+    /// If `ids` is empty, all installed templates are verified.
+    pub async fn verify(
+        &self,
+        ids: &[String],
+        options: &VerifyOptions,
+        reporter: &impl ProgressReporter,
+    ) -> anyhow::Result<Vec<VerificationResult>> {
+        if let Some(id) = ids.iter().find(|id| self.store.get_layout(id).is_none()) {
+            anyhow::bail!("Template '{id}' is not installed");
+        }
+        let layouts = self.store.list_layouts().await?;
+        Ok(verify_layouts(layouts, ids, options, reporter).await)
+    }
 }
 
 async fn copy_template_over_existing(
@@ -523,6 +540,60 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn can_install_from_archive() {
+        let temp_dir = tempdir().unwrap();
+        let store = TemplateStore::new(temp_dir.path());
+        let manager = TemplateManager { store };
+
+        let archive_dir = tempdir().unwrap();
+        let archive_path = spin_oci::utils::archive(&test_data_root(), archive_dir.path())
+            .await
+            .unwrap();
+        let digest = spin_common::sha256::hex_digest_from_file(&archive_path).unwrap();
+        let archive_url = url::Url::from_file_path(&archive_path).unwrap();
+        let source = TemplateSource::try_from_archive(&archive_url, digest).unwrap();
+
+        let install_result = manager
+            .install(&source, &InstallOptions::default(), &DiscardingReporter)
+            .await
+            .unwrap();
+        assert!(install_result
+            .installed
+            .iter()
+            .any(|t| t.id() == "typed-parameters"));
+
+        let template = manager.get("typed-parameters").unwrap().unwrap();
+        assert_eq!(archive_url.as_str(), template.installed_from_or_empty());
+        assert!(matches!(
+            template.source_artifact(),
+            Some(TemplateSource::Archive(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cannot_install_from_archive_with_wrong_digest() {
+        let temp_dir = tempdir().unwrap();
+        let store = TemplateStore::new(temp_dir.path());
+        let manager = TemplateManager { store };
+
+        let archive_dir = tempdir().unwrap();
+        let archive_path = spin_oci::utils::archive(&test_data_root(), archive_dir.path())
+            .await
+            .unwrap();
+        let archive_url = url::Url::from_file_path(&archive_path).unwrap();
+        let wrong_digest = spin_common::sha256::hex_digest_from_bytes("not the archive");
+        let source = TemplateSource::try_from_archive(&archive_url, wrong_digest).unwrap();
+
+        let err = manager
+            .install(&source, &InstallOptions::default(), &DiscardingReporter)
+            .await
+            .expect_err("Expected install to fail on digest mismatch");
+
+        assert_contains(&format!("{err:#}"), "did not match");
+        assert_eq!(0, manager.list().await.unwrap().templates.len());
+    }
+
     #[tokio::test]
     async fn can_list_all_templates_with_empty_tags() {
         let temp_dir = tempdir().unwrap();
@@ -782,6 +853,7 @@ mod tests {
             values,
             accept_defaults: false,
             no_vcs: false,
+            allow_run_commands: false,
         };
 
         template.run(options).silent().await.unwrap();
@@ -816,6 +888,7 @@ mod tests {
             values,
             accept_defaults: true,
             no_vcs: false,
+            allow_run_commands: false,
         };
 
         template.run(options).silent().await.unwrap();
@@ -882,6 +955,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: false,
+                allow_run_commands: false,
             };
 
             template.run(options).silent().await.unwrap();
@@ -910,6 +984,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: false,
+                allow_run_commands: false,
             };
 
             template.run(options).silent().await.unwrap();
@@ -935,6 +1010,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: false,
+                allow_run_commands: false,
             };
 
             template.run(options).silent().await.unwrap();
@@ -992,6 +1068,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: false,
+                allow_run_commands: false,
             };
 
             template.run(options).silent().await.unwrap();
@@ -1020,6 +1097,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: false,
+                allow_run_commands: false,
             };
 
             template.run(options).silent().await.unwrap();
@@ -1072,6 +1150,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: false,
+                allow_run_commands: false,
             };
 
             template.run(options).silent().await.unwrap();
@@ -1100,6 +1179,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: false,
+                allow_run_commands: false,
             };
 
             template.run(options).silent().await.unwrap();
@@ -1125,6 +1205,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: false,
+                allow_run_commands: false,
             };
 
             template.run(options).silent().await.unwrap();
@@ -1166,6 +1247,7 @@ mod tests {
             values,
             accept_defaults: false,
             no_vcs: true,
+            allow_run_commands: false,
         };
 
         template.run(options).silent().await.unwrap();
@@ -1205,6 +1287,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: true,
+                allow_run_commands: false,
             };
 
             template.run(options).silent().await.unwrap();
@@ -1238,6 +1321,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: true,
+                allow_run_commands: false,
             };
             template.run(options).silent().await.unwrap();
         }
@@ -1288,6 +1372,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: false,
+                allow_run_commands: false,
             };
 
             template.run(options).silent().await.unwrap();
@@ -1316,6 +1401,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: false,
+                allow_run_commands: false,
             };
 
             template.run(options).silent().await.unwrap();
@@ -1377,6 +1463,7 @@ mod tests {
                 values,
                 accept_defaults: false,
                 no_vcs: false,
+                allow_run_commands: false,
             };
 
             template
@@ -1421,6 +1508,343 @@ mod tests {
         assert!(!http_empty.supports_variant(&add_component));
     }
 
+    #[tokio::test]
+    async fn can_verify_installed_templates() {
+        let temp_dir = tempdir().unwrap();
+        let store = TemplateStore::new(temp_dir.path());
+        let manager = TemplateManager { store };
+        let source = TemplateSource::File(project_root());
+
+        manager
+            .install(&source, &InstallOptions::default(), &DiscardingReporter)
+            .await
+            .unwrap();
+
+        let ids = ["http-empty".to_owned(), "redirect".to_owned()];
+        let results = manager
+            .verify(&ids, &VerifyOptions::default(), &DiscardingReporter)
+            .await
+            .unwrap();
+
+        assert_eq!(3, results.len()); // redirect supports both variants
+        for result in &results {
+            assert!(
+                result.passed(),
+                "{} ({:?}) failed: {:?}",
+                result.template_id,
+                result.variant,
+                result.error
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn cannot_verify_template_that_is_not_installed() {
+        let temp_dir = tempdir().unwrap();
+        let store = TemplateStore::new(temp_dir.path());
+        let manager = TemplateManager { store };
+
+        let err = manager
+            .verify(
+                &["no-such-template".to_owned()],
+                &VerifyOptions::default(),
+                &DiscardingReporter,
+            )
+            .await
+            .expect_err("should not verify a template that isn't installed");
+        assert_contains(&err.to_string(), "no-such-template");
+    }
+
+    #[tokio::test]
+    async fn verify_reports_each_sample() {
+        let source = TemplateSource::File(test_data_root());
+
+        let results = crate::verify_source(&source, &VerifyOptions::default(), &DiscardingReporter)
+            .await
+            .unwrap();
+
+        let samples: Vec<_> = results
+            .iter()
+            .filter(|r| r.template_id == "verify-samples")
+            .collect();
+        assert_eq!(2, samples.len());
+
+        let nested = samples.iter().find(|r| r.sample == "nested-route").unwrap();
+        assert!(nested.passed(), "{:?}", nested.error);
+        // Verification lists the template's commands rather than running them
+        assert_eq!(vec!["echo generated > hook.txt"], nested.skipped_commands);
+
+        let broken = samples
+            .iter()
+            .find(|r| r.sample == "broken-manifest")
+            .unwrap();
+        let err = broken.error.as_ref().expect("broken sample should fail");
+        assert_contains(&format!("{err:#}"), "not a valid application manifest");
+
+        // Templates which fail to load are reported rather than skipped
+        assert!(results
+            .iter()
+            .any(|r| r.template_id == "testing-custom-filter" && !r.passed()));
+    }
+
+    #[tokio::test]
+    async fn can_run_template_with_typed_parameters() {
+        let temp_dir = tempdir().unwrap();
+        let store = TemplateStore::new(temp_dir.path());
+        let manager = TemplateManager { store };
+        let source = TemplateSource::File(test_data_root());
+
+        manager
+            .install(&source, &InstallOptions::default(), &DiscardingReporter)
+            .await
+            .unwrap();
+
+        let template = manager.get("typed-parameters").unwrap().unwrap();
+
+        let dest_temp_dir = tempdir().unwrap();
+        let output_dir = dest_temp_dir.path().join("myproj");
+        let values = [
+            ("include-db".to_owned(), "yes".to_owned()),
+            ("framework".to_owned(), "axum".to_owned()),
+            ("port".to_owned(), "3000".to_owned()),
+            ("regions".to_owned(), "eu-west, us-east".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+        let options = RunOptions {
+            variant: crate::template::TemplateVariantInfo::NewApplication,
+            output_path: output_dir.clone(),
+            name: "my project".to_owned(),
+            values,
+            accept_defaults: false,
+            no_vcs: false,
+            allow_run_commands: false,
+        };
+
+        template.run(options).silent().await.unwrap();
+
+        let settings = tokio::fs::read_to_string(output_dir.join("settings.txt"))
+            .await
+            .unwrap();
+        assert_contains(&settings, "framework: axum");
+        assert_contains(&settings, "port: 3001");
+        assert_contains(&settings, "database: enabled");
+        assert_contains(&settings, "region: eu-west\nregion: us-east");
+    }
+
+    #[tokio::test]
+    async fn can_run_template_with_typed_parameter_defaults() {
+        let temp_dir = tempdir().unwrap();
+        let store = TemplateStore::new(temp_dir.path());
+        let manager = TemplateManager { store };
+        let source = TemplateSource::File(test_data_root());
+
+        manager
+            .install(&source, &InstallOptions::default(), &DiscardingReporter)
+            .await
+            .unwrap();
+
+        let template = manager.get("typed-parameters").unwrap().unwrap();
+
+        let dest_temp_dir = tempdir().unwrap();
+        let output_dir = dest_temp_dir.path().join("myproj");
+        let options = RunOptions {
+            variant: crate::template::TemplateVariantInfo::NewApplication,
+            output_path: output_dir.clone(),
+            name: "my project".to_owned(),
+            values: HashMap::new(),
+            accept_defaults: true,
+            no_vcs: false,
+            allow_run_commands: false,
+        };
+
+        template.run(options).silent().await.unwrap();
+
+        let settings = tokio::fs::read_to_string(output_dir.join("settings.txt"))
+            .await
+            .unwrap();
+        assert_contains(&settings, "framework: none");
+        assert_contains(&settings, "port: 8081");
+        assert_contains(&settings, "database: disabled");
+        assert_contains(&settings, "region: eu-west");
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_typed_parameter_values() {
+        let temp_dir = tempdir().unwrap();
+        let store = TemplateStore::new(temp_dir.path());
+        let manager = TemplateManager { store };
+        let source = TemplateSource::File(test_data_root());
+
+        manager
+            .install(&source, &InstallOptions::default(), &DiscardingReporter)
+            .await
+            .unwrap();
+
+        let template = manager.get("typed-parameters").unwrap().unwrap();
+
+        let dest_temp_dir = tempdir().unwrap();
+        let output_dir = dest_temp_dir.path().join("myproj");
+        let values = [
+            ("include-db".to_owned(), "perhaps".to_owned()),
+            ("framework".to_owned(), "rails".to_owned()),
+            ("port".to_owned(), "80".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+        let options = RunOptions {
+            variant: crate::template::TemplateVariantInfo::NewApplication,
+            output_path: output_dir.clone(),
+            name: "my project".to_owned(),
+            values,
+            accept_defaults: true,
+            no_vcs: false,
+            allow_run_commands: false,
+        };
+
+        let err = template
+            .run(options)
+            .silent()
+            .await
+            .expect_err("Expected invalid values to be rejected");
+
+        let err_str = err.to_string();
+
+        assert_contains(
+            &err_str,
+            "include-db: Input 'perhaps' is not a yes/no value",
+        );
+        assert_contains(
+            &err_str,
+            "framework: Input 'rails' is not one of the allowed values",
+        );
+        assert_contains(
+            &err_str,
+            "port: Input '80' is less than the minimum of 1024",
+        );
+    }
+
+    #[tokio::test]
+    async fn can_run_template_with_conditions_and_computed_parameters() {
+        let temp_dir = tempdir().unwrap();
+        let store = TemplateStore::new(temp_dir.path());
+        let manager = TemplateManager { store };
+        let source = TemplateSource::File(test_data_root());
+
+        manager
+            .install(&source, &InstallOptions::default(), &DiscardingReporter)
+            .await
+            .unwrap();
+
+        let template = manager.get("conditional-outputs").unwrap().unwrap();
+
+        let dest_temp_dir = tempdir().unwrap();
+        let output_dir = dest_temp_dir.path().join("myproj");
+        let values = [
+            ("include-db".to_owned(), "true".to_owned()),
+            ("framework".to_owned(), "axum".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+        let options = RunOptions {
+            variant: crate::template::TemplateVariantInfo::NewApplication,
+            output_path: output_dir.clone(),
+            name: "my project".to_owned(),
+            values,
+            accept_defaults: false,
+            no_vcs: false,
+            allow_run_commands: true,
+        };
+
+        template.run(options).silent().await.unwrap();
+
+        let db_sql = tokio::fs::read_to_string(output_dir.join("db.sql"))
+            .await
+            .unwrap();
+        assert_contains(&db_sql, "-- my_project_db.sqlite");
+        assert_contains(&db_sql, "CREATE DATABASE my_project_db;");
+
+        assert!(!output_dir.join("notes.txt").exists());
+        let notes = tokio::fs::read_to_string(output_dir.join("axum-notes.txt"))
+            .await
+            .unwrap();
+        assert_contains(&notes, "Notes for my project");
+        assert!(!notes.contains("No framework"), "{notes}");
+
+        let hook = tokio::fs::read_to_string(output_dir.join("hook.txt"))
+            .await
+            .unwrap();
+        assert_contains(&hook, "axum");
+    }
+
+    #[tokio::test]
+    async fn does_not_run_commands_unless_allowed() {
+        let temp_dir = tempdir().unwrap();
+        let store = TemplateStore::new(temp_dir.path());
+        let manager = TemplateManager { store };
+        let source = TemplateSource::File(test_data_root());
+
+        manager
+            .install(&source, &InstallOptions::default(), &DiscardingReporter)
+            .await
+            .unwrap();
+
+        let template = manager.get("conditional-outputs").unwrap().unwrap();
+
+        let dest_temp_dir = tempdir().unwrap();
+        let output_dir = dest_temp_dir.path().join("myproj");
+        let values = [("framework".to_owned(), "axum".to_owned())]
+            .into_iter()
+            .collect();
+        let options = RunOptions {
+            variant: crate::template::TemplateVariantInfo::NewApplication,
+            output_path: output_dir.clone(),
+            name: "my project".to_owned(),
+            values,
+            accept_defaults: true,
+            no_vcs: false,
+            allow_run_commands: false,
+        };
+
+        template.run(options).silent().await.unwrap();
+
+        assert!(output_dir.join("axum-notes.txt").exists());
+        assert!(!output_dir.join("hook.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn conditions_can_skip_files_and_outputs() {
+        let temp_dir = tempdir().unwrap();
+        let store = TemplateStore::new(temp_dir.path());
+        let manager = TemplateManager { store };
+        let source = TemplateSource::File(test_data_root());
+
+        manager
+            .install(&source, &InstallOptions::default(), &DiscardingReporter)
+            .await
+            .unwrap();
+
+        let template = manager.get("conditional-outputs").unwrap().unwrap();
+
+        let dest_temp_dir = tempdir().unwrap();
+        let output_dir = dest_temp_dir.path().join("myproj");
+        let options = RunOptions {
+            variant: crate::template::TemplateVariantInfo::NewApplication,
+            output_path: output_dir.clone(),
+            name: "my project".to_owned(),
+            values: HashMap::new(),
+            accept_defaults: true,
+            no_vcs: false,
+            allow_run_commands: false,
+        };
+
+        template.run(options).silent().await.unwrap();
+
+        assert!(!output_dir.join("db.sql").exists());
+        assert!(output_dir.join("none-notes.txt").exists());
+        assert!(!output_dir.join("hook.txt").exists());
+    }
+
     #[tokio::test]
     async fn fails_on_unknown_filter() {
         let temp_dir = tempdir().unwrap();
@@ -1447,6 +1871,7 @@ mod tests {
             values,
             accept_defaults: false,
             no_vcs: false,
+            allow_run_commands: false,
         };
 
         let err = template
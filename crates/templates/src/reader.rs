@@ -22,8 +22,10 @@ pub(crate) struct RawTemplateManifestV1 {
     pub new_application: Option<RawTemplateVariant>,
     pub add_component: Option<RawTemplateVariant>,
     pub parameters: Option<IndexMap<String, RawParameter>>,
+    pub computed_parameters: Option<IndexMap<String, String>>,
     pub custom_filters: Option<serde::de::IgnoredAny>, // kept for error messaging
     pub outputs: Option<IndexMap<String, RawExtraOutput>>,
+    pub samples: Option<IndexMap<String, IndexMap<String, RawSampleValue>>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -45,7 +47,7 @@ pub(crate) struct RawConditional {
     pub skip_snippets: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(
     deny_unknown_fields,
     rename_all = "snake_case",
@@ -53,6 +55,7 @@ pub(crate) struct RawConditional {
 )]
 pub(crate) enum RawCondition {
     ManifestEntryExists(String),
+    Expression(String),
 }
 
 impl TryFrom<toml::Value> for RawCondition {
@@ -65,15 +68,23 @@ impl TryFrom<toml::Value> for RawCondition {
         if table.keys().len() != 1 {
             anyhow::bail!("Invalid condition: should be a single-entry table");
         }
-        let Some(value) = table.get("manifest_entry_exists") else {
-            anyhow::bail!("Invalid condition: unknown condition type");
-        };
-        let Some(path) = value.as_str() else {
-            anyhow::bail!(
-                "Invalid condition: 'manifest_entry_exists' should be a dotted-path string"
-            );
-        };
-        Ok(Self::ManifestEntryExists(path.to_owned()))
+        if let Some(value) = table.get("manifest_entry_exists") {
+            let Some(path) = value.as_str() else {
+                anyhow::bail!(
+                    "Invalid condition: 'manifest_entry_exists' should be a dotted-path string"
+                );
+            };
+            return Ok(Self::ManifestEntryExists(path.to_owned()));
+        }
+        if let Some(value) = table.get("expression") {
+            let Some(expression) = value.as_str() else {
+                anyhow::bail!(
+                    "Invalid condition: 'expression' should be a Liquid expression string"
+                );
+            };
+            return Ok(Self::Expression(expression.to_owned()));
+        }
+        anyhow::bail!("Invalid condition: unknown condition type")
     }
 }
 
@@ -83,15 +94,98 @@ pub(crate) struct RawParameter {
     #[serde(rename = "type")]
     pub data_type: String,
     pub prompt: String,
-    #[serde(rename = "default")]
+    #[serde(
+        rename = "default",
+        default,
+        deserialize_with = "deserialize_default_value"
+    )]
     pub default_value: Option<String>,
     pub pattern: Option<String>,
+    pub allowed_values: Option<Vec<String>>,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+// Parameter defaults are held as strings, like values supplied on the command line,
+// but template authors may write them using the natural TOML type (e.g. `default = true`
+// for a `bool` parameter, or `default = ["a", "b"]` for a `list` parameter).
+fn deserialize_default_value<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let Some(value) = Option::<toml::Value>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    let text = value_text(value).map_err(|e| D::Error::custom(format!("default {e}")))?;
+    Ok(Some(text))
+}
+
+/// A parameter value in a verification sample. As with defaults, this may be
+/// written using the natural TOML type for the parameter.
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "toml::Value")]
+pub(crate) struct RawSampleValue(pub String);
+
+impl TryFrom<toml::Value> for RawSampleValue {
+    type Error = anyhow::Error;
+
+    fn try_from(value: toml::Value) -> Result<Self, Self::Error> {
+        value_text(value)
+            .map(Self)
+            .map_err(|e| anyhow::anyhow!("sample value {e}"))
+    }
+}
+
+/// A parameter value read from a values file. As with defaults, this may be
+/// written using the natural TOML type for the parameter, and is converted to
+/// the text the user would pass on the command line.
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "toml::Value")]
+pub struct ParameterValueText(String);
+
+impl ParameterValueText {
+    /// The value as it would be passed on the command line.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl TryFrom<toml::Value> for ParameterValueText {
+    type Error = anyhow::Error;
+
+    fn try_from(value: toml::Value) -> Result<Self, Self::Error> {
+        value_text(value)
+            .map(Self)
+            .map_err(|e| anyhow::anyhow!("value {e}"))
+    }
+}
+
+fn value_text(value: toml::Value) -> Result<String, &'static str> {
+    let text = match value {
+        toml::Value::String(s) => s,
+        toml::Value::Boolean(b) => b.to_string(),
+        toml::Value::Integer(i) => i.to_string(),
+        toml::Value::Array(items) => items
+            .into_iter()
+            .map(|item| match item {
+                toml::Value::String(s) => Ok(s),
+                _ => Err("lists must contain only strings"),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .join(","),
+        _ => return Err("must be a string, boolean, integer or list of strings"),
+    };
+    Ok(text)
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "snake_case", tag = "action")]
 pub(crate) enum RawExtraOutput {
     CreateDir(RawCreateDir),
+    Rename(RawRename),
+    RunCommand(RawRunCommand),
 }
 
 #[derive(Debug, Deserialize)]
@@ -99,6 +193,24 @@ pub(crate) enum RawExtraOutput {
 pub(crate) struct RawCreateDir {
     pub path: String,
     pub at: Option<CreateLocation>,
+    pub condition: Option<RawCondition>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub(crate) struct RawRename {
+    pub from: String,
+    pub to: String,
+    pub at: Option<CreateLocation>,
+    pub condition: Option<RawCondition>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub(crate) struct RawRunCommand {
+    pub command: String,
+    pub at: Option<CreateLocation>,
+    pub condition: Option<RawCondition>,
 }
 
 #[derive(Debug, Deserialize, Clone, Copy, Default)]
@@ -118,6 +230,8 @@ pub(crate) fn parse_manifest_toml(text: impl AsRef<str>) -> anyhow::Result<RawTe
 pub(crate) enum RawInstalledFrom {
     Git { git: String },
     File { dir: String },
+    Oci { oci: String },
+    Archive { archive: String, sha256: String },
 }
 
 pub(crate) fn parse_installed_from(text: impl AsRef<str>) -> Option<RawInstalledFrom> {
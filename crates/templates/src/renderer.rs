@@ -1,6 +1,9 @@
 use anyhow::anyhow;
 use lazy_static::lazy_static;
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+};
 
 use crate::writer::{TemplateOutput, TemplateOutputs};
 
@@ -8,7 +11,7 @@ use crate::writer::{TemplateOutput, TemplateOutputs};
 // it needs to render.
 pub(crate) struct TemplateRenderer {
     pub render_operations: Vec<RenderOperation>,
-    pub parameter_values: HashMap<String, String>,
+    pub parameter_values: HashMap<String, liquid_core::Value>,
 }
 
 pub(crate) enum TemplateContent {
@@ -21,6 +24,12 @@ pub(crate) enum RenderOperation {
     MergeToml(PathBuf, MergeTarget, TemplateContent), // file to merge into, table to merge into, content to merge
     WriteFile(PathBuf, TemplateContent),
     CreateDirectory(PathBuf, std::sync::Arc<liquid::Template>),
+    Rename(
+        PathBuf,
+        std::sync::Arc<liquid::Template>,
+        std::sync::Arc<liquid::Template>,
+    ), // base directory, from, to
+    RunCommand(PathBuf, String), // working directory, already-rendered command
 }
 
 pub(crate) enum MergeTarget {
@@ -45,17 +54,18 @@ impl TemplateRenderer {
     }
 
     fn renderer_globals(&self) -> liquid::Object {
-        let mut object = liquid::Object::new();
+        to_liquid_object(&self.parameter_values)
+    }
+}
 
-        for (k, v) in &self.parameter_values {
-            object.insert(
-                k.to_owned().into(),
-                liquid_core::Value::Scalar(v.to_owned().into()),
-            );
-        }
+pub(crate) fn to_liquid_object(values: &HashMap<String, liquid_core::Value>) -> liquid::Object {
+    let mut object = liquid::Object::new();
 
-        object
+    for (k, v) in values {
+        object.insert(k.to_owned().into(), v.to_owned());
     }
+
+    object
 }
 
 impl RenderOperation {
@@ -78,13 +88,34 @@ impl RenderOperation {
             }
             Self::CreateDirectory(path, template) => {
                 let rendered = template.render(globals)?;
-                let path = path.join(rendered); // TODO: should we validate that `rendered` was relative?`
+                let path = join_relative(&path, &rendered)?;
                 Ok(TemplateOutput::CreateDirectory(path))
             }
+            Self::Rename(base, from, to) => {
+                let from = join_relative(&base, &from.render(globals)?)?;
+                let to = join_relative(&base, &to.render(globals)?)?;
+                Ok(TemplateOutput::Rename(from, to))
+            }
+            Self::RunCommand(dir, command) => Ok(TemplateOutput::RunCommand(dir, command)),
         }
     }
 }
 
+// Joins a rendered path onto `base`, refusing paths which could point
+// outside it.
+fn join_relative(base: &Path, rendered: &str) -> anyhow::Result<PathBuf> {
+    let relative = Path::new(rendered);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(anyhow!(
+            "Template output path '{rendered}' must be relative and must not contain '..'"
+        ));
+    }
+    Ok(base.join(relative))
+}
+
 impl TemplateContent {
     pub(crate) fn infer_from_bytes(
         raw: Vec<u8>,
@@ -155,3 +186,47 @@ fn understand_liquid_error(e: liquid::Error) -> TemplateParseFailure {
         _ => TemplateParseFailure::Other(e),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn rename(from: &str, to: &str) -> RenderOperation {
+        let parser = liquid::ParserBuilder::with_stdlib().build().unwrap();
+        RenderOperation::Rename(
+            PathBuf::from("/base"),
+            Arc::new(parser.parse(from).unwrap()),
+            Arc::new(parser.parse(to).unwrap()),
+        )
+    }
+
+    #[test]
+    fn renames_within_base_directory() {
+        let globals = liquid::object!({ "name": "app" });
+        let TemplateOutput::Rename(from, to) =
+            rename("old", "{{ name }}/new").render(&globals).unwrap()
+        else {
+            panic!("expected a rename");
+        };
+        assert_eq!(PathBuf::from("/base/old"), from);
+        assert_eq!(PathBuf::from("/base/app/new"), to);
+    }
+
+    #[test]
+    fn refuses_to_rename_outside_base_directory() {
+        for (from, to) in [
+            ("old", "../new"),
+            ("old", "{{ name }}"),
+            ("old", "sub/../../new"),
+            ("/etc/passwd", "new"),
+        ] {
+            let globals = liquid::object!({ "name": "/tmp/new" });
+            assert!(
+                rename(from, to).render(&globals).is_err(),
+                "should refuse to rename {from} to {to}"
+            );
+        }
+    }
+}
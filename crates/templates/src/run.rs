@@ -10,8 +10,8 @@ use walkdir::WalkDir;
 
 use crate::{
     cancellable::Cancellable,
-    interaction::{InteractionStrategy, Interactive, Silent},
-    renderer::MergeTarget,
+    interaction::{InteractionStrategy, Interactive, Silent, Verification},
+    renderer::{to_liquid_object, MergeTarget},
     template::{ExtraOutputAction, TemplateVariantInfo},
 };
 use crate::{
@@ -40,6 +40,10 @@ pub struct RunOptions {
     pub accept_defaults: bool,
     /// If true, do not create a .gitignore file
     pub no_vcs: bool,
+    /// If true, run any commands the template specifies after generating
+    /// files without asking. Otherwise, interactive runs ask before running
+    /// them, and silent runs skip them.
+    pub allow_run_commands: bool,
 }
 
 impl Run {
@@ -62,6 +66,15 @@ impl Run {
         self.run(Silent).await
     }
 
+    /// Runs the template silently for verification. Commands the template
+    /// specifies are never run, even if the options allow it; instead they
+    /// are returned so that the caller can report them.
+    pub(crate) async fn verify(&self) -> anyhow::Result<Vec<String>> {
+        let interaction = Verification::default();
+        self.run(&interaction).await?;
+        Ok(interaction.into_skipped_commands())
+    }
+
     async fn run(&self, interaction: impl InteractionStrategy) -> anyhow::Result<()> {
         self.build_renderer(interaction)
             .await
@@ -101,52 +114,83 @@ impl Run {
 
         self.validate_provided_values()?;
 
+        let parameter_values = match interaction.populate_parameters(self) {
+            Cancellable::Ok(parameter_values) => parameter_values,
+            Cancellable::Cancelled => return Ok(None),
+            Cancellable::Err(e) => return Err(e),
+        };
+
+        let mut values: HashMap<_, _> = self
+            .special_values()
+            .await
+            .into_iter()
+            .map(|(k, v)| (k, liquid_core::Value::scalar(v)))
+            .chain(
+                parameter_values
+                    .into_iter()
+                    .map(|(k, v)| self.typed_value(k, v)),
+            )
+            .collect();
+
+        // Computed parameters are evaluated in the order they are declared, so each
+        // can refer to any user-provided parameter or to earlier computed parameters.
+        for computed in self.template.computed_parameters() {
+            let value = computed.compute(&to_liquid_object(&values))?;
+            values.insert(computed.id().to_owned(), value);
+        }
+
+        let globals = to_liquid_object(&values);
+
         let files = match self.template.content_dir() {
             None => vec![],
             Some(path) => {
                 let from = path
                     .absolutize()
                     .context("Failed to get absolute path of template directory")?;
-                self.included_files(&from, &to)?
+                self.included_files(&from, &to, &globals)?
             }
         };
 
         let snippets = self
             .template
-            .snippets(&self.options.variant)
+            .snippets(&self.options.variant, &globals)?
             .iter()
             .map(|(id, path)| self.snippet_operation(id, path))
             .collect::<anyhow::Result<Vec<_>>>()?;
 
-        let extras = self
+        let mut extras = self
             .template
-            .extra_outputs()
-            .iter()
-            .map(|extra| self.extra_operation(extra))
+            .extra_outputs(&self.options.variant, &globals)?
+            .into_iter()
+            .map(|extra| self.extra_operation(extra, &globals))
             .collect::<anyhow::Result<Vec<_>>>()?;
 
+        let commands = extras
+            .iter()
+            .filter_map(|op| match op {
+                RenderOperation::RunCommand(_, command) => Some(command.clone()),
+                _ => None,
+            })
+            .collect_vec();
+        if !commands.is_empty() && !interaction.allow_run_commands(self, &commands)? {
+            extras.retain(|op| !matches!(op, RenderOperation::RunCommand(..)));
+        }
+
         let render_operations = files.into_iter().chain(snippets).chain(extras).collect();
 
-        match interaction.populate_parameters(self) {
-            Cancellable::Ok(parameter_values) => {
-                let values = self
-                    .special_values()
-                    .await
-                    .into_iter()
-                    .chain(parameter_values)
-                    .collect();
-                let prepared_template = TemplateRenderer {
-                    render_operations,
-                    parameter_values: values,
-                };
-                Ok(Some(prepared_template))
-            }
-            Cancellable::Cancelled => Ok(None),
-            Cancellable::Err(e) => Err(e),
-        }
+        let prepared_template = TemplateRenderer {
+            render_operations,
+            parameter_values: values,
+        };
+        Ok(Some(prepared_template))
     }
 
-    fn included_files(&self, from: &Path, to: &Path) -> anyhow::Result<Vec<RenderOperation>> {
+    fn included_files(
+        &self,
+        from: &Path,
+        to: &Path,
+        values: &liquid::Object,
+    ) -> anyhow::Result<Vec<RenderOperation>> {
         let gitignore = ".gitignore";
         let mut all_content_files = Self::list_content_files(from)?;
         // If user asked for no_vcs
@@ -158,7 +202,7 @@ impl Run {
         }
         let included_files =
             self.template
-                .included_files(from, all_content_files, &self.options.variant);
+                .included_files(from, all_content_files, &self.options.variant, values)?;
         let template_contents = self.read_all(included_files)?;
         let outputs = Self::to_output_paths(from, to, template_contents);
         let file_ops = outputs
@@ -183,6 +227,14 @@ impl Run {
         values
     }
 
+    fn typed_value(&self, id: String, value: String) -> (String, liquid_core::Value) {
+        let typed = match self.template.parameter(&id) {
+            Some(p) => p.data_type().to_liquid_value(&value),
+            None => liquid_core::Value::scalar(value),
+        };
+        (id, typed)
+    }
+
     fn relative_target_dir(&self) -> &Path {
         &self.options.output_path
     }
@@ -310,28 +362,48 @@ impl Run {
         }
     }
 
-    fn extra_operation(&self, extra: &ExtraOutputAction) -> anyhow::Result<RenderOperation> {
+    fn extra_operation(
+        &self,
+        extra: &ExtraOutputAction,
+        values: &liquid::Object,
+    ) -> anyhow::Result<RenderOperation> {
         match extra {
-            ExtraOutputAction::CreateDirectory(_, template, at) => {
-                let component_path = self.options.output_path.clone();
-                let base_path = match at {
-                    crate::reader::CreateLocation::Component => component_path,
-                    crate::reader::CreateLocation::Manifest => match &self.options.variant {
-                        TemplateVariantInfo::NewApplication => component_path,
-                        TemplateVariantInfo::AddComponent { manifest_path } => manifest_path
-                            .parent()
-                            .map(|p| p.to_owned())
-                            .unwrap_or(component_path),
-                    },
-                };
-                Ok(RenderOperation::CreateDirectory(
-                    base_path,
-                    template.clone(),
+            ExtraOutputAction::CreateDirectory(_, template, at) => Ok(
+                RenderOperation::CreateDirectory(self.extra_output_base(at), template.clone()),
+            ),
+            ExtraOutputAction::Rename { from, to, at } => Ok(RenderOperation::Rename(
+                self.extra_output_base(at),
+                from.clone(),
+                to.clone(),
+            )),
+            ExtraOutputAction::RunCommand { command, at } => {
+                // Commands are rendered up front so that the user can see exactly
+                // what will be run before agreeing to it.
+                let command = command
+                    .render(values)
+                    .context("Failed to render post-generation command")?;
+                Ok(RenderOperation::RunCommand(
+                    self.extra_output_base(at),
+                    command,
                 ))
             }
         }
     }
 
+    fn extra_output_base(&self, at: &crate::reader::CreateLocation) -> PathBuf {
+        let component_path = self.options.output_path.clone();
+        match at {
+            crate::reader::CreateLocation::Component => component_path,
+            crate::reader::CreateLocation::Manifest => match &self.options.variant {
+                TemplateVariantInfo::NewApplication => component_path,
+                TemplateVariantInfo::AddComponent { manifest_path } => manifest_path
+                    .parent()
+                    .map(|p| p.to_owned())
+                    .unwrap_or(component_path),
+            },
+        }
+    }
+
     fn list_content_files(from: &Path) -> anyhow::Result<Vec<PathBuf>> {
         let walker = WalkDir::new(from);
         let files = walker
@@ -389,7 +461,7 @@ impl Run {
         pathdiff::diff_paths(source, src_dir).map(|rel| (dest_dir.join(rel), cont))
     }
 
-    fn template_parser() -> liquid::Parser {
+    pub(crate) fn template_parser() -> liquid::Parser {
         let builder = liquid::ParserBuilder::with_stdlib()
             .filter(crate::filters::KebabCaseFilterParser)
             .filter(crate::filters::PascalCaseFilterParser)
@@ -25,6 +25,19 @@ pub enum TemplateSource {
     /// Templates much be in a `/templates` directory under the specified
     /// root.
     File(PathBuf),
+    /// Install from an OCI artifact at the specified reference. Registry
+    /// credentials are the same as for `spin registry` commands.
+    ///
+    /// The artifact's compressed archive layers are unpacked, in order, and
+    /// templates must be in a `/templates` directory under the root of
+    /// the unpacked content.
+    Oci(String),
+    /// Install from a `.tar.gz` archive at the specified URL (which may be
+    /// a `file:` URL), verified against the specified digest.
+    ///
+    /// Templates must be in a `/templates` directory under the root of the
+    /// archive, or under a single top-level directory in the archive.
+    Archive(ArchiveTemplateSource),
 }
 
 /// Settings for installing templates from a Git repository.
@@ -39,6 +52,15 @@ pub struct GitTemplateSource {
     spin_version: String,
 }
 
+/// Settings for installing templates from a `.tar.gz` archive.
+#[derive(Debug)]
+pub struct ArchiveTemplateSource {
+    /// The URL of the archive.
+    url: Url,
+    /// The expected SHA256 digest of the archive, as a hex string.
+    sha256: String,
+}
+
 impl TemplateSource {
     /// Creates a `TemplateSource` referring to the specified Git repository
     /// and branch.
@@ -57,6 +79,35 @@ impl TemplateSource {
         }))
     }
 
+    /// Creates a `TemplateSource` referring to the specified OCI reference.
+    pub fn try_from_oci(reference: impl AsRef<str>) -> anyhow::Result<Self> {
+        let reference = reference.as_ref();
+        if !spin_oci::is_probably_oci_reference(reference) {
+            anyhow::bail!("{reference} is not a valid OCI reference");
+        }
+        Ok(Self::Oci(reference.to_owned()))
+    }
+
+    /// Creates a `TemplateSource` referring to the specified archive URL. The
+    /// digest may be given as a bare hex string or in `sha256:<hex>` form.
+    pub fn try_from_archive(
+        archive_url: impl AsRef<str>,
+        digest: impl AsRef<str>,
+    ) -> anyhow::Result<Self> {
+        let url_str = archive_url.as_ref();
+        let url =
+            Url::parse(url_str).with_context(|| format!("Failed to parse {} as URL", url_str))?;
+        let digest = digest.as_ref();
+        let sha256 = digest.strip_prefix("sha256:").unwrap_or(digest);
+        if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!("Digest '{digest}' is not a valid SHA256 digest");
+        }
+        Ok(Self::Archive(ArchiveTemplateSource {
+            url,
+            sha256: sha256.to_ascii_lowercase(),
+        }))
+    }
+
     pub(crate) fn to_install_record(&self) -> Option<crate::reader::RawInstalledFrom> {
         match self {
             Self::Git(g) => Some(crate::reader::RawInstalledFrom::Git {
@@ -72,6 +123,13 @@ impl TemplateSource {
                     None
                 }
             }
+            Self::Oci(reference) => Some(crate::reader::RawInstalledFrom::Oci {
+                oci: reference.clone(),
+            }),
+            Self::Archive(a) => Some(crate::reader::RawInstalledFrom::Archive {
+                archive: a.url.to_string(),
+                sha256: a.sha256.clone(),
+            }),
         }
     }
 
@@ -96,6 +154,8 @@ impl TemplateSource {
         match self {
             Self::Git(git_source) => clone_local(git_source).await,
             Self::File(path) => check_local(path).await,
+            Self::Oci(reference) => pull_local(reference).await,
+            Self::Archive(archive_source) => download_local(archive_source).await,
         }
     }
 
@@ -103,6 +163,8 @@ impl TemplateSource {
         match self {
             Self::Git { .. } => true,
             Self::File(_) => false,
+            Self::Oci(_) => true,
+            Self::Archive(_) => true,
         }
     }
 }
@@ -158,6 +220,81 @@ async fn clone_local(git_source: &GitTemplateSource) -> anyhow::Result<LocalTemp
     }
 }
 
+async fn pull_local(reference: &str) -> anyhow::Result<LocalTemplateSource> {
+    let temp_dir = tempdir()?;
+    let path = temp_dir.path().to_owned();
+
+    let mut client = spin_oci::Client::new(false, None)
+        .await
+        .context("Failed to create registry client")?;
+    client
+        .pull_archive_artifact(reference, &path)
+        .await
+        .with_context(|| format!("Error pulling templates from {reference}"))?;
+
+    Ok(LocalTemplateSource {
+        root: path,
+        _temp_dir: Some(temp_dir),
+    })
+}
+
+async fn download_local(
+    archive_source: &ArchiveTemplateSource,
+) -> anyhow::Result<LocalTemplateSource> {
+    let url = &archive_source.url;
+
+    let bytes = if url.scheme() == "file" {
+        let path = url
+            .to_file_path()
+            .map_err(|_| anyhow!("Invalid file URL: {url}"))?;
+        tokio::fs::read(&path)
+            .await
+            .with_context(|| format!("Failed to read archive {}", path.display()))?
+    } else {
+        let response = reqwest::get(url.clone())
+            .await
+            .with_context(|| format!("Failed to download archive {url}"))?;
+        if !response.status().is_success() {
+            anyhow::bail!("HTTP error {} when downloading {url}", response.status());
+        }
+        response.bytes().await?.to_vec()
+    };
+
+    let actual_sha256 = spin_common::sha256::hex_digest_from_bytes(&bytes);
+    if actual_sha256 != archive_source.sha256 {
+        anyhow::bail!(
+            "Digest of {url} did not match: expected {}, got {actual_sha256}",
+            archive_source.sha256
+        );
+    }
+
+    let temp_dir = tempdir()?;
+    let archive_path = temp_dir.path().join("templates.tar.gz");
+    let unpack_dir = temp_dir.path().join("unpacked");
+    tokio::fs::write(&archive_path, &bytes).await?;
+    tokio::fs::create_dir_all(&unpack_dir).await?;
+    spin_oci::utils::unarchive(&archive_path, &unpack_dir)
+        .await
+        .with_context(|| format!("Failed to unpack archive {url}"))?;
+
+    Ok(LocalTemplateSource {
+        root: archive_root(&unpack_dir)?,
+        _temp_dir: Some(temp_dir),
+    })
+}
+
+// Archives such as GitHub release tarballs wrap everything in a single
+// top-level directory, so look inside it if need be.
+fn archive_root(unpack_dir: &Path) -> anyhow::Result<PathBuf> {
+    if unpack_dir.join(TEMPLATE_SOURCE_DIR).exists() {
+        return Ok(unpack_dir.to_owned());
+    }
+    match subdirectories(unpack_dir)?.as_slice() {
+        [single] => Ok(single.clone()),
+        _ => Ok(unpack_dir.to_owned()),
+    }
+}
+
 async fn version_matched_tag(url: &str, spin_version: &str) -> Option<String> {
     let preferred_tag = version_preferred_tag(spin_version);
 
@@ -196,6 +333,49 @@ async fn check_local(path: &Path) -> anyhow::Result<LocalTemplateSource> {
 mod test {
     use super::*;
 
+    #[test]
+    fn archive_digest_is_normalised() {
+        let digest = "sha256:A5A2729FFA0EEACC15323A9168807C72D18D1CB375DBDE899C44D6803DAD2B19";
+        let source =
+            TemplateSource::try_from_archive("https://example.com/t.tar.gz", digest).unwrap();
+        let TemplateSource::Archive(archive) = source else {
+            panic!("expected archive source");
+        };
+        assert_eq!(
+            "a5a2729ffa0eeacc15323a9168807c72d18d1cb375dbde899c44d6803dad2b19",
+            archive.sha256
+        );
+    }
+
+    #[test]
+    fn archive_digest_must_be_sha256() {
+        TemplateSource::try_from_archive("https://example.com/t.tar.gz", "sha256:1234")
+            .unwrap_err();
+        TemplateSource::try_from_archive("https://example.com/t.tar.gz", "not a digest")
+            .unwrap_err();
+    }
+
+    #[test]
+    fn archive_root_looks_inside_single_top_level_directory() {
+        let temp_dir = tempdir().unwrap();
+        let wrapped = temp_dir.path().join("spin-templates-1.0");
+        std::fs::create_dir_all(wrapped.join(TEMPLATE_SOURCE_DIR)).unwrap();
+        assert_eq!(wrapped, archive_root(temp_dir.path()).unwrap());
+
+        std::fs::create_dir_all(temp_dir.path().join(TEMPLATE_SOURCE_DIR)).unwrap();
+        assert_eq!(temp_dir.path(), archive_root(temp_dir.path()).unwrap());
+    }
+
+    #[test]
+    fn install_record_round_trips_for_artifacts() {
+        let source = TemplateSource::try_from_oci("ghcr.io/example/templates:v1").unwrap();
+        let record = toml::to_string(&source.to_install_record()).unwrap();
+        assert!(matches!(
+            crate::reader::parse_installed_from(record),
+            Some(crate::reader::RawInstalledFrom::Oci { oci }) if oci == "ghcr.io/example/templates:v1"
+        ));
+    }
+
     #[test]
     fn preferred_tag_excludes_patch_version() {
         assert_eq!("spin/templates/v1.2", version_preferred_tag("1.2.3"));
@@ -80,6 +80,10 @@ impl TemplateLayout {
         }
     }
 
+    pub fn template_dir(&self) -> &Path {
+        &self.template_dir
+    }
+
     pub fn metadata_dir(&self) -> PathBuf {
         self.template_dir.join(METADATA_DIR_NAME)
     }
@@ -9,12 +9,13 @@ use itertools::Itertools;
 use regex::Regex;
 
 use crate::{
-    constraints::StringConstraints,
+    constraints::{ChoiceConstraints, IntegerConstraints, StringConstraints},
     reader::{
-        RawCondition, RawConditional, RawExtraOutput, RawParameter, RawTemplateManifest,
-        RawTemplateManifestV1, RawTemplateVariant,
+        RawCondition, RawConditional, RawExtraOutput, RawParameter, RawSampleValue,
+        RawTemplateManifest, RawTemplateManifestV1, RawTemplateVariant,
     },
     run::{Run, RunOptions},
+    source::TemplateSource,
     store::TemplateLayout,
 };
 
@@ -28,7 +29,9 @@ pub struct Template {
     trigger: TemplateTriggerCompatibility,
     variants: HashMap<TemplateVariantKind, TemplateVariant>,
     parameters: Vec<TemplateParameter>,
-    extra_outputs: Vec<ExtraOutputAction>,
+    computed_parameters: Vec<ComputedParameter>,
+    extra_outputs: Vec<ExtraOutput>,
+    samples: Vec<TemplateSample>,
     snippets_dir: Option<PathBuf>,
     content_dir: Option<PathBuf>, // TODO: maybe always need a spin.toml file in there?
 }
@@ -37,6 +40,8 @@ pub struct Template {
 enum InstalledFrom {
     Git(String),
     Directory(String),
+    Oci(String),
+    Archive { url: String, sha256: String },
     Unknown,
 }
 
@@ -111,10 +116,28 @@ pub(crate) struct Conditional {
 #[derive(Clone, Debug)]
 pub(crate) enum Condition {
     ManifestEntryExists(Vec<String>),
+    Expression(ParameterExpression),
     #[cfg(test)]
     Always(bool),
 }
 
+/// A Liquid expression over parameter values, such as `include-db` or
+/// `framework == "axum"`, which can be used wherever a Liquid `if` tag would
+/// accept it.
+#[derive(Clone)]
+pub(crate) struct ParameterExpression {
+    expression: String,
+    template: std::sync::Arc<liquid::Template>,
+}
+
+impl std::fmt::Debug for ParameterExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ParameterExpression")
+            .field(&self.expression)
+            .finish()
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub(crate) enum TemplateTriggerCompatibility {
     Any,
@@ -124,6 +147,10 @@ pub(crate) enum TemplateTriggerCompatibility {
 #[derive(Clone, Debug)]
 pub(crate) enum TemplateParameterDataType {
     String(StringConstraints),
+    Bool,
+    Choice(ChoiceConstraints),
+    Integer(IntegerConstraints),
+    List(StringConstraints),
 }
 
 #[derive(Debug)]
@@ -134,12 +161,48 @@ pub(crate) struct TemplateParameter {
     default_value: Option<String>,
 }
 
+/// A parameter whose value is computed from other parameters, rather than
+/// being provided by the user.
+pub(crate) struct ComputedParameter {
+    id: String,
+    template: liquid::Template,
+}
+
+impl std::fmt::Debug for ComputedParameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ComputedParameter").field(&self.id).finish()
+    }
+}
+
+/// A named set of parameter values with which the template is expected
+/// to render successfully. Used by template verification.
+#[derive(Clone, Debug)]
+pub(crate) struct TemplateSample {
+    pub name: String,
+    pub values: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ExtraOutput {
+    condition: Option<Condition>,
+    action: ExtraOutputAction,
+}
+
 pub(crate) enum ExtraOutputAction {
     CreateDirectory(
         String,
         std::sync::Arc<liquid::Template>,
         crate::reader::CreateLocation,
     ),
+    Rename {
+        from: std::sync::Arc<liquid::Template>,
+        to: std::sync::Arc<liquid::Template>,
+        at: crate::reader::CreateLocation,
+    },
+    RunCommand {
+        command: liquid::Template,
+        at: crate::reader::CreateLocation,
+    },
 }
 
 impl std::fmt::Debug for ExtraOutputAction {
@@ -148,6 +211,8 @@ impl std::fmt::Debug for ExtraOutputAction {
             Self::CreateDirectory(orig, ..) => {
                 f.debug_tuple("CreateDirectory").field(orig).finish()
             }
+            Self::Rename { .. } => f.debug_tuple("Rename").finish(),
+            Self::RunCommand { .. } => f.debug_tuple("RunCommand").finish(),
         }
     }
 }
@@ -186,18 +251,30 @@ impl Template {
         let installed_from = read_install_record(layout);
 
         let template = match raw {
-            RawTemplateManifest::V1(raw) => Self {
-                id: raw.id.clone(),
-                tags: raw.tags.map(Self::normalize_tags).unwrap_or_default(),
-                description: raw.description.clone(),
-                installed_from,
-                trigger: Self::parse_trigger_type(raw.trigger_type, layout),
-                variants: Self::parse_template_variants(raw.new_application, raw.add_component),
-                parameters: Self::parse_parameters(&raw.parameters)?,
-                extra_outputs: Self::parse_extra_outputs(&raw.outputs)?,
-                snippets_dir,
-                content_dir,
-            },
+            RawTemplateManifest::V1(raw) => {
+                let parameters = Self::parse_parameters(&raw.parameters)?;
+                let samples = Self::parse_samples(&raw.samples, &parameters)?;
+                Self {
+                    id: raw.id.clone(),
+                    tags: raw.tags.map(Self::normalize_tags).unwrap_or_default(),
+                    description: raw.description.clone(),
+                    installed_from,
+                    trigger: Self::parse_trigger_type(raw.trigger_type, layout),
+                    variants: Self::parse_template_variants(
+                        raw.new_application,
+                        raw.add_component,
+                    )?,
+                    parameters,
+                    computed_parameters: Self::parse_computed_parameters(
+                        &raw.computed_parameters,
+                        &parameters,
+                    )?,
+                    extra_outputs: Self::parse_extra_outputs(&raw.outputs)?,
+                    samples,
+                    snippets_dir,
+                    content_dir,
+                }
+            }
         };
         Ok(template)
     }
@@ -248,22 +325,49 @@ impl Template {
         }
     }
 
+    /// The OCI reference or archive from which the template was installed,
+    /// as a source from which it can be reinstalled or upgraded; otherwise None.
+    pub fn source_artifact(&self) -> Option<TemplateSource> {
+        match &self.installed_from {
+            InstalledFrom::Oci(reference) => TemplateSource::try_from_oci(reference).ok(),
+            InstalledFrom::Archive { url, sha256 } => {
+                TemplateSource::try_from_archive(url, sha256).ok()
+            }
+            _ => None,
+        }
+    }
+
     /// A human-readable description of where the template was installed
     /// from.
     pub fn installed_from_or_empty(&self) -> &str {
         match &self.installed_from {
             InstalledFrom::Git(repo) => repo,
             InstalledFrom::Directory(path) => path,
+            InstalledFrom::Oci(reference) => reference,
+            InstalledFrom::Archive { url, .. } => url,
             InstalledFrom::Unknown => "",
         }
     }
 
     // TODO: we should resolve this once at the start of Run and then use that forever
     fn variant(&self, variant_info: &TemplateVariantInfo) -> Option<TemplateVariant> {
+        // Without parameter values, conditions cannot fail to evaluate.
+        self.variant_with_values(variant_info, None).ok().flatten()
+    }
+
+    // Conditions which depend on parameter values are only applied once the values
+    // are known. They are not allowed to skip parameters, so the parameter list
+    // is the same either way.
+    fn variant_with_values(
+        &self,
+        variant_info: &TemplateVariantInfo,
+        values: Option<&liquid::Object>,
+    ) -> anyhow::Result<Option<TemplateVariant>> {
         let kind = variant_info.kind();
         self.variants
             .get(&kind)
-            .map(|vt| vt.resolve_conditions(variant_info))
+            .map(|vt| vt.resolve_conditions(variant_info, values))
+            .transpose()
     }
 
     pub(crate) fn parameters(
@@ -280,8 +384,31 @@ impl Template {
         self.parameters.iter().find(|p| p.id == name.as_ref())
     }
 
-    pub(crate) fn extra_outputs(&self) -> &[ExtraOutputAction] {
-        &self.extra_outputs
+    pub(crate) fn computed_parameters(&self) -> &[ComputedParameter] {
+        &self.computed_parameters
+    }
+
+    /// The extra output actions which apply given the variant and parameter values.
+    pub(crate) fn extra_outputs<'a>(
+        &'a self,
+        variant_info: &'a TemplateVariantInfo,
+        values: &'a liquid::Object,
+    ) -> anyhow::Result<Vec<&'a ExtraOutputAction>> {
+        let mut actions = vec![];
+        for output in &self.extra_outputs {
+            let applies = match &output.condition {
+                None => true,
+                Some(c) => c.evaluate(variant_info, Some(values))?,
+            };
+            if applies {
+                actions.push(&output.action);
+            }
+        }
+        Ok(actions)
+    }
+
+    pub(crate) fn samples(&self) -> &[TemplateSample] {
+        &self.samples
     }
 
     pub(crate) fn content_dir(&self) -> &Option<PathBuf> {
@@ -297,9 +424,15 @@ impl Template {
         self.variants.contains_key(&variant.kind())
     }
 
-    pub(crate) fn snippets(&self, variant_kind: &TemplateVariantInfo) -> HashMap<String, String> {
-        let variant = self.variant(variant_kind).unwrap(); // TODO: for now
-        variant.snippets
+    pub(crate) fn snippets(
+        &self,
+        variant_kind: &TemplateVariantInfo,
+        values: &liquid::Object,
+    ) -> anyhow::Result<HashMap<String, String>> {
+        let variant = self
+            .variant_with_values(variant_kind, Some(values))?
+            .unwrap(); // TODO: for now
+        Ok(variant.snippets)
     }
 
     /// Creates a runner for the template, governed by the given options. Call
@@ -337,67 +470,98 @@ impl Template {
     fn parse_template_variants(
         new_application: Option<RawTemplateVariant>,
         add_component: Option<RawTemplateVariant>,
-    ) -> HashMap<TemplateVariantKind, TemplateVariant> {
+    ) -> anyhow::Result<HashMap<TemplateVariantKind, TemplateVariant>> {
         let mut variants = HashMap::default();
-        if let Some(vt) = Self::get_variant(new_application, true) {
+        if let Some(vt) = Self::get_variant(new_application, true)? {
             variants.insert(TemplateVariantKind::NewApplication, vt);
         }
-        if let Some(vt) = Self::get_variant(add_component, false) {
+        if let Some(vt) = Self::get_variant(add_component, false)? {
             variants.insert(TemplateVariantKind::AddComponent, vt);
         }
-        variants
+        Ok(variants)
     }
 
     fn get_variant(
         raw: Option<RawTemplateVariant>,
         default_supported: bool,
-    ) -> Option<TemplateVariant> {
+    ) -> anyhow::Result<Option<TemplateVariant>> {
         match raw {
             None => {
                 if default_supported {
-                    Some(Default::default())
+                    Ok(Some(Default::default()))
                 } else {
-                    None
+                    Ok(None)
                 }
             }
             Some(rv) => {
                 if rv.supported.unwrap_or(true) {
-                    Some(Self::parse_template_variant(rv))
+                    Ok(Some(Self::parse_template_variant(rv)?))
                 } else {
-                    None
+                    Ok(None)
                 }
             }
         }
     }
 
-    fn parse_template_variant(raw: RawTemplateVariant) -> TemplateVariant {
-        TemplateVariant {
+    fn parse_template_variant(raw: RawTemplateVariant) -> anyhow::Result<TemplateVariant> {
+        Ok(TemplateVariant {
             skip_files: raw.skip_files.unwrap_or_default(),
             skip_parameters: raw.skip_parameters.unwrap_or_default(),
             snippets: raw.snippets.unwrap_or_default(),
             conditions: raw
                 .conditions
                 .unwrap_or_default()
-                .into_values()
-                .map(Self::parse_conditional)
-                .collect(),
-        }
+                .into_iter()
+                .map(|(id, c)| Self::parse_conditional(&id, c))
+                .collect::<anyhow::Result<_>>()?,
+        })
     }
 
-    fn parse_conditional(conditional: RawConditional) -> Conditional {
-        Conditional {
-            condition: Self::parse_condition(conditional.condition),
+    fn parse_conditional(id: &str, conditional: RawConditional) -> anyhow::Result<Conditional> {
+        let condition = Self::parse_condition(conditional.condition)
+            .with_context(|| format!("Invalid condition '{id}'"))?;
+        let skip_parameters = conditional.skip_parameters.unwrap_or_default();
+        if matches!(condition, Condition::Expression(_)) && !skip_parameters.is_empty() {
+            anyhow::bail!(
+                "Condition '{id}' depends on parameter values, so cannot be used to skip parameters"
+            );
+        }
+        Ok(Conditional {
+            condition,
             skip_files: conditional.skip_files.unwrap_or_default(),
-            skip_parameters: conditional.skip_parameters.unwrap_or_default(),
+            skip_parameters,
             skip_snippets: conditional.skip_snippets.unwrap_or_default(),
-        }
+        })
     }
 
-    fn parse_condition(condition: RawCondition) -> Condition {
+    fn parse_condition(condition: RawCondition) -> anyhow::Result<Condition> {
         match condition {
-            RawCondition::ManifestEntryExists(path) => {
-                Condition::ManifestEntryExists(path.split('.').map(|s| s.to_string()).collect_vec())
-            }
+            RawCondition::ManifestEntryExists(path) => Ok(Condition::ManifestEntryExists(
+                path.split('.').map(|s| s.to_string()).collect_vec(),
+            )),
+            RawCondition::Expression(expression) => Ok(Condition::Expression(
+                ParameterExpression::parse(&expression)?,
+            )),
+        }
+    }
+
+    fn parse_computed_parameters(
+        raw: &Option<IndexMap<String, String>>,
+        parameters: &[TemplateParameter],
+    ) -> anyhow::Result<Vec<ComputedParameter>> {
+        match raw {
+            None => Ok(vec![]),
+            Some(computed) => computed
+                .iter()
+                .map(|(id, expr)| {
+                    if parameters.iter().any(|p| p.id() == id) {
+                        return Err(anyhow!(
+                            "Template error: computed parameter {id} has the same name as a parameter"
+                        ));
+                    }
+                    ComputedParameter::parse(id, expr)
+                })
+                .collect(),
         }
     }
 
@@ -413,14 +577,44 @@ impl Template {
         }
     }
 
+    fn parse_samples(
+        raw: &Option<IndexMap<String, IndexMap<String, RawSampleValue>>>,
+        parameters: &[TemplateParameter],
+    ) -> anyhow::Result<Vec<TemplateSample>> {
+        let Some(samples) = raw else {
+            return Ok(vec![]);
+        };
+        samples
+            .iter()
+            .map(|(name, raw_values)| {
+                let values = raw_values
+                    .iter()
+                    .map(|(id, RawSampleValue(value))| {
+                        let Some(parameter) = parameters.iter().find(|p| &p.id == id) else {
+                            anyhow::bail!("Sample '{name}' sets unknown parameter '{id}'");
+                        };
+                        let value = parameter.validate_value(value).with_context(|| {
+                            format!("Sample '{name}' has an invalid value for parameter '{id}'")
+                        })?;
+                        Ok((id.clone(), value))
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                Ok(TemplateSample {
+                    name: name.clone(),
+                    values,
+                })
+            })
+            .collect()
+    }
+
     fn parse_extra_outputs(
         raw: &Option<IndexMap<String, RawExtraOutput>>,
-    ) -> anyhow::Result<Vec<ExtraOutputAction>> {
+    ) -> anyhow::Result<Vec<ExtraOutput>> {
         match raw {
             None => Ok(vec![]),
             Some(parameters) => parameters
                 .iter()
-                .map(|(k, v)| ExtraOutputAction::from_raw(k, v))
+                .map(|(k, v)| ExtraOutput::from_raw(k, v))
                 .collect(),
         }
     }
@@ -430,12 +624,15 @@ impl Template {
         base: &std::path::Path,
         all_files: Vec<PathBuf>,
         variant_kind: &TemplateVariantInfo,
-    ) -> Vec<PathBuf> {
-        let variant = self.variant(variant_kind).unwrap(); // TODO: for now
-        all_files
+        values: &liquid::Object,
+    ) -> anyhow::Result<Vec<PathBuf>> {
+        let variant = self
+            .variant_with_values(variant_kind, Some(values))?
+            .unwrap(); // TODO: for now
+        Ok(all_files
             .into_iter()
             .filter(|path| !variant.skip_file(base, path))
-            .collect()
+            .collect())
     }
 
     pub(crate) fn check_compatible_trigger(&self, app_trigger: Option<&str>) -> anyhow::Result<()> {
@@ -496,7 +693,14 @@ impl Template {
 
 impl TemplateParameter {
     fn from_raw(id: &str, raw: &RawParameter) -> anyhow::Result<Self> {
-        let data_type = TemplateParameterDataType::parse(raw)?;
+        let data_type = TemplateParameterDataType::parse(raw)
+            .with_context(|| format!("Invalid definition for parameter '{id}'"))?;
+
+        if let Some(default_value) = &raw.default_value {
+            data_type
+                .validate_value(default_value.clone())
+                .with_context(|| format!("Invalid default value for parameter '{id}'"))?;
+        }
 
         Ok(Self {
             id: id.to_owned(),
@@ -529,21 +733,160 @@ impl TemplateParameter {
 
 impl TemplateParameterDataType {
     fn parse(raw: &RawParameter) -> anyhow::Result<Self> {
-        match &raw.data_type[..] {
-            "string" => Ok(Self::String(parse_string_constraints(raw)?)),
-            _ => Err(anyhow!("Unrecognised data type '{}'", raw.data_type)),
+        let data_type = match &raw.data_type[..] {
+            "string" => Self::String(parse_string_constraints(raw)?),
+            "bool" => Self::Bool,
+            "choice" => Self::Choice(parse_choice_constraints(raw)?),
+            "integer" => Self::Integer(parse_integer_constraints(raw)?),
+            "list" => Self::List(parse_string_constraints(raw)?),
+            _ => return Err(anyhow!("Unrecognised data type '{}'", raw.data_type)),
+        };
+        data_type.check_applicable_constraints(raw)?;
+        Ok(data_type)
+    }
+
+    fn check_applicable_constraints(&self, raw: &RawParameter) -> anyhow::Result<()> {
+        let allows_pattern = matches!(self, Self::String(_) | Self::List(_));
+        let allows_allowed_values = matches!(self, Self::Choice(_));
+        let allows_range = matches!(self, Self::Integer(_));
+
+        if raw.pattern.is_some() && !allows_pattern {
+            anyhow::bail!(
+                "'pattern' is not supported for '{}' parameters",
+                raw.data_type
+            );
         }
+        if raw.allowed_values.is_some() && !allows_allowed_values {
+            anyhow::bail!(
+                "'allowed_values' is not supported for '{}' parameters",
+                raw.data_type
+            );
+        }
+        if (raw.min.is_some() || raw.max.is_some()) && !allows_range {
+            anyhow::bail!(
+                "'min' and 'max' are not supported for '{}' parameters",
+                raw.data_type
+            );
+        }
+        Ok(())
     }
 
     fn validate_value(&self, value: String) -> anyhow::Result<String> {
         match self {
             TemplateParameterDataType::String(constraints) => constraints.validate(value),
+            TemplateParameterDataType::Bool => crate::constraints::validate_bool(value),
+            TemplateParameterDataType::Choice(constraints) => constraints.validate(value),
+            TemplateParameterDataType::Integer(constraints) => constraints.validate(value),
+            TemplateParameterDataType::List(constraints) => {
+                crate::constraints::validate_list(constraints, value)
+            }
+        }
+    }
+
+    /// Converts a validated value to the Liquid value exposed to templates. This
+    /// allows templates to use typed parameters directly in conditionals and loops
+    /// (e.g. `{% if include-db %}` or `{% for region in regions %}`).
+    pub(crate) fn to_liquid_value(&self, value: &str) -> liquid_core::Value {
+        match self {
+            TemplateParameterDataType::String(_) | TemplateParameterDataType::Choice(_) => {
+                liquid_core::Value::scalar(value.to_owned())
+            }
+            TemplateParameterDataType::Bool => liquid_core::Value::scalar(value == "true"),
+            TemplateParameterDataType::Integer(_) => match value.parse::<i64>() {
+                Ok(i) => liquid_core::Value::scalar(i),
+                Err(_) => liquid_core::Value::scalar(value.to_owned()),
+            },
+            TemplateParameterDataType::List(_) => liquid_core::Value::Array(
+                crate::constraints::split_list(value)
+                    .map(|item| liquid_core::Value::scalar(item.to_owned()))
+                    .collect(),
+            ),
         }
     }
 }
 
+impl ParameterExpression {
+    fn parse(expression: &str) -> anyhow::Result<Self> {
+        let text = format!("{{% if {expression} %}}true{{% else %}}false{{% endif %}}");
+        let template = Run::template_parser()
+            .parse(&text)
+            .with_context(|| format!("'{expression}' is not a valid Liquid expression"))?;
+        Ok(Self {
+            expression: expression.to_owned(),
+            template: std::sync::Arc::new(template),
+        })
+    }
+
+    fn evaluate(&self, values: &liquid::Object) -> anyhow::Result<bool> {
+        let result = self.template.render(values).with_context(|| {
+            format!(
+                "Template error: failed to evaluate condition '{}'",
+                self.expression
+            )
+        })?;
+        Ok(result == "true")
+    }
+}
+
+impl ComputedParameter {
+    fn parse(id: &str, expression: &str) -> anyhow::Result<Self> {
+        let template = Run::template_parser().parse(expression).with_context(|| {
+            format!("Template error: computed parameter {id} is not a valid template")
+        })?;
+        Ok(Self {
+            id: id.to_owned(),
+            template,
+        })
+    }
+
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Renders the parameter's value. Values which render as `true`, `false` or
+    /// an integer are typed accordingly, so that e.g. a computed `false` is
+    /// falsy in conditionals.
+    pub(crate) fn compute(&self, values: &liquid::Object) -> anyhow::Result<liquid_core::Value> {
+        let text = self
+            .template
+            .render(values)
+            .with_context(|| format!("Failed to compute parameter '{}'", self.id))?;
+        Ok(match text.as_str() {
+            "true" => liquid_core::Value::scalar(true),
+            "false" => liquid_core::Value::scalar(false),
+            _ => match text.parse::<i64>() {
+                Ok(i) if i.to_string() == text => liquid_core::Value::scalar(i),
+                _ => liquid_core::Value::scalar(text),
+            },
+        })
+    }
+}
+
+impl ExtraOutput {
+    fn from_raw(id: &str, raw: &RawExtraOutput) -> anyhow::Result<Self> {
+        let condition = match raw {
+            RawExtraOutput::CreateDir(create) => create.condition.as_ref(),
+            RawExtraOutput::Rename(rename) => rename.condition.as_ref(),
+            RawExtraOutput::RunCommand(run) => run.condition.as_ref(),
+        };
+        let condition = condition
+            .map(|c| Template::parse_condition(c.clone()))
+            .transpose()
+            .with_context(|| format!("Template error: output {id} has an invalid condition"))?;
+        Ok(Self {
+            condition,
+            action: ExtraOutputAction::from_raw(id, raw)?,
+        })
+    }
+}
+
 impl ExtraOutputAction {
     fn from_raw(id: &str, raw: &RawExtraOutput) -> anyhow::Result<Self> {
+        let parse = |text: &str| {
+            Run::template_parser()
+                .parse(text)
+                .with_context(|| format!("Template error: output {id} is not a valid template"))
+        };
         Ok(match raw {
             RawExtraOutput::CreateDir(create) => {
                 let path_template =
@@ -556,6 +899,15 @@ impl ExtraOutputAction {
                     create.at.unwrap_or_default(),
                 )
             }
+            RawExtraOutput::Rename(rename) => Self::Rename {
+                from: std::sync::Arc::new(parse(&rename.from)?),
+                to: std::sync::Arc::new(parse(&rename.to)?),
+                at: rename.at.unwrap_or_default(),
+            },
+            RawExtraOutput::RunCommand(run) => Self::RunCommand {
+                command: parse(&run.command)?,
+                at: run.at.unwrap_or_default(),
+            },
         })
     }
 }
@@ -572,10 +924,14 @@ impl TemplateVariant {
         self.skip_parameters.iter().any(|p| &parameter.id == p)
     }
 
-    fn resolve_conditions(&self, variant_info: &TemplateVariantInfo) -> Self {
+    fn resolve_conditions(
+        &self,
+        variant_info: &TemplateVariantInfo,
+        values: Option<&liquid::Object>,
+    ) -> anyhow::Result<Self> {
         let mut resolved = self.clone();
         for condition in &self.conditions {
-            if condition.condition.is_true(variant_info) {
+            if condition.condition.evaluate(variant_info, values)? {
                 resolved
                     .skip_files
                     .append(&mut condition.skip_files.clone());
@@ -587,28 +943,43 @@ impl TemplateVariant {
                     .retain(|id, _| !condition.skip_snippets.contains(id));
             }
         }
-        resolved
+        Ok(resolved)
     }
 }
 
 impl Condition {
+    #[cfg(test)]
     fn is_true(&self, variant_info: &TemplateVariantInfo) -> bool {
-        match self {
+        self.evaluate(variant_info, None).unwrap()
+    }
+
+    // Expression conditions are false until parameter values are available.
+    fn evaluate(
+        &self,
+        variant_info: &TemplateVariantInfo,
+        values: Option<&liquid::Object>,
+    ) -> anyhow::Result<bool> {
+        let result = match self {
             Self::ManifestEntryExists(path) => match variant_info {
                 TemplateVariantInfo::NewApplication => false,
                 TemplateVariantInfo::AddComponent { manifest_path } => {
                     let Ok(toml_text) = std::fs::read_to_string(manifest_path) else {
-                        return false;
+                        return Ok(false);
                     };
                     let Ok(table) = toml::from_str::<toml::Value>(&toml_text) else {
-                        return false;
+                        return Ok(false);
                     };
                     crate::toml::get_at(table, path).is_some()
                 }
             },
+            Self::Expression(expression) => match values {
+                None => false,
+                Some(values) => expression.evaluate(values)?,
+            },
             #[cfg(test)]
             Self::Always(b) => *b,
-        }
+        };
+        Ok(result)
     }
 }
 
@@ -618,6 +989,26 @@ fn parse_string_constraints(raw: &RawParameter) -> anyhow::Result<StringConstrai
     Ok(StringConstraints { regex })
 }
 
+fn parse_choice_constraints(raw: &RawParameter) -> anyhow::Result<ChoiceConstraints> {
+    let allowed_values = raw.allowed_values.clone().unwrap_or_default();
+    if allowed_values.is_empty() {
+        anyhow::bail!("'choice' parameters must specify at least one entry in 'allowed_values'");
+    }
+    Ok(ChoiceConstraints { allowed_values })
+}
+
+fn parse_integer_constraints(raw: &RawParameter) -> anyhow::Result<IntegerConstraints> {
+    if let (Some(min), Some(max)) = (raw.min, raw.max) {
+        if min > max {
+            anyhow::bail!("'min' ({min}) must not be greater than 'max' ({max})");
+        }
+    }
+    Ok(IntegerConstraints {
+        min: raw.min,
+        max: raw.max,
+    })
+}
+
 fn read_install_record(layout: &TemplateLayout) -> InstalledFrom {
     use crate::reader::{parse_installed_from, RawInstalledFrom};
 
@@ -625,6 +1016,11 @@ fn read_install_record(layout: &TemplateLayout) -> InstalledFrom {
     match installed_from_text.and_then(parse_installed_from) {
         Some(RawInstalledFrom::Git { git }) => InstalledFrom::Git(git),
         Some(RawInstalledFrom::File { dir }) => InstalledFrom::Directory(dir),
+        Some(RawInstalledFrom::Oci { oci }) => InstalledFrom::Oci(oci),
+        Some(RawInstalledFrom::Archive { archive, sha256 }) => InstalledFrom::Archive {
+            url: archive,
+            sha256,
+        },
         None => InstalledFrom::Unknown,
     }
 }
@@ -671,7 +1067,8 @@ mod test {
     fn manifest_entry_exists_condition_is_false_for_new_app() {
         let condition = Template::parse_condition(RawCondition::ManifestEntryExists(
             "application.trigger.redis".to_owned(),
-        ));
+        ))
+        .unwrap();
         assert!(!condition.is_true(&TemplateVariantInfo::NewApplication));
     }
 
@@ -681,7 +1078,8 @@ mod test {
             make_temp_manifest("name = \"hello\"\n[application.trigger.http]\nbase = \"/\"");
         let condition = Template::parse_condition(RawCondition::ManifestEntryExists(
             "application.trigger.redis".to_owned(),
-        ));
+        ))
+        .unwrap();
         assert!(!condition.is_true(&TemplateVariantInfo::AddComponent {
             manifest_path: temp_file.path()
         }));
@@ -694,7 +1092,8 @@ mod test {
         );
         let condition = Template::parse_condition(RawCondition::ManifestEntryExists(
             "application.trigger.redis".to_owned(),
-        ));
+        ))
+        .unwrap();
         assert!(condition.is_true(&TemplateVariantInfo::AddComponent {
             manifest_path: temp_file.path()
         }));
@@ -704,12 +1103,157 @@ mod test {
     fn manifest_entry_exists_condition_is_false_if_path_does_not_exist() {
         let condition = Template::parse_condition(RawCondition::ManifestEntryExists(
             "application.trigger.redis".to_owned(),
-        ));
+        ))
+        .unwrap();
         assert!(!condition.is_true(&TemplateVariantInfo::AddComponent {
             manifest_path: PathBuf::from("this/file/does/not.exist")
         }));
     }
 
+    #[test]
+    fn expression_condition_is_evaluated_against_values() {
+        let condition =
+            Template::parse_condition(RawCondition::Expression("include-db".to_owned())).unwrap();
+        let variant_info = TemplateVariantInfo::NewApplication;
+
+        assert!(!condition.evaluate(&variant_info, None).unwrap());
+
+        let values = liquid::object!({ "include-db": true });
+        assert!(condition.evaluate(&variant_info, Some(&values)).unwrap());
+
+        let values = liquid::object!({ "include-db": false });
+        assert!(!condition.evaluate(&variant_info, Some(&values)).unwrap());
+    }
+
+    #[test]
+    fn expression_condition_reports_evaluation_errors() {
+        let condition =
+            Template::parse_condition(RawCondition::Expression("db-kind == 'pg'".to_owned()))
+                .unwrap();
+        let variant_info = TemplateVariantInfo::NewApplication;
+
+        let values = liquid::object!({ "include-db": true });
+        let err = condition
+            .evaluate(&variant_info, Some(&values))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Template error"), "{err}");
+    }
+
+    #[test]
+    fn expression_condition_cannot_skip_parameters() {
+        let raw: RawConditional = toml::from_str(
+            "condition = { expression = \"include-db\" }\nskip_parameters = [\"p1\"]",
+        )
+        .unwrap();
+        assert!(Template::parse_conditional("c1", raw).is_err());
+    }
+
+    #[test]
+    fn computed_parameters_are_typed() {
+        let values = liquid::object!({ "n": 3 });
+        for (expression, expected) in [
+            ("false", liquid_core::Value::scalar(false)),
+            (
+                "{% if n > 2 %}true{% endif %}",
+                liquid_core::Value::scalar(true),
+            ),
+            ("{{ n | plus: 1 }}", liquid_core::Value::scalar(4)),
+            ("007", liquid_core::Value::scalar("007")),
+            ("n{{ n }}", liquid_core::Value::scalar("n3")),
+        ] {
+            let computed = ComputedParameter::parse("c", expression).unwrap();
+            assert_eq!(expected, computed.compute(&values).unwrap(), "{expression}");
+        }
+    }
+
+    #[test]
+    fn computed_parameters_cannot_shadow_parameters() {
+        let parameters = vec![parse_parameter("type = \"bool\"\nprompt = \"?\"").unwrap()];
+        let computed = [("p".to_owned(), "true".to_owned())].into_iter().collect();
+        Template::parse_computed_parameters(&Some(computed), &parameters)
+            .expect_err("should reject a computed parameter named like a parameter");
+    }
+
+    fn parse_parameter(text: &str) -> anyhow::Result<TemplateParameter> {
+        let raw: RawParameter = toml::from_str(text).unwrap();
+        TemplateParameter::from_raw("p", &raw)
+    }
+
+    #[test]
+    fn typed_parameters_normalise_values() {
+        let bool_param = parse_parameter("type = \"bool\"\nprompt = \"?\"").unwrap();
+        assert_eq!("true", bool_param.validate_value("Yes").unwrap());
+        assert_eq!("false", bool_param.validate_value("n").unwrap());
+        assert!(bool_param.validate_value("maybe").is_err());
+
+        let int_param =
+            parse_parameter("type = \"integer\"\nprompt = \"?\"\nmin = 1\nmax = 10").unwrap();
+        assert_eq!("7", int_param.validate_value(" 7 ").unwrap());
+        assert!(int_param.validate_value("0").is_err());
+        assert!(int_param.validate_value("11").is_err());
+        assert!(int_param.validate_value("seven").is_err());
+
+        let list_param =
+            parse_parameter("type = \"list\"\nprompt = \"?\"\npattern = \"^[a-z]+$\"").unwrap();
+        assert_eq!("a,b", list_param.validate_value("a, b,").unwrap());
+        assert!(list_param.validate_value("a,B").is_err());
+    }
+
+    #[test]
+    fn typed_parameters_have_typed_liquid_values() {
+        let bool_param = parse_parameter("type = \"bool\"\nprompt = \"?\"").unwrap();
+        assert_eq!(
+            liquid_core::Value::scalar(false),
+            bool_param.data_type().to_liquid_value("false")
+        );
+
+        let int_param = parse_parameter("type = \"integer\"\nprompt = \"?\"").unwrap();
+        assert_eq!(
+            liquid_core::Value::scalar(42i64),
+            int_param.data_type().to_liquid_value("42")
+        );
+
+        let list_param = parse_parameter("type = \"list\"\nprompt = \"?\"").unwrap();
+        assert_eq!(
+            liquid_core::Value::Array(vec![
+                liquid_core::Value::scalar("a".to_owned()),
+                liquid_core::Value::scalar("b".to_owned())
+            ]),
+            list_param.data_type().to_liquid_value("a,b")
+        );
+    }
+
+    #[test]
+    fn invalid_typed_parameter_definitions_are_rejected() {
+        assert!(parse_parameter("type = \"choice\"\nprompt = \"?\"").is_err());
+        assert!(parse_parameter("type = \"bool\"\nprompt = \"?\"\npattern = \"x\"").is_err());
+        assert!(parse_parameter("type = \"string\"\nprompt = \"?\"\nmin = 1").is_err());
+        assert!(parse_parameter("type = \"integer\"\nprompt = \"?\"\nmin = 5\nmax = 1").is_err());
+        assert!(parse_parameter(
+            "type = \"choice\"\nprompt = \"?\"\nallowed_values = [\"a\"]\ndefault = \"b\""
+        )
+        .is_err());
+        assert!(parse_parameter("type = \"date\"\nprompt = \"?\"").is_err());
+    }
+
+    #[test]
+    fn samples_are_validated_against_parameters() {
+        let parameters = vec![parse_parameter("type = \"bool\"\nprompt = \"?\"").unwrap()];
+        let parse = |text: &str| {
+            let raw: IndexMap<String, IndexMap<String, RawSampleValue>> =
+                toml::from_str(text).unwrap();
+            Template::parse_samples(&Some(raw), &parameters)
+        };
+
+        let samples = parse("[s1]\np = true\n[s2]\np = \"no\"").unwrap();
+        assert_eq!("true", samples[0].values["p"]);
+        assert_eq!("false", samples[1].values["p"]);
+
+        assert!(parse("[s1]\nq = true").is_err());
+        assert!(parse("[s1]\np = \"maybe\"").is_err());
+    }
+
     #[test]
     fn selected_variant_respects_target() {
         let add_component_vt = TemplateVariant {
@@ -745,6 +1289,8 @@ mod test {
             trigger: TemplateTriggerCompatibility::Any,
             variants,
             parameters: vec![],
+            computed_parameters: vec![],
+            samples: vec![],
             extra_outputs: vec![],
             snippets_dir: None,
             content_dir: None,
@@ -0,0 +1,229 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::{
+    manager::ProgressReporter,
+    run::RunOptions,
+    source::TemplateSource,
+    store::TemplateLayout,
+    template::{Template, TemplateSample, TemplateVariantInfo},
+};
+
+// The name given to the generated application or component.
+const SAMPLE_NAME: &str = "verification-sample";
+
+// The application into which add-component variants are generated.
+const HOST_MANIFEST: &str = r#"spin_manifest_version = 2
+
+[application]
+name = "verification-host"
+"#;
+
+const DEFAULTS_SAMPLE_NAME: &str = "(defaults)";
+
+/// Options controlling template verification.
+#[derive(Debug, Default)]
+pub struct VerifyOptions {
+    /// If true, run the build commands of the generated components after
+    /// validating the generated manifest.
+    pub build: bool,
+}
+
+/// The outcome of verifying a template with one sample parameter set in
+/// one variant mode.
+#[derive(Debug)]
+pub struct VerificationResult {
+    /// The ID of the template. If the template could not be loaded, this
+    /// is the name of its directory.
+    pub template_id: String,
+    /// The variant mode that was verified, or `None` if the template could
+    /// not be loaded.
+    pub variant: Option<&'static str>,
+    /// The name of the sample parameter set.
+    pub sample: String,
+    /// The reason verification failed, or `None` if it succeeded.
+    pub error: Option<anyhow::Error>,
+    /// The commands the template would have run after generating files.
+    /// Verification never runs these.
+    pub skipped_commands: Vec<String>,
+}
+
+impl VerificationResult {
+    /// Whether the template passed verification.
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Verifies the templates in the specified source without installing them.
+pub async fn verify_source(
+    source: &TemplateSource,
+    options: &VerifyOptions,
+    reporter: &impl ProgressReporter,
+) -> anyhow::Result<Vec<VerificationResult>> {
+    if source.requires_copy() {
+        reporter.report("Copying remote template source");
+    }
+
+    let local_source = source
+        .get_local()
+        .await
+        .context("Failed to get template source")?;
+    let layouts = local_source
+        .template_directories()
+        .await
+        .context("Could not find templates in source")?
+        .iter()
+        .map(TemplateLayout::new)
+        .collect();
+
+    Ok(verify_layouts(layouts, &[], options, reporter).await)
+}
+
+/// Verifies the templates in the given layouts. If `ids` is non-empty, only
+/// templates with those IDs are verified.
+pub(crate) async fn verify_layouts(
+    layouts: Vec<TemplateLayout>,
+    ids: &[String],
+    options: &VerifyOptions,
+    reporter: &impl ProgressReporter,
+) -> Vec<VerificationResult> {
+    let mut results = vec![];
+
+    for layout in layouts {
+        let template = match Template::load_from(&layout) {
+            Ok(t) => t,
+            Err(e) => {
+                if ids.is_empty() {
+                    results.push(VerificationResult {
+                        template_id: layout_name(&layout),
+                        variant: None,
+                        sample: String::new(),
+                        error: Some(e),
+                        skipped_commands: vec![],
+                    });
+                }
+                continue;
+            }
+        };
+
+        if !ids.is_empty() && !ids.iter().any(|id| id == template.id()) {
+            continue;
+        }
+
+        for variant in verifiable_variants(&template) {
+            for sample in samples(&template) {
+                reporter.report(format!(
+                    "Verifying {} ({}, sample {})...",
+                    template.id(),
+                    variant.description(),
+                    sample.name
+                ));
+                let (error, skipped_commands) =
+                    match verify_sample(&layout, &variant, &sample, options).await {
+                        Ok(skipped_commands) => (None, skipped_commands),
+                        Err(e) => (Some(e), vec![]),
+                    };
+                results.push(VerificationResult {
+                    template_id: template.id().to_owned(),
+                    variant: Some(variant.description()),
+                    sample: sample.name,
+                    error,
+                    skipped_commands,
+                });
+            }
+        }
+    }
+
+    results.sort_by(|a, b| a.template_id.cmp(&b.template_id));
+    results
+}
+
+fn verifiable_variants(template: &Template) -> Vec<TemplateVariantInfo> {
+    // The manifest path here is a placeholder: each verification run
+    // generates into its own temporary directory.
+    let candidates = [
+        TemplateVariantInfo::NewApplication,
+        TemplateVariantInfo::AddComponent {
+            manifest_path: PathBuf::new(),
+        },
+    ];
+    candidates
+        .into_iter()
+        .filter(|v| template.supports_variant(v))
+        .collect()
+}
+
+fn samples(template: &Template) -> Vec<TemplateSample> {
+    if template.samples().is_empty() {
+        vec![TemplateSample {
+            name: DEFAULTS_SAMPLE_NAME.to_owned(),
+            values: Default::default(),
+        }]
+    } else {
+        template.samples().to_vec()
+    }
+}
+
+async fn verify_sample(
+    layout: &TemplateLayout,
+    variant: &TemplateVariantInfo,
+    sample: &TemplateSample,
+    options: &VerifyOptions,
+) -> anyhow::Result<Vec<String>> {
+    // Running a template consumes it, so each sample gets a fresh copy.
+    let template = Template::load_from(layout)?;
+
+    let temp_dir = tempfile::tempdir().context("Failed to create verification directory")?;
+    let app_dir = temp_dir.path().join("app");
+    let manifest_path = app_dir.join("spin.toml");
+
+    let (variant, output_path) = match variant {
+        TemplateVariantInfo::NewApplication => (TemplateVariantInfo::NewApplication, app_dir),
+        TemplateVariantInfo::AddComponent { .. } => {
+            tokio::fs::create_dir_all(&app_dir).await?;
+            tokio::fs::write(&manifest_path, HOST_MANIFEST).await?;
+            let variant = TemplateVariantInfo::AddComponent {
+                manifest_path: manifest_path.clone(),
+            };
+            (variant, PathBuf::from(SAMPLE_NAME))
+        }
+    };
+
+    let run_options = RunOptions {
+        variant,
+        name: SAMPLE_NAME.to_owned(),
+        output_path,
+        values: sample.values.clone(),
+        accept_defaults: true,
+        no_vcs: true,
+        allow_run_commands: false,
+    };
+    let skipped_commands = template
+        .run(run_options)
+        .verify()
+        .await
+        .context("Failed to generate from template")?;
+
+    if !manifest_path.exists() {
+        anyhow::bail!("Template did not generate a spin.toml file");
+    }
+    spin_manifest::manifest_from_file(&manifest_path)
+        .context("Generated spin.toml is not a valid application manifest")?;
+
+    if options.build {
+        spin_build::build(&manifest_path, &[])
+            .await
+            .context("Failed to build generated application")?;
+    }
+
+    Ok(skipped_commands)
+}
+
+fn layout_name(layout: &TemplateLayout) -> String {
+    let dir = layout.template_dir();
+    dir.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| format!("{}", dir.display()))
+}
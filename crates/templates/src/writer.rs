@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 
@@ -11,6 +11,8 @@ pub(crate) enum TemplateOutput {
     AppendToml(PathBuf, String),
     MergeToml(PathBuf, &'static str, String), // only have to worry about merging into root table for now
     CreateDirectory(PathBuf),
+    Rename(PathBuf, PathBuf),
+    RunCommand(PathBuf, String), // working directory, command
 }
 
 impl TemplateOutputs {
@@ -63,11 +65,41 @@ impl TemplateOutput {
                     .await
                     .with_context(|| format!("Failed to create directory {}", dir.display()))?;
             }
+            TemplateOutput::Rename(from, to) => {
+                tokio::fs::rename(from, to).await.with_context(|| {
+                    format!("Failed to rename {} to {}", from.display(), to.display())
+                })?;
+            }
+            TemplateOutput::RunCommand(dir, command) => {
+                run_command(dir, command).await?;
+            }
         }
         Ok(())
     }
 }
 
+async fn run_command(dir: &Path, command: &str) -> anyhow::Result<()> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    let status = cmd
+        .arg(command)
+        .current_dir(dir)
+        .status()
+        .await
+        .with_context(|| format!("Failed to run '{command}'"))?;
+    if !status.success() {
+        anyhow::bail!("Command '{command}' failed with {status}");
+    }
+    Ok(())
+}
+
 fn merge_toml(existing: &str, target: &str, text: &str) -> anyhow::Result<String> {
     use toml_edit::{Document, Entry, Item};
 
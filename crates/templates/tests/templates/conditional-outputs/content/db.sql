@@ -0,0 +1,2 @@
+-- {{ db-file }}
+CREATE DATABASE {{ db-name }};
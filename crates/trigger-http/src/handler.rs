@@ -114,6 +114,7 @@ impl HttpHandlerExecutor {
         };
 
         let (resp,) = func.call_async(&mut store, (req,)).await?;
+        record_memory_high_water(&store, route_match.component_id());
 
         if resp.status < 100 || resp.status > 600 {
             tracing::error!("malformed HTTP status code");
@@ -211,6 +212,7 @@ impl HttpHandlerExecutor {
                 }
             };
 
+        let component_id = route_match.component_id().to_owned();
         let span = tracing::debug_span!("execute_wasi");
         let handle = task::spawn(
             async move {
@@ -240,6 +242,7 @@ impl HttpHandlerExecutor {
                     "wasi-http memory consumed: {}",
                     store.as_ref().data().memory_consumed()
                 );
+                record_memory_high_water(&store, &component_id);
 
                 result
             }
@@ -414,6 +417,15 @@ fn set_http_origin_from_request(
     }
 }
 
+/// Records the memory consumed by an instance once it has handled a request.
+/// Wasm memories never shrink, so this is the instance's high-water mark.
+fn record_memory_high_water(store: &Store, component_id: &str) {
+    spin_telemetry::metrics::histogram!(
+        spin.instance_memory_high_water = store.as_ref().data().memory_consumed(),
+        component_id = component_id
+    );
+}
+
 fn contextualise_err(e: anyhow::Error) -> anyhow::Error {
     if e.to_string()
         .contains("failed to find function export `canonical_abi_free`")
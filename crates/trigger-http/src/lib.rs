@@ -2,6 +2,7 @@
 
 mod handler;
 mod instrument;
+mod metrics;
 mod tls;
 mod wagi;
 
@@ -86,6 +87,10 @@ pub struct CliArgs {
     /// The path to the certificate key to use for https, if this is not set, normal http will be used. The key should be in PKCS#8 format
     #[clap(long, env = "SPIN_TLS_KEY", requires = "tls-cert")]
     pub tls_key: Option<PathBuf>,
+
+    /// IP address and port on which to serve runtime metrics in the Prometheus text format, at the /metrics path. If this is not set, metrics are not served
+    #[clap(long = "metrics-listen", env = "SPIN_METRICS_LISTEN_ADDR", value_parser = parse_listen_addr)]
+    pub metrics_address: Option<SocketAddr>,
 }
 
 impl CliArgs {
@@ -168,8 +173,13 @@ impl TriggerExecutor for HttpTrigger {
 
     async fn run(self, config: Self::RunConfig) -> Result<()> {
         let listen_addr = config.address;
+        let metrics_addr = config.metrics_address;
         let tls = config.into_tls_config();
 
+        if let Some(metrics_addr) = metrics_addr {
+            metrics::spawn_metrics_server(metrics_addr).await?;
+        }
+
         let listener = TcpListener::bind(listen_addr)
             .await
             .with_context(|| format!("Unable to listen on {}", listen_addr))?;
@@ -276,6 +286,7 @@ impl HttpTrigger {
 
                 let executor = trigger.executor.as_ref().unwrap_or(&HttpExecutorType::Http);
 
+                let start = std::time::Instant::now();
                 let res = match executor {
                     HttpExecutorType::Http => {
                         HttpHandlerExecutor
@@ -303,6 +314,12 @@ impl HttpTrigger {
                             .await
                     }
                 };
+                spin_telemetry::metrics::histogram!(
+                    spin.request_duration = start.elapsed().as_secs_f64(),
+                    trigger_type = "http",
+                    app_id = &self.engine.app_name,
+                    component_id = component_id
+                );
                 match res {
                     Ok(res) => Ok(MatchedRoute::with_response_extension(
                         res,
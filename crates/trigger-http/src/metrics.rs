@@ -0,0 +1,68 @@
+//! Serves runtime metrics in the Prometheus text format.
+
+use std::net::SocketAddr;
+
+use anyhow::{Context, Result};
+use http::StatusCode;
+use hyper::{body::Incoming, server::conn::http1, service::service_fn, Request, Response};
+use hyper_util::rt::tokio::TokioIo;
+use spin_http::body;
+use tokio::{net::TcpListener, task};
+use tracing::log;
+use wasmtime_wasi_http::body::HyperIncomingBody as Body;
+
+/// The path at which metrics are served.
+const METRICS_PATH: &str = "/metrics";
+
+/// Binds the metrics listener and serves metrics from it in the background.
+pub(crate) async fn spawn_metrics_server(listen_addr: SocketAddr) -> Result<()> {
+    let listener = TcpListener::bind(listen_addr)
+        .await
+        .with_context(|| format!("Unable to listen for metrics on {}", listen_addr))?;
+    let local_addr = listener.local_addr()?;
+    terminal::step!("Metrics", "http://{local_addr}{METRICS_PATH}");
+    log::info!("Serving metrics on http://{local_addr}{METRICS_PATH}");
+
+    task::spawn(async move {
+        loop {
+            let (stream, _) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    log::warn!("Failed to accept metrics connection: {e:?}");
+                    continue;
+                }
+            };
+            task::spawn(async move {
+                if let Err(e) = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service_fn(handle))
+                    .await
+                {
+                    log::warn!("{e:?}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+async fn handle(req: Request<Incoming>) -> Result<Response<Body>> {
+    if req.uri().path() != METRICS_PATH {
+        return Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(body::empty())?);
+    }
+    match spin_telemetry::metrics::prometheus_text() {
+        Ok(text) => Ok(Response::builder()
+            .header(
+                "content-type",
+                spin_telemetry::metrics::PROMETHEUS_CONTENT_TYPE,
+            )
+            .body(body::full(text.into()))?),
+        Err(e) => {
+            log::error!("Failed to gather metrics: {e:?}");
+            Ok(Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(body::empty())?)
+        }
+    }
+}
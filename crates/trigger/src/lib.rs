@@ -17,7 +17,7 @@ use spin_core::{
     StoreBuilder, WasiVersion,
 };
 
-pub use crate::runtime_config::RuntimeConfig;
+pub use crate::runtime_config::{RuntimeConfig, RuntimeConfigOpts};
 
 #[async_trait]
 pub trait TriggerExecutor: Sized + Send + Sync {
@@ -166,15 +166,19 @@ impl<Executor: TriggerExecutor> TriggerExecutorBuilder<Executor> {
                 )?;
                 self.loader.add_dynamic_host_component(
                     &mut builder,
-                    outbound_mysql::OutboundMysqlComponent {
-                        resolver: resolver_cell.clone(),
-                    },
+                    outbound_mysql::OutboundMysqlComponent::new(
+                        resolver_cell.clone(),
+                        runtime_config.database_pool()?,
+                        runtime_config.mysql_databases()?,
+                    ),
                 )?;
                 self.loader.add_dynamic_host_component(
                     &mut builder,
-                    outbound_pg::OutboundPgComponent {
-                        resolver: resolver_cell.clone(),
-                    },
+                    outbound_pg::OutboundPgComponent::new(
+                        resolver_cell.clone(),
+                        runtime_config.database_pool()?,
+                        runtime_config.postgres_databases()?,
+                    ),
                 )?;
                 self.loader.add_dynamic_host_component(
                     &mut builder,
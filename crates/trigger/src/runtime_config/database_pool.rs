@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+use spin_db_pool::PoolConfig;
+
+/// Options for the `[database_pool]` runtime config section, which controls
+/// how outbound Postgres and MySQL connections are pooled. Unset options
+/// take the pool defaults.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DatabasePoolOpts {
+    /// The maximum number of connections open at once to each database.
+    #[serde(default)]
+    pub max_size: Option<usize>,
+    /// How long an unused connection is kept open, in seconds.
+    #[serde(default)]
+    pub idle_timeout_secs: Option<u64>,
+    /// How long to wait for a connection when the pool is full, in seconds.
+    #[serde(default)]
+    pub acquire_timeout_secs: Option<u64>,
+    /// Whether to check idle connections are usable before reusing them.
+    #[serde(default)]
+    pub health_check: Option<bool>,
+}
+
+impl DatabasePoolOpts {
+    pub(crate) fn pool_config(&self) -> anyhow::Result<PoolConfig> {
+        if self.max_size == Some(0) {
+            anyhow::bail!("[database_pool] `max_size` must be greater than zero");
+        }
+        let default = PoolConfig::default();
+        Ok(PoolConfig {
+            max_size: self.max_size.unwrap_or(default.max_size),
+            idle_timeout: self
+                .idle_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(default.idle_timeout),
+            acquire_timeout: self
+                .acquire_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(default.acquire_timeout),
+            health_check: self.health_check.unwrap_or(default.health_check),
+        })
+    }
+}
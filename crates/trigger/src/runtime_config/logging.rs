@@ -0,0 +1,78 @@
+use serde::Deserialize;
+
+const DEFAULT_RETAIN: usize = 5;
+
+/// Options for the `[logging]` runtime config section, which controls how
+/// component stdout/stderr is written to the log dir.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoggingOpts {
+    /// The format of component log files.
+    #[serde(default)]
+    pub format: LogFormat,
+    /// When to rotate component log files. If unset, log files grow without
+    /// bound.
+    #[serde(default)]
+    pub rotation: Option<RotationOpts>,
+}
+
+/// The format of component log files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Component output is written as-is.
+    #[default]
+    Text,
+    /// Each line of component output is written as a JSON object carrying
+    /// the timestamp, component ID, stream, request ID and trace ID.
+    Json,
+}
+
+/// Options for rotating component log files.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RotationOpts {
+    /// Rotate a log file before it would grow beyond this many bytes.
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+    /// Rotate log files at the start of each period.
+    #[serde(default)]
+    pub interval: Option<RotationInterval>,
+    /// The number of rotated log files to keep for each component stream.
+    #[serde(default = "default_retain")]
+    pub retain: usize,
+}
+
+fn default_retain() -> usize {
+    DEFAULT_RETAIN
+}
+
+impl RotationOpts {
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        if self.max_bytes.is_none() && self.interval.is_none() {
+            anyhow::bail!("[logging.rotation] must set at least one of `max_bytes` or `interval`");
+        }
+        if self.max_bytes == Some(0) {
+            anyhow::bail!("[logging.rotation] `max_bytes` must be greater than zero");
+        }
+        Ok(())
+    }
+}
+
+/// A time period after which log files are rotated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationInterval {
+    Hourly,
+    Daily,
+}
+
+impl RotationInterval {
+    /// The length of the period in seconds.
+    pub fn as_secs(&self) -> u64 {
+        match self {
+            Self::Hourly => 60 * 60,
+            Self::Daily => 24 * 60 * 60,
+        }
+    }
+}
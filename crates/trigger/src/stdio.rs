@@ -1,14 +1,26 @@
+mod log_file;
+
 use std::{
-    collections::HashSet,
-    path::{Path, PathBuf},
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
     task::Poll,
 };
 
 use anyhow::{Context, Result};
+use serde::Serialize;
 use spin_common::ui::quoted_path;
 use tokio::io::AsyncWrite;
 
-use crate::{runtime_config::RuntimeConfig, TriggerHooks};
+use crate::{
+    runtime_config::{
+        logging::{LogFormat, LoggingOpts},
+        RuntimeConfig,
+    },
+    TriggerHooks,
+};
+
+use self::log_file::LogFile;
 
 /// Which components should have their logs followed on stdout/stderr.
 #[derive(Clone, Debug)]
@@ -42,6 +54,9 @@ impl Default for FollowComponents {
 pub struct StdioLoggingTriggerHooks {
     follow_components: FollowComponents,
     log_dir: Option<PathBuf>,
+    log_format: LogFormat,
+    // Log files keyed by component ID and stream, shared by all instances
+    log_files: HashMap<(String, &'static str), Arc<LogFile>>,
 }
 
 impl StdioLoggingTriggerHooks {
@@ -49,24 +64,52 @@ impl StdioLoggingTriggerHooks {
         Self {
             follow_components,
             log_dir: None,
+            log_format: LogFormat::default(),
+            log_files: HashMap::new(),
         }
     }
 
+    fn open_log_files(&mut self, app: &spin_app::App, logging: &LoggingOpts) -> Result<()> {
+        let Some(log_dir) = &self.log_dir else {
+            return Ok(());
+        };
+        for component in app.components() {
+            let sanitized_component_id = sanitize_filename::sanitize(component.id());
+            for stream in ["stdout", "stderr"] {
+                let log_path = log_dir.join(format!("{sanitized_component_id}_{stream}.txt"));
+                let log_file =
+                    LogFile::open(&log_path, logging.rotation.clone()).with_context(|| {
+                        format!("Failed to open log file {}", quoted_path(&log_path))
+                    })?;
+                self.log_files
+                    .insert((component.id().to_owned(), stream), Arc::new(log_file));
+            }
+        }
+        Ok(())
+    }
+
     fn component_stdio_writer(
         &self,
         component_id: &str,
-        log_suffix: &str,
-        log_dir: Option<&Path>,
+        stream: &'static str,
+        request_id: &str,
     ) -> Result<ComponentStdioWriter> {
-        let sanitized_component_id = sanitize_filename::sanitize(component_id);
-        let log_path = log_dir
-            .map(|log_dir| log_dir.join(format!("{sanitized_component_id}_{log_suffix}.txt",)));
-        let log_path = log_path.as_deref();
-
         let follow = self.follow_components.should_follow(component_id);
-        match log_path {
-            Some(log_path) => ComponentStdioWriter::new_forward(log_path, follow)
-                .with_context(|| format!("Failed to open log file {}", quoted_path(log_path))),
+        match self.log_files.get(&(component_id.to_owned(), stream)) {
+            Some(file) => {
+                let log = ComponentLog {
+                    file: file.clone(),
+                    format: self.log_format,
+                    context: LogContext {
+                        component_id: component_id.to_owned(),
+                        stream,
+                        request_id: request_id.to_owned(),
+                        trace_id: spin_telemetry::current_trace_id(),
+                    },
+                    pending: vec![],
+                };
+                ComponentStdioWriter::new_forward(log, follow)
+            }
             None => ComponentStdioWriter::new_inherit(),
         }
     }
@@ -99,6 +142,8 @@ impl TriggerHooks for StdioLoggingTriggerHooks {
         _resolver: &std::sync::Arc<spin_expressions::PreparedResolver>,
     ) -> anyhow::Result<()> {
         self.log_dir = runtime_config.log_dir();
+        let logging = runtime_config.logging()?;
+        self.log_format = logging.format;
 
         self.validate_follows(app)?;
 
@@ -110,7 +155,7 @@ impl TriggerHooks for StdioLoggingTriggerHooks {
             println!("Logging component stdio to {}", quoted_path(dir.join("")))
         }
 
-        Ok(())
+        self.open_log_files(app, &logging)
     }
 
     fn component_store_builder(
@@ -118,21 +163,93 @@ impl TriggerHooks for StdioLoggingTriggerHooks {
         component: &spin_app::AppComponent,
         builder: &mut spin_core::StoreBuilder,
     ) -> anyhow::Result<()> {
-        builder.stdout_pipe(self.component_stdio_writer(
-            component.id(),
-            "stdout",
-            self.log_dir.as_deref(),
-        )?);
-        builder.stderr_pipe(self.component_stdio_writer(
-            component.id(),
-            "stderr",
-            self.log_dir.as_deref(),
-        )?);
+        // Each store serves a single request, so this correlates all output
+        // written while handling it.
+        let request_id = uuid::Uuid::new_v4().to_string();
+        builder.stdout_pipe(self.component_stdio_writer(component.id(), "stdout", &request_id)?);
+        builder.stderr_pipe(self.component_stdio_writer(component.id(), "stderr", &request_id)?);
 
         Ok(())
     }
 }
 
+/// The fields which identify where a line of component output came from.
+struct LogContext {
+    component_id: String,
+    stream: &'static str,
+    request_id: String,
+    trace_id: Option<String>,
+}
+
+/// A single line of component output in the JSON log format.
+#[derive(Serialize)]
+struct LogRecord<'a> {
+    timestamp: String,
+    component_id: &'a str,
+    stream: &'a str,
+    request_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace_id: Option<&'a str>,
+    message: std::borrow::Cow<'a, str>,
+}
+
+/// One instance's view of a component log file.
+struct ComponentLog {
+    file: Arc<LogFile>,
+    format: LogFormat,
+    context: LogContext,
+    // In JSON format, output not yet terminated by a newline
+    pending: Vec<u8>,
+}
+
+impl ComponentLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        match self.format {
+            LogFormat::Text => self.file.write_all(buf),
+            LogFormat::Json => {
+                self.pending.extend_from_slice(buf);
+                while let Some(pos) = self.pending.iter().position(|b| *b == b'\n') {
+                    let line = self.pending.drain(..=pos).collect::<Vec<_>>();
+                    self.write_record(&line[..pos])?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes out any incomplete final line.
+    fn finish(&mut self) -> std::io::Result<()> {
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            self.write_record(&line)?;
+        }
+        Ok(())
+    }
+
+    fn write_record(&self, line: &[u8]) -> std::io::Result<()> {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let record = LogRecord {
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            component_id: &self.context.component_id,
+            stream: self.context.stream,
+            request_id: &self.context.request_id,
+            trace_id: self.context.trace_id.as_deref(),
+            message: String::from_utf8_lossy(line),
+        };
+        let mut json = serde_json::to_vec(&record)?;
+        json.push(b'\n');
+        self.file.write_all(&json)
+    }
+}
+
+impl Drop for ComponentLog {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            tracing::warn!("Failed to write component log: {e}");
+        }
+    }
+}
+
 /// ComponentStdioWriter forwards output to a log file, (optionally) stderr, and (optionally) to a
 /// tracing compatibility layer.
 pub struct ComponentStdioWriter {
@@ -144,8 +261,7 @@ enum ComponentStdioWriterInner {
     Inherit,
     /// Forward stdout/stderr to a file in addition to the inherited stdout/stderr.
     Forward {
-        sync_file: std::fs::File,
-        async_file: tokio::fs::File,
+        log: ComponentLog,
         state: ComponentStdioWriterState,
         follow: bool,
     },
@@ -158,21 +274,10 @@ enum ComponentStdioWriterState {
 }
 
 impl ComponentStdioWriter {
-    fn new_forward(log_path: &Path, follow: bool) -> anyhow::Result<Self> {
-        let sync_file = std::fs::File::options()
-            .create(true)
-            .append(true)
-            .open(log_path)?;
-
-        let async_file = sync_file
-            .try_clone()
-            .context("could not get async file handle")?
-            .into();
-
+    fn new_forward(log: ComponentLog, follow: bool) -> anyhow::Result<Self> {
         Ok(Self {
             inner: ComponentStdioWriterInner::Forward {
-                sync_file,
-                async_file,
+                log,
                 state: ComponentStdioWriterState::File,
                 follow,
             },
@@ -207,18 +312,15 @@ impl AsyncWrite for ComponentStdioWriter {
                     return Poll::Ready(Ok(written));
                 }
                 ComponentStdioWriterInner::Forward {
-                    async_file,
-                    state,
-                    follow,
-                    ..
+                    log, state, follow, ..
                 } => match &state {
                     ComponentStdioWriterState::File => {
-                        let written =
-                            futures::ready!(std::pin::Pin::new(async_file).poll_write(cx, buf));
-                        let written = match written {
-                            Ok(w) => w,
-                            Err(e) => return Poll::Ready(Err(e)),
-                        };
+                        // Output is queued for the log file's writer thread, so
+                        // this doesn't block.
+                        if let Err(e) = log.write(buf) {
+                            return Poll::Ready(Err(e));
+                        }
+                        let written = buf.len();
                         if *follow {
                             *state = ComponentStdioWriterState::Follow(0..written);
                         } else {
@@ -257,10 +359,10 @@ impl AsyncWrite for ComponentStdioWriter {
             ComponentStdioWriterInner::Inherit => {
                 std::pin::Pin::new(&mut tokio::io::stderr()).poll_flush(cx)
             }
-            ComponentStdioWriterInner::Forward {
-                async_file, state, ..
-            } => match state {
-                ComponentStdioWriterState::File => std::pin::Pin::new(async_file).poll_flush(cx),
+            ComponentStdioWriterInner::Forward { state, .. } => match state {
+                // The log file's writer thread writes queued output as soon as
+                // it can, and there is nothing buffered here to flush.
+                ComponentStdioWriterState::File => Poll::Ready(Ok(())),
                 ComponentStdioWriterState::Follow(_) => {
                     std::pin::Pin::new(&mut tokio::io::stderr()).poll_flush(cx)
                 }
@@ -278,10 +380,8 @@ impl AsyncWrite for ComponentStdioWriter {
             ComponentStdioWriterInner::Inherit => {
                 std::pin::Pin::new(&mut tokio::io::stderr()).poll_flush(cx)
             }
-            ComponentStdioWriterInner::Forward {
-                async_file, state, ..
-            } => match state {
-                ComponentStdioWriterState::File => std::pin::Pin::new(async_file).poll_shutdown(cx),
+            ComponentStdioWriterInner::Forward { log, state, .. } => match state {
+                ComponentStdioWriterState::File => Poll::Ready(log.finish()),
                 ComponentStdioWriterState::Follow(_) => {
                     std::pin::Pin::new(&mut tokio::io::stderr()).poll_flush(cx)
                 }
@@ -299,14 +399,12 @@ impl std::io::Write for ComponentStdioWriter {
                 std::io::stderr().write_all(buf)?;
                 Ok(buf.len())
             }
-            ComponentStdioWriterInner::Forward {
-                sync_file, follow, ..
-            } => {
-                let written = sync_file.write(buf)?;
+            ComponentStdioWriterInner::Forward { log, follow, .. } => {
+                log.write(buf)?;
                 if *follow {
-                    std::io::stderr().write_all(&buf[..written])?;
+                    std::io::stderr().write_all(buf)?;
                 }
-                Ok(written)
+                Ok(buf.len())
             }
         }
     }
@@ -314,10 +412,7 @@ impl std::io::Write for ComponentStdioWriter {
     fn flush(&mut self) -> std::io::Result<()> {
         match &mut self.inner {
             ComponentStdioWriterInner::Inherit => std::io::stderr().flush(),
-            ComponentStdioWriterInner::Forward {
-                sync_file, follow, ..
-            } => {
-                sync_file.flush()?;
+            ComponentStdioWriterInner::Forward { follow, .. } => {
                 if *follow {
                     std::io::stderr().flush()?;
                 }
@@ -0,0 +1,233 @@
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread::JoinHandle,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::runtime_config::logging::RotationOpts;
+
+/// A component log file which may be shared by many concurrent instances of
+/// the component, and which is rotated according to the logging runtime config.
+///
+/// Writing and rotating the file block, so they are done by a thread which
+/// owns the file rather than on the async runtime. Instances queue output for
+/// it and never wait on the file or on each other.
+pub(crate) struct LogFile {
+    sender: Option<mpsc::Sender<Message>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+enum Message {
+    Write(Vec<u8>),
+    Flush(mpsc::Sender<std::io::Result<()>>),
+}
+
+struct LogFileWriter {
+    path: PathBuf,
+    rotation: Option<RotationOpts>,
+    file: File,
+    size: u64,
+    // The rotation period the file was last written in.
+    period: Option<u64>,
+}
+
+impl LogFile {
+    pub fn open(path: &Path, rotation: Option<RotationOpts>) -> std::io::Result<Self> {
+        let file = open_append(path)?;
+        let metadata = file.metadata()?;
+        let period = rotation
+            .as_ref()
+            .and_then(|r| r.interval)
+            .map(|interval| period_of(metadata.modified().ok(), interval.as_secs()));
+        let mut writer = LogFileWriter {
+            path: path.to_owned(),
+            rotation,
+            file,
+            size: metadata.len(),
+            period,
+        };
+        let (sender, receiver) = mpsc::channel();
+        let writer = std::thread::Builder::new()
+            .name("component-log-writer".into())
+            .spawn(move || writer.run(receiver))?;
+        Ok(Self {
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+
+    /// Queues `buf` to be appended to the log file, rotating it first if
+    /// required. The whole buffer is written to one file so that records are
+    /// never split across files.
+    pub fn write_all(&self, buf: &[u8]) -> std::io::Result<()> {
+        self.send(Message::Write(buf.to_vec()))
+    }
+
+    /// Waits until everything queued so far has been written to the file.
+    #[cfg(test)]
+    pub fn flush(&self) -> std::io::Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        self.send(Message::Flush(sender))?;
+        receiver.recv().map_err(|_| writer_stopped())?
+    }
+
+    fn send(&self, message: Message) -> std::io::Result<()> {
+        self.sender
+            .as_ref()
+            .ok_or_else(writer_stopped)?
+            .send(message)
+            .map_err(|_| writer_stopped())
+    }
+}
+
+impl Drop for LogFile {
+    // Waits for queued output to be written, so that none is lost on exit.
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        if let Some(writer) = self.writer.take() {
+            if writer.join().is_err() {
+                tracing::error!("Component log writer panicked");
+            }
+        }
+    }
+}
+
+impl LogFileWriter {
+    fn run(&mut self, receiver: mpsc::Receiver<Message>) {
+        for message in receiver {
+            match message {
+                Message::Write(buf) => {
+                    if let Err(e) = self.write_all(&buf) {
+                        tracing::warn!(
+                            "Failed to write component log {}: {e}",
+                            self.path.display()
+                        );
+                    }
+                }
+                Message::Flush(done) => {
+                    _ = done.send(self.file.flush());
+                }
+            }
+        }
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        if let Some(rotation) = self.rotation.clone() {
+            if self.needs_rotation(&rotation, buf.len() as u64) {
+                self.file = self.rotate(&rotation)?;
+                self.size = 0;
+            }
+        }
+        self.file.write_all(buf)?;
+        self.size += buf.len() as u64;
+        Ok(())
+    }
+
+    fn needs_rotation(&mut self, rotation: &RotationOpts, len: u64) -> bool {
+        let mut rotate = false;
+        if let Some(max_bytes) = rotation.max_bytes {
+            rotate |= self.size > 0 && self.size + len > max_bytes;
+        }
+        if let Some(interval) = rotation.interval {
+            let now = period_of(Some(SystemTime::now()), interval.as_secs());
+            rotate |= self.size > 0 && self.period.is_some_and(|p| p < now);
+            self.period = Some(now);
+        }
+        rotate
+    }
+
+    // Shifts `<path>.1` .. `<path>.<retain - 1>` up by one, moves the current
+    // file to `<path>.1`, and drops anything beyond the retention count.
+    fn rotate(&self, rotation: &RotationOpts) -> std::io::Result<File> {
+        if rotation.retain == 0 {
+            return File::create(&self.path);
+        }
+        remove_if_exists(&self.rotated_path(rotation.retain))?;
+        for n in (1..rotation.retain).rev() {
+            let from = self.rotated_path(n);
+            if from.exists() {
+                std::fs::rename(from, self.rotated_path(n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated_path(1))?;
+        open_append(&self.path)
+    }
+
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_owned();
+        name.push(format!(".{n}"));
+        self.path.with_file_name(name)
+    }
+}
+
+fn writer_stopped() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::BrokenPipe,
+        "component log writer has stopped",
+    )
+}
+
+fn open_append(path: &Path) -> std::io::Result<File> {
+    File::options().create(true).append(true).open(path)
+}
+
+fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn period_of(time: Option<SystemTime>, interval_secs: u64) -> u64 {
+    time.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() / interval_secs)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rotation(max_bytes: u64, retain: usize) -> Option<RotationOpts> {
+        Some(RotationOpts {
+            max_bytes: Some(max_bytes),
+            interval: None,
+            retain,
+        })
+    }
+
+    #[test]
+    fn rotates_by_size_and_keeps_retain_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("comp_stdout.txt");
+        let log = LogFile::open(&path, rotation(8, 2)).unwrap();
+
+        for line in ["one\n", "two\n", "three\n", "four\n", "five\n"] {
+            log.write_all(line.as_bytes()).unwrap();
+        }
+        log.flush().unwrap();
+
+        let read = |name: &str| std::fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!("five\n", read("comp_stdout.txt"));
+        assert_eq!("four\n", read("comp_stdout.txt.1"));
+        assert_eq!("three\n", read("comp_stdout.txt.2"));
+        assert!(!dir.path().join("comp_stdout.txt.3").exists());
+    }
+
+    #[test]
+    fn does_not_rotate_without_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("comp_stdout.txt");
+        let log = LogFile::open(&path, None).unwrap();
+
+        for _ in 0..100 {
+            log.write_all(b"a line of output\n").unwrap();
+        }
+        log.flush().unwrap();
+
+        assert!(!dir.path().join("comp_stdout.txt.1").exists());
+    }
+}
@@ -129,6 +129,157 @@ mod rdbms_types {
     }
 }
 
+mod rdbms_types_v3 {
+    use super::*;
+
+    impl From<v3::rdbms_types::Column> for v2::rdbms_types::Column {
+        fn from(value: v3::rdbms_types::Column) -> Self {
+            v2::rdbms_types::Column {
+                name: value.name,
+                data_type: value.data_type.into(),
+            }
+        }
+    }
+
+    // Types added in v3 have no v2 equivalent, so are reported as unsupported.
+    impl From<v3::rdbms_types::DbValue> for v2::rdbms_types::DbValue {
+        fn from(value: v3::rdbms_types::DbValue) -> v2::rdbms_types::DbValue {
+            match value {
+                v3::rdbms_types::DbValue::Boolean(b) => v2::rdbms_types::DbValue::Boolean(b),
+                v3::rdbms_types::DbValue::Int8(i) => v2::rdbms_types::DbValue::Int8(i),
+                v3::rdbms_types::DbValue::Int16(i) => v2::rdbms_types::DbValue::Int16(i),
+                v3::rdbms_types::DbValue::Int32(i) => v2::rdbms_types::DbValue::Int32(i),
+                v3::rdbms_types::DbValue::Int64(i) => v2::rdbms_types::DbValue::Int64(i),
+                v3::rdbms_types::DbValue::Uint8(j) => v2::rdbms_types::DbValue::Uint8(j),
+                v3::rdbms_types::DbValue::Uint16(u) => v2::rdbms_types::DbValue::Uint16(u),
+                v3::rdbms_types::DbValue::Uint32(u) => v2::rdbms_types::DbValue::Uint32(u),
+                v3::rdbms_types::DbValue::Uint64(u) => v2::rdbms_types::DbValue::Uint64(u),
+                v3::rdbms_types::DbValue::Floating32(r) => v2::rdbms_types::DbValue::Floating32(r),
+                v3::rdbms_types::DbValue::Floating64(r) => v2::rdbms_types::DbValue::Floating64(r),
+                v3::rdbms_types::DbValue::Str(s) => v2::rdbms_types::DbValue::Str(s),
+                v3::rdbms_types::DbValue::Binary(b) => v2::rdbms_types::DbValue::Binary(b),
+                v3::rdbms_types::DbValue::DbNull => v2::rdbms_types::DbValue::DbNull,
+                v3::rdbms_types::DbValue::Date(_)
+                | v3::rdbms_types::DbValue::Time(_)
+                | v3::rdbms_types::DbValue::Datetime(_)
+                | v3::rdbms_types::DbValue::Timestamp(_)
+                | v3::rdbms_types::DbValue::Uuid(_)
+                | v3::rdbms_types::DbValue::Json(_)
+                | v3::rdbms_types::DbValue::Decimal(_)
+                | v3::rdbms_types::DbValue::ArrayBoolean(_)
+                | v3::rdbms_types::DbValue::ArrayInt32(_)
+                | v3::rdbms_types::DbValue::ArrayInt64(_)
+                | v3::rdbms_types::DbValue::ArrayFloating64(_)
+                | v3::rdbms_types::DbValue::ArrayStr(_)
+                | v3::rdbms_types::DbValue::Unsupported => v2::rdbms_types::DbValue::Unsupported,
+            }
+        }
+    }
+
+    impl From<v3::rdbms_types::DbDataType> for v2::rdbms_types::DbDataType {
+        fn from(value: v3::rdbms_types::DbDataType) -> v2::rdbms_types::DbDataType {
+            match value {
+                v3::rdbms_types::DbDataType::Boolean => v2::rdbms_types::DbDataType::Boolean,
+                v3::rdbms_types::DbDataType::Int8 => v2::rdbms_types::DbDataType::Int8,
+                v3::rdbms_types::DbDataType::Int16 => v2::rdbms_types::DbDataType::Int16,
+                v3::rdbms_types::DbDataType::Int32 => v2::rdbms_types::DbDataType::Int32,
+                v3::rdbms_types::DbDataType::Int64 => v2::rdbms_types::DbDataType::Int64,
+                v3::rdbms_types::DbDataType::Uint8 => v2::rdbms_types::DbDataType::Uint8,
+                v3::rdbms_types::DbDataType::Uint16 => v2::rdbms_types::DbDataType::Uint16,
+                v3::rdbms_types::DbDataType::Uint32 => v2::rdbms_types::DbDataType::Uint32,
+                v3::rdbms_types::DbDataType::Uint64 => v2::rdbms_types::DbDataType::Uint64,
+                v3::rdbms_types::DbDataType::Floating32 => v2::rdbms_types::DbDataType::Floating32,
+                v3::rdbms_types::DbDataType::Floating64 => v2::rdbms_types::DbDataType::Floating64,
+                v3::rdbms_types::DbDataType::Str => v2::rdbms_types::DbDataType::Str,
+                v3::rdbms_types::DbDataType::Binary => v2::rdbms_types::DbDataType::Binary,
+                v3::rdbms_types::DbDataType::Date
+                | v3::rdbms_types::DbDataType::Time
+                | v3::rdbms_types::DbDataType::Datetime
+                | v3::rdbms_types::DbDataType::Timestamp
+                | v3::rdbms_types::DbDataType::Uuid
+                | v3::rdbms_types::DbDataType::Json
+                | v3::rdbms_types::DbDataType::Decimal
+                | v3::rdbms_types::DbDataType::Array
+                | v3::rdbms_types::DbDataType::Other => v2::rdbms_types::DbDataType::Other,
+            }
+        }
+    }
+
+    impl From<v3::rdbms_types::RowSet> for v2::rdbms_types::RowSet {
+        fn from(value: v3::rdbms_types::RowSet) -> v2::rdbms_types::RowSet {
+            v2::rdbms_types::RowSet {
+                columns: value.columns.into_iter().map(Into::into).collect(),
+                rows: value
+                    .rows
+                    .into_iter()
+                    .map(|r| r.into_iter().map(Into::into).collect())
+                    .collect(),
+            }
+        }
+    }
+
+    impl From<v2::rdbms_types::ParameterValue> for v3::rdbms_types::ParameterValue {
+        fn from(value: v2::rdbms_types::ParameterValue) -> v3::rdbms_types::ParameterValue {
+            match value {
+                v2::rdbms_types::ParameterValue::Boolean(b) => {
+                    v3::rdbms_types::ParameterValue::Boolean(b)
+                }
+                v2::rdbms_types::ParameterValue::Int8(i) => {
+                    v3::rdbms_types::ParameterValue::Int8(i)
+                }
+                v2::rdbms_types::ParameterValue::Int16(i) => {
+                    v3::rdbms_types::ParameterValue::Int16(i)
+                }
+                v2::rdbms_types::ParameterValue::Int32(i) => {
+                    v3::rdbms_types::ParameterValue::Int32(i)
+                }
+                v2::rdbms_types::ParameterValue::Int64(i) => {
+                    v3::rdbms_types::ParameterValue::Int64(i)
+                }
+                v2::rdbms_types::ParameterValue::Uint8(u) => {
+                    v3::rdbms_types::ParameterValue::Uint8(u)
+                }
+                v2::rdbms_types::ParameterValue::Uint16(u) => {
+                    v3::rdbms_types::ParameterValue::Uint16(u)
+                }
+                v2::rdbms_types::ParameterValue::Uint32(u) => {
+                    v3::rdbms_types::ParameterValue::Uint32(u)
+                }
+                v2::rdbms_types::ParameterValue::Uint64(u) => {
+                    v3::rdbms_types::ParameterValue::Uint64(u)
+                }
+                v2::rdbms_types::ParameterValue::Floating32(r) => {
+                    v3::rdbms_types::ParameterValue::Floating32(r)
+                }
+                v2::rdbms_types::ParameterValue::Floating64(r) => {
+                    v3::rdbms_types::ParameterValue::Floating64(r)
+                }
+                v2::rdbms_types::ParameterValue::Str(s) => v3::rdbms_types::ParameterValue::Str(s),
+                v2::rdbms_types::ParameterValue::Binary(b) => {
+                    v3::rdbms_types::ParameterValue::Binary(b)
+                }
+                v2::rdbms_types::ParameterValue::DbNull => v3::rdbms_types::ParameterValue::DbNull,
+            }
+        }
+    }
+
+    impl From<v3::rdbms_types::Error> for v2::rdbms_types::Error {
+        fn from(error: v3::rdbms_types::Error) -> v2::rdbms_types::Error {
+            match error {
+                v3::rdbms_types::Error::ConnectionFailed(e) => {
+                    v2::rdbms_types::Error::ConnectionFailed(e)
+                }
+                v3::rdbms_types::Error::BadParameter(e) => v2::rdbms_types::Error::BadParameter(e),
+                v3::rdbms_types::Error::QueryFailed(e) => v2::rdbms_types::Error::QueryFailed(e),
+                v3::rdbms_types::Error::ValueConversionFailed(e) => {
+                    v2::rdbms_types::Error::ValueConversionFailed(e)
+                }
+                v3::rdbms_types::Error::Other(e) => v2::rdbms_types::Error::Other(e),
+            }
+        }
+    }
+}
+
 mod mysql {
     use super::*;
     impl From<v2::mysql::RowSet> for v1::mysql::RowSet {
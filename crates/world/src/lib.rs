@@ -6,6 +6,7 @@ wasmtime::component::bindgen!({
     world host {
         include fermyon:spin/host;
         include fermyon:spin/platform@2.0.0;
+        include spin:rdbms/platform@3.0.0;
     }
     "#,
     path: "wit",
@@ -25,6 +26,7 @@ wasmtime::component::bindgen!({
         "fermyon:spin/sqlite@2.0.0/error" => v2::sqlite::Error,
         "fermyon:spin/sqlite/error" => v1::sqlite::Error,
         "fermyon:spin/variables@2.0.0/error" => v2::variables::Error,
+        "spin:rdbms/rdbms-types@3.0.0/error" => v3::rdbms_types::Error,
     },
     trappable_imports: true,
 });
@@ -32,4 +34,9 @@ wasmtime::component::bindgen!({
 pub use fermyon::spin as v1;
 pub use fermyon::spin2_0_0 as v2;
 
+/// Relational database interfaces with support for rich SQL types.
+pub mod v3 {
+    pub use crate::spin::rdbms::{mysql, postgres, rdbms_types};
+}
+
 mod conversions;
@@ -27,6 +27,17 @@ pub struct BuildCommand {
     #[clap(short = 'c', long, multiple = true)]
     pub component_id: Vec<String>,
 
+    /// Maximum number of component builds to run at the same time. The
+    /// default is the number of CPUs. When more than one build can run at a
+    /// time, each line of build output is prefixed with the component ID.
+    #[clap(short = 'j', long = "jobs")]
+    pub jobs: Option<usize>,
+
+    /// Build all components, even those whose watched files have not changed
+    /// since they were last built.
+    #[clap(long = "force")]
+    pub force: bool,
+
     /// Run the application after building.
     #[clap(name = BUILD_UP_OPT, short = 'u', long = "up")]
     pub up: bool,
@@ -38,7 +49,14 @@ pub struct BuildCommand {
 impl BuildCommand {
     pub async fn run(self) -> Result<()> {
         let manifest_file = spin_common::paths::resolve_manifest_file_path(&self.app_source)?;
-        spin_build::build(&manifest_file, &self.component_id).await?;
+        let mut options = spin_build::BuildOptions {
+            force: self.force,
+            ..Default::default()
+        };
+        if let Some(jobs) = self.jobs {
+            options.jobs = jobs;
+        }
+        spin_build::build_with_options(&manifest_file, &self.component_id, &options).await?;
 
         if self.up {
             let mut cmd = UpCommand::parse_from(
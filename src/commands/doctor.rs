@@ -1,8 +1,9 @@
 use std::{fmt::Debug, path::PathBuf};
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use dialoguer::{console::Emoji, Confirm, Select};
+use serde::Serialize;
 use spin_doctor::{Diagnosis, DryRunNotSupported, PatientDiagnosis};
 
 use crate::opts::{APP_MANIFEST_FILE_OPT, DEFAULT_MANIFEST_FILE};
@@ -21,12 +22,33 @@ pub struct DoctorCommand {
         default_value = DEFAULT_MANIFEST_FILE
     )]
     pub app_source: PathBuf,
+
+    /// The format in which to report problems. The JSON format does not
+    /// prompt, and fails if any critical problems remain.
+    #[clap(value_enum, long = "format", default_value = "plain")]
+    pub format: OutputFormat,
+
+    /// Apply all available fixes without prompting, and fail if any critical
+    /// problems remain. Fixes which install software are not applied, as they
+    /// need confirmation.
+    #[clap(long = "fix", alias = "yes", short = 'y', takes_value = false)]
+    pub fix: bool,
+}
+
+#[derive(ValueEnum, Clone, Debug, PartialEq)]
+pub enum OutputFormat {
+    Plain,
+    Json,
 }
 
 impl DoctorCommand {
     pub async fn run(self) -> Result<()> {
         let manifest_file = spin_common::paths::resolve_manifest_file_path(&self.app_source)?;
 
+        if self.fix || self.format == OutputFormat::Json {
+            return self.run_unattended(manifest_file).await;
+        }
+
         println!("{icon}The Spin Doctor is in.", icon = Emoji("📟 ", ""));
         println!(
             "{icon}Checking {}...",
@@ -83,6 +105,134 @@ impl DoctorCommand {
     }
 }
 
+impl DoctorCommand {
+    async fn run_unattended(&self, manifest_file: PathBuf) -> Result<()> {
+        let plain = self.format == OutputFormat::Plain;
+        if plain {
+            println!(
+                "{icon}Checking {}...",
+                manifest_file.display(),
+                icon = Emoji("🩺 ", "")
+            );
+        }
+
+        let mut checkup = spin_doctor::Checkup::new(&manifest_file)?;
+        let mut reports = vec![];
+        while let Some(PatientDiagnosis { diagnosis, patient }) = checkup.next_diagnosis().await? {
+            if plain {
+                show_diagnosis(&*diagnosis);
+            }
+            let mut report = DiagnosisReport::new(&*diagnosis);
+            let mut stop = false;
+
+            if let (true, Some(treatment)) = (self.fix, diagnosis.treatment()) {
+                if treatment.requires_confirmation() {
+                    let message = format!(
+                        "{} needs confirmation, so was not applied. Run `spin doctor` without `--fix` to apply it.",
+                        treatment.summary()
+                    );
+                    if plain {
+                        terminal::warn!("{message}");
+                    }
+                    report.error = Some(message);
+                    reports.push(report);
+                    continue;
+                }
+                match treatment.treat(patient).await {
+                    Ok(()) => {
+                        report.treated = true;
+                        if plain {
+                            println!(
+                                "{icon}Treatment applied: {}",
+                                treatment.summary(),
+                                icon = Emoji("❤  ", "")
+                            );
+                        }
+                    }
+                    Err(err) => {
+                        // Later diagnoses may depend on this one being fixed
+                        stop = err.is::<spin_doctor::StopDiagnosing>();
+                        if plain {
+                            show_error("Treatment failed: ", &err);
+                        }
+                        report.error = Some(format!("{err:#}"));
+                    }
+                }
+            }
+
+            reports.push(report);
+            if stop {
+                break;
+            }
+        }
+
+        if plain && reports.is_empty() {
+            println!("{icon}No problems found.", icon = Emoji("❤  ", ""));
+        }
+        if !plain {
+            let output = DoctorReport {
+                manifest: manifest_file,
+                diagnoses: reports.iter().collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+
+        let unfixed = reports
+            .iter()
+            .filter(|r| r.severity == Severity::Critical && !r.treated)
+            .count();
+        if unfixed > 0 {
+            anyhow::bail!("{unfixed} critical problem(s) remain");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct DoctorReport<'a> {
+    manifest: PathBuf,
+    diagnoses: Vec<&'a DiagnosisReport>,
+}
+
+#[derive(Serialize)]
+struct DiagnosisReport {
+    kind: &'static str,
+    severity: Severity,
+    description: String,
+    has_treatment: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    treatment: Option<String>,
+    treated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl DiagnosisReport {
+    fn new(diagnosis: &dyn Diagnosis) -> Self {
+        let treatment = diagnosis.treatment().map(|t| t.summary());
+        Self {
+            kind: diagnosis.kind(),
+            severity: if diagnosis.is_critical() {
+                Severity::Critical
+            } else {
+                Severity::Warning
+            },
+            description: diagnosis.description(),
+            has_treatment: treatment.is_some(),
+            treatment,
+            treated: false,
+            error: None,
+        }
+    }
+}
+
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Critical,
+    Warning,
+}
+
 fn show_diagnosis(diagnosis: &dyn Diagnosis) {
     let icon = if diagnosis.is_critical() {
         Emoji("❗ ", "")
@@ -10,7 +10,9 @@ use itertools::Itertools;
 use path_absolutize::Absolutize;
 use tokio;
 
-use spin_templates::{RunOptions, Template, TemplateManager, TemplateVariantInfo};
+use spin_templates::{
+    ParameterValueText, RunOptions, Template, TemplateManager, TemplateVariantInfo,
+};
 
 use crate::opts::{APP_MANIFEST_FILE_OPT, DEFAULT_MANIFEST_FILE};
 
@@ -66,6 +68,11 @@ pub struct TemplateNewCommandCore {
     /// An optional argument that allows to skip creating .gitignore
     #[clap(long = "no-vcs", takes_value = false)]
     pub no_vcs: bool,
+
+    /// Run any commands the template specifies after generating files,
+    /// without prompting for confirmation.
+    #[clap(long = "allow-run-commands", takes_value = false)]
+    pub allow_run_commands: bool,
 }
 
 /// Scaffold a new application based on a template.
@@ -184,6 +191,7 @@ impl TemplateNewCommandCore {
             values,
             accept_defaults: self.accept_defaults,
             no_vcs: self.no_vcs,
+            allow_run_commands: self.allow_run_commands,
         };
 
         template.run(options).interactive().await
@@ -271,8 +279,15 @@ async fn values_from_file(path: impl AsRef<Path>) -> Result<HashMap<String, Stri
         .await
         .with_context(|| format!("Failed to read text from values file {}", path.display()))?;
 
-    // Parse the TOML file into a hashmap of values.
-    toml::from_str(&text).context("Failed to deserialize values file")
+    // Parse the TOML file into a hashmap of values. Non-string values (such as
+    // booleans, integers and lists) are converted to the text the user would
+    // pass on the command line, and validated by the template.
+    let values: HashMap<String, ParameterValueText> =
+        toml::from_str(&text).context("Failed to deserialize values file")?;
+    Ok(values
+        .into_iter()
+        .map(|(name, value)| (name, value.into_string()))
+        .collect())
 }
 
 /// Merges values from file and values passed as command line options. CLI
@@ -419,7 +434,9 @@ mod tests {
     #[tokio::test]
     async fn test_values_from_file_bad() {
         let bad_content = [
-            "key_1 = 1", // value is not a string
+            "key_1 = 1.5",          // value is not a supported type
+            "key_1 = [1, 2]",       // list items are not strings
+            "[key_1]\nkey_2 = 'v'", // value is a table
         ];
         for content in bad_content {
             let file = create_tempfile(content).unwrap();
@@ -431,6 +448,18 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_values_from_file_typed() {
+        let file = create_tempfile("key_1 = true\nkey_2 = 8080\nkey_3 = ['a', 'b']\n").unwrap();
+        let values = values_from_file(&file).await.unwrap();
+        let want: HashMap<_, _> = HashMap::from_iter([
+            ("key_1".to_owned(), "true".to_owned()),
+            ("key_2".to_owned(), "8080".to_owned()),
+            ("key_3".to_owned(), "a,b".to_owned()),
+        ]);
+        assert_eq!(want, values);
+    }
+
     /// Verify values passed as CLI option overwrite values set in file.
     #[test]
     fn merge_values_cli_option_precedence() {
@@ -6,9 +6,12 @@ use clap::{Parser, Subcommand};
 use semver::Version;
 use spin_plugins::{
     error::Error,
-    lookup::{fetch_plugins_repo, plugins_repo_url, PluginLookup},
+    lockfile::{LockedPlugin, PluginLockfile, PluginRequirement, PLUGINS_LOCKFILE_NAME},
+    lookup::{fetch_plugins_repos, PluginLookup},
     manager::{self, InstallAction, ManifestLocation, PluginManager},
     manifest::{PluginManifest, PluginPackage},
+    repos::{PluginRepo, PluginRepoSource, PluginRepos},
+    trust::{TrustPolicy, TrustedKey},
 };
 use std::path::{Path, PathBuf};
 use url::Url;
@@ -37,8 +40,23 @@ pub enum PluginCommands {
     /// Upgrade one or all plugins.
     Upgrade(Upgrade),
 
-    /// Fetch the latest Spin plugins from the spin-plugins repository.
+    /// Fetch the latest Spin plugins from the configured plugin repositories.
     Update,
+
+    /// Install the plugins required by an application.
+    ///
+    /// Required plugins are declared in the `[application.tool.spin.plugins]`
+    /// table of the application manifest. The exact versions are pinned in a
+    /// lockfile alongside the manifest.
+    Sync(Sync),
+
+    /// Manage the keys trusted to sign plugins.
+    #[clap(subcommand)]
+    Trust(TrustCommands),
+
+    /// Manage the repositories in which plugins are looked up.
+    #[clap(subcommand)]
+    Repo(RepoCommands),
 }
 
 impl PluginCommands {
@@ -50,6 +68,9 @@ impl PluginCommands {
             PluginCommands::Uninstall(cmd) => cmd.run().await,
             PluginCommands::Upgrade(cmd) => cmd.run().await,
             PluginCommands::Update => update().await,
+            PluginCommands::Sync(cmd) => cmd.run().await,
+            PluginCommands::Trust(cmd) => cmd.run(),
+            PluginCommands::Repo(cmd) => cmd.run().await,
         }
     }
 }
@@ -416,6 +437,332 @@ impl Upgrade {
     }
 }
 
+/// Install the plugins required by an application.
+#[derive(Parser, Debug)]
+pub struct Sync {
+    /// The application whose plugins to install. This may be a manifest
+    /// (spin.toml) file, or a directory containing a spin.toml file.
+    /// If omitted, it defaults to "spin.toml".
+    #[clap(
+        name = APP_MANIFEST_FILE_OPT,
+        short = 'f',
+        long = "from",
+        alias = "file",
+        default_value = DEFAULT_MANIFEST_FILE
+    )]
+    pub app_source: PathBuf,
+
+    /// Fail if the lockfile is missing or does not match the application's
+    /// requirements, instead of updating it.
+    #[clap(long = "locked", takes_value = false, conflicts_with = "update")]
+    pub locked: bool,
+
+    /// Resolve all requirements to the latest matching versions, even if
+    /// the lockfile already satisfies them.
+    #[clap(long = "update", takes_value = false)]
+    pub update: bool,
+
+    /// Skips prompt to accept the installation of the plugins.
+    #[clap(short = 'y', long = "yes", takes_value = false)]
+    pub yes_to_all: bool,
+}
+
+impl Sync {
+    pub async fn run(self) -> Result<()> {
+        let manifest_file = spin_common::paths::resolve_manifest_file_path(&self.app_source)?;
+        let app_dir = spin_common::paths::parent_dir(&manifest_file)?;
+        let manifest = spin_manifest::manifest_from_file(&manifest_file)?;
+        let requirements = plugin_requirements(&manifest)?;
+
+        if requirements.is_empty() {
+            println!("The application does not declare any required plugins");
+            return Ok(());
+        }
+
+        let lockfile = match PluginLockfile::load(&app_dir)? {
+            Some(lockfile) if !self.update && lockfile.satisfies(&requirements) => lockfile,
+            _ if self.locked => {
+                anyhow::bail!("{PLUGINS_LOCKFILE_NAME} is missing or does not match the application's plugin requirements. Run `spin plugins sync` without `--locked` to update it.");
+            }
+            _ => {
+                if update_silent().await.is_err() {
+                    terminal::warn!("Couldn't update plugins registry cache - using most recent");
+                }
+                let manager = PluginManager::try_default()?;
+                let lockfile = PluginLockfile::resolve(
+                    &requirements,
+                    manager.store().get_plugins_directory(),
+                    SPIN_VERSION,
+                )?;
+                lockfile.save(&app_dir)?;
+                println!("Updated {PLUGINS_LOCKFILE_NAME}");
+                lockfile
+            }
+        };
+
+        let manager = PluginManager::try_default()?;
+        for locked in &lockfile.plugins {
+            install_locked(&manager, locked, self.yes_to_all).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Gets the plugins declared in the `[application.tool.spin.plugins]` table,
+/// which maps plugin names to semver requirements.
+pub(crate) fn plugin_requirements(
+    manifest: &spin_manifest::schema::v2::AppManifest,
+) -> Result<Vec<PluginRequirement>> {
+    let Some(plugins) = manifest
+        .application
+        .tool
+        .get("spin")
+        .and_then(|spin| spin.get("plugins"))
+    else {
+        return Ok(vec![]);
+    };
+    let plugins = plugins
+        .as_table()
+        .context("`application.tool.spin.plugins` must be a table of plugin names to versions")?;
+    plugins
+        .iter()
+        .map(|(name, version)| {
+            let version = version.as_str().with_context(|| {
+                format!("Version requirement for plugin '{name}' must be a string")
+            })?;
+            PluginRequirement::parse(name, version)
+        })
+        .collect()
+}
+
+/// Installs the version of a plugin pinned in a lockfile, if it is not
+/// already installed. Returns true if the plugin was installed.
+pub(crate) async fn install_locked(
+    manager: &PluginManager,
+    locked: &LockedPlugin,
+    yes_to_all: bool,
+) -> Result<bool> {
+    if locked.is_installed_in(manager.store()) {
+        return Ok(false);
+    }
+    let manifest_location = ManifestLocation::PluginsRepository(locked.lookup());
+    let manifest = manager
+        .get_manifest(&manifest_location, false, SPIN_VERSION)
+        .await?;
+    locked.verify(&manifest)?;
+    try_install(
+        &manifest,
+        manager,
+        yes_to_all,
+        false,
+        false,
+        &manifest_location,
+    )
+    .await
+}
+
+/// Manage the keys trusted to sign plugins.
+///
+/// If a plugin package declares a signature, it is checked against the trusted
+/// keys when the plugin is installed, and the plugin is refused unless one of
+/// them verifies it. Set SPIN_PLUGINS_REQUIRE_SIGNATURES to refuse unsigned
+/// plugins too, regardless of the policy.
+#[derive(Subcommand, Debug)]
+pub enum TrustCommands {
+    /// List the trusted keys.
+    List,
+
+    /// Trust a key to sign plugins.
+    Add(TrustAdd),
+
+    /// Stop trusting a key.
+    Remove(TrustRemove),
+
+    /// Set whether plugins must be signed by a trusted key to be installed.
+    Require(TrustRequire),
+}
+
+#[derive(Parser, Debug)]
+pub struct TrustAdd {
+    /// A name to identify the key, e.g. the publisher.
+    pub name: String,
+
+    /// The minisign public key, or the path to a minisign public key file.
+    pub public_key: String,
+}
+
+#[derive(Parser, Debug)]
+pub struct TrustRemove {
+    /// The name of the key to remove.
+    pub name: String,
+}
+
+#[derive(Parser, Debug)]
+pub struct TrustRequire {
+    /// Whether signatures are required.
+    #[clap(action = clap::ArgAction::Set)]
+    pub required: bool,
+}
+
+impl TrustCommands {
+    pub fn run(self) -> Result<()> {
+        let manager = PluginManager::try_default()?;
+        let store = manager.store();
+        let mut policy = TrustPolicy::load(store)?;
+
+        match self {
+            Self::List => {
+                if policy.keys.is_empty() {
+                    println!("No keys are trusted to sign plugins");
+                }
+                for key in &policy.keys {
+                    println!("{} {}", key.name, key.public_key);
+                }
+                if policy.require_signatures {
+                    println!("Plugins must be signed by a trusted key");
+                }
+                return Ok(());
+            }
+            Self::Add(cmd) => {
+                if policy.keys.iter().any(|k| k.name == cmd.name) {
+                    anyhow::bail!("A key named '{}' is already trusted", cmd.name);
+                }
+                let public_key = read_public_key(&cmd.public_key)?;
+                policy.keys.push(TrustedKey::new(&cmd.name, public_key)?);
+                println!("Key '{}' is now trusted to sign plugins", cmd.name);
+            }
+            Self::Remove(cmd) => {
+                let count = policy.keys.len();
+                policy.keys.retain(|k| k.name != cmd.name);
+                if policy.keys.len() == count {
+                    anyhow::bail!("No trusted key is named '{}'", cmd.name);
+                }
+                println!("Key '{}' is no longer trusted", cmd.name);
+            }
+            Self::Require(cmd) => {
+                policy.require_signatures = cmd.required;
+            }
+        }
+
+        policy.save(store)
+    }
+}
+
+/// Manage the repositories in which plugins are looked up.
+///
+/// Repositories are searched in priority order, with the default Spin plugins
+/// repository last. To install from a specific repository, qualify the plugin
+/// name with the repository name, e.g. `spin plugins install internal/my-trigger`.
+#[derive(Subcommand, Debug)]
+pub enum RepoCommands {
+    /// List the plugin repositories, highest priority first.
+    List,
+
+    /// Add a plugin repository.
+    Add(RepoAdd),
+
+    /// Remove a plugin repository.
+    Remove(RepoRemove),
+}
+
+#[derive(Parser, Debug)]
+pub struct RepoAdd {
+    /// A name for the repository. This is used to qualify plugin names.
+    pub name: String,
+
+    /// The URL of a Git repository, or the path to a local directory, with the
+    /// same layout as the Spin plugins repository.
+    pub source: String,
+
+    /// Search this repository before all others. By default, it is searched
+    /// after other added repositories.
+    #[clap(long = "first", takes_value = false)]
+    pub first: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct RepoRemove {
+    /// The name of the repository to remove.
+    pub name: String,
+}
+
+impl RepoCommands {
+    pub async fn run(self) -> Result<()> {
+        let manager = PluginManager::try_default()?;
+        let store = manager.store();
+        let mut repos = PluginRepos::load(store)?;
+
+        match self {
+            Self::List => {
+                for repo in repos.all() {
+                    println!("{} {}", repo.name, repo.source_text());
+                }
+                return Ok(());
+            }
+            Self::Add(cmd) => {
+                let repo = PluginRepo {
+                    name: cmd.name,
+                    source: parse_repo_source(&cmd.source)?,
+                };
+                repos.add(repo.clone(), cmd.first)?;
+                repo.fetch(store.get_plugins_directory(), true)
+                    .await
+                    .with_context(|| {
+                        format!("Failed to fetch plugin repository '{}'", repo.name)
+                    })?;
+                println!("Added plugin repository '{}'", repo.name);
+            }
+            Self::Remove(cmd) => {
+                let Some(repo) = repos.remove(&cmd.name)? else {
+                    anyhow::bail!("No plugin repository is named '{}'", cmd.name);
+                };
+                if let PluginRepoSource::Git(_) = &repo.source {
+                    let clone_dir = repo.local_dir(store.get_plugins_directory());
+                    if clone_dir.exists() {
+                        std::fs::remove_dir_all(&clone_dir)
+                            .with_context(|| format!("Failed to remove {}", clone_dir.display()))?;
+                    }
+                }
+                println!("Removed plugin repository '{}'", repo.name);
+            }
+        }
+
+        repos.save(store)
+    }
+}
+
+// Treats the source as a directory if it exists locally, otherwise as a Git URL.
+fn parse_repo_source(source: &str) -> Result<PluginRepoSource> {
+    let path = Path::new(source);
+    if path.is_dir() {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve {}", path.display()))?;
+        return Ok(PluginRepoSource::Dir(path));
+    }
+    let url = Url::parse(source).with_context(|| {
+        format!("'{source}' is neither a local directory nor a valid repository URL")
+    })?;
+    Ok(PluginRepoSource::Git(url))
+}
+
+// Accepts either a bare base64 key or the path to a minisign .pub file (whose
+// last line is the key).
+fn read_public_key(key_or_path: &str) -> Result<String> {
+    let path = Path::new(key_or_path);
+    if !path.is_file() {
+        return Ok(key_or_path.to_owned());
+    }
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read public key file {}", path.display()))?;
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("untrusted comment:"))
+        .last()
+        .map(str::to_owned)
+        .with_context(|| format!("No public key found in {}", path.display()))
+}
+
 fn is_potential_upgrade(current: &PluginManifest, candidate: &PluginManifest) -> bool {
     match (current.try_version(), candidate.try_version()) {
         (Ok(cur_ver), Ok(cand_ver)) => cand_ver > cur_ver,
@@ -629,8 +976,7 @@ pub(crate) async fn update_silent() -> Result<()> {
     }
 
     let plugins_dir = manager.store().get_plugins_directory();
-    let url = plugins_repo_url()?;
-    fetch_plugins_repo(&url, plugins_dir, true).await?;
+    fetch_plugins_repos(plugins_dir, true).await?;
     Ok(())
 }
 
@@ -1,9 +1,13 @@
+mod cache;
+
 use crate::opts::*;
 use anyhow::{Context, Result};
+use bytesize::ByteSize;
 use clap::{Parser, Subcommand};
+use comfy_table::Table;
 use indicatif::{ProgressBar, ProgressStyle};
-use spin_common::arg_parser::parse_kv;
-use spin_oci::Client;
+use spin_common::{arg_parser::parse_kv, ui::quoted_path};
+use spin_oci::{Client, Signer, Verifier};
 use std::{io::Read, path::PathBuf, time::Duration};
 
 /// Commands for working with OCI registries to distribute applications.
@@ -13,8 +17,19 @@ pub enum RegistryCommands {
     Push(Push),
     /// Pull a Spin application from a registry.
     Pull(Pull),
+    /// Save a Spin application from a registry as an OCI image layout, for
+    /// transfer to machines without access to the registry.
+    Save(Save),
+    /// Load a Spin application from an OCI image layout into the local cache.
+    Load(Load),
+    /// Show the manifest, layers and attached metadata of a Spin application
+    /// in a registry without running it.
+    Inspect(Inspect),
     /// Log in to a registry.
     Login(Login),
+    /// Inspect and clean up the local cache of registry data.
+    #[clap(subcommand)]
+    Cache(cache::CacheCommands),
 }
 
 impl RegistryCommands {
@@ -22,15 +37,20 @@ impl RegistryCommands {
         match self {
             RegistryCommands::Push(cmd) => cmd.run().await,
             RegistryCommands::Pull(cmd) => cmd.run().await,
+            RegistryCommands::Save(cmd) => cmd.run().await,
+            RegistryCommands::Load(cmd) => cmd.run().await,
+            RegistryCommands::Inspect(cmd) => cmd.run().await,
             RegistryCommands::Login(cmd) => cmd.run().await,
+            RegistryCommands::Cache(cmd) => cmd.run().await,
         }
     }
 }
 
 #[derive(Parser, Debug)]
 pub struct Push {
-    /// The application to push. This may be a manifest (spin.toml) file, or a
-    /// directory containing a spin.toml file.
+    /// The application to push. This may be a manifest (spin.toml) file, a
+    /// directory containing a spin.toml file, or an OCI image layout directory
+    /// or `.tar` archive as written by `spin registry save`.
     /// If omitted, it defaults to "spin.toml".
     #[clap(
         name = APP_MANIFEST_FILE_OPT,
@@ -67,10 +87,19 @@ pub struct Push {
     /// Any existing value will be overwritten. Can be used multiple times.
     #[clap(long = "annotation", parse(try_from_str = parse_kv))]
     pub annotations: Vec<(String, String)>,
+
+    /// Sign the pushed application with the Ed25519 private key in this
+    /// PKCS#8 PEM file. The signature is pushed to the same repository.
+    #[clap(long = "sign-key")]
+    pub sign_key: Option<PathBuf>,
 }
 
 impl Push {
     pub async fn run(self) -> Result<()> {
+        if spin_oci::layout::is_oci_layout(&self.app_source) {
+            return self.push_layout().await;
+        }
+
         let app_file = spin_common::paths::resolve_manifest_file_path(&self.app_source)?;
         if self.build {
             spin_build::build(&app_file, &[]).await?;
@@ -83,17 +112,47 @@ impl Push {
         };
 
         let mut client = spin_oci::Client::new(self.insecure, self.cache_dir.clone()).await?;
+        if let Some(sign_key) = &self.sign_key {
+            client = client.with_signer(Signer::from_pem_file(sign_key)?);
+        }
 
         let _spinner = create_dotted_spinner(2000, "Pushing app to the Registry".to_owned());
 
         let digest = client.push(&app_file, &self.reference, annotations).await?;
-        match digest {
-            Some(digest) => println!("Pushed with digest {digest}"),
-            None => println!("Pushed; the registry did not return the digest"),
-        };
+        print_pushed(digest);
 
         Ok(())
     }
+
+    async fn push_layout(self) -> Result<()> {
+        if self.build {
+            anyhow::bail!("`--build` cannot be used when pushing an OCI image layout");
+        }
+        if !self.annotations.is_empty() {
+            anyhow::bail!("`--annotation` cannot be used when pushing an OCI image layout");
+        }
+
+        let mut client = spin_oci::Client::new(self.insecure, self.cache_dir.clone()).await?;
+        if let Some(sign_key) = &self.sign_key {
+            client = client.with_signer(Signer::from_pem_file(sign_key)?);
+        }
+
+        let _spinner = create_dotted_spinner(2000, "Pushing app to the Registry".to_owned());
+
+        let digest = client
+            .push_layout(&self.app_source, &self.reference)
+            .await?;
+        print_pushed(digest);
+
+        Ok(())
+    }
+}
+
+fn print_pushed(digest: Option<String>) {
+    match digest {
+        Some(digest) => println!("Pushed with digest {digest}"),
+        None => println!("Pushed; the registry did not return the digest"),
+    };
 }
 
 #[derive(Parser, Debug)]
@@ -115,12 +174,18 @@ pub struct Pull {
     /// Cache directory for downloaded registry data.
     #[clap(long)]
     pub cache_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub verify: VerifyOpts,
 }
 
 impl Pull {
     /// Pull a Spin application from an OCI registry
     pub async fn run(self) -> Result<()> {
         let mut client = spin_oci::Client::new(self.insecure, self.cache_dir.clone()).await?;
+        if let Some(verifier) = self.verify.verifier()? {
+            client = client.with_verifier(verifier);
+        }
 
         let _spinner = create_dotted_spinner(2000, "Pulling app from the Registry".to_owned());
 
@@ -130,6 +195,217 @@ impl Pull {
     }
 }
 
+#[derive(Parser, Debug)]
+pub struct Save {
+    /// Ignore server certificate errors
+    #[clap(
+        name = INSECURE_OPT,
+        short = 'k',
+        long = "insecure",
+        takes_value = false,
+    )]
+    pub insecure: bool,
+
+    /// Reference in the registry of the published Spin application.
+    #[clap()]
+    pub reference: String,
+
+    /// Where to write the OCI image layout. If this ends in `.tar`, the
+    /// layout is written as a tar archive; otherwise it is written as a directory.
+    /// If the directory is already an image layout, the application is added
+    /// alongside those already saved there.
+    #[clap(short = 'o', long = "output")]
+    pub output: PathBuf,
+
+    /// Cache directory for downloaded registry data.
+    #[clap(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub verify: VerifyOpts,
+}
+
+impl Save {
+    pub async fn run(self) -> Result<()> {
+        let mut client = spin_oci::Client::new(self.insecure, self.cache_dir.clone()).await?;
+        if let Some(verifier) = self.verify.verifier()? {
+            client = client.with_verifier(verifier);
+        }
+
+        let _spinner = create_dotted_spinner(2000, "Saving app from the Registry".to_owned());
+
+        let digest = client.save(&self.reference, &self.output).await?;
+        println!(
+            "Saved {}@{digest} to {}",
+            self.reference,
+            quoted_path(&self.output)
+        );
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct Load {
+    /// The OCI image layout directory or `.tar` archive to load.
+    #[clap()]
+    pub layout: PathBuf,
+
+    /// The reference to load the application as. If omitted, the reference
+    /// the application was saved from is used. If the layout contains more
+    /// than one application, this selects the one saved under this reference.
+    #[clap(long = "tag")]
+    pub reference: Option<String>,
+
+    /// Cache directory for downloaded registry data.
+    #[clap(long)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Load {
+    pub async fn run(self) -> Result<()> {
+        let mut client = spin_oci::Client::new(false, self.cache_dir.clone()).await?;
+        let reference = client.load(&self.layout, self.reference.as_deref()).await?;
+        println!("Loaded {reference}; run it with `spin up --from-registry {reference}`");
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct Inspect {
+    /// Ignore server certificate errors
+    #[clap(
+        name = INSECURE_OPT,
+        short = 'k',
+        long = "insecure",
+        takes_value = false,
+    )]
+    pub insecure: bool,
+
+    /// Reference in the registry of the published Spin application.
+    #[clap()]
+    pub reference: String,
+
+    /// Print the details as JSON.
+    #[clap(long = "json", takes_value = false)]
+    pub json: bool,
+
+    /// Cache directory for downloaded registry data.
+    #[clap(long)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Inspect {
+    pub async fn run(self) -> Result<()> {
+        let mut client = spin_oci::Client::new(self.insecure, self.cache_dir.clone()).await?;
+        let inspection = client.inspect(&self.reference).await?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&inspection)?);
+            return Ok(());
+        }
+
+        println!("Reference: {}", inspection.reference);
+        println!("Digest:    {}", inspection.digest);
+        println!(
+            "Signed:    {}",
+            if inspection.signed {
+                "yes (use `spin registry pull --verify` to check the signature)"
+            } else {
+                "no"
+            }
+        );
+
+        if let Some(annotations) = &inspection.manifest.annotations {
+            println!();
+            println!("Annotations:");
+            for (key, value) in annotations {
+                println!("  {key} = {value}");
+            }
+        }
+
+        let mut layers = Table::new();
+        layers.set_header(vec!["Digest", "Media type", "Size"]);
+        layers.load_preset(comfy_table::presets::ASCII_BORDERS_ONLY_CONDENSED);
+        for layer in std::iter::once(&inspection.manifest.config).chain(&inspection.manifest.layers)
+        {
+            layers.add_row(vec![
+                layer.digest.clone(),
+                layer.media_type.clone(),
+                ByteSize(layer.size.try_into().unwrap_or_default()).to_string(),
+            ]);
+        }
+        println!();
+        println!("{layers}");
+
+        println!();
+        match &inspection.sbom {
+            Some(sbom) => {
+                let mut components = Table::new();
+                components.set_header(vec!["Component", "Digest", "Origin", "Build command"]);
+                components.load_preset(comfy_table::presets::ASCII_BORDERS_ONLY_CONDENSED);
+                for component in &sbom.components {
+                    let origin = match (&component.url, &component.oci, &component.package) {
+                        (Some(url), _, _) => url.clone(),
+                        (None, Some(oci), _) => oci.clone(),
+                        (None, None, Some(package)) => match &component.registry {
+                            Some(registry) => format!("{package} ({registry})"),
+                            None => package.clone(),
+                        },
+                        (None, None, None) => "local".to_owned(),
+                    };
+                    components.add_row(vec![
+                        component.id.clone(),
+                        component.digest.clone().unwrap_or_default(),
+                        origin,
+                        component.build_command.clone().unwrap_or_default(),
+                    ]);
+                }
+                println!("Bill of materials:");
+                println!("{components}");
+            }
+            None => println!("No bill of materials is attached"),
+        }
+        Ok(())
+    }
+}
+
+/// Options for verifying the signatures of applications pulled from a registry.
+#[derive(Parser, Debug, Default)]
+pub struct VerifyOpts {
+    /// Refuse to use the application unless it is signed by one of the keys
+    /// given by `--verify-key`.
+    #[clap(
+        name = VERIFY_OPT,
+        long = "verify",
+        takes_value = false,
+        requires = VERIFY_KEY_OPT,
+    )]
+    pub verify: bool,
+
+    /// An Ed25519 public key in PEM format which is trusted to sign
+    /// applications. Can be used multiple times.
+    #[clap(
+        name = VERIFY_KEY_OPT,
+        long = "verify-key",
+        env = "SPIN_VERIFY_KEYS",
+        multiple_occurrences = true,
+        use_value_delimiter = true
+    )]
+    pub verify_keys: Vec<PathBuf>,
+}
+
+impl VerifyOpts {
+    /// Loads the trusted keys if verification was requested.
+    pub fn verifier(&self) -> Result<Option<Verifier>> {
+        if !self.verify {
+            return Ok(None);
+        }
+        let verifier =
+            Verifier::from_pem_files(&self.verify_keys).context("cannot load trusted keys")?;
+        Ok(Some(verifier))
+    }
+}
+
 #[derive(Parser, Debug)]
 pub struct Login {
     /// Username for the registry
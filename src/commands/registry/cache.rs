@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use bytesize::ByteSize;
+use clap::{Parser, Subcommand};
+use comfy_table::Table;
+use spin_loader::cache::Cache;
+
+/// Commands for inspecting and cleaning up the local registry cache.
+#[derive(Subcommand, Debug)]
+pub enum CacheCommands {
+    /// List the applications in the cache and report disk usage.
+    List(List),
+    /// Remove cached data which is no longer needed.
+    Prune(Prune),
+    /// Remove everything from the cache.
+    Clear(Clear),
+}
+
+impl CacheCommands {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            CacheCommands::List(cmd) => cmd.run().await,
+            CacheCommands::Prune(cmd) => cmd.run().await,
+            CacheCommands::Clear(cmd) => cmd.run().await,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct List {
+    /// Cache directory for downloaded registry data.
+    #[clap(long)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl List {
+    pub async fn run(self) -> Result<()> {
+        let cache = Cache::new(self.cache_dir).await?;
+        let manifests = cache.manifests()?;
+
+        if manifests.is_empty() {
+            println!("No applications in the cache");
+        } else {
+            let mut table = Table::new();
+            table.set_header(vec!["Reference", "Size"]);
+            table.load_preset(comfy_table::presets::ASCII_BORDERS_ONLY_CONDENSED);
+            for manifest in manifests {
+                table.add_row(vec![
+                    manifest.reference,
+                    ByteSize(manifest.size).to_string(),
+                ]);
+            }
+            println!("{table}");
+        }
+
+        let unreferenced: u64 = cache
+            .blobs()?
+            .iter()
+            .filter(|blob| !blob.referenced)
+            .map(|blob| blob.size)
+            .sum();
+        println!();
+        println!("Total size: {}", ByteSize(cache.usage()?));
+        println!(
+            "Not used by any cached application: {} (remove with `spin registry cache prune`)",
+            ByteSize(unreferenced)
+        );
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct Prune {
+    /// Also remove the least recently used applications, and components
+    /// downloaded for local applications, until the cache is no larger than
+    /// this size (e.g. "2GiB"). Removed applications must be pulled again, or
+    /// loaded again if they were loaded from an image layout.
+    #[clap(long = "max-size")]
+    pub max_size: Option<ByteSize>,
+
+    /// Cache directory for downloaded registry data.
+    #[clap(long)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Prune {
+    pub async fn run(self) -> Result<()> {
+        let cache = Cache::new(self.cache_dir).await?;
+        let summary = cache.prune(self.max_size.map(|size| size.as_u64()))?;
+        if summary.apps_removed > 0 {
+            println!("Removed {} cached applications", summary.apps_removed);
+        }
+        println!(
+            "Removed {} cached files, freeing {}",
+            summary.removed,
+            ByteSize(summary.freed)
+        );
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct Clear {
+    /// Cache directory for downloaded registry data.
+    #[clap(long)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Clear {
+    pub async fn run(self) -> Result<()> {
+        let cache = Cache::new(self.cache_dir).await?;
+        let freed = cache.clear()?;
+        println!("Cleared the registry cache, freeing {}", ByteSize(freed));
+        Ok(())
+    }
+}
@@ -8,14 +8,17 @@ use path_absolutize::Absolutize;
 use serde::Serialize;
 use spin_templates::{
     InstallOptions, InstallationResults, InstalledTemplateWarning, ListResults, ProgressReporter,
-    SkippedReason, Template, TemplateManager, TemplateSource,
+    SkippedReason, Template, TemplateManager, TemplateSource, VerificationResult, VerifyOptions,
 };
 
 use crate::build_info::*;
 
 const INSTALL_FROM_DIR_OPT: &str = "FROM_DIR";
 const INSTALL_FROM_GIT_OPT: &str = "FROM_GIT";
+const INSTALL_FROM_OCI_OPT: &str = "FROM_OCI";
+const INSTALL_FROM_TAR_OPT: &str = "FROM_TAR";
 const UPGRADE_ONLY: &str = "GIT_URL";
+const VERIFY_FROM_DIR_OPT: &str = "VERIFY_FROM_DIR";
 
 const DEFAULT_TEMPLATES_INSTALL_PROMPT: &str =
     "You don't have any templates yet. Would you like to install the default set?";
@@ -24,7 +27,8 @@ const DEFAULT_TEMPLATE_REPO: &str = "https://github.com/fermyon/spin";
 /// Commands for working with WebAssembly component templates.
 #[derive(Subcommand, Debug)]
 pub enum TemplateCommands {
-    /// Install templates from a Git repository or local directory.
+    /// Install templates from a Git repository, local directory, OCI registry
+    /// or archive.
     ///
     /// The files of the templates are copied to the local template store: a
     /// directory in your data or home directory.
@@ -41,6 +45,13 @@ pub enum TemplateCommands {
 
     /// List the installed templates.
     List(List),
+
+    /// Check that templates generate valid applications.
+    ///
+    /// Each template is run with each of its sample parameter sets (or with
+    /// its default values if it declares no samples) into a temporary
+    /// directory, and the generated manifest is validated.
+    Verify(Verify),
 }
 
 impl TemplateCommands {
@@ -50,11 +61,13 @@ impl TemplateCommands {
             TemplateCommands::Upgrade(cmd) => cmd.run().await,
             TemplateCommands::Uninstall(cmd) => cmd.run().await,
             TemplateCommands::List(cmd) => cmd.run().await,
+            TemplateCommands::Verify(cmd) => cmd.run().await,
         }
     }
 }
 
-/// Install templates from a Git repository or local directory.
+/// Install templates from a Git repository, local directory, OCI registry
+/// or archive.
 #[derive(Parser, Debug)]
 pub struct Install {
     /// The URL of the templates git repository.
@@ -63,7 +76,7 @@ pub struct Install {
         name = INSTALL_FROM_GIT_OPT,
         long = "git",
         alias = "repo",
-        conflicts_with = INSTALL_FROM_DIR_OPT,
+        conflicts_with_all = &[INSTALL_FROM_DIR_OPT, INSTALL_FROM_OCI_OPT, INSTALL_FROM_TAR_OPT],
     )]
     pub git: Option<String>,
 
@@ -75,10 +88,33 @@ pub struct Install {
     #[clap(
         name = INSTALL_FROM_DIR_OPT,
         long = "dir",
-        conflicts_with = INSTALL_FROM_GIT_OPT,
+        conflicts_with_all = &[INSTALL_FROM_GIT_OPT, INSTALL_FROM_OCI_OPT, INSTALL_FROM_TAR_OPT],
     )]
     pub dir: Option<PathBuf>,
 
+    /// The OCI reference of an artifact containing the template(s) to install.
+    /// The artifact's archive layers must contain a "templates" directory.
+    #[clap(
+        name = INSTALL_FROM_OCI_OPT,
+        long = "oci",
+        conflicts_with_all = &[INSTALL_FROM_GIT_OPT, INSTALL_FROM_DIR_OPT, INSTALL_FROM_TAR_OPT],
+    )]
+    pub oci: Option<String>,
+
+    /// The URL of a .tar.gz archive containing the template(s) to install.
+    /// The archive must contain a "templates" directory. Requires --digest.
+    #[clap(
+        name = INSTALL_FROM_TAR_OPT,
+        long = "tar",
+        requires = "digest",
+        conflicts_with_all = &[INSTALL_FROM_GIT_OPT, INSTALL_FROM_DIR_OPT, INSTALL_FROM_OCI_OPT],
+    )]
+    pub tar: Option<String>,
+
+    /// The SHA256 digest of the archive specified by --tar.
+    #[clap(long = "digest", requires = INSTALL_FROM_TAR_OPT)]
+    pub digest: Option<String>,
+
     /// If present, updates existing templates instead of skipping.
     #[clap(long = "upgrade", alias = "update")]
     pub update: bool,
@@ -119,16 +155,25 @@ impl Install {
     pub async fn run(self) -> Result<()> {
         let template_manager = TemplateManager::try_default()
             .context("Failed to construct template directory path")?;
-        let source = match (&self.git, &self.dir) {
-            (Some(git), None) => {
+        let source = match (&self.git, &self.dir, &self.oci, &self.tar) {
+            (Some(git), None, None, None) => {
                 let git_url = infer_github(git);
                 TemplateSource::try_from_git(git_url, &self.branch, SPIN_VERSION)?
             }
-            (None, Some(dir)) => {
+            (None, Some(dir), None, None) => {
                 let abs_dir = dir.absolutize().map(|d| d.to_path_buf());
                 TemplateSource::File(abs_dir.unwrap_or_else(|_| dir.clone()))
             }
-            _ => anyhow::bail!("Exactly one of `git` and `dir` sources must be specified"),
+            (None, None, Some(oci), None) => TemplateSource::try_from_oci(oci)?,
+            (None, None, None, Some(tar)) => {
+                let Some(digest) = &self.digest else {
+                    anyhow::bail!("The `tar` source requires a `digest`");
+                };
+                TemplateSource::try_from_archive(infer_file_url(tar), digest)?
+            }
+            _ => anyhow::bail!(
+                "Exactly one of `git`, `dir`, `oci` and `tar` sources must be specified"
+            ),
         };
 
         let reporter = ConsoleProgressReporter;
@@ -196,6 +241,20 @@ fn infer_github(raw: &str) -> String {
     }
 }
 
+// Allow local archives to be given as paths rather than file URLs
+fn infer_file_url(raw: &str) -> String {
+    match url::Url::parse(raw) {
+        Ok(_) => raw.to_string(),
+        Err(_) => {
+            let path = PathBuf::from(raw);
+            let abs_path = path.absolutize().map(|p| p.to_path_buf()).unwrap_or(path);
+            url::Url::from_file_path(&abs_path)
+                .map(|u| u.to_string())
+                .unwrap_or_else(|_| raw.to_string())
+        }
+    }
+}
+
 impl Upgrade {
     pub async fn run(&self) -> Result<()> {
         if self.git.is_some() {
@@ -204,6 +263,9 @@ impl Upgrade {
                 git: self.git.clone(),
                 branch: self.branch.clone(),
                 dir: None,
+                oci: None,
+                tar: None,
+                digest: None,
                 update: true,
             };
 
@@ -245,7 +307,7 @@ impl Upgrade {
         let existing_templates = template_manager.list().await?.templates;
         let (origin, no_origin): (Vec<_>, Vec<_>) = existing_templates
             .iter()
-            .partition(|t| t.source_repo().is_some());
+            .partition(|t| t.source_repo().is_some() || t.source_artifact().is_some());
 
         let mut repos = origin
             .iter()
@@ -270,6 +332,22 @@ impl Upgrade {
             }
         }
 
+        // Templates installed from OCI or archives are upgraded by fetching the
+        // recorded artifact again.
+        let mut artifacts = HashSet::new();
+        for template in &origin {
+            if let Some(template_source) = template.source_artifact() {
+                let repo = template.installed_from_or_empty().to_owned();
+                if artifacts.insert(repo.clone()) {
+                    sources.push(RepoSelection {
+                        repo,
+                        template_source,
+                        resolved_tag: None,
+                    });
+                }
+            }
+        }
+
         if sources.is_empty() {
             eprintln!("No template repositories found to upgrade");
             eprintln!();
@@ -561,6 +639,111 @@ impl List {
     }
 }
 
+/// Check that templates generate valid applications.
+#[derive(Parser, Debug)]
+pub struct Verify {
+    /// The installed templates to verify. If omitted, all installed
+    /// templates are verified.
+    #[clap(conflicts_with = VERIFY_FROM_DIR_OPT)]
+    pub template_ids: Vec<String>,
+
+    /// Verify the templates in a local directory instead of the installed
+    /// templates.
+    #[clap(name = VERIFY_FROM_DIR_OPT, long = "dir")]
+    pub dir: Option<PathBuf>,
+
+    /// If present, also run the build commands of the generated components.
+    #[clap(long = "build", takes_value = false)]
+    pub build: bool,
+}
+
+impl Verify {
+    pub async fn run(self) -> Result<()> {
+        let reporter = ConsoleProgressReporter;
+        let options = VerifyOptions { build: self.build };
+
+        let results = match &self.dir {
+            Some(dir) => {
+                let abs_dir = dir.absolutize().map(|d| d.to_path_buf());
+                let source = TemplateSource::File(abs_dir.unwrap_or_else(|_| dir.clone()));
+                spin_templates::verify_source(&source, &options, &reporter).await
+            }
+            None => {
+                let template_manager = TemplateManager::try_default()
+                    .context("Failed to construct template directory path")?;
+                template_manager
+                    .verify(&self.template_ids, &options, &reporter)
+                    .await
+            }
+        }
+        .context("Failed to verify templates")?;
+
+        if results.is_empty() {
+            println!("No templates to verify");
+            return Ok(());
+        }
+
+        print_verification_table(&results);
+
+        for result in results.iter().filter(|r| !r.skipped_commands.is_empty()) {
+            println!();
+            println!(
+                "{} runs commands after generating files, which were not run:",
+                verification_label(result)
+            );
+            for command in &result.skipped_commands {
+                println!("  {command}");
+            }
+        }
+
+        let failures: Vec<_> = results.iter().filter(|r| !r.passed()).collect();
+        if failures.is_empty() {
+            return Ok(());
+        }
+
+        println!();
+        for failure in &failures {
+            if let Some(e) = &failure.error {
+                println!("{}: {:#}", verification_label(failure), e);
+            }
+        }
+        anyhow::bail!(
+            "{} of {} template verification(s) failed",
+            failures.len(),
+            results.len()
+        );
+    }
+}
+
+fn print_verification_table(results: &[VerificationResult]) {
+    let mut table = Table::new();
+    table.set_header(vec!["Name", "Variant", "Sample", "Result"]);
+    table.load_preset(comfy_table::presets::ASCII_BORDERS_ONLY_CONDENSED);
+
+    for result in results {
+        let outcome = if result.passed() { "passed" } else { "FAILED" };
+        table.add_row(vec![
+            result.template_id.as_str(),
+            result.variant.unwrap_or("-"),
+            result.sample.as_str(),
+            outcome,
+        ]);
+    }
+
+    println!();
+    println!("{}", table);
+}
+
+fn verification_label(result: &VerificationResult) -> String {
+    match result.variant {
+        Some(variant) => format!(
+            "{} ({}, sample {})",
+            result.template_id, variant, result.sample
+        ),
+        None => result.template_id.clone(),
+    }
+}
+
 fn json_list_format(template: &Template) -> TemplateListJson {
     TemplateListJson {
         id: template.id().to_owned(),
@@ -620,6 +803,9 @@ async fn install_default_templates() -> anyhow::Result<()> {
         git: Some(DEFAULT_TEMPLATE_REPO.to_owned()),
         branch: None,
         dir: None,
+        oci: None,
+        tar: None,
+        digest: None,
         update: false,
     };
     install_cmd
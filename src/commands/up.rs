@@ -4,21 +4,24 @@ use std::{
     collections::HashMap,
     ffi::OsString,
     fmt::Debug,
+    io::IsTerminal,
     path::{Path, PathBuf},
     process::Stdio,
+    sync::Arc,
 };
 
 use anyhow::{anyhow, bail, Context, Result};
 use clap::{CommandFactory, Parser};
 use reqwest::Url;
 use spin_app::locked::LockedApp;
-use spin_common::ui::quoted_path;
+use spin_common::{paths::parent_dir, ui::quoted_path};
 use spin_loader::FilesMountStrategy;
-use spin_oci::OciLoader;
+use spin_oci::{ComponentRegistry, OciLoader};
+use spin_plugins::lockfile::PluginLockfile;
 use spin_trigger::cli::{LaunchMetadata, SPIN_LOCAL_APP_DIR, SPIN_LOCKED_URL, SPIN_WORKING_DIR};
 use tempfile::TempDir;
 
-use crate::opts::*;
+use crate::{commands::registry::VerifyOpts, opts::*};
 
 use self::app_source::{AppSource, ResolvedAppSource};
 
@@ -87,6 +90,9 @@ pub struct UpCommand {
     )]
     pub insecure: bool,
 
+    #[clap(flatten)]
+    pub verify: VerifyOpts,
+
     /// Pass an environment variable (key=value) to all components of the application.
     #[clap(short = 'e', long = "env", parse(try_from_str = parse_env_var))]
     pub env: Vec<(String, String)>,
@@ -155,6 +161,8 @@ impl UpCommand {
             }
         }
 
+        self.ensure_verifiable(&app_source)?;
+
         // Get working dir holder and hold on to it for the rest of the function.
         // If the working dir is a temporary dir it will be deleted on drop.
         let working_dir_holder = self.get_canonical_working_dir()?;
@@ -166,6 +174,7 @@ impl UpCommand {
         let resolved_app_source = self.resolve_app_source(&app_source, &working_dir).await?;
 
         let trigger_cmds = trigger_command_for_resolved_app_source(&resolved_app_source)
+            .await
             .with_context(|| format!("Couldn't find trigger executor for {app_source}"))?;
 
         let is_multi = trigger_cmds.len() > 1;
@@ -411,6 +420,20 @@ impl UpCommand {
         Ok(locked_url)
     }
 
+    // Signatures are stored alongside the app in a registry, so can't be
+    // checked for apps from anywhere else.
+    fn ensure_verifiable(&self, app_source: &AppSource) -> anyhow::Result<()> {
+        if self.verify.verify
+            && matches!(
+                app_source,
+                AppSource::File(_) | AppSource::OciLayout(_) | AppSource::BareWasm(_)
+            )
+        {
+            bail!("Signatures can only be verified for apps loaded from a registry, but {app_source} is not a registry reference");
+        }
+        Ok(())
+    }
+
     // Take the AppSource and do the minimum amount of work necessary to
     // be able to resolve the trigger executor.
     async fn resolve_app_source(
@@ -429,12 +452,25 @@ impl UpCommand {
                 let mut client = spin_oci::Client::new(self.insecure, self.cache_dir.clone())
                     .await
                     .context("cannot create registry client")?;
+                if let Some(verifier) = self.verify.verifier()? {
+                    client = client.with_verifier(verifier);
+                }
 
                 let locked_app = OciLoader::new(working_dir)
                     .load_app(&mut client, reference)
                     .await?;
                 ResolvedAppSource::OciRegistry { locked_app }
             }
+            AppSource::OciLayout(path) => {
+                let mut client = spin_oci::Client::new(self.insecure, self.cache_dir.clone())
+                    .await
+                    .context("cannot create registry client")?;
+
+                let locked_app = OciLoader::new(working_dir)
+                    .load_layout(&mut client, path)
+                    .await?;
+                ResolvedAppSource::OciRegistry { locked_app }
+            }
             AppSource::BareWasm(path) => ResolvedAppSource::BareWasm {
                 wasm_path: path.clone(),
             },
@@ -456,14 +492,22 @@ impl UpCommand {
                 } else {
                     FilesMountStrategy::Copy(working_dir.join("assets"))
                 };
-                spin_loader::from_file(&manifest_path, files_mount_strategy, self.cache_dir.clone())
-                    .await
-                    .with_context(|| {
-                        format!(
-                            "Failed to load manifest from {}",
-                            quoted_path(&manifest_path)
-                        )
-                    })
+                // Components whose source is an OCI artifact are pulled with
+                // the same registry settings as OCI applications.
+                let oci_registry = Arc::new(ComponentRegistry::new(self.insecure));
+                spin_loader::from_file_with_oci_registry(
+                    &manifest_path,
+                    files_mount_strategy,
+                    self.cache_dir.clone(),
+                    oci_registry,
+                )
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to load manifest from {}",
+                        quoted_path(&manifest_path)
+                    )
+                })
             }
             ResolvedAppSource::OciRegistry { locked_app } => Ok(locked_app),
             ResolvedAppSource::BareWasm { wasm_path } => spin_loader::from_wasm_file(&wasm_path)
@@ -593,7 +637,10 @@ fn parse_env_var(s: &str) -> Result<(String, String)> {
     Ok((parts[0].to_owned(), parts[1].to_owned()))
 }
 
-fn resolve_trigger_plugin(trigger_type: &str) -> Result<String> {
+async fn resolve_trigger_plugin(
+    trigger_type: &str,
+    lockfile: Option<&PluginLockfile>,
+) -> Result<String> {
     use crate::commands::plugins::PluginCompatibility;
     use spin_plugins::manager::PluginManager;
 
@@ -611,6 +658,14 @@ fn resolve_trigger_plugin(trigger_type: &str) -> Result<String> {
         return Ok(subcommand);
     }
 
+    if let Some(locked) = lockfile.and_then(|l| l.get(&subcommand)) {
+        if offer_install_locked_trigger(trigger_type, &locked.version)? {
+            crate::commands::plugins::install_locked(&plugin_manager, locked, true).await?;
+            return Ok(subcommand);
+        }
+        bail!("No built-in trigger named '{trigger_type}'. Run `spin plugins sync` to install plugin '{subcommand}' {}", locked.version);
+    }
+
     if let Some(known) = plugin_store
         .catalogue_manifests()
         .unwrap_or_default()
@@ -626,24 +681,43 @@ fn resolve_trigger_plugin(trigger_type: &str) -> Result<String> {
     }
 }
 
+fn offer_install_locked_trigger(trigger_type: &str, version: &str) -> Result<bool> {
+    if !std::io::stderr().is_terminal() {
+        return Ok(false);
+    }
+    let prompt = format!("The application requires the '{trigger_type}' trigger plugin (version {version}), which is not installed. Install it now?");
+    let install = dialoguer::Confirm::new()
+        .with_prompt(prompt)
+        .default(true)
+        .interact_opt()?
+        .unwrap_or(false);
+    Ok(install)
+}
+
 fn trigger_command(trigger_type: &str) -> Vec<String> {
     vec!["trigger".to_owned(), trigger_type.to_owned()]
 }
 
-fn trigger_command_for_resolved_app_source(
+async fn trigger_command_for_resolved_app_source(
     resolved: &ResolvedAppSource,
 ) -> Result<Vec<Vec<String>>> {
-    let trigger_type = resolved.trigger_types()?;
-    trigger_type
-        .iter()
-        .map(|&t| match t {
-            "http" | "redis" => Ok(trigger_command(t)),
-            _ => {
-                let cmd = resolve_trigger_plugin(t)?;
-                Ok(vec![cmd])
-            }
-        })
-        .collect()
+    let lockfile = match resolved {
+        ResolvedAppSource::File { manifest_path, .. } => {
+            PluginLockfile::load(&parent_dir(manifest_path)?)?
+        }
+        _ => None,
+    };
+
+    let trigger_types = resolved.trigger_types()?;
+    let mut trigger_cmds = Vec::with_capacity(trigger_types.len());
+    for t in trigger_types {
+        let cmd = match t {
+            "http" | "redis" => trigger_command(t),
+            _ => vec![resolve_trigger_plugin(t, lockfile.as_ref()).await?],
+        };
+        trigger_cmds.push(cmd);
+    }
+    Ok(trigger_cmds)
 }
 
 #[cfg(test)]
@@ -687,6 +761,24 @@ mod test {
         );
     }
 
+    #[test]
+    fn can_infer_oci_layouts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("oci-layout"),
+            r#"{"imageLayoutVersion":"1.0.0"}"#,
+        )
+        .unwrap();
+
+        let source = UpCommand {
+            app_source: Some(dir.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        }
+        .app_source();
+
+        assert_eq!(AppSource::OciLayout(dir.path().to_owned()), source);
+    }
+
     #[test]
     fn reject_nonexistent_files() {
         let file = repo_path("src/commands/biscuits.toml");
@@ -804,6 +896,55 @@ mod test {
         .expect("Failed to parse --from-registry with trigger option");
     }
 
+    #[test]
+    fn parses_verify_options() {
+        let cmd = UpCommand::try_parse_from([
+            "up",
+            "--from-registry",
+            "ghcr.io/example/test:v1",
+            "--verify",
+            "--verify-key",
+            "a.pem",
+            "--verify-key",
+            "b.pem",
+        ])
+        .expect("Failed to parse --verify with keys");
+        assert!(cmd.verify.verify);
+        assert_eq!(
+            vec![PathBuf::from("a.pem"), PathBuf::from("b.pem")],
+            cmd.verify.verify_keys
+        );
+
+        UpCommand::try_parse_from([
+            "up",
+            "--from-registry",
+            "ghcr.io/example/test:v1",
+            "--verify",
+        ])
+        .expect_err("--verify without keys should be rejected");
+    }
+
+    #[test]
+    fn rejects_verify_for_non_registry_sources() {
+        let cmd = UpCommand::try_parse_from([
+            "up",
+            "--from",
+            "spin.toml",
+            "--verify",
+            "--verify-key",
+            "a.pem",
+        ])
+        .unwrap();
+        cmd.ensure_verifiable(&AppSource::File("spin.toml".into()))
+            .expect_err("--verify should be rejected for a manifest file");
+        cmd.ensure_verifiable(&AppSource::BareWasm("app.wasm".into()))
+            .expect_err("--verify should be rejected for a Wasm file");
+        cmd.ensure_verifiable(&AppSource::OciLayout("layout".into()))
+            .expect_err("--verify should be rejected for an OCI image layout");
+        cmd.ensure_verifiable(&AppSource::OciRegistry("ghcr.io/example/test:v1".into()))
+            .expect("--verify should be allowed for a registry reference");
+    }
+
     #[test]
     fn parses_implicit_source() {
         UpCommand::try_parse_from(["up"]).expect("Failed to parse implicit source with option");
@@ -13,6 +13,7 @@ use spin_manifest::schema::v2::AppManifest;
 pub enum AppSource {
     File(PathBuf),
     OciRegistry(String),
+    OciLayout(PathBuf),
     BareWasm(PathBuf),
     Unresolvable(String),
     None,
@@ -31,7 +32,11 @@ impl AppSource {
     }
 
     pub fn infer_file_source(path: impl Into<PathBuf>) -> Self {
-        match spin_common::paths::resolve_manifest_file_path(path.into()) {
+        let path = path.into();
+        if spin_oci::layout::is_oci_layout(&path) {
+            return Self::OciLayout(path);
+        }
+        match spin_common::paths::resolve_manifest_file_path(path) {
             Ok(file) => {
                 if is_wasm_file(&file) {
                     Self::BareWasm(file)
@@ -75,6 +80,7 @@ impl std::fmt::Display for AppSource {
         match self {
             Self::File(path) => write!(f, "local app {}", quoted_path(path)),
             Self::OciRegistry(reference) => write!(f, "remote app {reference:?}"),
+            Self::OciLayout(path) => write!(f, "OCI image layout {}", quoted_path(path)),
             Self::BareWasm(path) => write!(f, "Wasm file {}", quoted_path(path)),
             Self::Unresolvable(s) => write!(f, "unknown app source: {s:?}"),
             Self::None => write!(f, "<no source>"),
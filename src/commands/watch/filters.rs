@@ -43,7 +43,8 @@ impl FilterFactory for ArtifactFilterFactory {
         let wasm_globs = manifest
             .components
             .values()
-            .filter_map(|c| match &c.source {
+            .flat_map(|c| std::iter::once(&c.source).chain(c.dependencies.values()))
+            .filter_map(|source| match source {
                 v2::ComponentSource::Local(path) => Some(path.clone()),
                 _ => None,
             });
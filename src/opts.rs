@@ -9,6 +9,8 @@ pub const PLUGIN_ALL_OPT: &str = "ALL";
 pub const PLUGIN_OVERRIDE_COMPATIBILITY_CHECK_FLAG: &str = "override-compatibility-check";
 pub const HELP_ARGS_ONLY_TRIGGER_TYPE: &str = "provide-help-args-no-app";
 pub const FROM_REGISTRY_OPT: &str = "REGISTRY_REFERENCE";
+pub const VERIFY_OPT: &str = "VERIFY";
+pub const VERIFY_KEY_OPT: &str = "VERIFY_KEY";
 pub const WATCH_CLEAR_OPT: &str = "CLEAR";
 pub const WATCH_DEBOUNCE_OPT: &str = "DEBOUNCE";
 pub const WATCH_SKIP_BUILD_OPT: &str = "SKIP_BUILD";
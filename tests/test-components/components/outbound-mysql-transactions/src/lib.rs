@@ -0,0 +1,131 @@
+use helper::{ensure_eq, ensure_matches, ensure_ok};
+
+use rdbms::spin::rdbms::mysql::{Connection, Error, RowSet};
+use rdbms::spin::rdbms::rdbms_types::{DbValue, ParameterValue};
+
+helper::define_component!(Component);
+const DB_URL_ENV: &str = "DB_URL";
+
+// The default maximum number of pooled connections to a database.
+const POOL_MAX_SIZE: usize = 10;
+
+mod rdbms {
+    wit_bindgen::generate!({
+        world: "spin:rdbms/platform@3.0.0",
+        path: "../../../../crates/world/wit",
+    });
+}
+
+mod v1 {
+    wit_bindgen::generate!({
+        world: "fermyon:spin/platform",
+        path: "../../../../crates/world/wit",
+    });
+}
+
+impl Component {
+    fn main() -> Result<(), String> {
+        let address = ensure_ok!(std::env::var(DB_URL_ENV));
+
+        // Each v1 call returns its connection to the pool, so making more
+        // calls than the pool holds doesn't exhaust it
+        for _ in 0..=POOL_MAX_SIZE {
+            ensure_ok!(v1::fermyon::spin::mysql::query(&address, "SELECT 1", &[]));
+        }
+
+        let conn = ensure_ok!(Connection::open(&address));
+        ensure_ok!(conn.execute(
+            "CREATE TEMPORARY TABLE test_transactions (id INT NOT NULL)",
+            &[]
+        ));
+
+        // Committed changes are kept
+        let tx = ensure_ok!(conn.begin());
+        ensure_ok!(tx.execute("INSERT INTO test_transactions (id) VALUES (1)", &[]));
+        ensure_ok!(tx.commit());
+        ensure_eq!(vec![1], ids(ensure_ok!(select_ids(&conn))));
+
+        // Rolled back changes are discarded, and the transaction can't be used again
+        let tx = ensure_ok!(conn.begin());
+        ensure_ok!(tx.execute("INSERT INTO test_transactions (id) VALUES (2)", &[]));
+        ensure_ok!(tx.rollback());
+        ensure_eq!(vec![1], ids(ensure_ok!(select_ids(&conn))));
+        ensure_matches!(
+            tx.execute("INSERT INTO test_transactions (id) VALUES (2)", &[]),
+            Err(Error::Other(_))
+        );
+        drop(tx);
+
+        // Only one transaction may be open on a connection, and changes in a
+        // transaction which is dropped without being committed are rolled back
+        let tx = ensure_ok!(conn.begin());
+        ensure_matches!(conn.begin(), Err(Error::Other(_)));
+        ensure_ok!(tx.execute("INSERT INTO test_transactions (id) VALUES (3)", &[]));
+        drop(tx);
+        ensure_eq!(vec![1], ids(ensure_ok!(select_ids(&conn))));
+
+        // Prepared statements can be run repeatedly with different parameters
+        let insert = ensure_ok!(conn.prepare("INSERT INTO test_transactions (id) VALUES (?)"));
+        for id in [4, 5] {
+            ensure_ok!(insert.execute(&[ParameterValue::Int32(id)]));
+        }
+        let select =
+            ensure_ok!(conn.prepare("SELECT id FROM test_transactions WHERE id > ? ORDER BY id"));
+        ensure_eq!(
+            vec![4, 5],
+            ids(ensure_ok!(select.query(&[ParameterValue::Int32(1)])))
+        );
+        ensure_eq!(
+            vec![5],
+            ids(ensure_ok!(select.query(&[ParameterValue::Int32(4)])))
+        );
+        drop(insert);
+        drop(select);
+
+        // A connection without an open transaction is returned to the pool...
+        let id = connection_id(&conn)?;
+        drop(conn);
+        let conn = ensure_ok!(Connection::open(&address));
+        ensure_eq!(id, connection_id(&conn)?);
+
+        // ...with its session state, such as temporary tables, cleared
+        ensure_matches!(select_ids(&conn), Err(_));
+
+        // ...but one dropped with a transaction open is closed, which rolls the
+        // transaction back
+        ensure_ok!(conn.execute(
+            "CREATE TEMPORARY TABLE test_transactions (id INT NOT NULL)",
+            &[]
+        ));
+        let tx = ensure_ok!(conn.begin());
+        ensure_ok!(tx.execute("INSERT INTO test_transactions (id) VALUES (6)", &[]));
+        drop(conn);
+        drop(tx);
+        let conn = ensure_ok!(Connection::open(&address));
+        if connection_id(&conn)? == id {
+            helper::bail!("connection {} was reused with a transaction open", id);
+        }
+
+        Ok(())
+    }
+}
+
+fn select_ids(conn: &Connection) -> Result<RowSet, Error> {
+    conn.query("SELECT id FROM test_transactions ORDER BY id", &[])
+}
+
+fn ids(rowset: RowSet) -> Vec<i32> {
+    rowset
+        .rows
+        .iter()
+        .filter_map(|row| match row[0] {
+            DbValue::Int32(id) => Some(id),
+            _ => None,
+        })
+        .collect()
+}
+
+fn connection_id(conn: &Connection) -> Result<String, String> {
+    let rowset = ensure_ok!(conn.query("SELECT CONNECTION_ID()", &[]));
+    Ok(format!("{:?}", rowset.rows[0][0]))
+}